@@ -0,0 +1,112 @@
+//! Rough timings of the predicates on random points, to compare
+//! the ways of calling them. Run with `cargo bench`.
+
+use simplicity::context::{PredicateContext, Sorted};
+use simplicity::{nalgebra::Vector2, soa};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const POINTS: usize = 1000;
+const CALLS: usize = 200_000;
+
+/// Deterministic points in the unit square.
+fn random_points_2d() -> Vec<Vector2<f64>> {
+    let mut state = 0x9E3779B97F4A7C15u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    };
+    (0..POINTS).map(|_| Vector2::new(next(), next())).collect()
+}
+
+/// Distinct indexes for the `n`-th call.
+fn indexes<const N: usize>(n: usize) -> [usize; N] {
+    let mut indexes = [0; N];
+    for (k, index) in indexes.iter_mut().enumerate() {
+        *index = (n * 7919 + k * 104_729) % POINTS;
+    }
+    indexes
+}
+
+fn time(name: &str, mut f: impl FnMut(usize) -> bool) -> Duration {
+    let start = Instant::now();
+    for n in 0..CALLS {
+        black_box(f(n));
+    }
+    let elapsed = start.elapsed();
+    println!("{:<24} {:>8.1} ns/call", name, elapsed.as_nanos() as f64 / CALLS as f64);
+    elapsed
+}
+
+fn main() {
+    let points = random_points_2d();
+    let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+    let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+
+    time("orient_2d", |n| {
+        let [i, j, k] = indexes(n);
+        simplicity::orient_2d(&points, |l, i| l[i], i, j, k)
+    });
+    time("soa::orient_2d", |n| {
+        let [i, j, k] = indexes(n);
+        soa::orient_2d(&xs, &ys, i, j, k)
+    });
+    time("in_circle", |n| {
+        let [i, j, k, l] = indexes(n);
+        simplicity::in_circle(&points, |l, i| l[i], i, j, k, l)
+    });
+    time("soa::in_circle", |n| {
+        let [i, j, k, l] = indexes(n);
+        soa::in_circle(&xs, &ys, i, j, k, l)
+    });
+
+    let mut context = PredicateContext::new(&points, |l: &Vec<Vector2<f64>>, i| l[i]);
+    time("PredicateContext", |n| {
+        let [i, j, k, l] = indexes(n);
+        context.in_circle(i, j, k, l)
+    });
+    time("PredicateContext sorted", |n| {
+        context.in_circle_sorted(&Sorted::new(indexes(n)))
+    });
+
+    // The same queries against one circle, one by one and all at once
+    let queries = (3..POINTS).collect::<Vec<_>>();
+    let rounds = CALLS / queries.len();
+    let scalar = time("in_circle loop", |n| {
+        simplicity::in_circle(&points, |l, i| l[i], 0, 1, 2, queries[n % queries.len()])
+    });
+    let start = Instant::now();
+    for _ in 0..rounds {
+        black_box(simplicity::in_circle_multi(&points, |l, i| l[i], 0, 1, 2, &queries));
+    }
+    let multi = start.elapsed();
+    println!("{:<24} {:>8.1} ns/call", "in_circle_multi", multi.as_nanos() as f64 / (rounds * queries.len()) as f64);
+    let scalar = scalar.as_nanos() as f64 / CALLS as f64;
+    let multi = multi.as_nanos() as f64 / (rounds * queries.len()) as f64;
+    println!("in_circle_multi is {:.1}x as fast as the loop", scalar / multi);
+
+    #[cfg(feature = "high-dim")]
+    high_dim(&points);
+}
+
+/// The generated 5-dimensional predicates, on points lifted from the 2-dimensional ones.
+#[cfg(feature = "high-dim")]
+fn high_dim(points: &[Vector2<f64>]) {
+    use simplicity::nalgebra::Vector5;
+
+    let points = points
+        .windows(3)
+        .map(|w| Vector5::new(w[0].x, w[0].y, w[1].x, w[1].y, w[2].x))
+        .collect::<Vec<_>>();
+    let index = |n: usize, k: usize| (n * 7919 + k * 104_729) % points.len();
+    time("orient_5d", |n| {
+        let [i, j, k, l, m, o] = [0, 1, 2, 3, 4, 5].map(|k| index(n, k));
+        simplicity::orient_5d(&points, |l, i| l[i], i, j, k, l, m, o)
+    });
+    time("in_hypersphere_5d", |n| {
+        let [i, j, k, l, m, o, p] = [0, 1, 2, 3, 4, 5, 6].map(|k| index(n, k));
+        simplicity::in_hypersphere_5d(&points, |l, i| l[i], i, j, k, l, m, o, p)
+    });
+}
@@ -0,0 +1,11 @@
+fn main() {
+    println!("cargo:rerun-if-changed=src/shim.cpp");
+    cc::Build::new()
+        .cpp(true)
+        .file("src/shim.cpp")
+        .flag_if_supported("-std=c++17")
+        .compile("simplicity_cgal_shim");
+    // CGAL is header-only, but its exact number types use GMP and MPFR
+    println!("cargo:rustc-link-lib=gmp");
+    println!("cargo:rustc-link-lib=mpfr");
+}
@@ -0,0 +1,58 @@
+//! Cross-validation of simplicity's predicates against CGAL's exact predicates.
+//!
+//! This crate isn't part of the build. It wraps CGAL's exact predicates through a small
+//! C++ shim, and its tests compare them against simplicity on random and degenerate input.
+//! It needs a C++ compiler and CGAL, GMP, and MPFR installed. Run the tests with
+//! `cargo test` from this directory.
+//!
+//! # Conventions
+//!
+//! The wrappers here return CGAL's signs, -1, 0, or 1. Mapped to simplicity:
+//!
+//! * [`orientation_2`] is 1 when `orient_2d` is true; both mean a left turn.
+//! * [`orientation_3`] is 1 when `orient_3d` is *false*. CGAL's orientation is the sign of
+//!   det(q - p, r - p, s - p), while simplicity's is the opposite, matching Shewchuk's predicates.
+//! * [`side_of_oriented_circle`] is 1 when `in_circle` is true.
+//! * [`side_of_oriented_sphere`] is -1 when `in_sphere` is true, since the orientations differ.
+//!
+//! # Degenerate input
+//!
+//! The predicates agree whenever CGAL's sign is nonzero. When it's 0, the points are collinear,
+//! coplanar, cocircular, or cospherical, and CGAL reports that, while simplicity breaks the tie
+//! with the perturbation and still returns `true` or `false`. Exactly in those cases,
+//! the `_strict` predicates return an error with `ErrorReason::Degenerate`. The tests check both.
+
+use std::os::raw::c_int;
+
+extern "C" {
+    fn cgal_orientation_2(p: *const f64, q: *const f64, r: *const f64) -> c_int;
+    fn cgal_orientation_3(p: *const f64, q: *const f64, r: *const f64, s: *const f64) -> c_int;
+    fn cgal_side_of_oriented_circle(p: *const f64, q: *const f64, r: *const f64, t: *const f64) -> c_int;
+    fn cgal_side_of_oriented_sphere(
+        p: *const f64,
+        q: *const f64,
+        r: *const f64,
+        s: *const f64,
+        t: *const f64,
+    ) -> c_int;
+}
+
+/// `CGAL::orientation` of 3 points in 2D.
+pub fn orientation_2(p: [f64; 2], q: [f64; 2], r: [f64; 2]) -> i32 {
+    unsafe { cgal_orientation_2(p.as_ptr(), q.as_ptr(), r.as_ptr()) }
+}
+
+/// `CGAL::orientation` of 4 points in 3D.
+pub fn orientation_3(p: [f64; 3], q: [f64; 3], r: [f64; 3], s: [f64; 3]) -> i32 {
+    unsafe { cgal_orientation_3(p.as_ptr(), q.as_ptr(), r.as_ptr(), s.as_ptr()) }
+}
+
+/// `CGAL::side_of_oriented_circle`: whether `t` is on the positive side of the circle through `p`, `q`, `r`.
+pub fn side_of_oriented_circle(p: [f64; 2], q: [f64; 2], r: [f64; 2], t: [f64; 2]) -> i32 {
+    unsafe { cgal_side_of_oriented_circle(p.as_ptr(), q.as_ptr(), r.as_ptr(), t.as_ptr()) }
+}
+
+/// `CGAL::side_of_oriented_sphere`: whether `t` is on the positive side of the sphere through `p`, `q`, `r`, `s`.
+pub fn side_of_oriented_sphere(p: [f64; 3], q: [f64; 3], r: [f64; 3], s: [f64; 3], t: [f64; 3]) -> i32 {
+    unsafe { cgal_side_of_oriented_sphere(p.as_ptr(), q.as_ptr(), r.as_ptr(), s.as_ptr(), t.as_ptr()) }
+}
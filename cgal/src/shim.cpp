@@ -0,0 +1,31 @@
+// C wrappers around CGAL's exact predicates, for comparing against simplicity.
+// Each returns the sign of the predicate: -1, 0, or 1.
+
+#include <CGAL/Exact_predicates_inexact_constructions_kernel.h>
+
+typedef CGAL::Exact_predicates_inexact_constructions_kernel K;
+typedef K::Point_2 Point_2;
+typedef K::Point_3 Point_3;
+
+static Point_2 p2(const double *p) { return Point_2(p[0], p[1]); }
+static Point_3 p3(const double *p) { return Point_3(p[0], p[1], p[2]); }
+
+extern "C" {
+
+int cgal_orientation_2(const double *p, const double *q, const double *r) {
+    return static_cast<int>(CGAL::orientation(p2(p), p2(q), p2(r)));
+}
+
+int cgal_orientation_3(const double *p, const double *q, const double *r, const double *s) {
+    return static_cast<int>(CGAL::orientation(p3(p), p3(q), p3(r), p3(s)));
+}
+
+int cgal_side_of_oriented_circle(const double *p, const double *q, const double *r, const double *t) {
+    return static_cast<int>(CGAL::side_of_oriented_circle(p2(p), p2(q), p2(r), p2(t)));
+}
+
+int cgal_side_of_oriented_sphere(const double *p, const double *q, const double *r, const double *s, const double *t) {
+    return static_cast<int>(CGAL::side_of_oriented_sphere(p3(p), p3(q), p3(r), p3(s), p3(t)));
+}
+
+}
@@ -0,0 +1,133 @@
+use simplicity::degenerate::Generator;
+use simplicity::nalgebra::{Vector2, Vector3};
+use simplicity::{in_circle, in_circle_strict, in_sphere, in_sphere_strict, orient_2d, orient_2d_strict};
+use simplicity::{orient_3d, orient_3d_strict, SimplicityError};
+use simplicity_cgal::*;
+
+/// Random coordinates in [-1, 1], so the points are almost never degenerate.
+fn random_coords(seed: u64, n: usize) -> Vec<f64> {
+    let mut state = seed | 1;
+    (0..n)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+        })
+        .collect()
+}
+
+/// Calls `f` with every tuple of `k` distinct indexes below `n`, in every order.
+fn for_each_tuple(n: usize, k: usize, f: &mut impl FnMut(&[usize])) {
+    fn recurse(n: usize, k: usize, tuple: &mut Vec<usize>, f: &mut impl FnMut(&[usize])) {
+        if tuple.len() == k {
+            return f(tuple);
+        }
+        for i in 0..n {
+            if tuple.contains(&i) {
+                continue;
+            }
+            tuple.push(i);
+            recurse(n, k, tuple, f);
+            tuple.pop();
+        }
+    }
+    recurse(n, k, &mut vec![], f)
+}
+
+/// Checks that simplicity agrees with CGAL when CGAL's sign is nonzero,
+/// and that the strict predicates reject exactly the inputs where it's 0.
+/// Returns the number of degenerate inputs.
+fn check(sign: i32, sos: bool, strict: Result<bool, SimplicityError>) -> usize {
+    if sign == 0 {
+        assert!(strict.unwrap_err().is_degenerate());
+        1
+    } else {
+        assert_eq!(sos, sign > 0);
+        assert_eq!(strict, Ok(sign > 0));
+        0
+    }
+}
+
+/// Returns the number of degenerate orientations and in-circles.
+fn check_2d(points: &[Vector2<f64>]) -> (usize, usize) {
+    let a = |i: usize| [points[i].x, points[i].y];
+    let mut degenerate = (0, 0);
+    for_each_tuple(points.len(), 3, &mut |t| {
+        degenerate.0 += check(
+            orientation_2(a(t[0]), a(t[1]), a(t[2])),
+            orient_2d(points, |l, i| l[i], t[0], t[1], t[2]),
+            orient_2d_strict(points, |l, i| l[i], t[0], t[1], t[2]),
+        );
+    });
+    for_each_tuple(points.len(), 4, &mut |t| {
+        degenerate.1 += check(
+            side_of_oriented_circle(a(t[0]), a(t[1]), a(t[2]), a(t[3])),
+            in_circle(points, |l, i| l[i], t[0], t[1], t[2], t[3]),
+            in_circle_strict(points, |l, i| l[i], t[0], t[1], t[2], t[3]),
+        );
+    });
+    degenerate
+}
+
+/// Returns the number of degenerate orientations and in-spheres.
+fn check_3d(points: &[Vector3<f64>]) -> (usize, usize) {
+    let a = |i: usize| [points[i].x, points[i].y, points[i].z];
+    let mut degenerate = (0, 0);
+    for_each_tuple(points.len(), 4, &mut |t| {
+        // CGAL's 3D orientation is the opposite of simplicity's
+        degenerate.0 += check(
+            -orientation_3(a(t[0]), a(t[1]), a(t[2]), a(t[3])),
+            orient_3d(points, |l, i| l[i], t[0], t[1], t[2], t[3]),
+            orient_3d_strict(points, |l, i| l[i], t[0], t[1], t[2], t[3]),
+        );
+    });
+    for_each_tuple(points.len(), 5, &mut |t| {
+        degenerate.1 += check(
+            -side_of_oriented_sphere(a(t[0]), a(t[1]), a(t[2]), a(t[3]), a(t[4])),
+            in_sphere(points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4]),
+            in_sphere_strict(points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4]),
+        );
+    });
+    degenerate
+}
+
+#[test]
+fn test_random_2d() {
+    for seed in 1..=20 {
+        let coords = random_coords(seed, 16);
+        let points = coords.chunks(2).map(Vector2::from_column_slice).collect::<Vec<_>>();
+        assert_eq!(check_2d(&points), (0, 0));
+    }
+}
+
+#[test]
+fn test_random_3d() {
+    for seed in 1..=5 {
+        let coords = random_coords(seed, 21);
+        let points = coords.chunks(3).map(Vector3::from_column_slice).collect::<Vec<_>>();
+        assert_eq!(check_3d(&points), (0, 0));
+    }
+}
+
+#[test]
+fn test_degenerate_2d() {
+    for seed in 1..=5 {
+        let mut gen = Generator::new(seed);
+        let (orient, _) = check_2d(&gen.grid_clusters_2d(2, 4));
+        assert!(orient > 0);
+        let (_, circle) = check_2d(&gen.cocircular_2d(6));
+        assert!(circle > 0);
+    }
+}
+
+#[test]
+fn test_degenerate_3d() {
+    for seed in 1..=3 {
+        let mut gen = Generator::new(seed);
+        let (orient, _) = check_3d(&gen.coplanar_3d(6));
+        assert!(orient > 0);
+        let (_, sphere) = check_3d(&gen.cospherical_3d(7));
+        assert!(sphere > 0);
+    }
+}
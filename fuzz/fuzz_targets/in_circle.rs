@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use simplicity::nalgebra::Vector2;
+use simplicity::reference;
+use simplicity_fuzz::Input;
+
+fuzz_target!(|input: Input| {
+    if let Some((points, ix)) = input.decode(2, 4, Vector2::from_column_slice) {
+        assert_eq!(
+            simplicity::in_circle(&points, |l, i| l[i], ix[0], ix[1], ix[2], ix[3]),
+            reference::in_circle(&points, |l, i| l[i], ix[0], ix[1], ix[2], ix[3]),
+            "indexes {:?} into points {:?}",
+            ix,
+            points,
+        );
+    }
+});
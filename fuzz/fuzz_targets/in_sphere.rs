@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use simplicity::nalgebra::Vector3;
+use simplicity::reference;
+use simplicity_fuzz::Input;
+
+fuzz_target!(|input: Input| {
+    if let Some((points, ix)) = input.decode(3, 5, Vector3::from_column_slice) {
+        assert_eq!(
+            simplicity::in_sphere(&points, |l, i| l[i], ix[0], ix[1], ix[2], ix[3], ix[4]),
+            reference::in_sphere(&points, |l, i| l[i], ix[0], ix[1], ix[2], ix[3], ix[4]),
+            "indexes {:?} into points {:?}",
+            ix,
+            points,
+        );
+    }
+});
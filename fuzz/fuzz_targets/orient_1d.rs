@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use simplicity::nalgebra::Vector1;
+use simplicity::reference;
+use simplicity_fuzz::Input;
+
+fuzz_target!(|input: Input| {
+    if let Some((points, ix)) = input.decode(1, 2, Vector1::from_column_slice) {
+        assert_eq!(
+            simplicity::orient_1d(&points, |l, i| l[i], ix[0], ix[1]),
+            reference::orient_1d(&points, |l, i| l[i], ix[0], ix[1]),
+            "indexes {:?} into points {:?}",
+            ix,
+            points,
+        );
+    }
+});
@@ -0,0 +1,17 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use simplicity::nalgebra::Vector2;
+use simplicity::reference;
+use simplicity_fuzz::Input;
+
+fuzz_target!(|input: Input| {
+    if let Some((points, ix)) = input.decode(2, 3, Vector2::from_column_slice) {
+        assert_eq!(
+            simplicity::orient_2d(&points, |l, i| l[i], ix[0], ix[1], ix[2]),
+            reference::orient_2d(&points, |l, i| l[i], ix[0], ix[1], ix[2]),
+            "indexes {:?} into points {:?}",
+            ix,
+            points,
+        );
+    }
+});
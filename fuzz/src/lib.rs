@@ -0,0 +1,75 @@
+//! Input decoding shared by the fuzz targets.
+//!
+//! Each target compares a predicate against its implementation in `simplicity::reference`
+//! on point sets decoded from the fuzzer's input. The coordinates are mostly small integers,
+//! integers nudged by a few ulps, and repeats of earlier points, so the point sets are
+//! degenerate, or nearly so, far more often than random floats would be.
+//!
+//! Run a target with `cargo fuzz run <target>` from the repository root.
+
+use arbitrary::Arbitrary;
+
+/// A coordinate.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+pub enum Coord {
+    /// A small integer
+    Small(i8),
+    /// A small integer plus a few multiples of machine epsilon
+    Nudged(i8, i8),
+    /// Any float. Ones too large or too small for the exact filters to stay exact become 0.
+    Float(f64),
+}
+
+impl Coord {
+    pub fn value(self) -> f64 {
+        match self {
+            Coord::Small(n) => f64::from(n),
+            Coord::Nudged(n, k) => f64::from(n) + f64::from(k) * f64::EPSILON,
+            Coord::Float(x) if x.is_finite() && x.abs() <= 1e20 && (x == 0.0 || x.abs() >= 1e-20) => x,
+            Coord::Float(_) => 0.0,
+        }
+    }
+}
+
+/// A point, as its coordinates or as a repeat of an earlier point.
+#[derive(Clone, Copy, Debug, Arbitrary)]
+pub enum Point {
+    /// The first coordinates are used, as many as the dimension
+    Coords([Coord; 3]),
+    /// The earlier point at this index, modulo the number of earlier points
+    Repeat(u8),
+}
+
+/// A point set and the indexes to call a predicate with.
+#[derive(Clone, Debug, Arbitrary)]
+pub struct Input {
+    /// Each picks one of the points that haven't been picked yet, modulo their number
+    pub indexes: [u8; 5],
+    pub points: Vec<Point>,
+}
+
+impl Input {
+    /// Decodes the points with `dim` coordinates each, converting them with `point`,
+    /// and `n` distinct indexes into them.
+    /// Returns `None` if there aren't enough points.
+    pub fn decode<V>(&self, dim: usize, n: usize, point: impl Fn(&[f64]) -> V) -> Option<(Vec<V>, Vec<usize>)> {
+        let mut coords: Vec<Vec<f64>> = vec![];
+        for p in &self.points {
+            let c = match p {
+                Point::Coords(c) => c[..dim].iter().map(|c| c.value()).collect(),
+                Point::Repeat(_) if coords.is_empty() => vec![0.0; dim],
+                Point::Repeat(i) => coords[*i as usize % coords.len()].clone(),
+            };
+            coords.push(c);
+        }
+
+        if coords.len() < n {
+            return None;
+        }
+        let mut unpicked = (0..coords.len()).collect::<Vec<_>>();
+        let indexes = self.indexes[..n].iter()
+            .map(|i| unpicked.remove(*i as usize % unpicked.len()))
+            .collect();
+        Some((coords.iter().map(|c| point(c)).collect(), indexes))
+    }
+}
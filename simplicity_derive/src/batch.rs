@@ -0,0 +1,247 @@
+//! Generation of `_batch`, `_batch_async`, and `_multi` variants of a predicate.
+//!
+//! The `_batch` variant evaluates the predicate on many tuples of indexes,
+//! fetching each point only once.
+//! The `_batch_async` variant does the same with an async accessor. It awaits the points
+//! one at a time, in the order the tuples need them, evaluates each tuple as soon as its points
+//! have arrived, and drops each point after the last tuple that uses it, so only the points
+//! that later tuples still need are kept in memory.
+//! The `_multi` variant fixes all points but the last and evaluates
+//! the predicate for many last points. The cofactors of the last row
+//! of the leading determinant are calculated once and shared, and each query
+//! takes their dot product with its lifted point in floating point, with an error bound.
+//! The dot product is only calculated exactly when that bound doesn't decide its sign,
+//! and the scalar predicate is only called when the leading determinant is 0
+//! or the fixed points are small enough to need prescaling.
+
+use crate::sos::{self, SosPredicate};
+use crate::InHypersphere;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::punctuated::Punctuated;
+use syn::{Error, Expr, FnArg, GenericParam, Ident, ItemFn, Pat, PathArguments, ReturnType, Stmt, Token, Type, TypeParamBound};
+use syn::parse::{Parser, Result};
+
+/// Finds the lifting functions of the generator invoked in a predicate's body.
+fn inferred_columns(item: &ItemFn) -> Result<Vec<Expr>> {
+    let mac = match item.block.stmts.last() {
+        Some(Stmt::Expr(Expr::Macro(mac))) => &mac.mac,
+        Some(Stmt::Item(syn::Item::Macro(mac))) => &mac.mac,
+        _ => return Err(Error::new(Span::call_site(),
+            "expected the body to be a generator invocation; otherwise pass the lifting functions to #[batch]")),
+    };
+    let name = mac.path.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+
+    match name.as_str() {
+        "generate_in_hypersphere" => Ok(crate::hypersphere_columns(mac.parse_body::<InHypersphere>()?.indexes.len() - 2)),
+        "generate_sos_predicate" => Ok(mac.parse_body::<SosPredicate>()?.columns),
+        _ => Err(Error::new(Span::call_site(),
+            "unknown generator; pass the lifting functions to #[batch]")),
+    }
+}
+
+/// Finds the return type of the indexing function's type, `impl Fn(&T, Idx) -> Point`.
+fn point_type(ty: &Type) -> Result<Type> {
+    if let Type::ImplTrait(imp) = ty {
+        for bound in &imp.bounds {
+            if let TypeParamBound::Trait(bound) = bound {
+                let segment = bound.path.segments.last().unwrap();
+                if let PathArguments::Parenthesized(args) = &segment.arguments {
+                    if let ReturnType::Type(_, ty) = &args.output {
+                        return Ok((**ty).clone());
+                    }
+                }
+            }
+        }
+    }
+    Err(Error::new(Span::call_site(), "expected the indexing function to have type `impl Fn(&T, Idx) -> Point`"))
+}
+
+/// Finds the type parameter of the list's type, `&T`.
+fn list_param(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Reference(reference) => match &*reference.elem {
+            Type::Path(path) if path.qself.is_none() => path.path.get_ident(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub(crate) fn batch(attr: TokenStream2, item: ItemFn) -> Result<TokenStream2> {
+    let columns = if attr.is_empty() {
+        inferred_columns(&item)?
+    } else {
+        Punctuated::<Expr, Token![,]>::parse_terminated.parse2(attr)?.into_iter().collect()
+    };
+
+    let args = item.sig.inputs.iter().map(|arg| match arg {
+        FnArg::Typed(arg) => match &*arg.pat {
+            Pat::Ident(ident) => Ok((ident.ident.clone(), (*arg.ty).clone())),
+            _ => Err(Error::new(Span::call_site(), "expected simple parameter names")),
+        },
+        FnArg::Receiver(_) => Err(Error::new(Span::call_site(), "expected a free function")),
+    }).collect::<Result<Vec<_>>>()?;
+
+    if args.len() != columns.len() + 3 {
+        return Err(Error::new(Span::call_site(),
+            format!("expected a list, an indexing function, and {} indexes", columns.len() + 1)));
+    }
+
+    let mut dim = 0;
+    for col in &columns {
+        dim = dim.max(sos::expr_dim(col)?);
+    }
+    let columns = columns.iter().map(|col| sos::expr_to_poly(col, dim)).collect::<Result<Vec<_>>>()?;
+
+    let vis = &item.vis;
+    let name = &item.sig.ident;
+    let generics = &item.sig.generics;
+    let where_clause = &item.sig.generics.where_clause;
+    let (list, list_ty) = &args[0];
+    let (index_fn, index_fn_ty) = &args[1];
+    let point_ty = point_type(index_fn_ty)?;
+    // The points are converted once, as they're fetched
+    let vector_ty = quote! { <#point_ty as ::simplicity::SosPoint>::Vector };
+    let indexes = args[2..].iter().map(|(ident, _)| ident.clone()).collect::<Vec<_>>();
+    let idx_ty = &args[2].1;
+    let n = indexes.len();
+
+    let batch_name = format_ident!("{}_batch", name);
+    let batch_async_name = format_ident!("{}_batch_async", name);
+    let multi_name = format_ident!("{}_multi", name);
+    let batch_doc = format!(
+        "Evaluates [`{}`] for each tuple of indexes, fetching each point only once.",
+        name
+    );
+    let batch_async_doc = format!(
+        "Evaluates [`{}`] for each tuple of indexes, awaiting each point from `fetch` only once. \
+         The points are awaited one at a time, in the order the tuples need them. \
+         Each tuple is evaluated as soon as its points have arrived, \
+         and each point is dropped after the last tuple that uses it.",
+        name
+    );
+    let multi_doc = format!(
+        "Evaluates [`{}`] with all points but the last fixed, once for each query index. \
+         The leading determinant is shared between the queries, and [`{}`] is only called \
+         when it is 0, or when the fixed points are small enough to need prescaling.",
+        name, name
+    );
+
+    // The async variant has no list, since the accessor can capture whatever it needs
+    let mut async_generics = generics.clone();
+    if let Some(list_param) = list_param(list_ty) {
+        async_generics.params = async_generics.params.into_iter()
+            .filter(|param| !matches!(param, GenericParam::Type(param) if param.ident == *list_param))
+            .collect();
+    }
+    async_generics.params.push(syn::parse_quote! { Fut: ::core::future::Future<Output = #point_ty> });
+
+    let fixed = &indexes[..n - 1];
+    let fixed_args = args[2..n + 1].iter().map(|(ident, ty)| quote! { #ident: #ty, }).collect::<TokenStream2>();
+    let fixed_points = crate::point_idents(fixed);
+    let query = Ident::new("query", Span::mixed_site());
+    let query_point = Ident::new("p_query", Span::mixed_site());
+    let lifted = |point: &Ident| {
+        let entries = columns.iter().map(|col| col.tokens(point));
+        quote! { ::simplicity::__private::vec![#(#entries,)* ::simplicity::exact::Expansion::from(1.0)] }
+    };
+    let fixed_rows = fixed_points.iter().map(&lifted);
+    let query_row = lifted(&query_point);
+    let query_entries = columns.iter().map(|col| col.float_tokens(&query_point));
+    let query_coords = (0..dim).map(|c| quote! { #query_point[#c] });
+    let degree = columns.iter().map(sos::Poly::degree).max().unwrap_or(0);
+    let ops = columns.iter().map(sos::Poly::float_ops).max().unwrap_or(0);
+    let index_seq = (0..n).map(|i| quote_spanned! {Span::mixed_site()=> tuple[#i], }).collect::<TokenStream2>();
+    let fixed_seq = fixed.iter().map(|index| quote! { #index, }).collect::<TokenStream2>();
+
+    Ok(quote_spanned! {Span::mixed_site()=>
+        #item
+
+        #[doc = #batch_doc]
+        #vis fn #batch_name #generics(
+            #list: #list_ty,
+            #index_fn: #index_fn_ty,
+            tuples: &[[#idx_ty; #n]],
+        ) -> ::simplicity::__private::Vec<bool> #where_clause {
+            let mut points = ::simplicity::__private::BTreeMap::new();
+            for tuple in tuples {
+                for index in tuple {
+                    points.entry(*index).or_insert_with(|| ::simplicity::SosPoint::to_vector(&#index_fn(#list, *index)));
+                }
+            }
+
+            tuples.iter().map(|tuple| {
+                #name(&points, |points: &::simplicity::__private::BTreeMap<#idx_ty, #vector_ty>, i| points[&i], #index_seq)
+            }).collect()
+        }
+
+        #[doc = #batch_async_doc]
+        #vis async fn #batch_async_name #async_generics(
+            mut fetch: impl FnMut(#idx_ty) -> Fut,
+            tuples: &[[#idx_ty; #n]],
+        ) -> ::simplicity::__private::Vec<bool> #where_clause {
+            // The position of the last tuple that uses each index
+            let mut last_use = ::simplicity::__private::BTreeMap::new();
+            for (t, tuple) in tuples.iter().enumerate() {
+                for index in tuple {
+                    last_use.insert(*index, t);
+                }
+            }
+
+            let mut points = ::simplicity::__private::BTreeMap::new();
+            let mut results = ::simplicity::__private::Vec::with_capacity(tuples.len());
+            for (t, tuple) in tuples.iter().enumerate() {
+                for index in tuple {
+                    if !points.contains_key(index) {
+                        let point = ::simplicity::SosPoint::to_vector(&fetch(*index).await);
+                        points.insert(*index, point);
+                    }
+                }
+                results.push(#name(&points, |points: &::simplicity::__private::BTreeMap<#idx_ty, #vector_ty>, i| points[&i], #index_seq));
+                for index in tuple {
+                    if last_use[index] == t {
+                        points.remove(index);
+                    }
+                }
+            }
+            results
+        }
+
+        #[doc = #multi_doc]
+        #vis fn #multi_name #generics(
+            #list: #list_ty,
+            #index_fn: #index_fn_ty,
+            #fixed_args
+            queries: &[#idx_ty],
+        ) -> ::simplicity::__private::Vec<bool> #where_clause {
+            #(let #fixed_points = ::simplicity::SosPoint::to_vector(&#index_fn(#list, #fixed));)*
+            let cofactors = ::simplicity::exact::last_row_cofactors(&[#(#fixed_rows),*]);
+            let estimates = ::simplicity::exact::estimates(&cofactors);
+            // Cofactors of points small enough to need prescaling may have underflowed
+            let prescaled = ::simplicity::__private::prescale_exponent(&[#(#fixed_points),*]) != 0;
+
+            queries.iter().map(|#query| {
+                let #query_point = ::simplicity::SosPoint::to_vector(&#index_fn(#list, *#query));
+                let val = if prescaled {
+                    0.0
+                } else {
+                    match ::simplicity::exact::filtered_dot_signum(
+                        &estimates,
+                        &[#(#query_coords),*],
+                        &[#(#query_entries,)* (1.0, 1.0)],
+                        #degree,
+                        #ops,
+                    ) {
+                        val if val != 0.0 => val,
+                        _ => ::simplicity::exact::dot(&cofactors, #query_row).signum(),
+                    }
+                };
+                if val != 0.0 {
+                    val > 0.0
+                } else {
+                    #name(#list, &#index_fn, #fixed_seq *#query)
+                }
+            }).collect()
+        }
+    })
+}
@@ -10,7 +10,13 @@ use itertools::Itertools;
 use permutator::Combination;
 use std::{collections::HashSet, fmt::{self, Display, Formatter}};
 use std::iter::{once, repeat};
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+
+mod batch;
+mod point;
+mod sos;
+#[cfg(feature = "verify")]
+mod verify;
 
 struct InHypersphere {
     /// The list to index on
@@ -19,6 +25,8 @@ struct InHypersphere {
     index_fn: Ident,
     /// The list of indexes
     indexes: Vec<Ident>,
+    /// Whether to return the index of the deciding case instead of the sign
+    case_index: bool,
 }
 
 impl Parse for InHypersphere {
@@ -27,12 +35,30 @@ impl Parse for InHypersphere {
         input.parse::<Token![,]>()?;
         let index_fn: Ident = input.parse()?;
         input.parse::<Token![,]>()?;
-        let indexes = input.parse_terminated::<Ident, Token![,]>(Ident::parse)?;
-        
+
+        let mut indexes = vec![];
+        while !input.is_empty() && !input.peek(Token![;]) {
+            indexes.push(input.parse::<Ident>()?);
+            if !input.is_empty() && !input.peek(Token![;]) {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        let case_index = if input.parse::<Option<Token![;]>>()?.is_some() {
+            let mode: Ident = input.parse()?;
+            if mode != "case" {
+                return Err(syn::Error::new(mode.span(), "expected `case`"));
+            }
+            true
+        } else {
+            false
+        };
+
         Ok(InHypersphere {
             list,
             index_fn,
-            indexes: indexes.into_iter().collect()
+            indexes,
+            case_index,
         })
     }
 }
@@ -71,6 +97,24 @@ impl Determinant {
         Some(self)
     }
 
+    /// Exact value at some integer points, before prepare_dets_for_cases.
+    #[cfg(feature = "verify")]
+    fn eval(&self, points: &[Vec<i128>]) -> i128 {
+        let dim = points.len() - 2;
+        let rows = self.rows.iter().copied().chain(once(dim + 1)).map(|r| {
+            self.cols.iter().copied().chain(once(dim + 1)).map(|c| {
+                if c < dim {
+                    points[r][c]
+                } else if c == dim {
+                    points[r].iter().map(|x| x * x).sum()
+                } else {
+                    1
+                }
+            }).collect()
+        }).collect::<Vec<_>>();
+        sos::det_i128(&rows)
+    }
+
     /// To be called after prepare_dets_for_cases
     fn vector_tokens(&self, points: &[Ident]) -> TokenStream2 {
         let dim = points.len() - 2;
@@ -92,7 +136,7 @@ impl Determinant {
 
             if coords.len() > 1 {
                 let coords = coords.into_iter().collect::<TokenStream2>();
-                quote! { nalgebra::#vector::new(#coords), }
+                quote! { ::simplicity::nalgebra::#vector::new(#coords), }
             } else {
                 coords.pop().unwrap()
             }
@@ -201,12 +245,25 @@ impl TermSum {
         if self.terms.is_empty() { None } else { Some(self) }
     }
 
+    /// Exact value at some integer points, before prepare_dets_for_cases.
+    #[cfg(feature = "verify")]
+    fn eval(&self, points: &[Vec<i128>]) -> i128 {
+        self.terms.iter().map(|term| {
+            let mult = term.var_mult.map_or(1, |[r, c]| points[r][c]);
+            term.const_mult as i128 * mult * term.det.eval(points)
+        }).sum()
+    }
+
     fn prepare_dets_for_cases(&mut self, dim: usize) {
+        // Terms sharing a determinant keep their signs in their multipliers instead
+        let shared_det = self.terms.len() == 2 && self.terms.iter().all(|t| t.var_mult.is_some());
+
         for term in &mut self.terms {
             // For convenience of including the last point
             term.det.rows.push(dim + 1);
 
-            if term.const_mult < 0 {
+            // A 1×1 determinant has no rows to swap, so it keeps its sign too
+            if term.const_mult < 0 && !shared_det && term.det.rows.len() > 1 {
                 term.const_mult *= -1;
                 let n = term.det.rows.len();
                 term.det.rows.swap(n - 2, n - 1);
@@ -214,10 +271,19 @@ impl TermSum {
         }
     }
 
-    fn case(mut self, points: &[Ident]) -> TokenStream2 {
+    /// `result` is what the case returns if it decides the sign;
+    /// the sign itself if `None`.
+    fn case(mut self, points: &[Ident], result: Option<usize>) -> TokenStream2 {
+        let private = private_path();
+        let odd = odd_ident();
         let coords = "xyzw".chars().collect::<Vec<_>>();
         let dim = points.len() - 2;
         self.prepare_dets_for_cases(dim);
+        // The sign is still evaluated in case it's the only use of a variable
+        let ret = |val: TokenStream2| match result {
+            Some(index) => quote! { { let _ = #val; #index } },
+            None => val,
+        };
 
         if self.terms.len() == 1 && self.terms[0].det.cols.len() == dim + 1 {
             assert_eq!(self.terms[0].const_mult, 1);
@@ -225,18 +291,20 @@ impl TermSum {
 
             if dim == 2 {
                 let [i, j, k, l] = [&points[0], &points[1], &points[2], &points[3]];
+                let ret_val = ret(quote! { (val > 0.0) != #odd });
                 quote! {
-                    let val = rg::in_circle(#i, #j, #k, #l);
+                    let val = #private::rg::in_circle(#i, #j, #k, #l);
                     if val != 0.0 {
-                        return (val > 0.0) != odd;
+                        return #ret_val;
                     }
                 }
             } else if dim == 3 {
                 let [i, j, k, l, m] = [&points[0], &points[1], &points[2], &points[3], &points[4]];
+                let ret_val = ret(quote! { (val > 0.0) != #odd });
                 quote! {
-                    let val = rg::in_sphere(#i, #j, #k, #l, #m);
+                    let val = #private::rg::in_sphere(#i, #j, #k, #l, #m);
                     if val != 0.0 {
-                        return (val > 0.0) != odd;
+                        return #ret_val;
                     }
                 }
             } else {
@@ -256,34 +324,37 @@ impl TermSum {
                     coords[..dim].iter().map(|c| c.to_string() + "2").join(""),
                 )
             };
+            let ret_val = ret(quote! { (val > 0.0) != #odd });
             quote! {
-                let val = rg::#func(#det);
+                let val = #private::rg::#func(#det);
                 if val != 0.0 {
-                    return (val > 0.0) != odd;
+                    return #ret_val;
                 }
             }
         } else if self.terms.len() == 1 {
-            assert_eq!(self.terms[0].const_mult, 1);
+            assert_eq!(self.terms[0].const_mult.abs(), 1);
             assert_eq!(self.terms[0].var_mult, None);
             
             if self.terms[0].det.cols.len() == 0 {
-                quote! { !odd }
+                ret(if self.terms[0].const_mult < 0 { quote! { #odd } } else { quote! { !#odd } })
             } else if self.terms[0].det.cols.len() == 1 {
                 let coord = self.terms[0].det.cols[0];
                 let p1 = &points[self.terms[0].det.rows[0]];
                 let p2 = &points[self.terms[0].det.rows[1]];
+                let ret_val = ret(quote! { (#p1[#coord] > #p2[#coord]) != #odd });
                 quote! {
                     if #p1[#coord] != #p2[#coord] {
-                        return (#p1[#coord] > #p2[#coord]) != odd;
+                        return #ret_val;
                     }
                 }
             } else {
                 let det = self.terms[0].det.vector_tokens(points);
                 let func = format_ident!("orient_{}d", self.terms[0].det.cols.len());
+                let ret_val = ret(quote! { (val > 0.0) != #odd });
                 quote! {
-                    let val = rg::#func(#det);
+                    let val = #private::rg::#func(#det);
                     if val != 0.0 {
-                        return (val > 0.0) != odd;
+                        return #ret_val;
                     }
                 }
             }
@@ -304,49 +375,56 @@ impl TermSum {
                 coords[..dim].iter().map(|c| c.to_string() + "2").join(""),
                 coords[..self.terms[1].det.cols.len()].iter().join("_"),
             );
+            let ret_val = ret(quote! { (val > 0.0) != #odd });
             quote! { 
-                let val = rg::#func(#det1 #mult[#mult_coord], #det2);
+                let val = #private::rg::#func(#det1 #mult[#mult_coord], #det2);
                 if val != 0.0 {
-                    return (val > 0.0) != odd;
+                    return #ret_val;
                 }
             }
         } else if self.terms.len() == 2 {
-            assert_eq!(self.terms[0].const_mult, 2);
+            assert_eq!(self.terms[0].const_mult.abs(), 2);
             assert_ne!(self.terms[0].det.cols.last(), Some(&dim));
-            assert_eq!(self.terms[1].const_mult, 2);
+            assert_eq!(self.terms[1].const_mult.abs(), 2);
             assert!(self.terms[1].var_mult.is_some());
             assert_eq!(self.terms[0].det, self.terms[1].det);
 
+            let sign = |term: &Term| if term.const_mult < 0 { quote! { - } } else { quote! {} };
             let mult1 = &points[self.terms[0].var_mult.unwrap()[0]];
             let mult1_coord = self.terms[0].var_mult.unwrap()[1];
+            let mult1 = { let sign = sign(&self.terms[0]); quote! { #sign #mult1[#mult1_coord] } };
             let mult2 = &points[self.terms[1].var_mult.unwrap()[0]];
             let mult2_coord = self.terms[1].var_mult.unwrap()[1];
+            let mult2 = { let sign = sign(&self.terms[1]); quote! { (#sign #mult2[#mult2_coord]) } };
             
             let inner = if self.terms[0].det.cols.len() == 0 {
-                quote! { return negate == odd; }
+                let ret_val = ret(quote! { negate == #odd });
+                quote! { return #ret_val; }
             } else if self.terms[0].det.cols.len() == 1 {
                 let coord = self.terms[0].det.cols[0];
                 let p1 = &points[self.terms[0].det.rows[0]];
                 let p2 = &points[self.terms[0].det.rows[1]];
+                let ret_val = ret(quote! { (#p1[#coord] > #p2[#coord]) != (negate != #odd) });
                 quote! {
                     if #p1[#coord] != #p2[#coord] {
-                        return (#p1[#coord] > #p2[#coord]) != (negate != odd);
+                        return #ret_val;
                     }
                 }
             } else {
                 let det = self.terms[0].det.vector_tokens(points);
                 let func = format_ident!("orient_{}d", self.terms[0].det.cols.len());
+                let ret_val = ret(quote! { (val > 0.0) != (negate != #odd) });
                 quote! {
-                    let val = rg::#func(#det);
+                    let val = #private::rg::#func(#det);
                     if val != 0.0 {
-                        return (val > 0.0) != (negate != odd);
+                        return #ret_val;
                     }
                 }
             };
 
             quote! {
-                if #mult1[#mult1_coord] != -#mult2[#mult2_coord] {
-                    let negate = #mult1[#mult1_coord] < -#mult2[#mult2_coord];
+                if #mult1 != -#mult2 {
+                    let negate = #mult1 < -#mult2;
                     #inner
                 }
             }
@@ -375,6 +453,21 @@ impl EFactor {
         Self(coords.into_iter().map(|[r, c]| 3u64.pow((dim * r + dim - 1 - c) as u32)).sum())
     }
 
+    /// The exponent of each coordinate of each of `n` points.
+    #[cfg(feature = "verify")]
+    fn exponents(mut self, dim: usize, n: usize) -> Vec<Vec<u32>> {
+        (0..n).map(|_| {
+            let mut digits = (0..dim).map(|_| {
+                let digit = (self.0 % 3) as u32;
+                self.0 /= 3;
+                digit
+            }).collect::<Vec<_>>();
+            // Less significant digits are later coordinates
+            digits.reverse();
+            digits
+        }).collect()
+    }
+
     fn to_repr(mut self, indexes: &[Ident]) -> String {
         let coords = "xyzw".chars().collect::<Vec<_>>();
         let mut res = String::new();
@@ -445,7 +538,8 @@ fn terms(dim: usize) -> Vec<(EFactor, Term)> {
             for rc in remove.chunks_exact(2) {
                 let er = rows.remove(rc[0]);
                 let ec = cols.remove(rc[1]);
-                if (er + ec) % 2 == 1 {
+                // Cofactor sign by position in the remaining matrix, not in the original one
+                if (rc[0] + rc[1]) % 2 == 1 {
                     mult *= -1;
                 }
                 e_factors.push([er, ec]);
@@ -519,27 +613,99 @@ fn term_sums(dim: usize) -> Vec<(EFactor, TermSum)> {
     sums
 }
 
+/// The lifting functions of the in-hypersphere determinant:
+/// the coordinates and the squared magnitude.
+fn hypersphere_columns(dim: usize) -> Vec<syn::Expr> {
+    let coords = &["x", "y", "z", "w"][..dim];
+    let magnitude = coords.iter().map(|c| format!("{}*{}", c, c)).collect::<Vec<_>>().join(" + ");
+    coords.iter().copied().chain(once(magnitude.as_str()))
+        .map(|col| syn::parse_str(col).unwrap())
+        .collect()
+}
+
+/// The module with the items generated code depends on.
+/// Proc macros can't use `$crate`, so `simplicity` refers to itself by name.
+fn private_path() -> TokenStream2 {
+    quote! { ::simplicity::__private }
+}
+
+/// The parity of the sorting permutation in generated code.
+/// It resolves at the macro's definition site so it can't clash with the caller's variables.
+fn odd_ident() -> Ident {
+    Ident::new("odd", Span::mixed_site())
+}
+
+/// The names of the points of some indexes in generated code.
+fn point_idents(indexes: &[Ident]) -> Vec<Ident> {
+    indexes.iter().map(|index| format_ident!("p{}", index, span = Span::mixed_site())).collect()
+}
+
+/// Generates code that sorts the indexes in place and binds the parity of the permutation.
+fn sort_tokens(indexes: &[Ident]) -> TokenStream2 {
+    let odd = odd_ident();
+    let n = indexes.len();
+
+    quote_spanned! {Span::mixed_site()=>
+        let ([#(#indexes),*], #odd) = {
+            let mut arr = [#(#indexes),*];
+            let mut odd = false;
+            for i in 1..#n {
+                for j in (0..i).rev() {
+                    if arr[j] > arr[j + 1] {
+                        arr.swap(j, j + 1);
+                        odd = !odd;
+                    } else {
+                        break;
+                    }
+                }
+            }
+            (arr, odd)
+        };
+    }
+}
+
 fn fn_body(h: InHypersphere, sums: Vec<(EFactor, TermSum)>) -> TokenStream2 {
     let list = h.list;
     let index_fn = h.index_fn;
+    let case_index = h.case_index;
     let dim = h.indexes.len() - 2;
 
-    let sorted = format_ident!("sorted_{}", h.indexes.len());
-    let index_seq = h.indexes.iter().map(|index| quote!{#index,}).collect::<TokenStream2>();
+    let sort = sort_tokens(&h.indexes);
 
-    let points = h.indexes.iter().map(|index| format_ident!("p{}", index)).collect::<Vec<_>>();
+    let points = point_idents(&h.indexes);
     let indexing_seq = h.indexes.iter().zip(points.iter()).map(|(index, point)| quote! {
         let #point = #index_fn(#list, #index);
     }).collect::<TokenStream2>();
 
     let mut zero_dets = HashSet::new();
-    let cases = sums.into_iter()
+    let sums = sums.into_iter()
         .flat_map(|(e, sum)| sum.without_zero_dets(dim, &mut zero_dets).map(|sum| (e, sum)))
-        .map(|(_, sum)| sum.case(&points))
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "verify")]
+    {
+        let n = h.indexes.len();
+        let columns = hypersphere_columns(dim).iter()
+            .map(|col| sos::expr_to_poly(col, dim))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let claims = sums.iter().map(|(e, sum)| verify::Claim {
+            exponents: e.exponents(dim, n),
+            eval: Box::new(move |points| sum.eval(points)),
+        }).collect::<Vec<_>>();
+        if let Err(msg) = verify::verify(n, dim, &columns, &claims, true) {
+            return syn::Error::new(Span::call_site(), format!("verification of the cascade failed: {}", msg))
+                .to_compile_error();
+        }
+    }
+
+    let cases = sums.into_iter()
+        .enumerate()
+        .map(|(i, (_, sum))| sum.case(&points, if case_index { Some(i) } else { None }))
         .collect::<TokenStream2>();
 
     let tokens = quote! { 
-        let ([#index_seq], odd) = #sorted([#index_seq]);
+        #sort
 
         #indexing_seq
 
@@ -549,6 +715,10 @@ fn fn_body(h: InHypersphere, sums: Vec<(EFactor, TermSum)>) -> TokenStream2 {
     tokens
 }
 
+/// Generates the body of an in-hypersphere predicate.
+///
+/// Takes the list, the indexing function, and the indexes of the points.
+/// With a trailing `; case`, the body returns the index of the case that decides the sign instead.
 #[proc_macro]
 pub fn generate_in_hypersphere(input: TokenStream) -> TokenStream {
     let h = syn::parse_macro_input!(input as InHypersphere);
@@ -577,4 +747,82 @@ pub fn generate_in_hypersphere(input: TokenStream) -> TokenStream {
     //})).collect::<TokenStream2>();
 
     TokenStream::from(fn_body(h, sums))
-}
\ No newline at end of file
+}
+
+/// Generates the body of a predicate that returns whether the determinant
+/// of a matrix with one row per point is positive after perturbing the points.
+///
+/// Takes the list, the indexing function, the indexes of the points,
+/// and after a `;`, one lifting function per column. A column of 1's is
+/// implicitly appended, so there must be one fewer lifting function than points.
+/// Lifting functions are polynomials in the point's coordinates, named `x`, `y`, `z`, `w`
+/// or `p[n]`, using `+`, `-`, `*`, and integer literals. Each coordinate can have
+/// degree at most 2 in each lifting function.
+///
+/// For points stored as separate coordinate slices, the list and indexing function
+/// can be replaced by the slices in brackets. The generated code then reads the coordinates
+/// of each point straight from the slices, and the indexes must be `usize`.
+/// If every lifting function is homogeneous, tiny points are scaled up by a power of 2 first,
+/// like the predicates in the crate root, so their products don't underflow.
+///
+/// The generated code refers to items through `::simplicity`,
+/// so the calling crate must depend on `simplicity`.
+///
+/// ```ignore
+/// // In-circle test
+/// generate_sos_predicate!{list, index_fn, i, j, k, l; x, y, x*x + y*y}
+/// // In-circle test on coordinate slices
+/// generate_sos_predicate!{[xs, ys], i, j, k, l; x, y, x*x + y*y}
+/// ```
+#[proc_macro]
+pub fn generate_sos_predicate(input: TokenStream) -> TokenStream {
+    let p = syn::parse_macro_input!(input as sos::SosPredicate);
+
+    match sos::fn_body(p) {
+        Ok(tokens) => TokenStream::from(tokens),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+/// Emits `<name>_batch`, `<name>_batch_async`, and `<name>_multi` variants next to a predicate.
+///
+/// `<name>_batch` takes a slice of index tuples and fetches each point only once.
+/// `<name>_batch_async` takes an async accessor instead of a list and an indexing function,
+/// and evaluates each tuple as soon as its points have arrived.
+/// `<name>_multi` takes all indexes but the last, and a slice of last indexes.
+/// The cofactors of the leading determinant are calculated once,
+/// and the predicate itself is only called for queries that make that determinant 0.
+///
+/// The lifting functions of the determinant's columns are inferred if the body
+/// is a `generate_in_hypersphere` or `generate_sos_predicate` invocation,
+/// and otherwise must be passed in the same syntax as `generate_sos_predicate`'s.
+///
+/// The indexing function's points must implement `SosPoint`, and they're converted
+/// to its vector type once, as they're fetched.
+///
+/// ```ignore
+/// #[batch(x, y)]
+/// pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(/* ... */) -> bool { /* ... */ }
+/// ```
+#[proc_macro_attribute]
+pub fn batch(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemFn);
+
+    match batch::batch(TokenStream2::from(attr), item) {
+        Ok(tokens) => TokenStream::from(tokens),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
+
+/// Implements `simplicity::SosPoint` for a struct whose coordinate fields are annotated
+/// with `#[sos(x)]`, `#[sos(y)]`, `#[sos(z)]`, `#[sos(w)]`, or `#[sos(n)]` for coordinate *n*.
+/// The coordinates must be contiguous from `x`.
+#[proc_macro_derive(SosPoint, attributes(sos))]
+pub fn derive_sos_point(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    match point::derive_sos_point(input) {
+        Ok(tokens) => TokenStream::from(tokens),
+        Err(err) => TokenStream::from(err.to_compile_error()),
+    }
+}
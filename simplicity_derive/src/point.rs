@@ -0,0 +1,93 @@
+//! `#[derive(SosPoint)]`, implementing `simplicity::SosPoint` for structs
+//! whose coordinate fields are annotated with `#[sos(x)]`, `#[sos(y)]`, etc.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::{Data, DeriveInput, Error, Fields, Index, Lit, Meta, NestedMeta};
+use syn::parse::Result;
+
+/// The maximum number of coordinates, which is the largest vector the predicates take.
+const MAX_DIM: usize = 5;
+
+/// Finds the coordinate a field is annotated with, if any.
+fn field_coord(attrs: &[syn::Attribute]) -> Result<Option<usize>> {
+    let mut coord = None;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("sos")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) if list.nested.len() == 1 => list,
+            meta => return Err(Error::new_spanned(meta, "expected #[sos(x)], #[sos(y)], #[sos(z)], #[sos(w)], or #[sos(n)]")),
+        };
+        let index = match &list.nested[0] {
+            NestedMeta::Meta(Meta::Path(path)) => path.get_ident()
+                .and_then(|ident| crate::sos::COORD_NAMES.iter().position(|name| ident == name)),
+            NestedMeta::Lit(Lit::Int(int)) => int.base10_parse::<usize>().ok(),
+            _ => None,
+        };
+        let index = index.ok_or_else(|| Error::new_spanned(&list.nested[0], "unknown coordinate; expected x, y, z, w, or an integer"))?;
+        if coord.replace(index).is_some() {
+            return Err(Error::new_spanned(attr, "a field can only be one coordinate"));
+        }
+    }
+    Ok(coord)
+}
+
+pub(crate) fn derive_sos_point(input: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new(Span::call_site(), "SosPoint can only be derived for structs")),
+    };
+
+    let mut coords = vec![None; MAX_DIM];
+    let members = match fields {
+        Fields::Named(fields) => fields.named.iter().map(|f| {
+            let ident = f.ident.as_ref().unwrap();
+            quote! { #ident }
+        }).collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len()).map(|i| {
+            let index = Index::from(i);
+            quote! { #index }
+        }).collect(),
+        Fields::Unit => vec![],
+    };
+    for (field, member) in fields.iter().zip(members) {
+        if let Some(coord) = field_coord(&field.attrs)? {
+            let slot = coords.get_mut(coord)
+                .ok_or_else(|| Error::new_spanned(field, format!("at most {} coordinates are supported", MAX_DIM)))?;
+            if slot.replace(member).is_some() {
+                return Err(Error::new_spanned(field, "coordinate is already a different field"));
+            }
+        }
+    }
+
+    let dim = coords.iter().position(Option::is_none).unwrap_or(MAX_DIM);
+    if dim == 0 {
+        return Err(Error::new(Span::call_site(), "expected a field annotated with #[sos(x)]"));
+    }
+    if coords[dim..].iter().any(Option::is_some) {
+        return Err(Error::new(Span::call_site(), format!("coordinate {} is missing", dim)));
+    }
+    let coords = coords[..dim].iter().map(|member| member.as_ref().unwrap()).collect::<Vec<_>>();
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let vector = format_ident!("Vector{}", dim);
+    let indexes = 0..dim;
+
+    Ok(quote! {
+        impl #impl_generics ::simplicity::SosPoint for #name #ty_generics #where_clause {
+            type Vector = ::simplicity::nalgebra::#vector<f64>;
+
+            const DIM: usize = #dim;
+
+            fn coord(&self, i: usize) -> f64 {
+                match i {
+                    #(#indexes => f64::from(self.#coords),)*
+                    _ => panic!("coordinate {} out of bounds for {} coordinates", i, #dim),
+                }
+            }
+
+            fn to_vector(&self) -> Self::Vector {
+                ::simplicity::nalgebra::#vector::new(#(f64::from(self.#coords)),*)
+            }
+        }
+    })
+}
@@ -0,0 +1,878 @@
+//! Generation of SoS cascades for arbitrary determinant predicates.
+//!
+//! The predicate is the sign of the determinant of a matrix
+//! with one row per point. Each column is a polynomial in the
+//! coordinates of that row's point (a *lifting function*),
+//! and a column of 1's is implicitly appended.
+//!
+//! Perturbing coordinate *c* of the point of rank *r* by ε^(3^(*d*\**r* + *d* - 1 - *c*))
+//! and expanding by multilinearity in the rows, the coefficient of each ε-monomial
+//! is a determinant whose rows are Taylor coefficients of the lifting functions.
+//! Those coefficients are visited in order of increasing ε-exponent
+//! until one of them is a nonzero constant.
+//! Identically zero coefficients are skipped, and so are coefficients
+//! that are implied to be 0 by the coefficients that came before them.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use std::collections::BTreeMap;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{BinOp, Error, Expr, Ident, Lit, Token, UnOp};
+
+/// Names of the first few coordinates.
+pub(crate) const COORD_NAMES: [&str; 4] = ["x", "y", "z", "w"];
+
+/// How generated code reads the coordinates of the points.
+enum Layout {
+    /// Points are fetched from a list with an indexing function.
+    Aos {
+        /// The list to index on
+        list: Ident,
+        /// The indexing function
+        index_fn: Ident,
+    },
+    /// Each coordinate has its own slice, read directly at the indexes.
+    Soa(Vec<Ident>),
+}
+
+pub(crate) struct SosPredicate {
+    /// Where the points come from
+    layout: Layout,
+    /// The list of indexes
+    indexes: Vec<Ident>,
+    /// The lifting functions, one per column
+    pub(crate) columns: Vec<Expr>,
+}
+
+impl Parse for SosPredicate {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let layout = if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let slices = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+            Layout::Soa(slices.into_iter().collect())
+        } else {
+            let list: Ident = input.parse()?;
+            input.parse::<Token![,]>()?;
+            let index_fn: Ident = input.parse()?;
+            Layout::Aos { list, index_fn }
+        };
+        input.parse::<Token![,]>()?;
+
+        let mut indexes = vec![];
+        loop {
+            indexes.push(input.parse::<Ident>()?);
+            if input.peek(Token![;]) {
+                input.parse::<Token![;]>()?;
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+
+        let columns = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
+
+        Ok(SosPredicate {
+            layout,
+            indexes,
+            columns: columns.into_iter().collect(),
+        })
+    }
+}
+
+/// A polynomial with integer coefficients in the coordinates of a single point.
+/// Monomials are stored as exponent vectors.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Poly {
+    terms: BTreeMap<Vec<u32>, i64>,
+}
+
+impl Poly {
+    fn constant(dim: usize, value: i64) -> Self {
+        let mut terms = BTreeMap::new();
+        if value != 0 {
+            terms.insert(vec![0; dim], value);
+        }
+        Self { terms }
+    }
+
+    fn coord(dim: usize, coord: usize) -> Self {
+        let mut exps = vec![0; dim];
+        exps[coord] = 1;
+        Self { terms: once_map(exps, 1) }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    fn constant_term(&self) -> i64 {
+        self.terms.iter().find(|(m, _)| m.iter().all(|e| *e == 0)).map_or(0, |(_, c)| *c)
+    }
+
+    /// The constant value if this polynomial is constant.
+    fn as_constant(&self) -> Option<i64> {
+        if self.terms.keys().all(|m| m.iter().all(|e| *e == 0)) {
+            Some(self.constant_term())
+        } else {
+            None
+        }
+    }
+
+    fn add_term(&mut self, mono: Vec<u32>, coeff: i64) {
+        let entry = self.terms.entry(mono).or_insert(0);
+        *entry += coeff;
+        if *entry == 0 {
+            self.terms.retain(|_, c| *c != 0);
+        }
+    }
+
+    fn add(mut self, rhs: &Poly) -> Self {
+        for (mono, coeff) in &rhs.terms {
+            self.add_term(mono.clone(), *coeff);
+        }
+        self
+    }
+
+    fn neg(mut self) -> Self {
+        for coeff in self.terms.values_mut() {
+            *coeff = -*coeff;
+        }
+        self
+    }
+
+    fn mul(&self, rhs: &Poly) -> Self {
+        let mut res = Poly::default();
+        for (m1, c1) in &self.terms {
+            for (m2, c2) in &rhs.terms {
+                let mono = m1.iter().zip(m2).map(|(a, b)| a + b).collect();
+                res.add_term(mono, c1 * c2);
+            }
+        }
+        res
+    }
+
+    /// Whether all the monomials have the same degree, so scaling the point scales the value.
+    fn is_homogeneous(&self) -> bool {
+        let mut degrees = self.terms.keys().map(|mono| mono.iter().sum::<u32>());
+        degrees.next().map_or(true, |first| degrees.all(|d| d == first))
+    }
+
+    /// The maximum exponent of each coordinate.
+    fn degrees(&self, dim: usize) -> Vec<u32> {
+        let mut degs = vec![0; dim];
+        for mono in self.terms.keys() {
+            for (d, e) in degs.iter_mut().zip(mono) {
+                *d = (*d).max(*e);
+            }
+        }
+        degs
+    }
+
+    /// The Taylor coefficient of δ^α in the expansion of this polynomial at **p** + **δ**;
+    /// the α-th partial derivative divided by α!.
+    pub(crate) fn taylor(&self, alpha: &[u32]) -> Self {
+        let mut res = Poly::default();
+        for (mono, coeff) in &self.terms {
+            if mono.iter().zip(alpha).any(|(m, a)| m < a) {
+                continue;
+            }
+            let mult = mono.iter().zip(alpha).map(|(m, a)| binomial(*m, *a)).product::<i64>();
+            let mono = mono.iter().zip(alpha).map(|(m, a)| m - a).collect();
+            res.add_term(mono, coeff * mult);
+        }
+        res
+    }
+
+    fn eval_mod(&self, values: &[u64]) -> u64 {
+        self.terms.iter().fold(0, |acc, (mono, coeff)| {
+            let term = mono.iter().zip(values).fold(from_i64(*coeff), |acc, (e, v)| {
+                (0..*e).fold(acc, |acc, _| mul_mod(acc, *v))
+            });
+            add_mod(acc, term)
+        })
+    }
+
+    /// Exact value of this polynomial at a small integer point.
+    #[cfg(feature = "verify")]
+    pub(crate) fn eval_i128(&self, values: &[i128]) -> i128 {
+        self.terms.iter().map(|(mono, coeff)| {
+            mono.iter().zip(values).fold(*coeff as i128, |acc, (e, v)| acc * v.pow(*e))
+        }).sum()
+    }
+
+    /// The total degree.
+    pub(crate) fn degree(&self) -> u32 {
+        self.terms.keys().map(|mono| mono.iter().sum::<u32>()).max().unwrap_or(0)
+    }
+
+    /// How many roundings a term can go through in [`float_tokens_with`](Self::float_tokens_with).
+    pub(crate) fn float_ops(&self) -> u32 {
+        self.degree() + self.terms.len() as u32
+    }
+
+    /// Code calculating this polynomial in floating point at some point,
+    /// along with the sum of the absolute values of its terms.
+    pub(crate) fn float_tokens(&self, point: &Ident) -> TokenStream2 {
+        self.float_tokens_with(|c| quote! { #point[#c] })
+    }
+
+    /// Code calculating this polynomial in floating point, given the code reading each coordinate,
+    /// along with the sum of the absolute values of its terms.
+    fn float_tokens_with(&self, coord: impl Fn(usize) -> TokenStream2) -> TokenStream2 {
+        let len = self.terms.len();
+        let terms = self.terms.iter().map(|(mono, coeff)| {
+            let coeff = *coeff as f64;
+            let factors = mono.iter().enumerate().flat_map(|(c, e)| (0..*e).map(move |_| c)).map(&coord);
+            quote! { #coeff #(* #factors)* }
+        });
+        quote! {{
+            let terms: [f64; #len] = [#(#terms),*];
+            (terms.iter().sum::<f64>(), terms.iter().map(|t| t.abs()).sum::<f64>())
+        }}
+    }
+
+    /// Code calculating this polynomial exactly at some point.
+    pub(crate) fn tokens(&self, point: &Ident) -> TokenStream2 {
+        self.tokens_with(|c| quote! { #point[#c] })
+    }
+
+    /// Code calculating this polynomial exactly, given the code reading each coordinate.
+    fn tokens_with(&self, coord: impl Fn(usize) -> TokenStream2) -> TokenStream2 {
+        if self.is_zero() {
+            return quote! { ::simplicity::exact::Expansion::zero() };
+        }
+
+        let terms = self.terms.iter().map(|(mono, coeff)| {
+            if mono.iter().sum::<u32>() == 0 {
+                let coeff = *coeff as f64;
+                return quote! { ::simplicity::exact::Expansion::from(#coeff) };
+            }
+            if *coeff == 1 && mono.iter().sum::<u32>() == 1 {
+                let c = coord(mono.iter().position(|e| *e == 1).unwrap());
+                return quote! { ::simplicity::exact::Expansion::from(#c) };
+            }
+
+            let coeff = *coeff as f64;
+            let factors = mono.iter().enumerate()
+                .flat_map(|(c, e)| (0..*e).map(move |_| c))
+                .map(|c| {
+                    let c = coord(c);
+                    quote! { #c, }
+                })
+                .collect::<TokenStream2>();
+            quote! { ::simplicity::exact::Expansion::product(#coeff, &[#factors]) }
+        });
+        quote! { #(#terms)+* }
+    }
+}
+
+fn once_map(mono: Vec<u32>, coeff: i64) -> BTreeMap<Vec<u32>, i64> {
+    let mut map = BTreeMap::new();
+    map.insert(mono, coeff);
+    map
+}
+
+fn binomial(n: u32, k: u32) -> i64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as i64 / (i + 1) as i64)
+}
+
+/// Finds the coordinate an expression refers to, if it's a coordinate.
+fn coord_index(expr: &Expr) -> Result<Option<usize>> {
+    match expr {
+        Expr::Path(path) => {
+            let ident = path.path.get_ident()
+                .ok_or_else(|| Error::new(path.span(), "expected a coordinate"))?;
+            COORD_NAMES.iter().position(|name| ident == name)
+                .map(Some)
+                .ok_or_else(|| Error::new(ident.span(), "unknown coordinate; expected x, y, z, w, or p[n]"))
+        }
+        Expr::Index(index) => match (&*index.expr, &*index.index) {
+            (Expr::Path(path), Expr::Lit(lit)) if path.path.is_ident("p") => match &lit.lit {
+                Lit::Int(int) => int.base10_parse::<usize>().map(Some),
+                _ => Err(Error::new(lit.span(), "expected a coordinate index")),
+            },
+            _ => Err(Error::new(index.span(), "expected p[n]")),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// The number of coordinates referred to by an expression.
+pub(crate) fn expr_dim(expr: &Expr) -> Result<usize> {
+    if let Some(coord) = coord_index(expr)? {
+        return Ok(coord + 1);
+    }
+    match expr {
+        Expr::Binary(bin) => Ok(expr_dim(&bin.left)?.max(expr_dim(&bin.right)?)),
+        Expr::Unary(un) => expr_dim(&un.expr),
+        Expr::Paren(paren) => expr_dim(&paren.expr),
+        Expr::Group(group) => expr_dim(&group.expr),
+        _ => Ok(0),
+    }
+}
+
+pub(crate) fn expr_to_poly(expr: &Expr, dim: usize) -> Result<Poly> {
+    if let Some(coord) = coord_index(expr)? {
+        return Ok(Poly::coord(dim, coord));
+    }
+    match expr {
+        Expr::Binary(bin) => {
+            let left = expr_to_poly(&bin.left, dim)?;
+            let right = expr_to_poly(&bin.right, dim)?;
+            match bin.op {
+                BinOp::Add(_) => Ok(left.add(&right)),
+                BinOp::Sub(_) => Ok(left.add(&right.neg())),
+                BinOp::Mul(_) => Ok(left.mul(&right)),
+                _ => Err(Error::new(bin.op.span(), "only +, -, and * are supported")),
+            }
+        }
+        Expr::Unary(un) => match un.op {
+            UnOp::Neg(_) => Ok(expr_to_poly(&un.expr, dim)?.neg()),
+            _ => Err(Error::new(un.op.span(), "only unary - is supported")),
+        },
+        Expr::Paren(paren) => expr_to_poly(&paren.expr, dim),
+        Expr::Group(group) => expr_to_poly(&group.expr, dim),
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Int(int) => Ok(Poly::constant(dim, int.base10_parse::<i64>()?)),
+            _ => Err(Error::new(lit.span(), "only integer literals are supported")),
+        },
+        _ => Err(Error::new(expr.span(), "unsupported expression in lifting function")),
+    }
+}
+
+/// Arithmetic modulo a large prime, for probabilistic polynomial identity testing.
+const PRIME: u64 = (1 << 61) - 1;
+
+fn add_mod(a: u64, b: u64) -> u64 {
+    ((a as u128 + b as u128) % PRIME as u128) as u64
+}
+
+fn sub_mod(a: u64, b: u64) -> u64 {
+    add_mod(a, PRIME - b)
+}
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % PRIME as u128) as u64
+}
+
+fn inv_mod(a: u64) -> u64 {
+    let mut res = 1;
+    let mut base = a;
+    let mut exp = PRIME - 2;
+    while exp > 0 {
+        if exp & 1 != 0 {
+            res = mul_mod(res, base);
+        }
+        base = mul_mod(base, base);
+        exp >>= 1;
+    }
+    res
+}
+
+pub(crate) fn from_i64(a: i64) -> u64 {
+    if a >= 0 {
+        a as u64 % PRIME
+    } else {
+        sub_mod(0, (-a) as u64 % PRIME)
+    }
+}
+
+/// Subtracts multiples of the first row from the other rows
+/// so that their entries in column `col` become 0.
+fn eliminate(rows: &mut [Vec<u64>], col: usize) {
+    let (pivot, rest) = rows.split_first_mut().unwrap();
+    let inv = inv_mod(pivot[col]);
+    for row in rest {
+        let factor = mul_mod(row[col], inv);
+        for (e, p) in row[col..].iter_mut().zip(&pivot[col..]) {
+            *e = sub_mod(*e, mul_mod(factor, *p));
+        }
+    }
+}
+
+/// Rank of a list of vectors modulo the prime.
+pub(crate) fn rank_mod(mut rows: Vec<Vec<u64>>) -> usize {
+    let cols = rows.first().map_or(0, |r| r.len());
+    let mut rank = 0;
+    for col in 0..cols {
+        let pivot = match (rank..rows.len()).find(|r| rows[*r][col] != 0) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        eliminate(&mut rows[rank..], col);
+        rank += 1;
+    }
+    rank
+}
+
+/// Determinant of a square matrix modulo the prime.
+fn det_mod(mut rows: Vec<Vec<u64>>) -> u64 {
+    let n = rows.len();
+    let mut det = 1;
+    for col in 0..n {
+        let pivot = match (col..n).find(|r| rows[*r][col] != 0) {
+            Some(pivot) => pivot,
+            None => return 0,
+        };
+        if pivot != col {
+            rows.swap(col, pivot);
+            det = sub_mod(0, det);
+        }
+        det = mul_mod(det, rows[col][col]);
+        eliminate(&mut rows[col..], col);
+    }
+    det
+}
+
+/// Exact determinant of a small integer matrix.
+pub(crate) fn det_i128(rows: &[Vec<i128>]) -> i128 {
+    let n = rows.len();
+    let mut minors = vec![0i128; 1 << n];
+    minors[0] = 1;
+    for cols in 1usize..1 << n {
+        let row = &rows[n - cols.count_ones() as usize];
+        minors[cols] = (0..n).filter(|c| cols & (1 << c) != 0).enumerate()
+            .map(|(pos, col)| {
+                let term = row[col] * minors[cols & !(1 << col)];
+                if pos % 2 == 0 { term } else { -term }
+            })
+            .sum();
+    }
+    minors[(1 << n) - 1]
+}
+
+/// A simple deterministic generator for the evaluation points.
+pub(crate) struct SplitMix(pub(crate) u64);
+
+impl SplitMix {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        (z ^ (z >> 31)) % PRIME
+    }
+}
+
+/// Number of random evaluation points
+const NUM_SAMPLES: usize = 4;
+
+/// A case of the cascade: the determinant of a submatrix of Taylor coefficient rows.
+pub(crate) struct Case {
+    /// For each row of the determinant, the point it belongs to.
+    pub(crate) points: Vec<usize>,
+    /// Entries of the determinant, as polynomials in each row's point.
+    pub(crate) entries: Vec<Vec<Poly>>,
+    /// The ε-exponents of each point's coordinates in the monomial this is the coefficient of
+    #[cfg_attr(not(feature = "verify"), allow(dead_code))]
+    pub(crate) exponents: Vec<Vec<u32>>,
+    /// Whether the coefficient is the negative of the determinant.
+    pub(crate) negate: bool,
+}
+
+/// The result of generating a cascade.
+pub(crate) struct Cascade {
+    /// The cases, in order
+    pub(crate) cases: Vec<Case>,
+    /// Whether the constant term at the end of the cascade is positive
+    pub(crate) last_positive: bool,
+}
+
+struct Generator {
+    /// Number of points, which is also the size of the matrix
+    n: usize,
+    /// Allowed multi-indexes of a row's Taylor coefficients, in order of significance
+    alphas: Vec<Vec<u32>>,
+    /// Taylor coefficients of a row, indexed by multi-index, including the column of 1's
+    taylor: Vec<Vec<Poly>>,
+    /// Row vectors evaluated at random points, indexed by sample, point, and multi-index
+    evals: Vec<Vec<Vec<Vec<u64>>>>,
+    /// Normalized fingerprints of coefficients known to be 0 once they're passed
+    zero: Vec<Vec<u64>>,
+    cascade: Vec<Case>,
+}
+
+impl Generator {
+    fn new(n: usize, dim: usize, columns: Vec<Poly>) -> std::result::Result<Self, String> {
+        let mut degs = vec![0; dim];
+        for col in &columns {
+            for (d, e) in degs.iter_mut().zip(col.degrees(dim)) {
+                *d = (*d).max(e);
+            }
+        }
+        if degs.iter().any(|d| *d > 2) {
+            return Err("each coordinate can have degree at most 2, or the ε-exponents collide".to_owned());
+        }
+
+        // Lexicographic order on (α_x, α_y, ...) is increasing ε-exponent order.
+        let mut alphas = vec![vec![]];
+        for d in &degs {
+            alphas = alphas.into_iter()
+                .flat_map(|alpha: Vec<u32>| (0..=*d).map(move |e| {
+                    let mut alpha = alpha.clone();
+                    alpha.push(e);
+                    alpha
+                }))
+                .collect();
+        }
+
+        let mut taylor = vec![];
+        alphas.retain(|alpha| {
+            let mut row = columns.iter().map(|col| col.taylor(alpha)).collect::<Vec<_>>();
+            row.push(Poly::constant(dim, if alpha.iter().all(|e| *e == 0) { 1 } else { 0 }));
+            if row.iter().all(Poly::is_zero) {
+                false
+            } else {
+                taylor.push(row);
+                true
+            }
+        });
+
+        let mut rng = SplitMix(0x5150_5150);
+        let evals = (0..NUM_SAMPLES).map(|_| {
+            (0..n).map(|_| {
+                let values = (0..dim).map(|_| rng.next()).collect::<Vec<_>>();
+                taylor.iter().map(|row| row.iter().map(|e| e.eval_mod(&values)).collect()).collect()
+            }).collect()
+        }).collect();
+
+        Ok(Self { n, alphas, taylor, evals, zero: vec![], cascade: vec![] })
+    }
+
+    fn rows_eval(&self, sample: usize, chosen: &[(usize, usize)]) -> Vec<Vec<u64>> {
+        chosen.iter().map(|(p, a)| self.evals[sample][*p][*a].clone()).collect()
+    }
+
+    /// Searches the choices of multi-indexes for points 0..=point, given the choices for the later points.
+    /// Returns the sign of the constant term if it was reached.
+    fn search(&mut self, point: usize, chosen: &mut Vec<(usize, usize)>) -> Option<bool> {
+        for a in 0..self.alphas.len() {
+            chosen.push((point, a));
+
+            let dependent = (0..2).all(|s| rank_mod(self.rows_eval(s, chosen)) < chosen.len());
+            if !dependent {
+                let res = if point == 0 { self.visit(chosen) } else { self.search(point - 1, chosen) };
+                if res.is_some() {
+                    chosen.pop();
+                    return res;
+                }
+            }
+
+            chosen.pop();
+        }
+        None
+    }
+
+    fn fingerprint(&self, rows: &[(usize, usize)], cols: &[usize]) -> Option<Vec<u64>> {
+        let values = (0..NUM_SAMPLES).map(|s| {
+            det_mod(rows.iter().map(|(p, a)| cols.iter().map(|c| self.evals[s][*p][*a][*c]).collect()).collect())
+        }).collect::<Vec<_>>();
+        let inv = inv_mod(*values.iter().find(|v| **v != 0)?);
+        Some(values.into_iter().map(|v| mul_mod(v, inv)).collect())
+    }
+
+    /// Whether the Laplace expansion along some set of rows
+    /// only has minors that are known to be 0.
+    fn implied_zero(&self, rows: &[(usize, usize)], cols: &[usize]) -> bool {
+        let known = |rows: &[(usize, usize)], cols: &[usize]| {
+            match self.fingerprint(rows, cols) {
+                Some(f) => self.zero.contains(&f),
+                None => true,
+            }
+        };
+        if known(rows, cols) {
+            return true;
+        }
+
+        let m = rows.len();
+        (1usize..(1 << m) - 1).any(|row_set| {
+            let sub_rows = (0..m).filter(|i| row_set & (1 << i) != 0).map(|i| rows[i]).collect::<Vec<_>>();
+            (0usize..1 << m)
+                .filter(|col_set| col_set.count_ones() == row_set.count_ones())
+                .all(|col_set| {
+                    let sub_cols = (0..m).filter(|i| col_set & (1 << i) != 0).map(|i| cols[i]).collect::<Vec<_>>();
+                    known(&sub_rows, &sub_cols)
+                })
+        })
+    }
+
+    /// Handles a complete choice of multi-indexes.
+    fn visit(&mut self, chosen: &[(usize, usize)]) -> Option<bool> {
+        // Order rows by point
+        let mut rows = chosen.to_vec();
+        rows.reverse();
+
+        let values = (0..NUM_SAMPLES)
+            .map(|s| det_mod(self.rows_eval(s, &rows)))
+            .collect::<Vec<_>>();
+        if values.iter().all(|v| *v == 0) {
+            return None;
+        }
+
+        let at_origin = det_i128(&rows.iter()
+            .map(|(_, a)| self.taylor[*a].iter().map(|e| e.constant_term() as i128).collect())
+            .collect::<Vec<_>>());
+        let origin_mod = if at_origin >= 0 {
+            (at_origin % PRIME as i128) as u64
+        } else {
+            sub_mod(0, ((-at_origin) % PRIME as i128) as u64)
+        };
+        if values.iter().all(|v| *v == origin_mod) {
+            return Some(at_origin > 0);
+        }
+
+        // Expand along rows and columns with a single constant entry
+        let mut row_ids = rows.clone();
+        let mut cols = (0..self.n).collect::<Vec<_>>();
+        let mut negate = false;
+        loop {
+            let entry = |r: &(usize, usize), c: usize| &self.taylor[r.1][c];
+            let single_row = row_ids.iter().enumerate().find_map(|(i, r)| {
+                let nonzero = cols.iter().enumerate().filter(|(_, c)| !entry(r, **c).is_zero()).collect::<Vec<_>>();
+                match nonzero.as_slice() {
+                    [(j, c)] => entry(r, **c).as_constant().map(|v| (i, *j, v)),
+                    _ => None,
+                }
+            });
+            let single = single_row.or_else(|| cols.iter().enumerate().find_map(|(j, c)| {
+                let nonzero = row_ids.iter().enumerate().filter(|(_, r)| !entry(r, *c).is_zero()).collect::<Vec<_>>();
+                match nonzero.as_slice() {
+                    [(i, r)] => entry(r, *c).as_constant().map(|v| (*i, j, v)),
+                    _ => None,
+                }
+            }));
+
+            match single {
+                Some((i, j, value)) => {
+                    negate ^= (value < 0) != ((i + j) % 2 == 1);
+                    row_ids.remove(i);
+                    cols.remove(j);
+                }
+                None => break,
+            }
+        }
+
+        if self.implied_zero(&row_ids, &cols) {
+            return None;
+        }
+        if let Some(f) = self.fingerprint(&row_ids, &cols) {
+            self.zero.push(f);
+        }
+
+        self.cascade.push(Case {
+            points: row_ids.iter().map(|(p, _)| *p).collect(),
+            entries: row_ids.iter().map(|(_, a)| cols.iter().map(|c| self.taylor[*a][*c].clone()).collect()).collect(),
+            exponents: rows.iter().map(|(_, a)| self.alphas[*a].clone()).collect(),
+            negate,
+        });
+        None
+    }
+}
+
+/// The distinct entries of the cases' determinants.
+/// They're calculated once per call, and the cases refer to them by index,
+/// which keeps the generated code small even for cascades with thousands of cases.
+#[derive(Default)]
+struct EntryTable {
+    /// Index of each entry, by the point it's evaluated at (`None` for constants) and its polynomial
+    indexes: BTreeMap<(Option<usize>, Poly), u16>,
+    /// Code calculating each entry
+    tokens: Vec<TokenStream2>,
+}
+
+impl EntryTable {
+    fn index(&mut self, point: usize, poly: &Poly, coord: &impl Fn(usize, usize) -> TokenStream2) -> u16 {
+        let key = (poly.as_constant().map_or(Some(point), |_| None), poly.clone());
+        let tokens = &mut self.tokens;
+        *self.indexes.entry(key).or_insert_with(|| {
+            tokens.push(poly.tokens_with(|c| coord(point, c)));
+            (tokens.len() - 1) as u16
+        })
+    }
+}
+
+impl Case {
+    /// The case's determinant as rows of indexes into the entry table.
+    /// `coord` is the code reading a coordinate of a point.
+    fn table_tokens(&self, table: &mut EntryTable, coord: &impl Fn(usize, usize) -> TokenStream2) -> TokenStream2 {
+        let rows = self.points.iter().zip(&self.entries).map(|(p, row)| {
+            let entries = row.iter().map(|e| table.index(*p, e, coord));
+            quote! { &[#(#entries),*] }
+        }).collect::<Vec<_>>();
+        quote! { &[#(#rows),*] }
+    }
+}
+
+/// The robust-geo predicate that calculates the determinant of `case`, if there is one.
+/// It has to be the whole determinant, with the lifting functions `columns` and then a column of 1s,
+/// and those have to be the coordinates, optionally followed by the squared norm.
+fn rg_predicate(case: &Case, dim: usize, columns: &[Poly]) -> Option<Ident> {
+    let predicate = match (dim, columns.len()) {
+        (2, 2) => "orient_2d",
+        (3, 3) => "orient_3d",
+        (2, 3) => "in_circle",
+        (3, 4) => "in_sphere",
+        _ => return None,
+    };
+    let coords = (0..dim).map(|c| Poly::coord(dim, c)).collect::<Vec<_>>();
+    let norm = coords.iter().fold(Poly::default(), |acc, c| acc.add(&c.mul(c)));
+    let mut expected = coords;
+    if columns.len() > dim {
+        expected.push(norm);
+    }
+    let mut row = expected.clone();
+    row.push(Poly::constant(dim, 1));
+
+    let whole = case.points.iter().copied().eq(0..columns.len() + 1) && case.entries.iter().all(|r| *r == row);
+    (columns == expected.as_slice() && whole).then(|| format_ident!("{}", predicate))
+}
+
+/// Generates the cascade of cases for a predicate.
+pub(crate) fn cascade(n: usize, columns: &[Expr]) -> Result<Cascade> {
+    if columns.len() + 1 != n {
+        return Err(Error::new(
+            Span::call_site(),
+            format!("{} points need {} lifting functions, got {}", n, n - 1, columns.len()),
+        ));
+    }
+
+    let mut dim = 0;
+    for col in columns {
+        dim = dim.max(expr_dim(col)?);
+    }
+    let columns = columns.iter().map(|col| expr_to_poly(col, dim)).collect::<Result<Vec<_>>>()?;
+
+    let mut gen = Generator::new(n, dim, columns.clone())
+        .map_err(|msg| Error::new(Span::call_site(), msg))?;
+    let last_positive = gen.search(n - 1, &mut vec![]).ok_or_else(|| Error::new(
+        Span::call_site(),
+        "the perturbed determinant is identically 0; the lifting functions are dependent",
+    ))?;
+
+    #[cfg(feature = "verify")]
+    {
+        let claims = gen.cascade.iter().map(|case| crate::verify::Claim {
+            exponents: case.exponents.clone(),
+            eval: Box::new(move |points| {
+                let det = det_i128(&case.points.iter().zip(&case.entries)
+                    .map(|(p, row)| row.iter().map(|e| e.eval_i128(&points[*p])).collect())
+                    .collect::<Vec<_>>());
+                if case.negate { -det } else { det }
+            }),
+        }).collect::<Vec<_>>();
+        crate::verify::verify(n, dim, &columns, &claims, last_positive)
+            .map_err(|msg| Error::new(Span::call_site(), format!("verification of the cascade failed: {}", msg)))?;
+    }
+
+    Ok(Cascade { cases: gen.cascade, last_positive })
+}
+
+pub(crate) fn fn_body(p: SosPredicate) -> Result<TokenStream2> {
+    let cascade = cascade(p.indexes.len(), &p.columns)?;
+
+    let sort = crate::sort_tokens(&p.indexes);
+    let odd = crate::odd_ident();
+
+    let points = crate::point_idents(&p.indexes);
+    let indexes = &p.indexes;
+    let dim = p.columns.iter().map(expr_dim).collect::<Result<Vec<_>>>()?.into_iter().max().unwrap_or(0);
+    let columns = p.columns.iter().map(|col| expr_to_poly(col, dim)).collect::<Result<Vec<_>>>()?;
+    let coord = |point: usize, c: usize| {
+        let point = &points[point];
+        quote! { #point[#c] }
+    };
+    let (indexing_seq, rg_predicate) = match &p.layout {
+        Layout::Aos { list, index_fn } => (
+            p.indexes.iter().zip(points.iter()).map(|(index, point)| quote! {
+                let #point = #index_fn(#list, #index);
+            }).collect::<TokenStream2>(),
+            None,
+        ),
+        Layout::Soa(slices) => {
+            if slices.len() < dim {
+                return Err(Error::new(
+                    Span::call_site(),
+                    format!("the lifting functions use {} coordinates, but only {} slices were given", dim, slices.len()),
+                ));
+            }
+            let dim_ty = format_ident!("U{}", slices.len());
+            let gathered = indexes.iter().map(|index| quote! {
+                ::simplicity::nalgebra::VectorN::<f64, ::simplicity::nalgebra::#dim_ty>::from_column_slice(&[#(#slices[#index]),*])
+            }).collect::<Vec<_>>();
+            // Scaling the points only scales the determinant if every column is homogeneous
+            let indexing_seq = if columns.iter().all(Poly::is_homogeneous) {
+                quote! { let [#(#points),*] = ::simplicity::__private::prescaled([#(#gathered),*]); }
+            } else {
+                quote! { #(let #points = #gathered;)* }
+            };
+            // The gathered points are nalgebra vectors, which robust-geo takes
+            let rg_predicate = cascade.cases.first()
+                .filter(|_| slices.len() == dim)
+                .and_then(|case| rg_predicate(case, dim, &columns));
+            (indexing_seq, rg_predicate)
+        }
+    };
+
+    // The first case is the unperturbed determinant, which is usually decided in floating point,
+    // so it gets a fast path and only goes through the exact cascade when that fails
+    let (fast_path, skip) = match (rg_predicate, cascade.cases.first()) {
+        // robust-geo is exact, so the exact cascade can skip the case
+        (Some(predicate), Some(case)) => {
+            let negate = case.negate;
+            (quote! {
+                let val = ::simplicity::__private::rg::#predicate(#(#points),*);
+                if val != 0.0 {
+                    return (val > 0.0) != (#odd != #negate);
+                }
+            }, 1)
+        }
+        (None, Some(case)) if case.points.len() <= 8 => {
+            let mut case_points = case.points.clone();
+            case_points.sort_unstable();
+            case_points.dedup();
+            let coords = case_points.iter().flat_map(|p| (0..dim).map(move |c| coord(*p, c)));
+            let entries = case.points.iter().zip(&case.entries)
+                .flat_map(|(p, row)| row.iter().map(move |e| e.float_tokens_with(|c| coord(*p, c))));
+            let degree = case.entries.iter().flatten().map(Poly::degree).max().unwrap_or(0);
+            let ops = case.entries.iter().flatten().map(Poly::float_ops).max().unwrap_or(0);
+            let negate = case.negate;
+            (quote! {
+                let val = ::simplicity::exact::filtered_det_signum(&[#(#coords),*], &[#(#entries),*], #degree, #ops);
+                if val != 0.0 {
+                    return (val > 0.0) != (#odd != #negate);
+                }
+            }, 0)
+        }
+        _ => (quote! {}, 0),
+    };
+
+    let mut table = EntryTable::default();
+    let cases = cascade.cases[skip..].iter().map(|case| {
+        let rows = case.table_tokens(&mut table, &coord);
+        let negate = case.negate;
+        quote! { (#rows, #negate), }
+    }).collect::<TokenStream2>();
+    let entries = &table.tokens;
+
+    let last = if cascade.last_positive { quote! { !#odd } } else { quote! { #odd } };
+
+    Ok(quote_spanned! {Span::mixed_site()=>
+        #sort
+
+        #indexing_seq
+
+        #fast_path
+
+        let entries = [#(#entries),*];
+        let cases: &[(&[&[u16]], bool)] = &[#cases];
+        for (rows, negate) in cases {
+            let val = ::simplicity::exact::det_from_table(&entries, rows).signum();
+            if val != 0.0 {
+                return (val > 0.0) != (#odd != *negate);
+            }
+        }
+        #last
+    })
+}
@@ -0,0 +1,146 @@
+//! Verification of generated cascades against the ε-expansion
+//! of the perturbed determinant, enabled by the `verify` feature.
+//!
+//! The coefficient of ε^(Σ α_(r,c)·3^(d·r + d - 1 - c)) in the perturbed determinant
+//! is the determinant whose row r is the α_r-th Taylor coefficient of the
+//! lifting functions at point r. For many small integer configurations,
+//! most of them degenerate, this checks with exact arithmetic that
+//! * each case reached has the sign of the coefficient of the ε-monomial it claims to be, and
+//! * the first nonzero case has the sign of the first nonzero coefficient,
+//!   so no case is out of order or wrongly eliminated.
+
+use crate::sos::{det_i128, from_i64, rank_mod, Poly, SplitMix};
+
+/// Number of configurations checked
+const NUM_CONFIGS: usize = 2000;
+
+/// Evaluates a case at some points
+pub(crate) type Eval<'a> = Box<dyn Fn(&[Vec<i128>]) -> i128 + 'a>;
+
+/// A case of a cascade, as claimed by a generator.
+pub(crate) struct Claim<'a> {
+    /// The ε-exponents of each point's coordinates in the monomial this is the coefficient of
+    pub(crate) exponents: Vec<Vec<u32>>,
+    /// Evaluates the case at some points. Only the sign matters.
+    pub(crate) eval: Eval<'a>,
+}
+
+struct Expansion<'a> {
+    n: usize,
+    columns: &'a [Poly],
+    /// Multi-indexes with a nonzero Taylor coefficient, in increasing ε-exponent order
+    alphas: Vec<Vec<u32>>,
+}
+
+impl<'a> Expansion<'a> {
+    fn new(n: usize, dim: usize, columns: &'a [Poly]) -> Self {
+        let mut alphas = vec![vec![]];
+        for _ in 0..dim {
+            alphas = alphas.into_iter()
+                .flat_map(|alpha: Vec<u32>| (0..=2).map(move |e| {
+                    let mut alpha = alpha.clone();
+                    alpha.push(e);
+                    alpha
+                }))
+                .collect();
+        }
+        alphas.retain(|alpha| alpha.iter().all(|e| *e == 0) || columns.iter().any(|col| !col.taylor(alpha).is_zero()));
+
+        Self { n, columns, alphas }
+    }
+
+    /// The α-th Taylor coefficient of the row of a point, including the column of 1's.
+    fn row(&self, point: &[i128], alpha: &[u32]) -> Vec<i128> {
+        let mut row = self.columns.iter().map(|col| col.taylor(alpha).eval_i128(point)).collect::<Vec<_>>();
+        row.push(if alpha.iter().all(|e| *e == 0) { 1 } else { 0 });
+        row
+    }
+
+    /// The coefficient of an ε-monomial.
+    fn coefficient(&self, points: &[Vec<i128>], exponents: &[Vec<u32>]) -> i128 {
+        det_i128(&points.iter().zip(exponents).map(|(p, alpha)| self.row(p, alpha)).collect::<Vec<_>>())
+    }
+
+    /// The exponents and value of the first nonzero coefficient,
+    /// choosing multi-indexes from the last point, the most significant, to the first.
+    fn first_nonzero(&self, points: &[Vec<i128>], chosen: &mut Vec<Vec<u32>>) -> Option<(Vec<Vec<u32>>, i128)> {
+        let point = self.n - 1 - chosen.len();
+        for alpha in &self.alphas {
+            chosen.push(alpha.clone());
+            let rows = chosen.iter().enumerate()
+                .map(|(i, alpha)| self.row(&points[self.n - 1 - i], alpha).into_iter().map(|e| from_i64(e as i64)).collect())
+                .collect::<Vec<_>>();
+
+            if rank_mod(rows) == chosen.len() {
+                if point == 0 {
+                    let exponents = chosen.iter().rev().cloned().collect::<Vec<_>>();
+                    let value = self.coefficient(points, &exponents);
+                    if value != 0 {
+                        chosen.pop();
+                        return Some((exponents, value));
+                    }
+                } else if let Some(res) = self.first_nonzero(points, chosen) {
+                    chosen.pop();
+                    return Some(res);
+                }
+            }
+            chosen.pop();
+        }
+        None
+    }
+}
+
+/// A small configuration of points with integer coordinates,
+/// with many repeated points and coordinates.
+fn config(rng: &mut SplitMix, n: usize, dim: usize) -> Vec<Vec<i128>> {
+    let mut points: Vec<Vec<i128>> = vec![];
+    for _ in 0..n {
+        let point = if !points.is_empty() && rng.next() & 3 == 0 {
+            points[rng.next() as usize % points.len()].clone()
+        } else {
+            (0..dim).map(|_| (rng.next() % 3) as i128 - 1).collect()
+        };
+        points.push(point);
+    }
+    points
+}
+
+/// Checks a cascade for `n` points whose lifting functions are `columns`.
+/// `last_positive` is the result if every case is 0.
+pub(crate) fn verify(n: usize, dim: usize, columns: &[Poly], claims: &[Claim], last_positive: bool) -> Result<(), String> {
+    let expansion = Expansion::new(n, dim, columns);
+    let mut rng = SplitMix(0x7E51_F1ED);
+
+    for _ in 0..NUM_CONFIGS {
+        let points = config(&mut rng, n, dim);
+
+        // Cases can drop terms that are 0 because the previous cases are,
+        // so a case is only the coefficient it claims to be until a case is nonzero.
+        let mut claimed = if last_positive { 1 } else { -1 };
+        for (i, claim) in claims.iter().enumerate() {
+            let sign = (claim.eval)(&points).signum();
+            let actual = expansion.coefficient(&points, &claim.exponents).signum();
+            if sign != actual {
+                return Err(format!(
+                    "case {}, with ε-exponents {:?}, has sign {} instead of {} at points {:?}",
+                    i, claim.exponents, sign, actual, points,
+                ));
+            }
+            if sign != 0 {
+                claimed = sign;
+                break;
+            }
+        }
+
+        let (exponents, actual) = expansion.first_nonzero(&points, &mut vec![])
+            .ok_or_else(|| "the perturbed determinant is identically 0".to_owned())?;
+        if claimed != actual.signum() {
+            return Err(format!(
+                "the cascade's sign is {} instead of {}, from the coefficient with ε-exponents {:?}, at points {:?}",
+                claimed, actual.signum(), exponents, points,
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! Classifying points against axis-aligned boxes consistently with the perturbation.
+//!
+//! Spatial hashes and quadtrees bucket points into boxes that share faces, and a point exactly
+//! on a shared face has to go in exactly one of them. Here each face of a box is given by a point
+//! on it, and each coordinate of a point is compared with the faces' by
+//! [`kdtree::axis_cmp`](crate::kdtree::axis_cmp), which breaks ties the way the perturbation does.
+//! A box contains the points that aren't less than its lower faces and are less than its upper
+//! faces, so boxes that are given the same point for a shared face never both contain a point,
+//! and a grid of them misses nothing inside it.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, aabb};
+//! # use nalgebra::Vector2;
+//! # use std::cmp::Ordering;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(1.0, 0.5),
+//! ];
+//! // Point 3 is on the face x = 1 between the boxes from 0 to 1 and from 1 to 2,
+//! // and point 1 is perturbed further in +x, so it goes in the left box
+//! assert!(aabb::contains(&points, |l, i| l[i], [0, 0], [1, 1], 3));
+//! assert!(!aabb::contains(&points, |l, i| l[i], [1, 1], [2, 2], 3));
+//! let sides = aabb::classify(&points, |l, i| l[i], [1, 1], [2, 2], 3);
+//! assert_eq!(sides, [Ordering::Less, Ordering::Less]);
+//! ```
+
+use crate::kdtree::axis_cmp;
+use core::cmp::Ordering;
+use core::ops::Index;
+
+/// Classifies point `p` against a box after perturbing the points, one axis at a time.
+/// Along each axis, returns `Ordering::Less` if `p` is less than the lower face,
+/// `Ordering::Greater` if it isn't less than the upper face, and `Ordering::Equal` if it's in between.
+/// So a point on a lower face is in the box, and a point on an upper face isn't.
+///
+/// Takes a list of all the points, an indexing function that returns something that can be
+/// indexed by axis, like a vector, the indexes of points on the lower and upper faces along
+/// each axis, and the index of the point. The lower face along each axis should be less than
+/// the upper face after perturbing the points. A box's corners work as its faces.
+pub fn classify<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>, const N: usize>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    lo: [Idx; N],
+    hi: [Idx; N],
+    p: Idx,
+) -> [Ordering; N] {
+    let mut sides = [Ordering::Equal; N];
+    for (axis, side) in sides.iter_mut().enumerate() {
+        *side = if axis_cmp(list, &index_fn, axis, p, lo[axis]) == Ordering::Less {
+            Ordering::Less
+        } else if axis_cmp(list, &index_fn, axis, p, hi[axis]) == Ordering::Less {
+            Ordering::Equal
+        } else {
+            Ordering::Greater
+        };
+    }
+    sides
+}
+
+/// Returns whether a box contains point `p` after perturbing the points;
+/// that is, whether [`classify`] puts it in between along every axis.
+pub fn contains<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>, const N: usize>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    lo: [Idx; N],
+    hi: [Idx; N],
+    p: Idx,
+) -> bool {
+    classify(list, index_fn, lo, hi, p) == [Ordering::Equal; N]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_aabb() {
+        use std::cmp::Ordering;
+
+        // A 3x2 grid of boxes, with the grid lines through points 0 to 3 along x
+        // and 4 to 6 along y, and points on all their boundaries
+        let mut points = (0..4).map(|x| Vector2::new(x as f64, 0.0)).collect::<Vec<_>>();
+        points.extend((0..3).map(|y| Vector2::new(0.0, y as f64)));
+        let mut rng = rng::SplitMix(1476);
+        for _ in 0..60 {
+            points.push(Vector2::new(rng.below(7) as f64 / 2.0, rng.below(5) as f64 / 2.0));
+        }
+        for p in 0..points.len() {
+            let mut containing = 0;
+            for x in 0..3 {
+                for y in 0..2 {
+                    let (lo, hi) = ([x, 4 + y], [x + 1, 5 + y]);
+                    let sides = super::classify(&points, |l, i| l[i], lo, hi, p);
+                    let contains = super::contains(&points, |l, i| l[i], lo, hi, p);
+                    assert_eq!(contains, sides == [Ordering::Equal; 2]);
+                    containing += contains as usize;
+                }
+            }
+            // In exactly one box if it's in the whole grid, and none otherwise
+            let grid = super::contains(&points, |l, i| l[i], [0, 4], [3, 6], p);
+            assert_eq!(containing, grid as usize);
+        }
+    }
+}
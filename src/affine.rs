@@ -0,0 +1,220 @@
+//! Variants of the predicates that apply an affine transform to the points first, exactly.
+//!
+//! Transforming points eagerly rounds their coordinates, which can move points off a line
+//! they were on, or onto one, like when a CAD assembly places the same part several times.
+//! These compute each transformed coordinate as an exact expansion instead, and evaluate
+//! the predicate on those, so they agree with the usual predicates called on the exactly
+//! transformed points. The transformed points are perturbed, so a translation alone
+//! doesn't change any results, even if adding it would round.
+//!
+//! The entries of a transform are `f64`s, so they're dyadic rationals. For a transform with
+//! other rational entries, multiply all of them by a common denominator first. That scales
+//! the points by a positive factor, which doesn't change any signs.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, affine::{self, Affine2}};
+//! # use nalgebra::{Matrix2, Vector2};
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(3.0, 3.0),
+//! ];
+//! let transform = Affine2 { matrix: Matrix2::new(0.1, 0.0, 0.0, 0.1), translation: Vector2::new(1e17, 1.0) };
+//! // Rounding the transformed points would put them all at the same x coordinate
+//! let rounded = points.iter().map(|p| transform.matrix * p + transform.translation).collect::<Vec<_>>();
+//! assert_eq!(rounded[0].x, rounded[2].x);
+//! // But they're still on a line after transforming them exactly
+//! let orient = affine::orient_2d(&points, |l, i| l[i], &transform, 0, 1, 2);
+//! assert_eq!(orient, simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed};
+use crate::{Vec2, Vec3};
+use nalgebra::{Matrix2, Matrix3, Vector2, Vector3};
+use alloc::vec::Vec;
+
+/// A 2D affine transform, which maps **p** to `matrix * p + translation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine2 {
+    pub matrix: Matrix2<f64>,
+    pub translation: Vector2<f64>,
+}
+
+/// A 3D affine transform, which maps **p** to `matrix * p + translation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine3 {
+    pub matrix: Matrix3<f64>,
+    pub translation: Vector3<f64>,
+}
+
+/// Coordinate `c` of the transform of a point, exactly.
+fn transformed(matrix: impl Fn(usize, usize) -> f64, translation: f64, point: &[f64], c: usize) -> Expansion {
+    (0..point.len()).fold(Expansion::from(translation), |acc, k| acc + Expansion::product(matrix(c, k), &[point[k]]))
+}
+
+impl Affine2 {
+    fn apply(&self, p: Vec2) -> [Expansion; 2] {
+        let coord = |c| transformed(|r, k| self.matrix[(r, k)], self.translation[c], p.as_slice(), c);
+        [coord(0), coord(1)]
+    }
+}
+
+impl Affine3 {
+    fn apply(&self, p: Vec3) -> [Expansion; 3] {
+        let coord = |c| transformed(|r, k| self.matrix[(r, k)], self.translation[c], p.as_slice(), c);
+        [coord(0), coord(1), coord(2)]
+    }
+}
+
+/// Evaluates the sign of a determinant with a row for each point but the last, relative to the last,
+/// and with the squared distance to the last point as an extra column if `lifted`.
+fn evaluate<Idx: Ord + Copy, const N: usize>(dim: usize, lifted: bool, indexes: &[Idx], point: impl Fn(Idx) -> [Expansion; N]) -> bool {
+    let degree = if lifted { dim as u64 + 1 } else { dim as u64 };
+    let mut perturbed = Perturbed::new(dim, degree, indexes, point);
+    let (last, rest) = indexes.split_last().unwrap();
+    let sign = perturbed.lazy_signum(|perturbed| {
+        let matrix = rest
+            .iter()
+            .map(|i| {
+                let mut row = (0..dim).map(|c| perturbed.diff(*i, *last, c)).collect::<Vec<_>>();
+                if lifted {
+                    row.push(row.iter().map(|d| d.clone() * d.clone()).fold(Default::default(), |a, b| a + b));
+                }
+                row
+            })
+            .collect::<Vec<_>>();
+        perturbed::det(&matrix)
+    });
+    sign > 0.0
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but on the points transformed by `transform`.
+/// If the transform flips orientation, so does this.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    transform: &Affine2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    evaluate(2, false, &[i, j, k], |i| transform.apply(index_fn(list, i)))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but on the points transformed by `transform`.
+/// If the transform flips orientation, so does this.
+#[allow(clippy::too_many_arguments)]
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    transform: &Affine3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    evaluate(3, false, &[i, j, k, l], |i| transform.apply(index_fn(list, i)))
+}
+
+/// Like [`in_circle`](crate::in_circle), but on the points transformed by `transform`.
+/// Circles don't stay circles under most affine transforms, so this is the in-circle test
+/// of the transformed points, not of the transformed circle.
+#[allow(clippy::too_many_arguments)]
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    transform: &Affine2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    evaluate(2, true, &[i, j, k, l], |i| transform.apply(index_fn(list, i)))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but on the points transformed by `transform`.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    transform: &Affine3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    evaluate(3, true, &[i, j, k, l, m], |i| transform.apply(index_fn(list, i)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_affine() {
+        use super::{Affine2, Affine3};
+        use nalgebra::{Matrix2, Matrix3};
+
+        let points = grid_points_2d();
+        let n = points.len();
+        let transforms = [
+            Affine2 { matrix: Matrix2::new(2.0, 0.5, -1.0, 3.0), translation: Vector2::new(0.25, -1.0) },
+            // Reflects, so orientations flip
+            Affine2 { matrix: Matrix2::new(0.0, 1.0, 1.0, 0.0), translation: Vector2::zeros() },
+            // Collapses everything onto a line
+            Affine2 { matrix: Matrix2::new(1.0, 2.0, 0.5, 1.0), translation: Vector2::new(-3.0, 0.0) },
+        ];
+        for transform in &transforms {
+            // These transforms don't round, so they can be applied eagerly
+            let moved = points.iter().map(|p| transform.matrix * p + transform.translation).collect::<Vec<_>>();
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (j + 1..n).filter(|k| *k != i) {
+                        assert_eq!(
+                            super::orient_2d(&points, |l, i| l[i], transform, i, j, k),
+                            orient_2d(&moved, |l, i| l[i], i, j, k)
+                        );
+                        for l in (k + 1..n).filter(|l| *l != i && *l != j) {
+                            assert_eq!(
+                                super::in_circle(&points, |l, i| l[i], transform, i, j, k, l),
+                                in_circle(&moved, |l, i| l[i], i, j, k, l)
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        // Translations far too large to add without rounding don't change anything
+        let far = Affine2 { matrix: Matrix2::identity(), translation: Vector2::new(1e20, -3e19) };
+        for [i, j, k, l] in [[0, 1, 2, 3], [0, 4, 8, 9], [1, 3, 5, 7], [10, 0, 4, 8]] {
+            assert_eq!(super::orient_2d(&points, |l, i| l[i], &far, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+            assert_eq!(super::in_circle(&points, |l, i| l[i], &far, i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        let transform = Affine3 {
+            matrix: Matrix3::new(1.0, 0.5, 0.0, 0.0, -2.0, 1.0, 0.25, 0.0, 4.0),
+            translation: Vector3::new(1.0, 0.0, -0.5),
+        };
+        let moved = points.iter().map(|p| transform.matrix * p + transform.translation).collect::<Vec<_>>();
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+            assert_eq!(
+                super::orient_3d(&points, |l, i| l[i], &transform, i, j, k, l),
+                orient_3d(&moved, |l, i| l[i], i, j, k, l)
+            );
+            assert_eq!(
+                super::in_sphere(&points, |l, i| l[i], &transform, i, j, k, l, m),
+                in_sphere(&moved, |l, i| l[i], i, j, k, l, m)
+            );
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Alpha complexes and alpha shapes of 2D Delaunay triangulations.
+//!
+//! Each simplex of a Delaunay triangulation enters the alpha complex at some squared radius α,
+//! its *birth*. A triangle is born at its squared circumradius. An edge is born at its squared
+//! half length if no vertex opposite it is inside its diametral circle, and otherwise
+//! with its first triangle. Vertices are born at 0. The alpha complex for α has the simplices
+//! born at α or before, and the alpha shape is its boundary.
+//!
+//! Radii are compared exactly, as fractions of exact products. Whether a vertex is inside
+//! an edge's diametral circle is decided by [`cdt::encroaches`](crate::cdt::encroaches),
+//! but when that's degenerate, the edge is born with its triangle either way.
+//!
+//! A [`Filtration`] can follow points that move or get removed, recomputing only the births
+//! of the simplices around them.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, alpha::Filtration, delaunay2};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(1.0, 3.0),
+//!     Vector2::new(9.0, 1.0),
+//! ];
+//! let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+//! let filtration = Filtration::new(&points, |l, i| l[i], &triangles);
+//!
+//! // The short edge at the bottom is born first
+//! assert_eq!(filtration.complex(1.0).len(), 5);
+//! // Then the other edges of the triangle on the left, and the triangle at 25/9
+//! assert_eq!(filtration.complex(3.0).len(), 8);
+//! assert_eq!(filtration.shape(3.0), vec![[0, 1], [0, 2], [1, 2]]);
+//! ```
+
+use crate::exact::Expansion;
+use crate::Vec2;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::cmp::Ordering;
+
+/// A squared radius, stored exactly as a fraction.
+#[derive(Clone, Debug)]
+pub struct SquaredRadius {
+    num: Expansion,
+    /// Nonnegative, and 0 for an infinite radius
+    den: Expansion,
+}
+
+impl SquaredRadius {
+    fn diff(a: Vec2, b: Vec2, c: usize) -> Expansion {
+        Expansion::from(a[c]) - Expansion::from(b[c])
+    }
+
+    fn squared_length(a: Vec2, b: Vec2) -> Expansion {
+        (0..2).fold(Expansion::zero(), |acc, c| acc + Self::diff(a, b, c) * Self::diff(a, b, c))
+    }
+
+    /// The squared radius of the diametral circle of segment `ab`.
+    pub fn of_edge(a: Vec2, b: Vec2) -> Self {
+        Self { num: Self::squared_length(a, b), den: Expansion::from(4.0) }
+    }
+
+    /// The squared circumradius of triangle `abc`, which is infinite if the points are collinear.
+    pub fn of_triangle(a: Vec2, b: Vec2, c: Vec2) -> Self {
+        let cross = Self::diff(b, a, 0) * Self::diff(c, a, 1) - Self::diff(b, a, 1) * Self::diff(c, a, 0);
+        Self {
+            num: Self::squared_length(a, b) * Self::squared_length(b, c) * Self::squared_length(c, a),
+            den: (cross.clone() * cross).scale(4.0),
+        }
+    }
+
+    /// Whether the radius is infinite.
+    pub fn is_infinite(&self) -> bool {
+        self.den.signum() == 0.0
+    }
+
+    /// Compares this squared radius with α exactly.
+    pub fn cmp_alpha(&self, alpha: f64) -> Ordering {
+        if self.is_infinite() {
+            return Ordering::Greater;
+        }
+        let diff = self.num.clone() - self.den.scale(alpha);
+        diff.signum().partial_cmp(&0.0).unwrap()
+    }
+
+    /// An approximation of the squared radius.
+    pub fn estimate(&self) -> f64 {
+        if self.is_infinite() {
+            f64::INFINITY
+        } else {
+            self.num.estimate() / self.den.estimate()
+        }
+    }
+}
+
+impl Ord for SquaredRadius {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_infinite(), other.is_infinite()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let diff = self.num.clone() * other.den.clone() - other.num.clone() * self.den.clone();
+                diff.signum().partial_cmp(&0.0).unwrap()
+            }
+        }
+    }
+}
+
+impl PartialOrd for SquaredRadius {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SquaredRadius {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SquaredRadius {}
+
+/// A simplex of a triangulation. Simplices are ordered by dimension first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Simplex {
+    Vertex(usize),
+    /// An edge, with its smaller index first
+    Edge([usize; 2]),
+    /// A triangle, oriented positive
+    Triangle([usize; 3]),
+}
+
+/// The simplices of a Delaunay triangulation, sorted by the squared radius they're born at.
+/// Simplices born at the same time are sorted by dimension, so every prefix is a complex.
+#[derive(Clone, Debug)]
+pub struct Filtration {
+    pub simplices: Vec<(Simplex, SquaredRadius)>,
+}
+
+impl Filtration {
+    /// Computes the filtration of a Delaunay triangulation, given a list of all the points,
+    /// an indexing function, and the triangles as triples of indexes oriented positive,
+    /// such as the ones returned by [`delaunay2::triangulate`](crate::delaunay2::triangulate).
+    pub fn new<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, triangles: &[[usize; 3]]) -> Self {
+        let point = |i| index_fn(list, i);
+        let mut simplices = vec![];
+        // The births of the triangles on each edge, with the vertex opposite it
+        let mut edges = BTreeMap::<[usize; 2], Vec<(usize, SquaredRadius)>>::new();
+        let mut vertices = vec![];
+
+        for tri in triangles {
+            let [i, j, k] = *tri;
+            let birth = SquaredRadius::of_triangle(point(i), point(j), point(k));
+            for e in 0..3 {
+                let (a, b) = (tri[e], tri[(e + 1) % 3]);
+                edges.entry([a.min(b), a.max(b)]).or_default().push((tri[(e + 2) % 3], birth.clone()));
+            }
+            vertices.extend_from_slice(tri);
+            simplices.push((Simplex::Triangle(*tri), birth));
+        }
+
+        for (edge, opposite) in edges {
+            simplices.push((Simplex::Edge(edge), Self::edge_birth(list, &index_fn, edge, opposite)));
+        }
+
+        vertices.sort_unstable();
+        vertices.dedup();
+        let zero = SquaredRadius { num: Expansion::zero(), den: Expansion::from(1.0) };
+        simplices.extend(vertices.into_iter().map(|v| (Simplex::Vertex(v), zero.clone())));
+
+        simplices.sort_by(|(s1, b1), (s2, b2)| b1.cmp(b2).then(s1.cmp(s2)));
+        Self { simplices }
+    }
+
+    /// The birth of edge `ab`, given the vertices opposite it and the births of their triangles.
+    fn edge_birth<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, [a, b]: [usize; 2], opposite: Vec<(usize, SquaredRadius)>) -> SquaredRadius {
+        let attached = opposite.iter().any(|(v, _)| crate::cdt::encroaches(list, &index_fn, a, b, *v));
+        if attached {
+            opposite.into_iter().map(|(_, birth)| birth).min().unwrap()
+        } else {
+            SquaredRadius::of_edge(index_fn(list, a), index_fn(list, b))
+        }
+    }
+
+    /// Inserts a simplex where it goes in the order.
+    fn insert(&mut self, simplex: Simplex, birth: SquaredRadius) {
+        let at = self.simplices.partition_point(|(s, b)| b.cmp(&birth).then(s.cmp(&simplex)) == Ordering::Less);
+        self.simplices.insert(at, (simplex, birth));
+    }
+
+    /// Recomputes the births of the simplices that depend on where `point` is, after it moves,
+    /// and leaves the others as they are. Those are the triangles on it and their edges.
+    ///
+    /// The triangles stay the same, so the result is the filtration that [`Filtration::new`]
+    /// would compute for them with the point moved. It's only an alpha complex filtration
+    /// if they're still Delaunay, so a kinetic triangulation should flip the ones that
+    /// aren't first, and update the points of the flipped ones too.
+    pub fn update_point<T: ?Sized>(&mut self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2, point: usize) {
+        self.invalidate(list, index_fn, point, false);
+    }
+
+    /// Removes `point` and the simplices on it, and recomputes the births of the edges
+    /// that were on the removed triangles. The hole isn't retriangulated, so the result is the
+    /// filtration that [`Filtration::new`] would compute for the remaining triangles.
+    pub fn remove_point<T: ?Sized>(&mut self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2, point: usize) {
+        self.invalidate(list, index_fn, point, true);
+    }
+
+    /// Takes out the simplices whose births depend on `point`, and puts them back
+    /// with their births recomputed, except for the ones on it if it's removed.
+    fn invalidate<T: ?Sized>(&mut self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2, point: usize, remove: bool) {
+        let star = self
+            .simplices
+            .iter()
+            .filter_map(|(s, _)| match s {
+                Simplex::Triangle(tri) if tri.contains(&point) => Some(*tri),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut edges = star
+            .iter()
+            .flat_map(|tri| (0..3).map(move |e| [tri[e].min(tri[(e + 1) % 3]), tri[e].max(tri[(e + 1) % 3])]))
+            .collect::<Vec<_>>();
+        edges.sort_unstable();
+        edges.dedup();
+
+        self.simplices.retain(|(s, _)| match s {
+            Simplex::Vertex(v) => !remove || *v != point,
+            Simplex::Edge(edge) => edges.binary_search(edge).is_err(),
+            Simplex::Triangle(tri) => !tri.contains(&point),
+        });
+        if !remove {
+            for tri in &star {
+                let birth = SquaredRadius::of_triangle(index_fn(list, tri[0]), index_fn(list, tri[1]), index_fn(list, tri[2]));
+                self.insert(Simplex::Triangle(*tri), birth);
+            }
+        }
+
+        // The births of the triangles on each affected edge, with the vertex opposite it
+        let mut opposite = BTreeMap::<[usize; 2], Vec<(usize, SquaredRadius)>>::new();
+        for (simplex, birth) in &self.simplices {
+            if let Simplex::Triangle(tri) = simplex {
+                for e in 0..3 {
+                    let (a, b) = (tri[e], tri[(e + 1) % 3]);
+                    if edges.binary_search(&[a.min(b), a.max(b)]).is_ok() {
+                        opposite.entry([a.min(b), a.max(b)]).or_default().push((tri[(e + 2) % 3], birth.clone()));
+                    }
+                }
+            }
+        }
+        // Removed edges and edges left without triangles don't come back
+        for (edge, opposite) in opposite {
+            let birth = Self::edge_birth(list, &index_fn, edge, opposite);
+            self.insert(Simplex::Edge(edge), birth);
+        }
+
+        if remove {
+            let mut orphans = star.iter().flatten().copied().filter(|v| *v != point).collect::<Vec<_>>();
+            orphans.retain(|v| !self.simplices.iter().any(|(s, _)| matches!(s, Simplex::Triangle(tri) if tri.contains(v))));
+            self.simplices.retain(|(s, _)| !matches!(s, Simplex::Vertex(v) if orphans.contains(v)));
+        }
+    }
+
+    /// The simplices of the alpha complex for α, which are the ones born at α or before.
+    pub fn complex(&self, alpha: f64) -> &[(Simplex, SquaredRadius)] {
+        let len = self.simplices.partition_point(|(_, birth)| birth.cmp_alpha(alpha) != Ordering::Greater);
+        &self.simplices[..len]
+    }
+
+    /// The edges of the alpha shape for α, which are the edges of the alpha complex
+    /// on fewer than 2 of its triangles, sorted.
+    pub fn shape(&self, alpha: f64) -> Vec<[usize; 2]> {
+        let mut count = BTreeMap::<[usize; 2], usize>::new();
+        for (simplex, _) in self.complex(alpha) {
+            match simplex {
+                Simplex::Edge(edge) => {
+                    count.entry(*edge).or_default();
+                }
+                Simplex::Triangle(tri) => {
+                    for e in 0..3 {
+                        let (a, b) = (tri[e], tri[(e + 1) % 3]);
+                        *count.entry([a.min(b), a.max(b)]).or_default() += 1;
+                    }
+                }
+                Simplex::Vertex(_) => {}
+            }
+        }
+        let mut shape = count.into_iter().filter(|(_, n)| *n < 2).map(|(edge, _)| edge).collect::<Vec<_>>();
+        shape.sort_unstable();
+        shape
+    }
+
+    /// The distinct squared radii that simplices are born at, in increasing order.
+    pub fn spectrum(&self) -> Vec<SquaredRadius> {
+        let mut spectrum = self.simplices.iter().map(|(_, birth)| birth.clone()).collect::<Vec<_>>();
+        spectrum.dedup();
+        spectrum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{delaunay2, orient_2d, rng};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_alpha() {
+        use super::{Filtration, Simplex};
+
+        // A grid of unit squares, whose triangles have circumradius sqrt(1/2)
+        let points = (0..16).map(|i| Vector2::new((i % 4) as f64, (i / 4) as f64)).collect::<Vec<_>>();
+        let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+        let filtration = Filtration::new(&points, |l, i| l[i], &triangles);
+        let spectrum = filtration.spectrum().iter().map(|r| r.estimate()).collect::<Vec<_>>();
+        assert_eq!(spectrum, vec![0.0, 0.25, 0.5, f64::INFINITY]);
+
+        let count = |alpha, dim: usize| {
+            filtration.complex(alpha).iter().filter(|(s, _)| match s {
+                Simplex::Vertex(_) => dim == 0,
+                Simplex::Edge(_) => dim == 1,
+                Simplex::Triangle(_) => dim == 2,
+            }).count()
+        };
+        assert_eq!((count(0.2, 0), count(0.2, 1), count(0.2, 2)), (16, 0, 0));
+        assert_eq!((count(0.25, 0), count(0.25, 1), count(0.25, 2)), (16, 24, 0));
+        assert_eq!(filtration.shape(0.25).len(), 24);
+        // The diagonals are born with their squares, and the thin triangles at the sides never are
+        assert_eq!((count(0.5, 0), count(0.5, 1), count(0.5, 2)), (16, 33, 18));
+        assert_eq!(filtration.shape(0.5).len(), 12);
+        assert_eq!(filtration.complex(1e300).len(), filtration.complex(0.5).len());
+
+        for (simplex, birth) in filtration.complex(0.5) {
+            if let Simplex::Triangle([i, j, k]) = simplex {
+                assert_eq!(birth.cmp_alpha(0.5), std::cmp::Ordering::Equal);
+                assert!(orient_2d(&points, |l, i| l[i], *i, *j, *k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_alpha_update() {
+
+        let mut rng = rng::SplitMix(3);
+        // Coarse coordinates, so there are cocircular points and ties between births
+        let mut points = (0..30).map(|_| Vector2::new(rng.int(0, 16), rng.int(0, 16)) / 4.0).collect::<Vec<_>>();
+        let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+        let same = |a: &Filtration, b: &Filtration| {
+            a.simplices.len() == b.simplices.len() && a.simplices.iter().zip(&b.simplices).all(|((s1, b1), (s2, b2))| s1 == s2 && b1 == b2)
+        };
+
+        let mut filtration = Filtration::new(&points, |l, i| l[i], &triangles);
+        for point in [0, 7, 25] {
+            points[point] += Vector2::new(0.25, -0.5);
+            filtration.update_point(&points, |l, i| l[i], point);
+            assert!(same(&filtration, &Filtration::new(&points, |l, i| l[i], &triangles)));
+        }
+
+        let mut remaining = triangles.clone();
+        for point in [3, 25, 20] {
+            remaining.retain(|tri| !tri.contains(&point));
+            filtration.remove_point(&points, |l, i| l[i], point);
+            assert!(same(&filtration, &Filtration::new(&points, |l, i| l[i], &remaining)));
+        }
+    }
+}
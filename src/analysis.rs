@@ -0,0 +1,257 @@
+//! Analysis of the exact degeneracies of a point set.
+//!
+//! This finds the subsets of a point set that are degenerate without perturbation,
+//! using the exact unperturbed determinants, to help decide whether the input needs
+//! simulation of simplicity at all or should be cleaned up first.
+//! Each degenerate subset is reported at its lowest level: a triple with a duplicate point
+//! isn't also reported as collinear, 4 collinear points aren't also reported as cocircular,
+//! and so on.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, analysis::{self, Kind, Options}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! let report = analysis::analyze_2d(&points, |l, i| l[i], points.len(), &Options::default());
+//! assert_eq!(report.count(Kind::Collinear), 1);
+//! assert_eq!(report.count(Kind::Cocircular), 1);
+//! ```
+
+use crate::rng::SplitMix;
+use crate::{rg, scale, Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+/// A kind of degeneracy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    /// 2 equal points
+    Duplicate,
+    /// 3 points on a line
+    Collinear,
+    /// 4 points on a circle
+    Cocircular,
+    /// 4 points in 3D on a plane
+    Coplanar,
+    /// 5 points on a sphere
+    Cospherical,
+}
+
+/// A degenerate subset of the points.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Degeneracy {
+    pub kind: Kind,
+    /// The indexes of the points, in increasing order
+    pub indexes: Vec<usize>,
+}
+
+/// The degenerate subsets found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub degeneracies: Vec<Degeneracy>,
+    /// Whether only some of the subsets were checked, so there may be more degeneracies.
+    /// Duplicates are always found exhaustively.
+    pub sampled: bool,
+}
+
+impl Report {
+    /// Whether any degeneracy was found.
+    pub fn is_degenerate(&self) -> bool {
+        !self.degeneracies.is_empty()
+    }
+
+    /// The number of degeneracies of some kind found.
+    pub fn count(&self, kind: Kind) -> usize {
+        self.degeneracies.iter().filter(|d| d.kind == kind).count()
+    }
+}
+
+/// Options for the analysis.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Options {
+    /// If set, checks this many random subsets of each size instead of all of them,
+    /// for point sets too large to check exhaustively.
+    pub samples: Option<usize>,
+    /// The seed for choosing the random subsets
+    pub seed: u64,
+}
+
+/// Calls `f` with every increasing tuple of `k` indexes below `n`.
+pub(crate) fn for_each_combination<E>(n: usize, k: usize, mut f: impl FnMut(&[usize]) -> Result<(), E>) -> Result<(), E> {
+    let mut combo = (0..k).collect::<Vec<_>>();
+    if k > n {
+        return Ok(());
+    }
+    loop {
+        f(&combo)?;
+        match (0..k).rev().find(|i| combo[*i] < n - k + i) {
+            Some(i) => {
+                combo[i] += 1;
+                for j in i + 1..k {
+                    combo[j] = combo[j - 1] + 1;
+                }
+            }
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Whether some subset of `indexes` with 1 fewer element satisfies `pred`.
+fn any_facet(indexes: &[usize], pred: impl Fn(&[usize]) -> bool) -> bool {
+    (0..indexes.len()).any(|skip| {
+        let facet = indexes.iter().enumerate().filter(|(i, _)| *i != skip).map(|(_, i)| *i).collect::<Vec<_>>();
+        pred(&facet)
+    })
+}
+
+/// Finds the duplicate pairs and the subsets of sizes 3 and up with the degeneracy `kinds[size - 3]`.
+fn analyze(points: &[&[f64]], options: &Options, kinds: &[Kind], degenerate: impl Fn(&[usize]) -> bool) -> Report {
+    let mut report = Report { degeneracies: vec![], sampled: options.samples.is_some() };
+
+    // Duplicates, by sorting lexicographically. Adding 0 turns -0 into 0.
+    let lex = |a: &[f64], b: &[f64]| {
+        a.iter().zip(b).map(|(a, b)| (a + 0.0).total_cmp(&(b + 0.0))).find(|o| *o != Ordering::Equal).unwrap_or(Ordering::Equal)
+    };
+    let mut sorted = (0..points.len()).collect::<Vec<_>>();
+    sorted.sort_by(|a, b| lex(points[*a], points[*b]));
+    for (i, a) in sorted.iter().enumerate() {
+        for b in sorted[i + 1..].iter().take_while(|b| points[**b] == points[*a]) {
+            report.degeneracies.push(Degeneracy { kind: Kind::Duplicate, indexes: vec![*a.min(b), *a.max(b)] });
+        }
+    }
+
+    let mut rng = SplitMix(options.seed);
+    for (size, kind) in (3..).zip(kinds) {
+        let mut found = vec![];
+        let mut check = |indexes: &[usize]| {
+            if degenerate(indexes) {
+                found.push(Degeneracy { kind: *kind, indexes: indexes.to_vec() });
+            }
+        };
+        match options.samples {
+            Some(_) if points.len() < size => {}
+            Some(samples) => {
+                for _ in 0..samples {
+                    let mut indexes = vec![];
+                    while indexes.len() < size {
+                        let i = rng.below(points.len());
+                        if !indexes.contains(&i) {
+                            indexes.push(i);
+                        }
+                    }
+                    indexes.sort_unstable();
+                    check(&indexes);
+                }
+                found.sort_by(|a, b| a.indexes.cmp(&b.indexes));
+                found.dedup();
+            }
+            None => for_each_combination(points.len(), size, |indexes| {
+                check(indexes);
+                Ok::<_, ()>(())
+            }).unwrap(),
+        }
+        report.degeneracies.extend(found);
+    }
+
+    report
+}
+
+/// Finds the duplicate pairs, collinear triples, and cocircular quadruples
+/// of the first `len` points in a list.
+pub fn analyze_2d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize, options: &Options) -> Report {
+    let points = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let p = |i: usize| points[i];
+
+    let duplicate = |i: &[usize]| p(i[0]) == p(i[1]);
+    // Each tuple is scaled up like in the predicates, so tiny points don't look degenerate
+    let collinear = |i: &[usize]| {
+        let [a, b, c] = scale::prescaled([p(i[0]), p(i[1]), p(i[2])]);
+        rg::orient_2d(a, b, c) == 0.0 && !any_facet(i, duplicate)
+    };
+    let cocircular = |i: &[usize]| {
+        let [a, b, c, d] = scale::prescaled([p(i[0]), p(i[1]), p(i[2]), p(i[3])]);
+        rg::in_circle(a, b, c, d) == 0.0 && !any_facet(i, |i| collinear(i) || any_facet(i, duplicate))
+    };
+
+    let coords = points.iter().map(|p| p.as_slice()).collect::<Vec<_>>();
+    analyze(&coords, options, &[Kind::Collinear, Kind::Cocircular], |i| match i.len() {
+        3 => collinear(i),
+        _ => cocircular(i),
+    })
+}
+
+/// Finds the duplicate pairs, collinear triples, coplanar quadruples, and cospherical quintuples
+/// of the first `len` points in a list.
+pub fn analyze_3d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize, options: &Options) -> Report {
+    let points = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let p = |i: usize| points[i];
+
+    let duplicate = |i: &[usize]| p(i[0]) == p(i[1]);
+    // Collinear iff collinear in all 3 coordinate planes
+    // Each tuple is scaled up like in the predicates, so tiny points don't look degenerate
+    let collinear = |i: &[usize]| {
+        let q = scale::prescaled([p(i[0]), p(i[1]), p(i[2])]);
+        [[0, 1], [1, 2], [2, 0]].iter().all(|[a, b]| {
+            let [u, v, w] = q.map(|q| Vec2::new(q[*a], q[*b]));
+            rg::orient_2d(u, v, w) == 0.0
+        }) && !any_facet(i, duplicate)
+    };
+    let any_collinear = |i: &[usize]| any_facet(i, |i| collinear(i) || any_facet(i, duplicate));
+    let coplanar = |i: &[usize]| {
+        let [a, b, c, d] = scale::prescaled([p(i[0]), p(i[1]), p(i[2]), p(i[3])]);
+        rg::orient_3d(a, b, c, d) == 0.0 && !any_collinear(i)
+    };
+    let cospherical = |i: &[usize]| {
+        let [a, b, c, d, e] = scale::prescaled([p(i[0]), p(i[1]), p(i[2]), p(i[3]), p(i[4])]);
+        rg::in_sphere(a, b, c, d, e) == 0.0 && !any_facet(i, |i| coplanar(i) || any_collinear(i))
+    };
+
+    let coords = points.iter().map(|p| p.as_slice()).collect::<Vec<_>>();
+    analyze(&coords, options, &[Kind::Collinear, Kind::Coplanar, Kind::Cospherical], |i| match i.len() {
+        3 => collinear(i),
+        4 => coplanar(i),
+        _ => cospherical(i),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_circle_strict;
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_analysis() {
+        use super::{Kind, Options};
+
+        let points = grid_points_2d();
+        let report = super::analyze_2d(&points, |l, i| l[i], points.len(), &Options::default());
+        assert!(!report.sampled);
+        assert_eq!(report.count(Kind::Duplicate), 2);
+        // 8 lines through the grid, plus the ones through the duplicated center and corner
+        assert_eq!(report.count(Kind::Collinear), 8 + 4 + 3 + 1);
+        for d in report.degeneracies.iter().filter(|d| d.kind == Kind::Cocircular) {
+            let i = &d.indexes;
+            assert!(in_circle_strict(&points, |l, i| l[i], i[0], i[1], i[2], i[3]).unwrap_err().is_degenerate());
+        }
+
+        let points = grid_points_3d();
+        let report = super::analyze_3d(&points, |l, i| l[i], points.len(), &Options::default());
+        assert_eq!(report.count(Kind::Duplicate), 2);
+        assert_eq!(report.count(Kind::Collinear), 0);
+        // 12 planes through the cube, plus the ones through the duplicated corners
+        assert_eq!(report.count(Kind::Coplanar), 12 + 6 + 6 + 2);
+        assert!(report.count(Kind::Cospherical) > 0);
+
+        let sampled = super::analyze_3d(&points, |l, i| l[i], points.len(), &Options { samples: Some(100), seed: 7 });
+        assert!(sampled.sampled);
+        assert!(sampled.count(Kind::Coplanar) > 0);
+        assert!(sampled.degeneracies.iter().all(|d| report.degeneracies.contains(d)));
+    }
+}
@@ -0,0 +1,115 @@
+//! Angular order of perturbed points around a pivot.
+//!
+//! Sorting points by angle, like hull and sweep algorithms start by doing, doesn't need
+//! the angles themselves. A point's *quadrant* around the pivot is decided by the signs of
+//! its coordinates relative to the pivot, and within a quadrant, the angles differ by less than
+//! a half-turn, so the sign of a cross product orders them. Both are decided exactly, with the
+//! signs of coordinate differences broken like [`orient_1d`](crate::orient_1d) breaks them and
+//! the cross products by [`orient_2d`](crate::orient_2d), so this is the angular order of the
+//! perturbed points, counterclockwise from the positive x axis, and no 2 points ever tie.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, angle};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(-1.0, 0.0),
+//!     Vector2::new(2.0, 2.0),
+//!     Vector2::new(1.0, -1.0),
+//! ];
+//! let mut around = vec![1, 2, 3, 4];
+//! around.sort_by(|a, b| angle::pseudo_angle_cmp(&points, |l, i| l[i], 0, *a, *b));
+//! // Points 1 and 3 are in the same direction, so the perturbation decides
+//! assert_eq!(around, vec![1, 3, 2, 4]);
+//! ```
+
+use crate::{Vec1, Vec2};
+use core::cmp::Ordering;
+
+/// Returns the quadrant of point `a` around point `pivot` after perturbing them, from 0 to 3,
+/// counterclockwise from the positive x axis. Quadrant 0 has positive x and y relative to the pivot,
+/// and quadrant 1 has negative x and positive y. `a` shouldn't be `pivot`.
+pub fn quadrant<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, pivot: Idx, a: Idx) -> u8 {
+    let right = crate::orient_1d(list, |l, i| Vec1::new(index_fn(l, i).x), a, pivot);
+    let above = crate::orient_1d(list, |l, i| Vec1::new(index_fn(l, i).y), a, pivot);
+    match (right, above) {
+        (true, true) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+        (true, false) => 3,
+    }
+}
+
+/// Compares the angles of points `a` and `b` around point `pivot` after perturbing them,
+/// counterclockwise from the positive x axis.
+/// Returns `Ordering::Equal` only if `a` and `b` are the same index. Neither should be `pivot`.
+pub fn pseudo_angle_cmp<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    pivot: Idx,
+    a: Idx,
+    b: Idx,
+) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    quadrant(list, &index_fn, pivot, a).cmp(&quadrant(list, &index_fn, pivot, b)).then_with(|| {
+        if crate::orient_2d(list, &index_fn, pivot, a, b) {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng;
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_pseudo_angle() {
+        use std::cmp::Ordering;
+
+        // Degenerate, so check it's a strict total order
+        let points = grid_points_2d();
+        let n = points.len();
+        for p in 0..n {
+            let cmp = |a: usize, b: usize| super::pseudo_angle_cmp(&points, |l, i| l[i], p, a, b);
+            let others = (0..n).filter(|a| *a != p).collect::<Vec<_>>();
+            for a in others.iter().copied() {
+                for b in others.iter().copied().filter(|b| *b != a) {
+                    assert_ne!(cmp(a, b), Ordering::Equal);
+                    assert_eq!(cmp(a, b), cmp(b, a).reverse());
+                    for c in others.iter().copied().filter(|c| *c != a && *c != b) {
+                        if cmp(a, b) == Ordering::Less && cmp(b, c) == Ordering::Less {
+                            assert_eq!(cmp(a, c), Ordering::Less);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Not degenerate, so it's the order of the angles
+        let mut rng = rng::SplitMix(0xA27);
+        let points = (0..40).map(|_| Vector2::new(rng.below(1000) as f64 - 500.0, rng.below(1000) as f64 - 500.0)).collect::<Vec<_>>();
+        let angle = |p: usize, a: usize| {
+            let d = points[a] - points[p];
+            d.y.atan2(d.x).rem_euclid(2.0 * std::f64::consts::PI)
+        };
+        for p in 0..5 {
+            // Points on an axis through the pivot are pushed off it by the perturbation
+            let mut by_cmp = (0..points.len())
+                .filter(|a| *a != p && points[*a].x != points[p].x && points[*a].y != points[p].y)
+                .collect::<Vec<_>>();
+            let mut by_angle = by_cmp.clone();
+            by_cmp.sort_by(|a, b| super::pseudo_angle_cmp(&points, |l, i| l[i], p, *a, *b));
+            by_angle.sort_by(|a, b| angle(p, *a).partial_cmp(&angle(p, *b)).unwrap());
+            assert_eq!(by_cmp, by_angle);
+        }
+    }
+}
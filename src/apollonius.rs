@@ -0,0 +1,490 @@
+//! Predicates for Apollonius diagrams, the additively weighted Voronoi diagrams of circles.
+//!
+//! A site is a circle, given as its center and radius, and the distance from a point to it is
+//! the distance to its center minus its radius. A vertex of the diagram is the center of a circle
+//! tangent to 3 sites from outside, and an edge is part of the curve of points equally far
+//! from 2 sites. The diagram is built incrementally with 3 predicates: whether 3 sites have
+//! a vertex touching them in counterclockwise order ([`vertex_exists`]), whether a new site is closer
+//! to a vertex than its sites are, so it conflicts with the vertex ([`vertex_conflict`]), and which
+//! part of an edge a new site conflicts with ([`edge_conflict`]). They're to the Apollonius graph,
+//! the dual of the diagram, what [`orient_2d`](crate::orient_2d) and [`in_circle`](crate::in_circle)
+//! are to the Delaunay triangulation.
+//!
+//! The tangent circles are roots of a quadratic, so these compare numbers with square roots
+//! in them, and they do that exactly by squaring. The centers and radii are perturbed like
+//! the coordinates of [`orient_3d`](crate::orient_3d), with the radius as the third coordinate,
+//! so no site is tangent to a vertex's circle and no 2 vertices on an edge coincide.
+//! Degenerate input takes longer, but only as long as it takes the perturbation to decide.
+//! No site should be inside another, since it has no region then.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, apollonius};
+//! # use nalgebra::Vector2;
+//! let sites = vec![
+//!     (Vector2::new(0.0, 0.0), 1.0),
+//!     (Vector2::new(6.0, 0.0), 2.0),
+//!     (Vector2::new(0.0, 6.0), 1.0),
+//!     (Vector2::new(2.0, 2.0), 0.5),
+//!     (Vector2::new(9.0, 9.0), 1.0),
+//! ];
+//! assert!(apollonius::vertex_exists(&sites, |l, i| l[i], 0, 1, 2));
+//! assert!(!apollonius::vertex_exists(&sites, |l, i| l[i], 0, 2, 1));
+//! // The small site in the middle is closer to the vertex than the others
+//! assert!(apollonius::vertex_conflict(&sites, |l, i| l[i], 0, 1, 2, 3));
+//! assert!(!apollonius::vertex_conflict(&sites, |l, i| l[i], 0, 1, 2, 4));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{Perturbed, Polynomial};
+use crate::{Vec2, Vec3};
+
+/// The highest degree of the polynomials that the vertex predicates take the signs of.
+const VERTEX_DEGREE: u64 = 18;
+/// The highest degree of the polynomials that comparisons of vertices along an edge take the signs of.
+const EDGE_DEGREE: u64 = 36;
+
+type Poly2 = [Polynomial; 2];
+
+fn dot(a: &Poly2, b: &Poly2) -> Polynomial {
+    a[0].clone() * b[0].clone() + a[1].clone() * b[1].clone()
+}
+
+fn cross(a: &Poly2, b: &Poly2) -> Polynomial {
+    a[0].clone() * b[1].clone() - a[1].clone() * b[0].clone()
+}
+
+/// `s * a - t * b`
+fn combine(s: &Polynomial, a: &Poly2, t: &Polynomial, b: &Poly2) -> Poly2 {
+    [s.clone() * a[0].clone() - t.clone() * b[0].clone(), s.clone() * a[1].clone() - t.clone() * b[1].clone()]
+}
+
+fn constant(x: f64) -> Polynomial {
+    Polynomial::from(Expansion::from(x))
+}
+
+/// The circles through the center of site `m` that are tangent to sites `o` and `w` from outside,
+/// after subtracting the radius of `m` from every radius, which doesn't move their centers.
+///
+/// With `p_i` and `r_i` the center and radius of site `i` relative to `m`, the center `z`
+/// of such a circle and its radius `ρ` satisfy |z| = ρ and |z - p_i| = ρ + r_i, so
+/// 2 p_i·z + 2 r_i ρ = |p_i|² - r_i². Solving for z, 2dz = U - ρV, where d is the cross product
+/// of p_o and p_w, and then |z|² = ρ² makes Aρ² - 2Mρ + C = 0 with A = |V|² - 4d², M = U·V,
+/// and C = |U|². So ρ = (M ± √Δ) / A, with Δ = M² - AC.
+struct Tangent {
+    p_o: Poly2,
+    p_w: Poly2,
+    r_o: Polynomial,
+    r_w: Polynomial,
+    d: Polynomial,
+    u: Poly2,
+    v: Poly2,
+    a: Polynomial,
+    m: Polynomial,
+    delta: Polynomial,
+}
+
+impl Tangent {
+    fn new<Idx: Ord + Copy>(perturbed: &Perturbed<Idx, impl Fn(Idx) -> Vec3>, [m, o, w]: [Idx; 3]) -> Self {
+        let p = |i| [perturbed.diff(i, m, 0), perturbed.diff(i, m, 1)];
+        let (p_o, p_w) = (p(o), p(w));
+        let (r_o, r_w) = (perturbed.diff(o, m, 2), perturbed.diff(w, m, 2));
+        let g_o = dot(&p_o, &p_o) - r_o.clone() * r_o.clone();
+        let g_w = dot(&p_w, &p_w) - r_w.clone() * r_w.clone();
+        // Rotated a quarter turn clockwise, so p_o·J(p_w) = d and p_o·J(p_o) = 0
+        let j = |p: &Poly2| [p[1].clone(), -p[0].clone()];
+        let d = cross(&p_o, &p_w);
+        let u = combine(&g_o, &j(&p_w), &g_w, &j(&p_o));
+        let v = combine(&r_o.scale(2.0), &j(&p_w), &r_w.scale(2.0), &j(&p_o));
+        let a = dot(&v, &v) - (d.clone() * d.clone()).scale(4.0);
+        let m = dot(&u, &v);
+        let delta = m.clone() * m.clone() - a.clone() * dot(&u, &u);
+        Self { p_o, p_w, r_o, r_w, d, u, v, a, m, delta }
+    }
+
+    /// A(`l0` + `l1` ρ) for the root with the sign `s` on its square root, as (x, y, Δ) for x + y√Δ.
+    fn linear(self, l0: Polynomial, l1: Polynomial, s: f64) -> (Polynomial, Polynomial, Polynomial) {
+        (l0 * self.a + l1.clone() * self.m, l1.scale(s), self.delta)
+    }
+}
+
+/// The sign of x + y√Δ, with Δ at least 0, where `terms` computes (x, y, Δ) from the perturbed sites.
+fn sign_sqrt<Idx: Ord + Copy, F: Fn(Idx) -> Vec3>(
+    perturbed: &mut Perturbed<Idx, F>,
+    terms: impl Fn(&Perturbed<Idx, F>) -> (Polynomial, Polynomial, Polynomial),
+) -> f64 {
+    let x = perturbed.lazy_signum(|p| terms(p).0);
+    let y = perturbed.lazy_signum(|p| terms(p).1);
+    if y == 0.0 || x == y {
+        return x;
+    }
+    if perturbed.lazy_signum(|p| terms(p).2) == 0.0 {
+        return x;
+    }
+    if x == 0.0 {
+        return y;
+    }
+    x * perturbed.lazy_signum(|p| {
+        let (x, y, delta) = terms(p);
+        x.clone() * x - y.clone() * y * delta
+    })
+}
+
+/// The sign on the square root of the tangent circle of `sites` whose points of tangency go around
+/// counterclockwise if `ccw`, or clockwise if not, if it exists.
+fn root<Idx: Ord + Copy, F: Fn(Idx) -> Vec3>(perturbed: &mut Perturbed<Idx, F>, sites: [Idx; 3], ccw: bool) -> Option<f64> {
+    if perturbed.lazy_signum(|p| Tangent::new(p, sites).delta) < 0.0 {
+        return None;
+    }
+    let a = perturbed.lazy_signum(|p| Tangent::new(p, sites).a);
+    let d = perturbed.lazy_signum(|p| Tangent::new(p, sites).d);
+    for s in [1.0, -1.0].iter().copied() {
+        // ρ, ρ + r_o, and ρ + r_w have to be positive
+        let valid = [None, Some(false), Some(true)].iter().all(|radius| {
+            let sign = sign_sqrt(perturbed, |p| {
+                let t = Tangent::new(p, sites);
+                let l0 = match radius {
+                    None => Polynomial::default(),
+                    Some(false) => t.r_o.clone(),
+                    Some(true) => t.r_w.clone(),
+                };
+                t.linear(l0, constant(1.0), s)
+            });
+            a * sign > 0.0
+        });
+        if !valid {
+            continue;
+        }
+        // The points of tangency are z + ρ(p_i - z) / (ρ + r_i), and m's is the origin, so the cross
+        // product of o's and w's times the positive (ρ + r_o)(ρ + r_w) / ρ is r_o cross(z, p_w) - r_w cross(z, p_o) + ρd.
+        // Times 2dA, that's x + sy√Δ with x always 0, so the 2 circles touch the sites in opposite orders
+        let orientation = d * a * s * perturbed.lazy_signum(|p| {
+            let t = Tangent::new(p, sites);
+            (t.d.clone() * t.d.clone()).scale(2.0) - t.r_o.clone() * cross(&t.v, &t.p_w) + t.r_w.clone() * cross(&t.v, &t.p_o)
+        });
+        if (orientation > 0.0) == ccw {
+            return Some(s);
+        }
+    }
+    None
+}
+
+/// Whether site `q` conflicts with the tangent circle of `sites` with the sign `s` on its square root.
+fn conflicts<Idx: Ord + Copy, F: Fn(Idx) -> Vec3>(perturbed: &mut Perturbed<Idx, F>, sites: [Idx; 3], s: f64, q: Idx) -> bool {
+    let m = sites[0];
+    let a = perturbed.lazy_signum(|p| Tangent::new(p, sites).a);
+    // If ρ + r_q isn't positive, q is further from the center than ρ
+    let positive = sign_sqrt(perturbed, |p| Tangent::new(p, sites).linear(p.diff(q, m, 2), constant(1.0), s));
+    if a * positive <= 0.0 {
+        return false;
+    }
+    // |z - p_q|² - (ρ + r_q)² = |p_q|² - r_q² - 2 p_q·z - 2ρr_q, which is negative for a conflict
+    let d = perturbed.lazy_signum(|p| Tangent::new(p, sites).d);
+    let sign = sign_sqrt(perturbed, |p| {
+        let t = Tangent::new(p, sites);
+        let p_q = [p.diff(q, m, 0), p.diff(q, m, 1)];
+        let r_q = p.diff(q, m, 2);
+        let g_q = dot(&p_q, &p_q) - r_q.clone() * r_q.clone();
+        let l0 = (t.d.clone() * g_q - dot(&p_q, &t.u)).scale(2.0);
+        let l1 = (dot(&p_q, &t.v) - (t.d.clone() * r_q).scale(2.0)).scale(2.0);
+        t.linear(l0, l1, s)
+    });
+    d * a * sign < 0.0
+}
+
+/// Gets a site as a 3D point, with its radius as the third coordinate.
+fn site<'a, T: ?Sized, Idx>(list: &'a T, index_fn: impl Fn(&T, Idx) -> (Vec2, f64) + 'a) -> impl Fn(Idx) -> Vec3 + 'a {
+    move |i| {
+        let (center, radius) = index_fn(list, i);
+        Vec3::new(center.x, center.y, radius)
+    }
+}
+
+/// Returns whether there's a circle tangent to sites `a`, `b`, and `c` from outside that touches
+/// them in counterclockwise order, after perturbing them. Its center is a vertex of the diagram
+/// if no other site conflicts with it.
+///
+/// Takes a list of all the sites, an indexing function that returns a site's center and radius,
+/// and 3 indexes. Rotating the indexes doesn't change the result. There can be a circle
+/// in both orders, unlike for points, or in neither.
+pub fn vertex_exists<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> (Vec2, f64), a: Idx, b: Idx, c: Idx) -> bool {
+    let mut perturbed = Perturbed::new(3, VERTEX_DEGREE, &[a, b, c], site(list, index_fn));
+    root(&mut perturbed, [a, b, c], true).is_some()
+}
+
+/// Returns whether site `q` conflicts with the vertex of sites `a`, `b`, and `c`, after perturbing
+/// them; that is, whether it's closer to the center of the circle tangent to them from outside
+/// that touches them in counterclockwise order than they are, so it intersects the circle.
+/// Returns `false` if there's no such circle.
+///
+/// Takes a list of all the sites, an indexing function that returns a site's center and radius,
+/// and 4 indexes. With radii of 0, this agrees with [`in_circle`](crate::in_circle)
+/// on non-degenerate input.
+pub fn vertex_conflict<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec2, f64),
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    q: Idx,
+) -> bool {
+    let mut perturbed = Perturbed::new(3, VERTEX_DEGREE, &[a, b, c, q], site(list, index_fn));
+    match root(&mut perturbed, [a, b, c], true) {
+        Some(s) => conflicts(&mut perturbed, [a, b, c], s, q),
+        None => false,
+    }
+}
+
+/// Which part of an edge of the diagram a new site conflicts with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EdgeConflict {
+    /// None of it
+    None,
+    /// A part in the middle, without either vertex
+    Interior,
+    /// A part with the first vertex and not the last
+    Start,
+    /// A part with the last vertex and not the first
+    End,
+    /// A part with each vertex, but not the middle
+    BothEnds,
+    /// All of it
+    Entire,
+}
+
+/// Whether the vertex of [`a`, `b`, `w`] with the sign `s` comes before the one of [`a`, `b`, `w2`]
+/// with the sign `s2` on the curve of points equally far from `a` and `b`, going from the left of
+/// the line from `a` to `b` to the right.
+///
+/// Along the curve, ρ is the distance from the center of `a`, and it decreases until the curve
+/// crosses the line and increases after.
+fn before<Idx: Ord + Copy, F: Fn(Idx) -> Vec3>(
+    perturbed: &mut Perturbed<Idx, F>,
+    [a, b]: [Idx; 2],
+    (w, s): (Idx, f64),
+    (w2, s2): (Idx, f64),
+) -> bool {
+    // Positive on the left, from the sign of cross(p_o, 2dz)
+    let mut left = |w, s| {
+        let d = perturbed.lazy_signum(|p| Tangent::new(p, [a, b, w]).d);
+        let sign_a = perturbed.lazy_signum(|p| Tangent::new(p, [a, b, w]).a);
+        let sign = sign_sqrt(perturbed, |p| {
+            let t = Tangent::new(p, [a, b, w]);
+            let (l0, l1) = (cross(&t.p_o, &t.u), -cross(&t.p_o, &t.v));
+            t.linear(l0, l1, s)
+        });
+        d * sign_a * sign > 0.0
+    };
+    let (left1, left2) = (left(w, s), left(w2, s2));
+    if left1 != left2 {
+        return left1;
+    }
+
+    // The sign of ρ - ρ2 is the sign of A A2 (ρ - ρ2) = (M A2 - M2 A) + s A2 √Δ - s2 A √Δ2
+    // times the signs of A and A2
+    let terms = |p: &Perturbed<Idx, F>| {
+        let (t1, t2) = (Tangent::new(p, [a, b, w]), Tangent::new(p, [a, b, w2]));
+        let alpha = t1.m * t2.a.clone() - t2.m * t1.a.clone();
+        (alpha, t2.a.scale(s), t1.delta, t1.a.scale(-s2), t2.delta)
+    };
+    let x = sign_sqrt(perturbed, |p| {
+        let (alpha, beta, delta, _, _) = terms(p);
+        (alpha, beta, delta)
+    });
+    let y = perturbed.lazy_signum(|p| terms(p).3);
+    let sign = if y == 0.0 || x == y || perturbed.lazy_signum(|p| terms(p).4) == 0.0 {
+        x
+    } else if x == 0.0 {
+        y
+    } else {
+        // (α + β√Δ)² - γ²Δ2 = α² + β²Δ - γ²Δ2 + 2αβ√Δ
+        x * sign_sqrt(perturbed, |p| {
+            let (alpha, beta, delta, gamma, delta2) = terms(p);
+            let x = alpha.clone() * alpha.clone() + beta.clone() * beta.clone() * delta.clone() - gamma.clone() * gamma * delta2;
+            (x, (alpha * beta).scale(2.0), delta)
+        })
+    };
+    let a1 = perturbed.lazy_signum(|p| Tangent::new(p, [a, b, w]).a);
+    let a2 = perturbed.lazy_signum(|p| Tangent::new(p, [a, b, w2]).a);
+    // Further from a comes first on the left, and last on the right
+    (a1 * a2 * sign > 0.0) == left1
+}
+
+/// Classifies the part of an edge of the diagram that site `q` conflicts with, after perturbing
+/// the sites. The edge is part of the curve of points equally far from sites `a` and `b`,
+/// and it goes from the vertex of `a`, `b`, and `c` to the vertex of `b`, `a`, and `d`,
+/// as in [`vertex_conflict`]. That's the edge dual to the edge from `a` to `b` in the Apollonius graph,
+/// with triangles [`a`, `b`, `c`] and [`b`, `a`, `d`] on either side of it.
+///
+/// Takes a list of all the sites, an indexing function that returns a site's center and radius,
+/// and 5 indexes. Panics if either vertex doesn't exist. The first vertex should come before
+/// the second, as it does when the edge is in the diagram.
+pub fn edge_conflict<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec2, f64),
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    d: Idx,
+    q: Idx,
+) -> EdgeConflict {
+    let mut perturbed = Perturbed::new(3, EDGE_DEGREE, &[a, b, c, d, q], site(list, index_fn));
+    let start = root(&mut perturbed, [a, b, c], true).expect("the first vertex doesn't exist");
+    let end = root(&mut perturbed, [a, b, d], false).expect("the last vertex doesn't exist");
+    let in_edge = |perturbed: &mut Perturbed<_, _>, ccw| match root(perturbed, [a, b, q], ccw) {
+        Some(s) => before(perturbed, [a, b], (c, start), (q, s)) && before(perturbed, [a, b], (q, s), (d, end)),
+        None => false,
+    };
+
+    // The conflict region of q on the curve starts at the vertex of b, a, and q,
+    // and ends at the vertex of a, b, and q, where they exist
+    match (conflicts(&mut perturbed, [a, b, c], start, q), conflicts(&mut perturbed, [a, b, d], end, q)) {
+        (false, false) if in_edge(&mut perturbed, false) => EdgeConflict::Interior,
+        (false, false) => EdgeConflict::None,
+        (true, false) => EdgeConflict::Start,
+        (false, true) => EdgeConflict::End,
+        (true, true) if in_edge(&mut perturbed, true) => EdgeConflict::BothEnds,
+        (true, true) => EdgeConflict::Entire,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, orient_2d, rg, rng};
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_apollonius() {
+
+        type Site = (Vec2, f64);
+        // Points equally far from a and b, from the left of the line from a to b to the right,
+        // parametrized by their direction from a
+        fn bisector(a: Site, b: Site) -> (f64, f64, impl Fn(f64) -> Vec2) {
+            let (p, r) = (b.0 - a.0, b.1 - a.1);
+            let (angle, width) = (p.y.atan2(p.x), (-r / p.norm()).acos());
+            let point = move |theta: f64| {
+                let u = Vector2::new(theta.cos(), theta.sin());
+                a.0 + u * (p.norm_squared() - r * r) / (2.0 * (u.dot(&p) + r))
+            };
+            (angle + width, angle - width, point)
+        }
+        let distance = |z: Vec2, s: Site| (z - s.0).norm() - s.1;
+        // The directions of the vertices of a, b, and c, and whether they touch them counterclockwise
+        let vertices = |a: Site, b: Site, c: Site| {
+            let (start, end, point) = bisector(a, b);
+            let f = |theta| distance(point(theta), c) - distance(point(theta), a);
+            let n = 4000;
+            let thetas = (1..n).map(|i| start + (end - start) * i as f64 / n as f64).collect::<Vec<_>>();
+            thetas
+                .windows(2)
+                .filter(|w| (f(w[0]) > 0.0) != (f(w[1]) > 0.0))
+                .map(|w| {
+                    let (mut lo, mut hi) = (w[0], w[1]);
+                    for _ in 0..60 {
+                        let mid = (lo + hi) / 2.0;
+                        if (f(mid) > 0.0) == (f(lo) > 0.0) {
+                            lo = mid;
+                        } else {
+                            hi = mid;
+                        }
+                    }
+                    let z = point(lo);
+                    let touch = |s: Site| z + (s.0 - z).normalize();
+                    (lo, rg::orient_2d(touch(a), touch(b), touch(c)) > 0.0)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut rng = rng::SplitMix(1478);
+        let mut random = |scale: f64| rng.next() as f64 / u64::MAX as f64 * scale;
+        let contained = |sites: &[Site]| {
+            let n = sites.len();
+            (0..n).any(|i| (0..n).any(|j| i != j && (sites[i].0 - sites[j].0).norm() <= sites[i].1 - sites[j].1))
+        };
+        let mut checked = [0; 6];
+        for _ in 0..60 {
+            let mut sites = (0..5).map(|_| (Vector2::new(random(10.0), random(10.0)), random(2.0))).collect::<Vec<_>>();
+            if contained(&sites) {
+                continue;
+            }
+            let [a, b, c, d, q] = [0, 1, 2, 3, 4];
+            let ccw = vertices(sites[a], sites[b], sites[c]).into_iter().find(|(_, ccw)| *ccw);
+            assert_eq!(super::vertex_exists(&sites, |l, i| l[i], a, b, c), ccw.is_some());
+            assert_eq!(super::vertex_exists(&sites, |l, i| l[i], b, c, a), ccw.is_some());
+            let (_, _, point) = bisector(sites[a], sites[b]);
+            let conflict = |sites: &[Site], theta| distance(point(theta), sites[q]) < distance(point(theta), sites[a]);
+            let expected = matches!(ccw, Some((theta, _)) if conflict(&sites, theta));
+            assert_eq!(super::vertex_conflict(&sites, |l, i| l[i], a, b, c, q), expected);
+
+            // An edge from the vertex of a, b, and c to the vertex of b, a, and d, if it's there,
+            // and new sites around its middle
+            let cw = vertices(sites[a], sites[b], sites[d]).into_iter().find(|(_, ccw)| !*ccw);
+            let (start, end) = match (ccw, cw) {
+                (Some((start, _)), Some((end, _))) if start > end => (start, end),
+                _ => continue,
+            };
+            let middle = point((start + end) / 2.0);
+            let radius = distance(middle, sites[a]);
+            for n in 0..10 {
+                if n == 1 {
+                    // Between the vertices, just reaching both, to miss a curved edge's middle
+                    let center = (point(start) + point(end)) / 2.0;
+                    let reach = |theta| (center - point(theta)).norm() - distance(point(theta), sites[a]);
+                    sites[q] = (center, reach(start).max(reach(end)) + 1e-3);
+                } else if n > 1 {
+                    let offset = Vector2::new(random(4.0) - 2.0, random(4.0) - 2.0) * radius;
+                    sites[q] = (middle + offset, random(radius));
+                }
+                if n > 0 && (sites[q].1 < 0.0 || contained(&sites)) {
+                    continue;
+                }
+                let samples = (0..=1000).map(|i| conflict(&sites, start + (end - start) * i as f64 / 1000.0)).collect::<Vec<_>>();
+                let changes = samples.windows(2).filter(|w| w[0] != w[1]).count();
+                let expected = match (samples[0], samples[1000], changes) {
+                    (false, false, 0) => EdgeConflict::None,
+                    (false, false, _) => EdgeConflict::Interior,
+                    (true, false, _) => EdgeConflict::Start,
+                    (false, true, _) => EdgeConflict::End,
+                    (true, true, 0) => EdgeConflict::Entire,
+                    (true, true, _) => EdgeConflict::BothEnds,
+                };
+                let result = super::edge_conflict(&sites, |l, i| l[i], a, b, c, d, q);
+                assert_eq!(result, expected);
+                checked[result as usize] += 1;
+            }
+        }
+        assert!(checked.iter().all(|n| *n > 0), "{:?}", checked);
+
+        // With radii of 0, these are the usual predicates
+        let points = (0..5).map(|_| Vector2::new(random(10.0), random(10.0))).collect::<Vec<_>>();
+        let sites = points.iter().map(|p| (*p, 0.0)).collect::<Vec<_>>();
+        for (a, b, c, q) in (0..5).flat_map(|a| (0..5).flat_map(move |b| (0..5).flat_map(move |c| (0..5).map(move |q| (a, b, c, q))))) {
+            if a == b || a == c || a == q || b == c || b == q || c == q {
+                continue;
+            }
+            let ccw = orient_2d(&points, |l, i| l[i], a, b, c);
+            assert_eq!(super::vertex_exists(&sites, |l, i| l[i], a, b, c), ccw);
+            let conflict = ccw && in_circle(&points, |l, i| l[i], a, b, c, q);
+            assert_eq!(super::vertex_conflict(&sites, |l, i| l[i], a, b, c, q), conflict);
+        }
+
+        // Equal radii on a grid are as degenerate as it gets, but the perturbation
+        // still decides consistently
+        let sites = grid_points_2d().into_iter().take(5).map(|p| (p, 0.25)).collect::<Vec<_>>();
+        for (a, b, c) in (0..5).flat_map(|a| (0..5).flat_map(move |b| (0..5).map(move |c| (a, b, c)))) {
+            if a == b || a == c || b == c {
+                continue;
+            }
+            let exists = super::vertex_exists(&sites, |l, i| l[i], a, b, c);
+            assert_eq!(super::vertex_exists(&sites, |l, i| l[i], b, c, a), exists);
+            for q in (0..5).filter(|q| ![a, b, c].contains(q)) {
+                let conflict = super::vertex_conflict(&sites, |l, i| l[i], a, b, c, q);
+                assert_eq!(super::vertex_conflict(&sites, |l, i| l[i], c, a, b, q), conflict);
+                assert!(exists || !conflict);
+            }
+        }
+    }
+}
@@ -0,0 +1,658 @@
+//! Intersections of 2D line segments with the Bentley–Ottmann sweep.
+//!
+//! A vertical line sweeps from left to right, keeping the segments it crosses in order
+//! from bottom to top, and stops at the endpoints of the segments and at the crossings
+//! it finds between segments next to each other. Endpoints are ordered by their perturbed
+//! x coordinates, segments by [`orient_2d`](crate::orient_2d), and crossings with
+//! [`cmp_crossing_point`] and [`cmp_crossings`], which compare the x coordinates of perturbed
+//! crossings exactly.
+//!
+//! Because the points are perturbed, no segment passes through an endpoint of another,
+//! collinear segments don't overlap, and no 3 segments cross at the same point, so the only
+//! intersections are proper crossings and endpoints that segments share by index.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, arrangement::{self, Intersection}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 2.0),
+//!     Vector2::new(0.0, 2.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(3.0, 1.0),
+//! ];
+//! let segments = [[0, 1], [2, 3], [3, 4]];
+//! let intersections = arrangement::intersections(&points, |l, i| l[i], &segments);
+//! assert_eq!(intersections, vec![
+//!     Intersection::Crossing([0, 1]),
+//!     Intersection::Endpoint { point: 3, segments: [1, 2] },
+//! ]);
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed, Polynomial};
+use crate::Vec2;
+use nalgebra::Vector1;
+use alloc::{collections::{BTreeMap, BTreeSet}, vec, vec::Vec};
+use core::cmp::Ordering;
+
+/// An intersection between 2 segments, given by their positions in the list of segments, smaller first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Intersection {
+    /// The segments cross at a point inside both of them.
+    Crossing([usize; 2]),
+    /// The segments share an endpoint.
+    Endpoint { point: usize, segments: [usize; 2] },
+}
+
+/// The x coordinate of the crossing of segments `ab` and `cd` as a fraction,
+/// from `a + (b - a) * cross(d - c, a - c) / cross(d - c, a - b)`.
+fn crossing_x<Idx: Ord + Copy>(
+    perturbed: &Perturbed<Idx, impl Fn(Idx) -> Vec2>,
+    [a, b]: [Idx; 2],
+    [c, d]: [Idx; 2],
+) -> (Polynomial, Polynomial) {
+    let oa = perturbed.cross_2d(c, d, a);
+    let den = oa.clone() - perturbed.cross_2d(c, d, b);
+    (perturbed.coord(a, 0) * den.clone() + oa * perturbed.diff(b, a, 0), den)
+}
+
+fn ordering(sign: f64) -> Ordering {
+    sign.partial_cmp(&0.0).unwrap()
+}
+
+/// Compares the x coordinate of the crossing of segments `s` and `t` with the x coordinate of point `p`
+/// after perturbing the points. The segments must cross.
+/// Returns `Ordering::Equal` only if the comparison is degenerate even after perturbing.
+pub fn cmp_crossing_point<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    s: [Idx; 2],
+    t: [Idx; 2],
+    p: Idx,
+) -> Ordering {
+    let perturbed = Perturbed::new(2, 3, &[s[0], s[1], t[0], t[1], p], |i| index_fn(list, i));
+    let (num, den) = crossing_x(&perturbed, s, t);
+    let diff = num - perturbed.coord(p, 0) * den.clone();
+    ordering(diff.signum() * den.signum())
+}
+
+/// Compares the x coordinate of the crossing of segments `s1` and `t1` with the x coordinate of the crossing of
+/// segments `s2` and `t2` after perturbing the points. Both pairs of segments must cross.
+/// Returns `Ordering::Equal` only if the comparison is degenerate even after perturbing,
+/// such as when they're the same crossing.
+pub fn cmp_crossings<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    s1: [Idx; 2],
+    t1: [Idx; 2],
+    s2: [Idx; 2],
+    t2: [Idx; 2],
+) -> Ordering {
+    let indexes = [s1[0], s1[1], t1[0], t1[1], s2[0], s2[1], t2[0], t2[1]];
+    let perturbed = Perturbed::new(2, 5, &indexes, |i| index_fn(list, i));
+    let (num1, den1) = crossing_x(&perturbed, s1, t1);
+    let (num2, den2) = crossing_x(&perturbed, s2, t2);
+    let diff = num1 * den2.clone() - num2 * den1.clone();
+    ordering(diff.signum() * den1.signum() * den2.signum())
+}
+
+/// Compares where segments `s` and `t` cross segment `pq`, along it from `p` to `q`,
+/// after perturbing the points. Each must cross `pq`, or share an endpoint with it.
+/// Returns `Ordering::Less` if `s` crosses it first, and `Ordering::Equal` only if they cross it
+/// at the same point even after perturbing, such as an endpoint they share.
+///
+/// This sorts the crossings on an edge when splitting it, like in [`boolean`](crate::boolean).
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, arrangement};
+/// # use nalgebra::Vector2;
+/// # use std::cmp::Ordering;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(4.0, 0.0),
+///     Vector2::new(1.0, -1.0),
+///     Vector2::new(1.0, 1.0),
+///     Vector2::new(3.0, -1.0),
+///     Vector2::new(1.0, 2.0),
+/// ];
+/// let cmp = |s, t| arrangement::cmp_along(&points, |l, i| l[i], [0, 1], s, t);
+/// assert_eq!(cmp([2, 3], [4, 5]), Ordering::Less);
+/// assert_eq!(cmp([4, 5], [2, 3]), Ordering::Greater);
+/// // Segments 2-3 and 2-5 cross at the same point of the line, so the perturbation decides
+/// assert_ne!(cmp([2, 3], [2, 5]), Ordering::Equal);
+/// ```
+pub fn cmp_along<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    [p, q]: [Idx; 2],
+    s: [Idx; 2],
+    t: [Idx; 2],
+) -> Ordering {
+    let perturbed = Perturbed::new(2, 4, &[p, q, s[0], s[1], t[0], t[1]], |i| index_fn(list, i));
+    // Segment cd crosses pq at p + (q - p) * op / (op - oq), with op and oq the cross products from cd
+    let fraction = |[c, d]: [Idx; 2]| {
+        let op = perturbed.cross_2d(c, d, p);
+        let den = op.clone() - perturbed.cross_2d(c, d, q);
+        (op, den)
+    };
+    let ((num1, den1), (num2, den2)) = (fraction(s), fraction(t));
+    let diff = num1 * den2.clone() - num2 * den1.clone();
+    ordering(diff.signum() * den1.signum() * den2.signum())
+}
+
+/// A point that's an argument of [`orient_implicit`], which can be an intersection
+/// that isn't one of the points. The vertices of [`boolean`](crate::boolean) results convert to these.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Implicit<Idx> {
+    /// A point, by index.
+    Point(Idx),
+    /// The intersection of the lines through 2 segments, each as the indexes of its endpoints.
+    /// It's represented by the segments, so it's never rounded.
+    Crossing([Idx; 2], [Idx; 2]),
+    /// The circumcenter of a triangle, as the indexes of its vertices, in any order.
+    Circumcenter([Idx; 3]),
+    /// The intersection of the perpendicular bisectors of 2 segments, each as the indexes
+    /// of its endpoints, like a vertex of a Voronoi diagram.
+    Bisectors([Idx; 2], [Idx; 2]),
+}
+
+impl<Idx: Copy> Implicit<Idx> {
+    /// The indexes of the points this is made of.
+    pub(crate) fn indexes(&self) -> Vec<Idx> {
+        match *self {
+            Implicit::Point(i) => vec![i],
+            Implicit::Crossing(s, t) | Implicit::Bisectors(s, t) => vec![s[0], s[1], t[0], t[1]],
+            Implicit::Circumcenter(tri) => tri.to_vec(),
+        }
+    }
+}
+
+/// The homogeneous coordinates (x w, y w, w) of an implicit point, perturbed.
+/// A point shared by 3 implicit points has degree at most 3 in each of their rows.
+pub(crate) fn homogeneous<Idx: Ord + Copy, F: Fn(Idx) -> Vec2>(perturbed: &Perturbed<Idx, F>, v: Implicit<Idx>) -> Vec<Polynomial> {
+    let squared = |[x, y]: &[Polynomial; 2]| x.clone() * x.clone() + y.clone() * y.clone();
+    match v {
+        Implicit::Point(i) => vec![perturbed.coord(i, 0), perturbed.coord(i, 1), Polynomial::from(Expansion::from(1.0))],
+        Implicit::Crossing([a, b], t) => {
+            let (x, w) = crossing_x(perturbed, [a, b], t);
+            let oa = perturbed.cross_2d(t[0], t[1], a);
+            let y = perturbed.coord(a, 1) * w.clone() + oa * perturbed.diff(b, a, 1);
+            vec![x, y, w]
+        }
+        Implicit::Circumcenter([a, b, c]) => {
+            let u = [perturbed.diff(b, a, 0), perturbed.diff(b, a, 1)];
+            let v = [perturbed.diff(c, a, 0), perturbed.diff(c, a, 1)];
+            let (lu, lv) = (squared(&u), squared(&v));
+            let w = perturbed.cross_2d(a, b, c).scale(2.0);
+            let x = perturbed.coord(a, 0) * w.clone() + lu.clone() * v[1].clone() - lv.clone() * u[1].clone();
+            let y = perturbed.coord(a, 1) * w.clone() + lv * u[0].clone() - lu * v[0].clone();
+            vec![x, y, w]
+        }
+        Implicit::Bisectors([a, b], [c, d]) => {
+            // The solution of 2 (b - a) · p = |b|² - |a|² and 2 (d - c) · p = |d|² - |c|²
+            let u = [perturbed.diff(b, a, 0), perturbed.diff(b, a, 1)];
+            let v = [perturbed.diff(d, c, 0), perturbed.diff(d, c, 1)];
+            let point = |i| [perturbed.coord(i, 0), perturbed.coord(i, 1)];
+            let s = squared(&point(b)) - squared(&point(a));
+            let t = squared(&point(d)) - squared(&point(c));
+            let w = (u[0].clone() * v[1].clone() - u[1].clone() * v[0].clone()).scale(2.0);
+            let x = s.clone() * v[1].clone() - t.clone() * u[1].clone();
+            let y = t * u[0].clone() - s * v[0].clone();
+            vec![x, y, w]
+        }
+    }
+}
+
+impl From<crate::boolean::Vertex> for Implicit<usize> {
+    fn from(vertex: crate::boolean::Vertex) -> Self {
+        match vertex {
+            crate::boolean::Vertex::Point(i) => Implicit::Point(i),
+            crate::boolean::Vertex::Crossing(s, t) => Implicit::Crossing(s, t),
+        }
+    }
+}
+
+/// Returns whether `p`, `q`, and `r` are oriented positive, like [`orient_2d`](crate::orient_2d),
+/// after perturbing the points they're made of. Crossings, circumcenters, and intersections of
+/// bisectors are computed exactly as fractions, so this is exact even for points that can't be
+/// represented. See [`constructions`](crate::constructions) for bounds on where they are.
+///
+/// Takes a list of all the points, an indexing function, and 3 implicit points.
+/// The segments of each crossing or pair of bisectors can't be parallel after perturbing,
+/// so they can't have the same endpoints. A crossing of 2 segments that share an endpoint is that endpoint.
+/// Returns `false` if the points are on a line even after perturbing, like 2 crossings
+/// on the same segment and an endpoint of it.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, arrangement::{self, Implicit}};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(3.0, 3.0),
+///     Vector2::new(0.0, 3.0),
+///     Vector2::new(3.0, 0.0),
+///     Vector2::new(0.0, 1.0),
+///     Vector2::new(3.0, 2.0),
+/// ];
+/// // The diagonals cross at (1.5, 1.5), which is above the line from (0, 1) to (3, 2)
+/// let crossing = Implicit::Crossing([0, 1], [2, 3]);
+/// assert!(arrangement::orient_implicit(&points, |l, i| l[i], Implicit::Point(4), Implicit::Point(5), crossing));
+/// ```
+pub fn orient_implicit<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    p: Implicit<Idx>,
+    q: Implicit<Idx>,
+    r: Implicit<Idx>,
+) -> bool {
+    let indexes = [p, q, r].iter().flat_map(Implicit::indexes).collect::<Vec<_>>();
+    let mut perturbed = Perturbed::new(2, 9, &indexes, |i| index_fn(list, i));
+    let orientation = perturbed.lazy_signum(|perturbed| {
+        perturbed::det(&[homogeneous(perturbed, p), homogeneous(perturbed, q), homogeneous(perturbed, r)])
+    });
+    let weights = [p, q, r]
+        .iter()
+        .map(|v| match v {
+            Implicit::Point(_) => 1.0,
+            _ => perturbed.lazy_signum(|perturbed| homogeneous(perturbed, *v).swap_remove(2)),
+        })
+        .product::<f64>();
+    orientation * weights > 0.0
+}
+
+/// An event of the sweep.
+#[derive(Clone, Copy, Debug)]
+enum Event {
+    Endpoint(usize),
+    Crossing([usize; 2]),
+}
+
+struct Sweep<'a, T: ?Sized, F> {
+    list: &'a T,
+    index_fn: F,
+    /// The segments with their left endpoint first
+    ends: Vec<[usize; 2]>,
+    /// The segments the sweep line crosses, from bottom to top
+    status: Vec<usize>,
+    /// Crossings found but not swept yet, sorted by x coordinate
+    crossings: Vec<[usize; 2]>,
+    found: BTreeSet<[usize; 2]>,
+}
+
+impl<'a, T: ?Sized, F: Fn(&T, usize) -> Vec2> Sweep<'a, T, F> {
+    fn orient(&self, i: usize, j: usize, k: usize) -> bool {
+        crate::orient_2d(self.list, &self.index_fn, i, j, k)
+    }
+
+    fn cmp_event(&self, e: Event, f: Event) -> Ordering {
+        let ends = |[s, t]: [usize; 2]| (self.ends[s], self.ends[t]);
+        match (e, f) {
+            (Event::Endpoint(p), Event::Endpoint(q)) => match (p == q, self.right_of(p, q)) {
+                (true, _) => Ordering::Equal,
+                (false, true) => Ordering::Greater,
+                (false, false) => Ordering::Less,
+            },
+            (Event::Crossing(c), Event::Endpoint(p)) => {
+                let (s, t) = ends(c);
+                cmp_crossing_point(self.list, &self.index_fn, s, t, p)
+            }
+            (Event::Endpoint(_), Event::Crossing(_)) => self.cmp_event(f, e).reverse(),
+            (Event::Crossing(c), Event::Crossing(d)) => {
+                let ((s1, t1), (s2, t2)) = (ends(c), ends(d));
+                cmp_crossings(self.list, &self.index_fn, s1, t1, s2, t2).then(c.cmp(&d))
+            }
+        }
+    }
+
+    fn right_of(&self, i: usize, j: usize) -> bool {
+        crate::orient_1d(self.list, |l, i| Vector1::new((self.index_fn)(l, i).x), i, j)
+    }
+
+    /// Whether segment `s` is below segment `t` just right of `s`'s left endpoint, which `t` spans.
+    fn below(&self, s: usize, t: usize) -> bool {
+        let ([p, q], [a, b]) = (self.ends[s], self.ends[t]);
+        !self.orient(a, b, if a == p { q } else { p })
+    }
+
+    /// Schedules the crossing of 2 segments if they cross and it isn't scheduled already.
+    fn check(&mut self, s: usize, t: usize) {
+        let ([a, b], [c, d]) = (self.ends[s], self.ends[t]);
+        let pair = [s.min(t), s.max(t)];
+        if self.found.contains(&pair) || !crate::cdt::segments_cross(self.list, &self.index_fn, a, b, c, d) {
+            return;
+        }
+        self.found.insert(pair);
+        let pos = self.crossings.partition_point(|c| {
+            self.cmp_event(Event::Crossing(*c), Event::Crossing(pair)) == Ordering::Less
+        });
+        self.crossings.insert(pos, pair);
+    }
+
+    /// Checks the segments at positions `i` and `i + 1` of the status, if they exist.
+    fn check_at(&mut self, i: usize) {
+        if i + 1 < self.status.len() {
+            self.check(self.status[i], self.status[i + 1]);
+        }
+    }
+}
+
+/// Finds all the intersections between segments after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, and the segments as pairs of indexes of
+/// their endpoints, which must be different. No 2 segments may have the same endpoints.
+/// Returns the intersections in the order the sweep finds them, from left to right.
+pub fn intersections<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, segments: &[[usize; 2]]) -> Vec<Intersection> {
+    let mut sweep = Sweep {
+        list,
+        index_fn,
+        ends: vec![],
+        status: vec![],
+        crossings: vec![],
+        found: BTreeSet::new(),
+    };
+    sweep.ends = segments
+        .iter()
+        .map(|[a, b]| if sweep.right_of(*a, *b) { [*b, *a] } else { [*a, *b] })
+        .collect();
+
+    let mut endpoints = segments.iter().flatten().copied().collect::<Vec<_>>();
+    endpoints.sort_unstable();
+    endpoints.dedup();
+    endpoints.sort_unstable_by(|p, q| sweep.cmp_event(Event::Endpoint(*p), Event::Endpoint(*q)));
+    let mut starting = vec![vec![]; endpoints.len()];
+    let mut ending = vec![vec![]; endpoints.len()];
+    let rank = endpoints.iter().enumerate().map(|(r, p)| (*p, r)).collect::<BTreeMap<_, _>>();
+    for (s, [a, b]) in sweep.ends.iter().enumerate() {
+        starting[rank[a]].push(s);
+        ending[rank[b]].push(s);
+    }
+
+    let mut intersections = vec![];
+    let mut next = 0;
+    while next < endpoints.len() || !sweep.crossings.is_empty() {
+        let crossing_first = sweep.crossings.first().is_some_and(|c| {
+            next == endpoints.len() || sweep.cmp_event(Event::Crossing(*c), Event::Endpoint(endpoints[next])) == Ordering::Less
+        });
+
+        if crossing_first {
+            let [s, t] = sweep.crossings.remove(0);
+            intersections.push(Intersection::Crossing([s, t]));
+            // The segments are next to each other until they cross
+            let i = sweep.status.iter().position(|u| *u == s || *u == t).unwrap();
+            sweep.status.swap(i, i + 1);
+            if i > 0 {
+                sweep.check_at(i - 1);
+            }
+            sweep.check_at(i + 1);
+            continue;
+        }
+
+        let p = endpoints[next];
+        let mut touching = ending[next].iter().chain(&starting[next]).copied().collect::<Vec<_>>();
+        touching.sort_unstable();
+        for (k, s) in touching.iter().enumerate() {
+            for t in &touching[k + 1..] {
+                intersections.push(Intersection::Endpoint { point: p, segments: [*s, *t] });
+            }
+        }
+
+        for s in &ending[next] {
+            let i = sweep.status.iter().position(|u| u == s).unwrap();
+            sweep.status.remove(i);
+            if i > 0 {
+                sweep.check_at(i - 1);
+            }
+        }
+        for s in &starting[next] {
+            let i = sweep.status.partition_point(|t| !sweep.below(*s, *t));
+            sweep.status.insert(i, *s);
+            if i > 0 {
+                sweep.check_at(i - 1);
+            }
+            sweep.check_at(i);
+        }
+        next += 1;
+    }
+    intersections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{boolean, cdt, degenerate, delaunay3, orient_1d, orient_2d, orient_3d, rg, rng};
+    use crate::tests::grid_points_2d;
+    use nalgebra::{Vector2, Vector3};
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    fn test_arrangement(seed: u64) {
+        use std::cmp::Ordering;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.collinear_2d(10),
+            _ => {
+                let points = gen.grid_clusters_2d(3, 4);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let n = points.len();
+        let mut rng = rng::SplitMix(seed);
+        let mut segments = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| [i, j])).collect::<Vec<_>>();
+        segments.retain(|_| rng.below(3) == 0);
+
+        let intersections = super::intersections(&points, |l, i| l[i], &segments);
+        let mut crossings = intersections
+            .iter()
+            .filter_map(|x| match x {
+                Intersection::Crossing(pair) => Some(*pair),
+                Intersection::Endpoint { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        // Found in order from left to right
+        let ends = |pair: [usize; 2]| (segments[pair[0]], segments[pair[1]]);
+        for w in crossings.windows(2) {
+            let ((s1, t1), (s2, t2)) = (ends(w[0]), ends(w[1]));
+            assert_eq!(super::cmp_crossings(&points, |l, i| l[i], s1, t1, s2, t2), Ordering::Less);
+        }
+        crossings.sort_unstable();
+
+        let m = segments.len();
+        let expected = (0..m)
+            .flat_map(|i| ((i + 1)..m).map(move |j| [i, j]))
+            .filter(|[i, j]| {
+                let ([a, b], [c, d]) = (segments[*i], segments[*j]);
+                cdt::segments_cross(&points, |l, i| l[i], a, b, c, d)
+            })
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(crossings, expected);
+        let endpoints = intersections.iter().filter(|x| matches!(x, Intersection::Endpoint { .. })).count();
+        let shared = (0..m)
+            .flat_map(|i| ((i + 1)..m).map(move |j| [i, j]))
+            .filter(|[i, j]| segments[*i].iter().any(|p| segments[*j].contains(p)))
+            .count();
+        assert_eq!(endpoints, shared);
+    }
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    fn test_cmp_along(seed: u64) {
+        use std::cmp::Ordering;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.collinear_2d(10),
+            _ => {
+                let points = gen.grid_clusters_2d(3, 4);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let n = points.len();
+        let mut rng = rng::SplitMix(seed);
+        let mut segments = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| [i, j])).collect::<Vec<_>>();
+        segments.retain(|_| rng.below(3) == 0);
+        let mut checked = 0;
+        for &[p, q] in segments.iter().step_by(8) {
+            let crosses = |[a, b]: [usize; 2]| cdt::segments_cross(&points, |l, i| l[i], p, q, a, b);
+            let mut on = segments
+                .iter()
+                .copied()
+                .filter(|s| crosses(*s) || (*s != [p, q] && (s.contains(&p) || s.contains(&q))))
+                .collect::<Vec<_>>();
+            let cmp = |s, t| super::cmp_along(&points, |l, i| l[i], [p, q], s, t);
+            on.sort_by(|s, t| cmp(*s, *t));
+
+            for (i, s) in on.iter().enumerate() {
+                for t in &on[i + 1..] {
+                    let order = cmp(*s, *t);
+                    assert_eq!(cmp(*t, *s), order.reverse());
+                    assert_ne!(order, Ordering::Greater, "{:?} {:?} along {:?}", s, t, [p, q]);
+                    // Only segments that meet pq at the same endpoint tie
+                    let shared = |e| s.contains(&e) && t.contains(&e);
+                    assert_eq!(order == Ordering::Equal, shared(p) || shared(q));
+                    if crosses(*s) && crosses(*t) {
+                        // The same order as the crossings' x coordinates, in the direction pq goes
+                        let x = super::cmp_crossings(&points, |l, i| l[i], [p, q], *s, [p, q], *t);
+                        let leftward = orient_1d(&points, |l, i| Vector1::new(l[i].x), p, q);
+                        assert_eq!(order, if leftward { x.reverse() } else { x });
+                    }
+                    checked += 1;
+                }
+            }
+            // Segments at p come first and segments at q come last
+            if let (Some(first), Some(last)) = (on.first(), on.last()) {
+                assert!(on.iter().any(|s| s.contains(&p)) <= first.contains(&p));
+                assert!(on.iter().any(|s| s.contains(&q)) <= last.contains(&q));
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_orient_implicit() {
+        use super::Implicit::{self, Bisectors, Circumcenter, Crossing, Point};
+
+        let mut rng = rng::SplitMix(1490);
+        let points = (0..12).map(|_| Vector2::new(rng.int(0, 4), rng.int(0, 4))).collect::<Vec<_>>();
+        let orient = |p, q, r| super::orient_implicit(&points, |l, i| l[i], p, q, r);
+        let estimate = |v: Implicit<usize>| match v {
+            Point(i) => points[i],
+            Crossing([a, b], [c, d]) => boolean::Vertex::Crossing([a, b], [c, d]).estimate(&points, |l, i| l[i]),
+            Circumcenter([a, b, c]) => {
+                let (u, v) = (points[b] - points[a], points[c] - points[a]);
+                let (lu, lv) = (u.norm_squared(), v.norm_squared());
+                points[a] + Vector2::new(lu * v.y - lv * u.y, lv * u.x - lu * v.x) / (2.0 * u.perp(&v))
+            }
+            Bisectors([a, b], [c, d]) => {
+                let (u, v) = (points[b] - points[a], points[d] - points[c]);
+                let s = points[b].norm_squared() - points[a].norm_squared();
+                let t = points[d].norm_squared() - points[c].norm_squared();
+                Vector2::new(s * v.y - t * u.y, t * u.x - s * v.x) / (2.0 * u.perp(&v))
+            }
+        };
+        let cross = |s: [usize; 2], t: [usize; 2]| rg::orient_2d(points[s[0]], points[s[1]], points[t[0]]) != rg::orient_2d(points[s[0]], points[s[1]], points[t[1]]);
+
+        for _ in 0..300 {
+            let mut pick = || rng.below(points.len());
+            let (a, b, c, d, e, f) = (pick(), pick(), pick(), pick(), pick(), pick());
+            let all = [a, b, c, d, e, f];
+            if all.iter().enumerate().any(|(n, i)| all[n + 1..].contains(i)) {
+                continue;
+            }
+            let ([s, t], u) = ([[a, b], [c, d]], [e, f]);
+            let x = Crossing(s, t);
+            // Without crossings, it's orient_2d
+            assert_eq!(orient(Point(a), Point(b), Point(c)), orient_2d(&points, |l, i| l[i], a, b, c));
+            // The same point however it's written
+            for y in [Crossing(t, s), Crossing([b, a], t), Crossing(s, [d, c])] {
+                assert_eq!(orient(Point(e), Point(f), x), orient(Point(e), Point(f), y));
+            }
+            // A crossing at a shared endpoint is that point
+            assert_eq!(orient(Point(c), Point(d), Crossing([a, b], [a, c])), orient_2d(&points, |l, i| l[i], c, d, a));
+            // Orientation axioms
+            let y = Crossing(t, u);
+            assert_eq!(orient(x, Point(e), y), orient(Point(e), y, x));
+            assert_ne!(orient(x, Point(e), y), orient(Point(e), x, y));
+            // Agrees with the rounded crossing when it's far from degenerate
+            if cross(s, t) && rg::orient_2d(points[a], points[b], points[c]) != 0.0 {
+                let approx = rg::orient_2d(points[e], points[f], estimate(x));
+                if approx.abs() > 1e-9 {
+                    assert_eq!(orient(Point(e), Point(f), x), approx > 0.0);
+                }
+            }
+            // Circumcenters don't depend on the order of the vertices
+            let z = Circumcenter([a, b, c]);
+            for w in [Circumcenter([b, c, a]), Circumcenter([b, a, c])] {
+                assert_eq!(orient(Point(e), Point(f), z), orient(Point(e), Point(f), w));
+            }
+            assert_ne!(orient(z, Point(e), x), orient(Point(e), z, x));
+            // The bisectors of 2 sides of a triangle meet at its circumcenter
+            assert_eq!(orient(Point(e), Point(f), z), orient(Point(e), Point(f), Bisectors([a, b], [b, c])));
+            assert_eq!(orient(Point(e), Point(f), z), orient(Point(e), Point(f), Bisectors([c, a], [b, c])));
+            if rg::orient_2d(points[a], points[b], points[c]) != 0.0 {
+                let approx = rg::orient_2d(points[e], points[f], estimate(z));
+                if approx.abs() > 1e-9 {
+                    assert_eq!(orient(Point(e), Point(f), z), approx > 0.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_orient_circumcenter() {
+        use super::Implicit::{Circumcenter, Point};
+
+        // Triangles and tetrahedra with their circumcenters at (1, 1) and (1, 1, 1),
+        // compared against the center itself on lines and planes that miss it
+        let mut points_2d = vec![Vector2::new(0.0, 0.0), Vector2::new(2.0, 0.0), Vector2::new(0.0, 2.0)];
+        points_2d.extend((0..16).map(|i| Vector2::new((i % 4) as f64 - 1.0, (i / 4) as f64 - 1.0)));
+        let center = points_2d.iter().rposition(|p| *p == Vector2::new(1.0, 1.0)).unwrap();
+        for p in 3..points_2d.len() {
+            for q in 3..points_2d.len() {
+                if rg::orient_2d(points_2d[p], points_2d[q], points_2d[center]) == 0.0 {
+                    continue;
+                }
+                let expected = orient_2d(&points_2d, |l, i| l[i], p, q, center);
+                for tri in [[0, 1, 2], [2, 1, 0], [1, 2, 0]] {
+                    let implicit = super::orient_implicit(&points_2d, |l, i| l[i], Point(p), Point(q), Circumcenter(tri));
+                    assert_eq!(implicit, expected);
+                }
+            }
+        }
+
+        let mut points_3d = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+        ];
+        points_3d.extend((0..27).map(|i| Vector3::new((i % 3) as f64, (i / 3 % 3) as f64, (i / 9) as f64)));
+        let center = points_3d.iter().rposition(|p| *p == Vector3::new(1.0, 1.0, 1.0)).unwrap();
+        let mut rng = rng::SplitMix(1491);
+        let mut checked = 0;
+        while checked < 50 {
+            let (a, b, c) = (4 + rng.below(27), 4 + rng.below(27), 4 + rng.below(27));
+            if rg::orient_3d(points_3d[a], points_3d[b], points_3d[c], points_3d[center]) == 0.0 {
+                continue;
+            }
+            let expected = orient_3d(&points_3d, |l, i| l[i], a, b, c, center);
+            for tet in [[0, 1, 2, 3], [3, 2, 1, 0], [1, 0, 2, 3]] {
+                assert_eq!(delaunay3::orient_circumcenter(&points_3d, |l, i| l[i], a, b, c, tet), expected);
+            }
+            checked += 1;
+        }
+    }
+}
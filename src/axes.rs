@@ -0,0 +1,213 @@
+//! Variants of the predicates that perturb the axes in a chosen order.
+//!
+//! The usual predicates perturb later coordinates of a point more, so ties between collinear
+//! points break along y before x, and along z before y. These take the axes from the most
+//! perturbed to the least instead, so the ties can agree with, say, the direction of a sweep.
+//! The usual order is `[1, 0]` in 2D and `[2, 1, 0]` in 3D.
+//!
+//! These reorder the coordinates of the points and call the usual predicates, which flips
+//! the determinants if the reordering is an odd permutation, so they flip the result back.
+//! Non-degenerate input gives the same answers as the usual predicates.
+//! They panic if the order isn't a permutation of the axes.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, orient_2d, axes};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 2.0),
+//! ];
+//! // Perturbing the first point up makes a left turn, but perturbing it right doesn't
+//! assert!(orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! assert!(axes::orient_2d(&points, |l, i| l[i], [1, 0], 0, 1, 2));
+//! assert!(!axes::orient_2d(&points, |l, i| l[i], [0, 1], 0, 1, 2));
+//! ```
+
+use crate::{Vec2, Vec3};
+use alloc::vec;
+use core::ops::IndexMut;
+
+/// Reorders the coordinates of each point so the axes in `order` are the most perturbed first.
+/// Also returns whether that's an odd permutation of the coordinates.
+fn reorder<T: ?Sized, Idx, P: Copy + IndexMut<usize, Output = f64>>(
+    index_fn: impl Fn(&T, Idx) -> P + Clone,
+    order: &[usize],
+) -> (impl Fn(&T, Idx) -> P + Clone, bool) {
+    let n = order.len();
+    let mut seen = vec![false; n];
+    for a in order {
+        assert!(*a < n && !seen[*a], "{:?} isn't a permutation of the axes", order);
+        seen[*a] = true;
+    }
+    // Inversions relative to the usual order
+    let inversions = (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).filter(|(i, j)| order[*i] < order[*j]).count();
+
+    let order = order.to_vec();
+    let reordered = move |list: &T, i| {
+        let p = index_fn(list, i);
+        let mut q = p;
+        for c in 0..n {
+            q[c] = p[order[n - 1 - c]];
+        }
+        q
+    };
+    (reordered, inversions % 2 == 1)
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but perturbing the axes in `order`, most perturbed first.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    order: [usize; 2],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    let (index_fn, odd) = reorder(index_fn, &order);
+    crate::orient_2d(list, index_fn, i, j, k) != odd
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but perturbing the axes in `order`, most perturbed first.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    order: [usize; 3],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let (index_fn, odd) = reorder(index_fn, &order);
+    crate::orient_3d(list, index_fn, i, j, k, l) != odd
+}
+
+/// Like [`in_circle`](crate::in_circle), but perturbing the axes in `order`, most perturbed first.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    order: [usize; 2],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let (index_fn, odd) = reorder(index_fn, &order);
+    crate::in_circle(list, index_fn, i, j, k, l) != odd
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but perturbing the axes in `order`,
+/// most perturbed first.
+pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    order: [usize; 2],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let (index_fn, _) = reorder(index_fn, &order);
+    crate::in_circle_unoriented(list, index_fn, i, j, k, l)
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but perturbing the axes in `order`, most perturbed first.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    order: [usize; 3],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    let (index_fn, odd) = reorder(index_fn, &order);
+    crate::in_sphere(list, index_fn, i, j, k, l, m) != odd
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but perturbing the axes in `order`,
+/// most perturbed first.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    order: [usize; 3],
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    let (index_fn, _) = reorder(index_fn, &order);
+    crate::in_sphere_unoriented(list, index_fn, i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_axes() {
+        let points = grid_points_2d();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    let orient = orient_2d(&points, |l, i| l[i], i, j, k);
+                    assert_eq!(super::orient_2d(&points, |l, i| l[i], [1, 0], i, j, k), orient);
+                    let swapped = super::orient_2d(&points, |l, i| l[i], [0, 1], i, j, k);
+
+                    // Distinct collinear points are decided by the derivative along the chosen axis
+                    // of the point with the lowest index, unless it's 0
+                    let tri = [points[i], points[j], points[k]];
+                    let pos = (0..3).min_by_key(|p| [i, j, k][*p]).unwrap();
+                    let (next, prev) = (tri[(pos + 1) % 3], tri[(pos + 2) % 3]);
+                    let (dx, dy) = (next.y - prev.y, prev.x - next.x);
+                    let distinct = tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0];
+                    if distinct && (tri[1] - tri[0]).perp(&(tri[2] - tri[0])) == 0.0 {
+                        assert_eq!(orient, if dy != 0.0 { dy > 0.0 } else { dx > 0.0 });
+                        assert_eq!(swapped, if dx != 0.0 { dx > 0.0 } else { dy > 0.0 });
+                    } else if (tri[1] - tri[0]).perp(&(tri[2] - tri[0])) != 0.0 {
+                        assert_eq!(swapped, orient);
+                    }
+
+                    let l = (k + 1) % n;
+                    if l != i && l != j {
+                        let inside = in_circle(&points, |l, i| l[i], i, j, k, l);
+                        assert_eq!(super::in_circle(&points, |l, i| l[i], [1, 0], i, j, k, l), inside);
+                        assert_eq!(
+                            super::in_circle_unoriented(&points, |l, i| l[i], [0, 1], i, j, k, l),
+                            swapped == super::in_circle(&points, |l, i| l[i], [0, 1], i, j, k, l)
+                        );
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+            assert_eq!(
+                super::orient_3d(&points, |l, i| l[i], [2, 1, 0], i, j, k, l),
+                orient_3d(&points, |l, i| l[i], i, j, k, l)
+            );
+            assert_eq!(
+                super::in_sphere(&points, |l, i| l[i], [2, 1, 0], i, j, k, l, m),
+                in_sphere(&points, |l, i| l[i], i, j, k, l, m)
+            );
+            for order in [[0, 1, 2], [1, 0, 2], [2, 0, 1]] {
+                assert_eq!(
+                    super::in_sphere_unoriented(&points, |l, i| l[i], order, i, j, k, l, m),
+                    super::orient_3d(&points, |l, i| l[i], order, i, j, k, l)
+                        == super::in_sphere(&points, |l, i| l[i], order, i, j, k, l, m)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Boolean operations on simple polygons.
+//!
+//! This is the Greiner–Hormann algorithm. The crossings between the edges of the 2 polygons
+//! are found with [`arrangement::intersections`] and sorted
+//! along each edge with [`arrangement::cmp_along`].
+//! Then the result is traced by following each polygon between crossings, switching polygons
+//! at each crossing. Whether a polygon enters the other at a crossing is decided by
+//! [`orient_2d`](crate::orient_2d), and polygons that don't cross at all are compared with
+//! [`polygon::contains`](crate::polygon::contains).
+//!
+//! Crossings are never computed. They're represented by the edges that cross, so every
+//! decision is exact, and the perturbation rules out the usual trouble with vertices on
+//! edges and overlapping edges, even when the input has them.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, boolean::{self, Vertex}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(2.0, 2.0),
+//!     Vector2::new(0.0, 2.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(3.0, 1.0),
+//!     Vector2::new(3.0, 3.0),
+//!     Vector2::new(1.0, 3.0),
+//! ];
+//! let a = [0, 1, 2, 3];
+//! let b = [4, 5, 6, 7];
+//! let rings = boolean::intersection(&points, |l, i| l[i], &a, &b);
+//! assert_eq!(rings.len(), 1);
+//! assert!(rings[0].contains(&Vertex::Point(2)));
+//! assert!(rings[0].contains(&Vertex::Point(4)));
+//! assert!(rings[0].contains(&Vertex::Crossing([1, 2], [4, 5])));
+//! assert_eq!(boolean::union(&points, |l, i| l[i], &a, &b)[0].len(), 8);
+//! ```
+
+use crate::arrangement::{self, Intersection};
+use crate::Vec2;
+use alloc::{vec, vec::Vec};
+
+/// A vertex of the result of a boolean operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Vertex {
+    /// A vertex of either polygon, by index.
+    Point(usize),
+    /// The crossing of an edge of the first polygon and an edge of the second polygon,
+    /// each as the indexes of its endpoints in the order the polygon goes.
+    Crossing([usize; 2], [usize; 2]),
+}
+
+impl Vertex {
+    /// An approximation of the vertex, given a list of all the points and an indexing function.
+    pub fn estimate<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2) -> Vec2 {
+        match *self {
+            Vertex::Point(i) => index_fn(list, i),
+            Vertex::Crossing([a, b], [c, d]) => {
+                let (a, b, c, d) = (index_fn(list, a), index_fn(list, b), index_fn(list, c), index_fn(list, d));
+                let t = (c - a).perp(&(d - c)) / (b - a).perp(&(d - c));
+                a + (b - a) * t
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Node {
+    Point(usize),
+    Crossing(usize),
+}
+
+/// The vertices of a polygon with its crossings inserted, and the position of each crossing.
+fn insert_crossings(polygon: &[usize], crossings_on: &[Vec<usize>], num_crossings: usize) -> (Vec<Node>, Vec<usize>) {
+    let mut nodes = vec![];
+    let mut positions = vec![0; num_crossings];
+    for (v, crossings) in polygon.iter().zip(crossings_on) {
+        nodes.push(Node::Point(*v));
+        for k in crossings {
+            positions[*k] = nodes.len();
+            nodes.push(Node::Crossing(*k));
+        }
+    }
+    (nodes, positions)
+}
+
+fn combine<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, a: &[usize], b: &[usize], op: Operation) -> Vec<Vec<Vertex>> {
+    let counterclockwise = |polygon: &[usize]| {
+        let mut polygon = polygon.to_vec();
+        if !crate::polygon::is_counterclockwise(list, &index_fn, &polygon) {
+            polygon.reverse();
+        }
+        polygon
+    };
+    let (a, b) = (counterclockwise(a), counterclockwise(b));
+    let edge = |polygon: &[usize], e: usize| [polygon[e], polygon[(e + 1) % polygon.len()]];
+
+    let segments = (0..a.len()).map(|e| edge(&a, e)).chain((0..b.len()).map(|e| edge(&b, e))).collect::<Vec<_>>();
+    // Pairs of crossing edges, by position in each polygon
+    let crossings = arrangement::intersections(list, &index_fn, &segments)
+        .into_iter()
+        .filter_map(|x| match x {
+            Intersection::Crossing([s, t]) if s < a.len() && t >= a.len() => Some((s, t - a.len())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let vertex = |k: usize| Vertex::Crossing(edge(&a, crossings[k].0), edge(&b, crossings[k].1));
+
+    if crossings.is_empty() {
+        let a_in_b = crate::polygon::contains(list, &index_fn, &b, a[0]);
+        let b_in_a = crate::polygon::contains(list, &index_fn, &a, b[0]);
+        let ring = |polygon: &[usize]| polygon.iter().map(|v| Vertex::Point(*v)).collect::<Vec<_>>();
+        let hole = |polygon: &[usize]| polygon.iter().rev().map(|v| Vertex::Point(*v)).collect::<Vec<_>>();
+        return match (op, a_in_b, b_in_a) {
+            (Operation::Union, true, _) => vec![ring(&b)],
+            (Operation::Union, _, true) => vec![ring(&a)],
+            (Operation::Union, _, _) => vec![ring(&a), ring(&b)],
+            (Operation::Intersection, true, _) => vec![ring(&a)],
+            (Operation::Intersection, _, true) => vec![ring(&b)],
+            (Operation::Intersection, _, _) => vec![],
+            (Operation::Difference, true, _) => vec![],
+            (Operation::Difference, _, true) => vec![ring(&a), hole(&b)],
+            (Operation::Difference, _, _) => vec![ring(&a)],
+        };
+    }
+
+    // Sort the crossings along each edge, in the direction the edge goes
+    let sorted_crossings = |polygon: &[usize], edge_of: &dyn Fn(usize) -> usize, other: &dyn Fn(usize) -> [usize; 2]| {
+        let mut on = vec![vec![]; polygon.len()];
+        for k in 0..crossings.len() {
+            on[edge_of(k)].push(k);
+        }
+        for (e, ks) in on.iter_mut().enumerate() {
+            let edge = edge(polygon, e);
+            ks.sort_by(|k1, k2| arrangement::cmp_along(list, &index_fn, edge, other(*k1), other(*k2)));
+        }
+        on
+    };
+    let on_a = sorted_crossings(&a, &|k| crossings[k].0, &|k| edge(&b, crossings[k].1));
+    let on_b = sorted_crossings(&b, &|k| crossings[k].1, &|k| edge(&a, crossings[k].0));
+    let (nodes_a, pos_a) = insert_crossings(&a, &on_a, crossings.len());
+    let (nodes_b, pos_b) = insert_crossings(&b, &on_b, crossings.len());
+
+    // Whether a enters b at each crossing, so the part of a after it is inside b
+    let enters = crossings
+        .iter()
+        .map(|(ea, eb)| {
+            let [b0, b1] = edge(&b, *eb);
+            crate::orient_2d(list, &index_fn, b0, b1, edge(&a, *ea)[1])
+        })
+        .collect::<Vec<_>>();
+    // The parts of a to keep are inside b for an intersection, and outside it otherwise.
+    // The parts of b to keep are traced backward for a difference.
+    let keep_inside = op == Operation::Intersection;
+    let b_step = if op == Operation::Difference { nodes_b.len() - 1 } else { 1 };
+
+    let mut visited = vec![false; crossings.len()];
+    let mut rings = vec![];
+    for start in 0..crossings.len() {
+        if visited[start] || enters[start] != keep_inside {
+            continue;
+        }
+        let mut ring = vec![];
+        let (mut k, mut on_a) = (start, true);
+        loop {
+            visited[k] = true;
+            ring.push(vertex(k));
+            let (nodes, step, mut i) = if on_a { (&nodes_a, 1, pos_a[k]) } else { (&nodes_b, b_step, pos_b[k]) };
+            loop {
+                i = (i + step) % nodes.len();
+                match nodes[i] {
+                    Node::Point(v) => ring.push(Vertex::Point(v)),
+                    Node::Crossing(next) => {
+                        k = next;
+                        break;
+                    }
+                }
+            }
+            on_a = !on_a;
+            if k == start {
+                break;
+            }
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+/// Computes the union of 2 simple polygons after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, and the polygons as sequences of indexes
+/// into the list, in either orientation. The polygons must not share any indexes, but they can
+/// have equal points. Returns the boundary of the union as rings, with outer boundaries
+/// counterclockwise and holes clockwise.
+pub fn union<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, a: &[usize], b: &[usize]) -> Vec<Vec<Vertex>> {
+    combine(list, index_fn, a, b, Operation::Union)
+}
+
+/// Computes the intersection of 2 simple polygons after perturbing the points.
+/// See [`union`] for the arguments and the result.
+pub fn intersection<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, a: &[usize], b: &[usize]) -> Vec<Vec<Vertex>> {
+    combine(list, index_fn, a, b, Operation::Intersection)
+}
+
+/// Computes the difference of 2 simple polygons, the part of `a` outside `b`, after perturbing the points.
+/// See [`union`] for the arguments and the result.
+pub fn difference<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, a: &[usize], b: &[usize]) -> Vec<Vec<Vertex>> {
+    combine(list, index_fn, a, b, Operation::Difference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_boolean() {
+        let area = |points: &[Vec2], rings: &[Vec<super::Vertex>]| {
+            rings
+                .iter()
+                .map(|ring| {
+                    let ring = ring.iter().map(|v| v.estimate(points, |l, i| l[i])).collect::<Vec<_>>();
+                    (0..ring.len()).map(|i| ring[i].perp(&ring[(i + 1) % ring.len()]) / 2.0).sum::<f64>()
+                })
+                .sum::<f64>()
+        };
+        let polygon_area = |points: &[Vec2], polygon: &[usize]| {
+            let ring = polygon.iter().map(|i| super::Vertex::Point(*i)).collect::<Vec<_>>();
+            area(points, &[ring]).abs()
+        };
+
+        // A square and a diamond with vertices in line with the square's side, then one inside, then one outside
+        let points = [(0.0, 0.0), (4.0, 0.0), (4.0, 4.0), (0.0, 4.0), (6.5, 2.0), (4.0, 4.5), (1.5, 2.0), (4.0, -0.5)]
+            .iter()
+            .chain(&[(1.0, 1.0), (3.0, 1.0), (2.0, 3.0), (5.0, 5.0), (7.0, 5.0), (6.0, 7.0)])
+            .map(|(x, y)| Vector2::new(*x, *y))
+            .collect::<Vec<_>>();
+        let a = [0, 1, 2, 3];
+        for (b, crossings) in [(vec![4, 5, 6, 7], true), (vec![8, 9, 10], false), (vec![13, 12, 11], false)] {
+            let intersection = super::intersection(&points, |l, i| l[i], &a, &b);
+            let union = super::union(&points, |l, i| l[i], &a, &b);
+            let difference = super::difference(&points, |l, i| l[i], &a, &b);
+            let (area_a, area_b) = (polygon_area(&points, &a), polygon_area(&points, &b));
+            let area_i = area(&points, &intersection);
+            assert!((area(&points, &union) + area_i - area_a - area_b).abs() < 1e-9);
+            assert!((area(&points, &difference) + area_i - area_a).abs() < 1e-9);
+            assert_eq!(crossings, intersection.iter().flatten().any(|v| matches!(v, super::Vertex::Crossing(..))));
+        }
+
+        // Equal squares with different indexes, where the perturbation decides everything
+        let mut points = grid_points_2d();
+        points.extend(points.clone());
+        let a = [0, 2, 8, 6];
+        let b = [11, 13, 19, 17];
+        for rings in [
+            super::intersection(&points, |l, i| l[i], &a, &b),
+            super::union(&points, |l, i| l[i], &a, &b),
+            super::difference(&points, |l, i| l[i], &a, &b),
+        ] {
+            assert!(rings.iter().all(|ring| ring.len() >= 3));
+        }
+        assert!(!super::union(&points, |l, i| l[i], &a, &b).is_empty());
+    }
+}
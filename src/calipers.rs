@@ -0,0 +1,114 @@
+//! The decision that drives rotating calipers.
+//!
+//! Rotating calipers on convex hulls, for widths, diameters, and antipodal pairs,
+//! keep a pair of parallel supporting lines and rotate them to the next edge of whichever hull
+//! turns the least. That's decided by the sign of the cross product of the 2 edge directions,
+//! which is 0 when the edges are parallel, like opposite sides of a rectangle. Here it's a
+//! polynomial in the perturbations instead, so parallel edges never tie, and the calipers
+//! visit the same pairs of points every time.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, calipers::{self, Caliper}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(0.0, 0.5),
+//! ];
+//! // Calipers at 0 and 2 of the rectangle 0123, whose next edges are parallel,
+//! // so the perturbation decides, and whichever hull is A, the same caliper advances
+//! let advance = calipers::advance(&points, |l, i| l[i], [0, 1], [2, 3]);
+//! assert_ne!(advance, calipers::advance(&points, |l, i| l[i], [2, 3], [0, 1]));
+//! // In the quadrilateral 0124, edge 24 turns further from the calipers than edge 01
+//! assert_eq!(calipers::advance(&points, |l, i| l[i], [0, 1], [2, 4]), Caliper::A);
+//! ```
+
+use crate::perturbed::Perturbed;
+use crate::scale::Prescaled;
+use crate::Vec2;
+use core::cmp::Ordering;
+
+/// Which caliper to advance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Caliper {
+    /// The caliper on the hull of the first edge
+    A,
+    /// The caliper on the hull of the second edge
+    B,
+}
+
+/// Returns the sign of the cross product of the directions of edges `a` and `b` after
+/// perturbing the points; that is, whether `b` points to the left of `a`.
+/// Returns `Ordering::Equal` only if the edges have the same endpoints.
+pub fn cross_sign<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    [a0, a1]: [Idx; 2],
+    [b0, b1]: [Idx; 2],
+) -> Ordering {
+    let points = Prescaled::new(2, 0, &[a0, a1, b0, b1], &[], |i| index_fn(list, i));
+    let perturbed = Perturbed::new(2, 2, &[a0, a1, b0, b1], |i| points.point(i));
+    let cross = perturbed.diff(a1, a0, 0) * perturbed.diff(b1, b0, 1) - perturbed.diff(a1, a0, 1) * perturbed.diff(b1, b0, 0);
+    cross.signum().partial_cmp(&0.0).unwrap()
+}
+
+/// Decides which caliper to advance, given the next edge of each hull after the calipers,
+/// after perturbing the points.
+///
+/// The hulls go counterclockwise, and the calipers are parallel lines with opposite directions
+/// at the starts of the edges. Both rotate counterclockwise, and the caliper whose next edge
+/// makes the smaller turn from its direction advances; that's A if `b` points to the right of `a`.
+/// For antipodal pairs of a single hull, both edges are on the same hull.
+/// If the edges have the same endpoints, advances A.
+pub fn advance<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, a: [Idx; 2], b: [Idx; 2]) -> Caliper {
+    match cross_sign(list, index_fn, a, b) {
+        Ordering::Greater => Caliper::B,
+        Ordering::Less | Ordering::Equal => Caliper::A,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orient_2d;
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_calipers() {
+        use std::cmp::Ordering;
+
+        let points = grid_points_2d();
+        let n = points.len();
+        let cross = |a, b| super::cross_sign(&points, |l, i| l[i], a, b);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i) {
+                    // Edges from the same point turn like the triangle
+                    if k != j {
+                        let orient = if orient_2d(&points, |l, i| l[i], i, j, k) { Ordering::Greater } else { Ordering::Less };
+                        assert_eq!(cross([i, j], [i, k]), orient);
+                    }
+                    let l = (i + j + k) % n;
+                    if l == k {
+                        continue;
+                    }
+                    let (a, b) = ([i, j], [k, l]);
+                    assert_eq!(cross(a, b), cross(b, a).reverse());
+                    assert_eq!(cross(a, b), cross([j, i], [l, k]));
+                    if a == b || a == [l, k] {
+                        assert_eq!(cross(a, b), Ordering::Equal);
+                    } else {
+                        assert_ne!(cross(a, b), Ordering::Equal);
+                    }
+                    let exact = (points[j] - points[i]).perp(&(points[l] - points[k]));
+                    if exact != 0.0 {
+                        assert_eq!(cross(a, b), exact.partial_cmp(&0.0).unwrap());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Utilities for 2D constrained Delaunay triangulations.
+//!
+//! Inserting a constraint segment into a triangulation finds the triangles it crosses,
+//! removes them, and retriangulates the polygons on either side of the segment.
+//! Refining the triangulation, as in Ruppert's algorithm, splits segments that
+//! other points encroach upon. Everything here is decided by perturbed predicates,
+//! so a segment never passes exactly through a vertex and a point is never exactly on
+//! a segment's diametral circle, and the answers are consistent with the rest of the crate.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, cdt, delaunay2};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(4.0, 0.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(2.0, -1.0),
+//!     Vector2::new(2.0, 3.0),
+//!     Vector2::new(2.0, -3.0),
+//! ];
+//! let mut triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+//! assert!(!cdt::crossing(&points, |l, i| l[i], &triangles, 0, 1).edges.is_empty());
+//!
+//! cdt::insert_constraint(&points, |l, i| l[i], &mut triangles, 0, 1);
+//! assert!(triangles.iter().any(|t| t.contains(&0) && t.contains(&1)));
+//! assert!(cdt::crossing(&points, |l, i| l[i], &triangles, 0, 1).edges.is_empty());
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{Perturbed, Polynomial};
+use crate::scale::{self, Prescaled};
+use crate::Vec2;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// Returns whether segment `ab` crosses segment `cd` after perturbing the points.
+/// Segments that share an endpoint don't cross.
+pub fn segments_cross<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    d: Idx,
+) -> bool {
+    if a == c || a == d || b == c || b == d {
+        return false;
+    }
+    let orient = |i, j, k| crate::orient_2d(list, &index_fn, i, j, k);
+    orient(a, b, c) != orient(a, b, d) && orient(c, d, a) != orient(c, d, b)
+}
+
+/// Returns whether `p` is inside the diametral circle of segment `ab` after perturbing the points;
+/// that is, if the angle `apb` is obtuse. Such a point encroaches upon the segment.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, cdt};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(2.0, 0.0),
+///     Vector2::new(1.0, 0.5),
+///     Vector2::new(1.0, 1.0),
+/// ];
+/// assert!(cdt::encroaches(&points, |l, i| l[i], 0, 1, 2));
+/// // Exactly on the diametral circle, so the perturbation decides
+/// let on_circle = cdt::encroaches(&points, |l, i| l[i], 0, 1, 3);
+/// assert_eq!(on_circle, cdt::encroaches(&points, |l, i| l[i], 1, 0, 3));
+/// ```
+pub fn encroaches<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    a: Idx,
+    b: Idx,
+    p: Idx,
+) -> bool {
+    let [pa, pb, pp] = scale::prescaled([index_fn(list, a), index_fn(list, b), index_fn(list, p)]);
+    let diff = |x: f64, y: f64| Expansion::from(x) - Expansion::from(y);
+    let dot = (0..2).fold(Expansion::zero(), |acc, c| acc + diff(pa[c], pp[c]) * diff(pb[c], pp[c]));
+    if dot.signum() != 0.0 {
+        return dot.signum() < 0.0;
+    }
+
+    // Expand (a - p)·(b - p) as a polynomial in ε. Coordinate c of the point
+    // with rank r is perturbed by ε^(3^(3r + 2 - c)), and the term with the smallest exponent
+    // and a nonzero coefficient decides the sign. Exponents of different terms are
+    // different numbers in base 3, so they never tie.
+    let rank = |i: Idx| [a, b, p].iter().filter(|j| **j < i).count() as u32;
+    let exponent = |i: Idx, c: usize| 3u64.pow(3 * rank(i) + 2 - c as u32);
+    let mut terms = vec![];
+    for c in 0..2 {
+        let (ea, eb, ep) = (exponent(a, c), exponent(b, c), exponent(p, c));
+        terms.push((ea, diff(pb[c], pp[c]).signum()));
+        terms.push((eb, diff(pa[c], pp[c]).signum()));
+        terms.push((ep, -(diff(pa[c], pp[c]) + diff(pb[c], pp[c])).signum()));
+        terms.push((ea + eb, 1.0));
+        terms.push((ea + ep, -1.0));
+        terms.push((eb + ep, -1.0));
+        terms.push((2 * ep, 1.0));
+    }
+    terms.sort_by_key(|(e, _)| *e);
+    // The ε^(2 * 3^(...)) terms of p always have coefficient 1
+    terms.iter().find(|(_, sign)| *sign != 0.0).unwrap().1 < 0.0
+}
+
+/// Returns whether segment `cd` passes through the inside of the diametral circle of segment `ab`
+/// after perturbing the points. Such a segment encroaches upon `ab` even if its endpoints don't,
+/// which happens when constraints face each other at small angles.
+///
+/// A segment that shares an endpoint with `ab` encroaches if it leaves that endpoint
+/// toward the inside of the circle, and `ab` itself encroaches on itself.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, cdt};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(2.0, 0.0),
+///     Vector2::new(-1.0, 0.5),
+///     Vector2::new(3.0, 0.5),
+///     Vector2::new(-1.0, 1.0),
+///     Vector2::new(3.0, 1.0),
+/// ];
+/// // Both endpoints are outside, but the middle passes through
+/// assert!(!cdt::encroaches(&points, |l, i| l[i], 0, 1, 2));
+/// assert!(cdt::segment_encroaches(&points, |l, i| l[i], 0, 1, 2, 3));
+/// // Tangent to the circle, so the perturbation decides
+/// let tangent = cdt::segment_encroaches(&points, |l, i| l[i], 0, 1, 4, 5);
+/// assert_eq!(tangent, cdt::segment_encroaches(&points, |l, i| l[i], 1, 0, 5, 4));
+/// ```
+pub fn segment_encroaches<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    d: Idx,
+) -> bool {
+    let points = Prescaled::new(2, 0, &[a, b, c, d], &[], |i| index_fn(list, i));
+    let perturbed = Perturbed::new(2, 4, &[a, b, c, d], |i| points.point(i));
+    let diff = |i, j| [perturbed.diff(i, j, 0), perturbed.diff(i, j, 1)];
+    let dot = |u: &[Polynomial; 2], v: &[Polynomial; 2]| u[0].clone() * v[0].clone() + u[1].clone() * v[1].clone();
+    let cross = |u: &[Polynomial; 2], v: &[Polynomial; 2]| u[0].clone() * v[1].clone() - u[1].clone() * v[0].clone();
+
+    if [c, d] == [a, b] || [c, d] == [b, a] {
+        return true;
+    }
+    // Leaving an endpoint of the diameter, it's inside iff the angle with the diameter is acute
+    for (p, q, r) in [(a, b, c), (a, b, d), (b, a, c), (b, a, d)].iter().copied() {
+        if p == r {
+            let other = if r == c { d } else { c };
+            return dot(&diff(other, p), &diff(q, p)).signum() > 0.0;
+        }
+    }
+
+    // An endpoint is inside if it sees the diameter at an obtuse angle
+    let inside = |p| dot(&diff(a, p), &diff(b, p)).signum() < 0.0;
+    if inside(c) || inside(d) {
+        return true;
+    }
+
+    // Otherwise, the closest point of the line to the center has to be between c and d,
+    // closer than the radius. Twice the vector from c to the center is (a - c) + (b - c).
+    let twice_center = |p| {
+        let [ax, ay] = diff(a, p);
+        let [bx, by] = diff(b, p);
+        [ax + bx, ay + by]
+    };
+    let (cd, dc) = (diff(d, c), diff(c, d));
+    if dot(&twice_center(c), &cd).signum() <= 0.0 || dot(&twice_center(d), &dc).signum() <= 0.0 {
+        return false;
+    }
+    let twice_distance = cross(&twice_center(c), &cd);
+    let ab = diff(b, a);
+    (dot(&ab, &ab) * dot(&cd, &cd) - twice_distance.clone() * twice_distance).signum() > 0.0
+}
+
+/// The part of a triangulation that a segment crosses.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Crossing {
+    /// The crossed edges, in order from the start of the segment to its end.
+    /// Each edge has the vertex on the right of the segment first.
+    pub edges: Vec<[usize; 2]>,
+    /// The indexes of the crossed triangles, in the same order
+    pub triangles: Vec<usize>,
+    /// The vertices of the crossed triangles on the left of the segment, in order
+    pub left: Vec<usize>,
+    /// The vertices of the crossed triangles on the right of the segment, in order
+    pub right: Vec<usize>,
+}
+
+/// Finds the edges and triangles that segment `ab` crosses, after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the triangles as triples of indexes
+/// oriented positive by [`orient_2d`](crate::orient_2d), such as the ones returned by
+/// [`delaunay2::triangulate`](crate::delaunay2::triangulate), and the endpoints,
+/// which must be vertices of the triangulation. The triangulation must cover the segment.
+/// If `ab` is already an edge, nothing is crossed.
+pub fn crossing<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    triangles: &[[usize; 3]],
+    a: usize,
+    b: usize,
+) -> Crossing {
+    let orient = |i, j, k| crate::orient_2d(list, &index_fn, i, j, k);
+    let mut result = Crossing::default();
+
+    // The triangle at `a` whose opposite edge the segment leaves through
+    let mut start = None;
+    for (t, tri) in triangles.iter().enumerate() {
+        if let Some(pos) = tri.iter().position(|v| *v == a) {
+            let (u, v) = (tri[(pos + 1) % 3], tri[(pos + 2) % 3]);
+            if u == b || v == b {
+                return result;
+            }
+            if orient(a, u, b) && orient(a, b, v) {
+                start = Some((t, u, v));
+            }
+        }
+    }
+    let (t, mut u, mut v) = start.expect("the segment's start should be in the triangulation");
+
+    let edges = triangles
+        .iter()
+        .enumerate()
+        .flat_map(|(t, tri)| (0..3).map(move |e| ((tri[e], tri[(e + 1) % 3]), t)))
+        .collect::<BTreeMap<_, _>>();
+    result.triangles.push(t);
+    result.right.push(u);
+    result.left.push(v);
+    loop {
+        result.edges.push([u, v]);
+        let t = edges[&(v, u)];
+        result.triangles.push(t);
+        let tri = triangles[t];
+        let w = tri[(tri.iter().position(|x| *x == v).unwrap() + 2) % 3];
+        if w == b {
+            return result;
+        }
+        if orient(a, b, w) {
+            result.left.push(w);
+            v = w;
+        } else {
+            result.right.push(w);
+            u = w;
+        }
+    }
+}
+
+/// Triangulates the polygon with edge `ab` and the vertices in `chain` on the left of it,
+/// in order from `a` to `b`, so that the triangles are Delaunay within the polygon.
+///
+/// This is how the polygons on either side of a constraint are retriangulated after
+/// the triangles it crossed are removed. The triangles are oriented positive.
+pub fn triangulate_cavity<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    a: usize,
+    b: usize,
+    chain: &[usize],
+) -> Vec<[usize; 3]> {
+    fn recurse<T: ?Sized>(
+        list: &T,
+        index_fn: &impl Fn(&T, usize) -> Vec2,
+        a: usize,
+        b: usize,
+        chain: &[usize],
+        triangles: &mut Vec<[usize; 3]>,
+    ) {
+        if chain.is_empty() {
+            return;
+        }
+        // The vertex whose circle with `a` and `b` has no other vertex inside.
+        // Those circles are nested on this side of `ab`, so 1 pass finds it.
+        let mut m = 0;
+        for n in 1..chain.len() {
+            if crate::in_circle(list, index_fn, a, b, chain[m], chain[n]) {
+                m = n;
+            }
+        }
+        recurse(list, index_fn, a, chain[m], &chain[..m], triangles);
+        recurse(list, index_fn, chain[m], b, &chain[m + 1..], triangles);
+        triangles.push([a, b, chain[m]]);
+    }
+
+    let mut triangles = vec![];
+    recurse(list, &index_fn, a, b, chain, &mut triangles);
+    triangles
+}
+
+/// Inserts segment `ab` into a triangulation as a constraint, replacing the triangles
+/// it crosses so that it's an edge.
+///
+/// Takes the same arguments as [`crossing`]. The new triangles are Delaunay within
+/// the polygons on either side of the segment, so if the triangulation was constrained
+/// Delaunay, it still is.
+pub fn insert_constraint<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    triangles: &mut Vec<[usize; 3]>,
+    a: usize,
+    b: usize,
+) {
+    let crossing = crossing(list, &index_fn, triangles, a, b);
+    if crossing.edges.is_empty() {
+        return;
+    }
+
+    let mut removed = crossing.triangles;
+    removed.sort_unstable();
+    for t in removed.into_iter().rev() {
+        triangles.swap_remove(t);
+    }
+    let right = crossing.right.into_iter().rev().collect::<Vec<_>>();
+    triangles.extend(triangulate_cavity(list, &index_fn, a, b, &crossing.left));
+    triangles.extend(triangulate_cavity(list, &index_fn, b, a, &right));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{delaunay2, orient_2d, rng, verify_delaunay_2d};
+    use nalgebra::Vector2;
+    use test_case::test_case;
+
+    #[test]
+    fn test_cdt() {
+        use std::collections::HashSet;
+
+        // A grid, with lots of collinear and cocircular points
+        let points = (0..25).map(|i| Vector2::new((i % 5) as f64, (i / 5) as f64)).collect::<Vec<_>>();
+        let mut triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+        let num_triangles = triangles.len();
+        let edges = |triangles: &[[usize; 3]]| {
+            triangles.iter().flat_map(|t| (0..3).map(move |e| [t[e], t[(e + 1) % 3]])).collect::<Vec<_>>()
+        };
+
+        let constraints = [[0, 24], [5, 23], [2, 14], [10, 21]];
+        for (n, [a, b]) in constraints.iter().copied().enumerate() {
+            // The crossed edges are exactly the ones that cross the segment
+            let crossing = super::crossing(&points, |l, i| l[i], &triangles, a, b);
+            let mut crossed = edges(&triangles).into_iter()
+                .filter(|[i, j]| super::segments_cross(&points, |l, i| l[i], a, b, *i, *j) && orient_2d(&points, |l, i| l[i], a, b, *j))
+                .collect::<Vec<_>>();
+            let mut expected = crossing.edges.clone();
+            crossed.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(crossed, expected);
+            assert_eq!(crossing.triangles.len(), crossing.edges.len() + 1);
+
+            super::insert_constraint(&points, |l, i| l[i], &mut triangles, a, b);
+            assert_eq!(triangles.len(), num_triangles);
+            let edges = edges(&triangles);
+            assert_eq!(edges.iter().collect::<HashSet<_>>().len(), edges.len());
+            assert!(edges.contains(&[a, b]) || edges.contains(&[b, a]));
+            for [i, j, k] in &triangles {
+                assert!(orient_2d(&points, |l, i| l[i], *i, *j, *k));
+            }
+
+            // The triangulation is Delaunay except across the constraints, which don't cross each other
+            let kept = &constraints[..=n];
+            for violation in verify_delaunay_2d(&points, |l, i| l[i], &triangles) {
+                let tri = triangles[violation.triangle];
+                let shared = tri.iter().copied().filter(|v| {
+                    triangles.iter().any(|t| t.contains(&violation.vertex) && t.contains(v) && tri.iter().filter(|u| t.contains(u)).count() == 2)
+                }).collect::<Vec<_>>();
+                assert!(kept.iter().any(|[a, b]| shared.contains(a) && shared.contains(b)), "{:?}", violation);
+            }
+        }
+    }
+
+    #[test_case([0, 1, 2], true; "a perturbed toward p")]
+    #[test_case([1, 2, 0], false; "p perturbed away")]
+    fn test_encroaches_degenerate(indexes: [usize; 3], expected: bool) {
+        // p is on the diametral circle of ab. The largest perturbation is
+        // in the y coordinate of the point with the lowest index.
+        let mut points = vec![Vector2::zeros(); 3];
+        points[indexes[0]] = Vector2::new(0.0, 0.0);
+        points[indexes[1]] = Vector2::new(2.0, 0.0);
+        points[indexes[2]] = Vector2::new(1.0, 1.0);
+        let [a, b, p] = indexes;
+        assert_eq!(super::encroaches(&points, |l, i| l[i], a, b, p), expected);
+        assert_eq!(super::encroaches(&points, |l, i| l[i], b, a, p), expected);
+    }
+
+    #[test]
+    fn test_segment_encroaches() {
+        let mut rng = rng::SplitMix(1473);
+        let points = (0..12)
+            .map(|_| Vector2::new(rng.below(5) as f64, rng.below(5) as f64))
+            .collect::<Vec<_>>();
+        let segment_encroaches = |a, b, c, d| super::segment_encroaches(&points, |l, i| l[i], a, b, c, d);
+        for _ in 0..500 {
+            let [a, b, c, d] = [0; 4].map(|_| rng.below(points.len()));
+            if a == b || c == d {
+                continue;
+            }
+            let expected = segment_encroaches(a, b, c, d);
+            assert_eq!(segment_encroaches(b, a, c, d), expected);
+            assert_eq!(segment_encroaches(a, b, d, c), expected);
+            let distinct = ![a, b].contains(&c) && ![a, b].contains(&d);
+            if distinct && (super::encroaches(&points, |l, i| l[i], a, b, c) || super::encroaches(&points, |l, i| l[i], a, b, d)) {
+                assert!(expected);
+            }
+            if distinct && super::segments_cross(&points, |l, i| l[i], a, b, c, d) {
+                assert!(expected);
+            }
+        }
+
+        // Generic points match sampling the segment
+        let points = (0..8)
+            .map(|_| Vector2::new(rng.next() as f64 / u64::MAX as f64, rng.next() as f64 / u64::MAX as f64))
+            .collect::<Vec<_>>();
+        for a in 0..4 {
+            for c in 4..7 {
+                let (p, q) = (points[a], points[a + 1]);
+                let (r, s) = (points[c], points[c + 1]);
+                let center = (p + q) / 2.0;
+                let closest = (0..=2000)
+                    .map(|t| (r + (s - r) * (t as f64 / 2000.0) - center).norm())
+                    .fold(f64::INFINITY, f64::min);
+                let radius = (q - p).norm() / 2.0;
+                if (closest - radius).abs() > 1e-3 {
+                    assert_eq!(super::segment_encroaches(&points, |l, i| l[i], a, a + 1, c, c + 1), closest < radius);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Variants of the predicates that return a certificate of their result.
+//!
+//! The perturbed determinant of a predicate is a polynomial in the perturbations of the coordinates,
+//! and its sign is the sign of its first nonzero coefficient, in the order of the ε-monomials.
+//! The determinant is linear in each row, so each coefficient is itself a determinant, with each
+//! point's row replaced by a coefficient of that row's polynomial in the point's perturbations.
+//! A [`Certificate`] lists the coefficients in order, up to the first nonzero one, with their exact
+//! signs. Checking it recomputes each of them with exact arithmetic, independently of how
+//! the predicate was evaluated, and checks that none was skipped.
+
+use crate::exact::{self, Expansion};
+use crate::transcript::Predicate;
+use crate::{SosPoint, Vec1, Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::ops::Index;
+
+/// A term of the polynomial of a point's row in that point's perturbations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Term {
+    /// The unperturbed row
+    Constant,
+    /// The coefficient of the perturbation of a coordinate
+    Linear(usize),
+    /// The coefficient of the square of the perturbation of a coordinate,
+    /// which only shows up in the squared magnitude of in-hypersphere predicates
+    Quadratic(usize),
+}
+
+/// A coefficient of the perturbed determinant, with its exact sign.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Step {
+    /// The term of each point's row, in the order of the arguments
+    pub terms: Vec<Term>,
+    /// The sign of the coefficient, as -1, 0, or 1
+    pub sign: i8,
+}
+
+/// A machine-checkable proof of the result of a predicate call.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d_certified};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 1.0),
+///     Vector2::new(2.0, 2.0),
+/// ];
+/// let (result, certificate) = orient_2d_certified(&points, |l, i| l[i], 0, 1, 2);
+/// assert_eq!(result, simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+/// // Collinear, so the unperturbed determinant is 0 and a perturbation decides
+/// assert_eq!(certificate.steps[0].sign, 0);
+/// assert!(certificate.check_2d(&points, |l, i| l[i]));
+///
+/// let mut forged = certificate.clone();
+/// forged.result = !forged.result;
+/// assert!(!forged.check_2d(&points, |l, i| l[i]));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Certificate<Idx = usize> {
+    /// The predicate that was called
+    pub predicate: Predicate,
+    /// The indexes passed to it
+    pub indexes: Vec<Idx>,
+    /// Its result
+    pub result: bool,
+    /// The coefficients of the perturbed determinant in order, up to and including
+    /// the first nonzero one
+    pub steps: Vec<Step>,
+}
+
+/// Whether the predicate has a squared magnitude column, or `None` if it isn't supported.
+fn lifted(predicate: Predicate) -> Option<bool> {
+    match predicate {
+        Predicate::Orient1d | Predicate::Orient2d | Predicate::Orient3d => Some(false),
+        Predicate::InCircle | Predicate::InSphere => Some(true),
+        Predicate::InCircleUnoriented | Predicate::InSphereUnoriented => None,
+    }
+}
+
+/// The terms of a row, in increasing order of their ε-monomials.
+/// Lower coordinates are perturbed more, so their terms come later.
+fn terms(dim: usize, lifted: bool) -> Vec<Term> {
+    let mut terms = vec![Term::Constant];
+    for c in (0..dim).rev() {
+        terms.push(Term::Linear(c));
+        if lifted {
+            terms.push(Term::Quadratic(c));
+        }
+    }
+    terms
+}
+
+/// The coefficient of a term in the row (**p**, |**p**|², 1), or (**p**, 1) if not `lifted`.
+fn row<P: Index<usize, Output = f64>>(dim: usize, lifted: bool, p: &P, term: Term) -> Vec<Expansion> {
+    let one = || Expansion::from(1.0);
+    let mut row = match term {
+        Term::Constant => (0..dim).map(|c| Expansion::from(p[c])).collect::<Vec<_>>(),
+        Term::Linear(c) | Term::Quadratic(c) => (0..dim)
+            .map(|k| if k == c && term == Term::Linear(c) { one() } else { Expansion::zero() })
+            .collect(),
+    };
+    if lifted {
+        row.push(match term {
+            Term::Constant => (0..dim).fold(Expansion::zero(), |acc, c| acc + Expansion::product(p[c], &[p[c]])),
+            Term::Linear(c) => Expansion::product(2.0, &[p[c]]),
+            Term::Quadratic(_) => one(),
+        });
+    }
+    row.push(if term == Term::Constant { one() } else { Expansion::zero() });
+    row
+}
+
+/// Calls `step` on each coefficient of the perturbed determinant in order,
+/// with the terms of each row, until it returns `false`.
+/// The terms of the point with the highest index change slowest.
+fn for_each_coefficient<Idx: Ord>(dim: usize, lifted: bool, indexes: &[Idx], mut step: impl FnMut(Vec<Term>) -> bool) {
+    let terms = terms(dim, lifted);
+    let mut by_rank = (0..indexes.len()).collect::<Vec<_>>();
+    by_rank.sort_by(|a, b| indexes[*a].cmp(&indexes[*b]));
+    let mut digits = vec![0; indexes.len()];
+    loop {
+        if !step(digits.iter().map(|d| terms[*d]).collect()) {
+            return;
+        }
+        let carry = by_rank.iter().all(|a| {
+            digits[*a] = (digits[*a] + 1) % terms.len();
+            digits[*a] == 0
+        });
+        if carry {
+            return;
+        }
+    }
+}
+
+fn has_repeat<Idx: PartialEq>(indexes: &[Idx]) -> bool {
+    indexes.iter().enumerate().any(|(n, i)| indexes[..n].contains(i))
+}
+
+fn certify<Idx: Ord + Clone, P: Index<usize, Output = f64>>(
+    predicate: Predicate,
+    indexes: Vec<Idx>,
+    point: impl Fn(Idx) -> P,
+) -> (bool, Certificate<Idx>) {
+    assert!(!has_repeat(&indexes), "an index is repeated");
+    let (dim, lifted) = (predicate.dim(), lifted(predicate).unwrap());
+    let points = indexes.iter().cloned().map(point).collect::<Vec<_>>();
+
+    let mut steps = vec![];
+    for_each_coefficient(dim, lifted, &indexes, |terms| {
+        let matrix = points.iter().zip(&terms).map(|(p, term)| row(dim, lifted, p, *term)).collect();
+        let sign = exact::det(matrix).signum() as i8;
+        steps.push(Step { terms, sign });
+        sign == 0
+    });
+    let result = steps.last().unwrap().sign > 0;
+    (result, Certificate { predicate, indexes, result, steps })
+}
+
+impl<Idx: Ord + Clone> Certificate<Idx> {
+    fn check<P: Index<usize, Output = f64>>(&self, dim: usize, point: impl Fn(Idx) -> P) -> bool {
+        let lifted = match lifted(self.predicate) {
+            Some(lifted) if self.predicate.dim() == dim => lifted,
+            _ => return false,
+        };
+        if self.indexes.len() != self.predicate.num_points() || has_repeat(&self.indexes) {
+            return false;
+        }
+        let points = self.indexes.iter().cloned().map(point).collect::<Vec<_>>();
+
+        // Every coefficient before the last must be listed and 0, and the last must be nonzero
+        let mut steps = self.steps.iter();
+        let mut valid = false;
+        for_each_coefficient(dim, lifted, &self.indexes, |terms| {
+            let step = match steps.next() {
+                Some(step) if step.terms == terms => step,
+                _ => return false,
+            };
+            let matrix = points.iter().zip(&terms).map(|(p, term)| row(dim, lifted, p, *term)).collect();
+            if exact::det(matrix).signum() as i8 != step.sign {
+                return false;
+            }
+            valid = step.sign != 0 && (step.sign > 0) == self.result;
+            step.sign == 0
+        });
+        valid && steps.next().is_none()
+    }
+
+    /// Checks the certificate of a 1-dimensional predicate against the points,
+    /// returning whether it proves its result.
+    pub fn check_1d<'a, T: ?Sized, P: SosPoint<Vector = Vec1>>(&self, list: &'a T, index_fn: impl Fn(&'a T, Idx) -> P) -> bool {
+        self.check(1, |i| index_fn(list, i).to_vector())
+    }
+
+    /// Checks the certificate of a 2-dimensional predicate against the points,
+    /// returning whether it proves its result.
+    pub fn check_2d<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(&self, list: &'a T, index_fn: impl Fn(&'a T, Idx) -> P) -> bool {
+        self.check(2, |i| index_fn(list, i).to_vector())
+    }
+
+    /// Checks the certificate of a 3-dimensional predicate against the points,
+    /// returning whether it proves its result.
+    pub fn check_3d<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(&self, list: &'a T, index_fn: impl Fn(&'a T, Idx) -> P) -> bool {
+        self.check(3, |i| index_fn(list, i).to_vector())
+    }
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but also returns a certificate of the result.
+///
+/// # Panics
+///
+/// Panics if an index is repeated.
+pub fn orient_1d_certified<'a, T: ?Sized, Idx: Ord + Clone, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+) -> (bool, Certificate<Idx>) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    certify(Predicate::Orient1d, vec![i, j], |i| index_fn(list, i))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but also returns a certificate of the result.
+/// See [`Certificate`] for an example.
+///
+/// # Panics
+///
+/// Panics if an index is repeated.
+pub fn orient_2d_certified<'a, T: ?Sized, Idx: Ord + Clone, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> (bool, Certificate<Idx>) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    certify(Predicate::Orient2d, vec![i, j, k], |i| index_fn(list, i))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but also returns a certificate of the result.
+///
+/// # Panics
+///
+/// Panics if an index is repeated.
+pub fn orient_3d_certified<'a, T: ?Sized, Idx: Ord + Clone, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> (bool, Certificate<Idx>) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    certify(Predicate::Orient3d, vec![i, j, k, l], |i| index_fn(list, i))
+}
+
+/// Like [`in_circle`](crate::in_circle), but also returns a certificate of the result.
+///
+/// # Panics
+///
+/// Panics if an index is repeated.
+pub fn in_circle_certified<'a, T: ?Sized, Idx: Ord + Clone, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> (bool, Certificate<Idx>) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    certify(Predicate::InCircle, vec![i, j, k, l], |i| index_fn(list, i))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but also returns a certificate of the result.
+///
+/// # Panics
+///
+/// Panics if an index is repeated.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_certified<'a, T: ?Sized, Idx: Ord + Clone, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> (bool, Certificate<Idx>) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    certify(Predicate::InSphere, vec![i, j, k, l, m], |i| index_fn(list, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_1d, orient_2d, orient_3d, rng, transcript};
+    use crate::diagnostics::orient_2d_case;
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::{Vector1, Vector2};
+
+    #[test]
+    fn test_certified() {
+        let points = grid_points_2d();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                let (result, cert) = orient_1d_certified(&points, |l, i| Vector1::new(l[i].x), i, j);
+                assert_eq!(result, orient_1d(&points, |l, i| Vector1::new(l[i].x), i, j));
+                assert!(cert.check_1d(&points, |l, i| Vector1::new(l[i].x)));
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    let (result, cert) = orient_2d_certified(&points, |l, i| l[i], i, j, k);
+                    assert_eq!(result, orient_2d(&points, |l, i| l[i], i, j, k));
+                    assert!(cert.check_2d(&points, |l, i| l[i]));
+                    assert_eq!(cert.steps.len() == 1, orient_2d_case(&points, |l, i| l[i], i, j, k) == [3, 3, 3]);
+                    for l in (0..n).filter(|l| *l != i && *l != j && *l != k) {
+                        let (result, cert) = in_circle_certified(&points, |l, i| l[i], i, j, k, l);
+                        assert_eq!(result, in_circle(&points, |l, i| l[i], i, j, k, l));
+                        assert!(cert.check_2d(&points, |l, i| l[i]));
+                    }
+                }
+            }
+        }
+
+        // Tampered certificates, or ones checked against other points, don't check
+        let (_, cert) = in_circle_certified(&points, |l, i| l[i], 0, 1, 2, 3);
+        let mut forged = cert.clone();
+        forged.steps.remove(0);
+        assert!(!forged.check_2d(&points, |l, i| l[i]));
+        let mut forged = cert.clone();
+        forged.steps.last_mut().unwrap().sign *= -1;
+        assert!(!forged.check_2d(&points, |l, i| l[i]));
+        let mut forged = cert.clone();
+        forged.predicate = transcript::Predicate::Orient3d;
+        assert!(!forged.check_3d(&grid_points_3d(), |l, i| l[i]));
+        let moved = points.iter().map(|p| Vector2::new(p.x, -p.y)).collect::<Vec<_>>();
+        assert!(!cert.check_2d(&moved, |l, i| l[i]));
+
+        let points = grid_points_3d();
+        let mut rng = rng::SplitMix(1493);
+        for _ in 0..300 {
+            let [i, j, k, l, m] = [(); 5].map(|_| rng.below(points.len()));
+            if [i, j, k, l, m].iter().enumerate().any(|(n, a)| [i, j, k, l, m][..n].contains(a)) {
+                continue;
+            }
+            let (result, cert) = orient_3d_certified(&points, |l, i| l[i], i, j, k, l);
+            assert_eq!(result, orient_3d(&points, |l, i| l[i], i, j, k, l));
+            assert!(cert.check_3d(&points, |l, i| l[i]));
+            let (result, cert) = in_sphere_certified(&points, |l, i| l[i], i, j, k, l, m);
+            assert_eq!(result, in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+            assert!(cert.check_3d(&points, |l, i| l[i]));
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! In-circle and in-sphere tests that break ties the way CGAL does.
+//!
+//! CGAL's Delaunay triangulations don't perturb the coordinates. They perturb only the lifted
+//! coordinate, |**p**|², of each point, and the lexicographically largest point gets the largest
+//! perturbation, as described in [Perturbations for Delaunay and weighted Delaunay 3D triangulations
+//! by Devillers and Teillaud](https://hal.inria.fr/inria-00560388). A degenerate test is then decided
+//! by the orientation of the other points, with the most perturbed point replaced by the query point,
+//! going down the points in lexicographic order until an orientation isn't 0.
+//! The orientations aren't perturbed, so these should be used with exact orientation tests,
+//! or with [`orient_2d`](crate::orient_2d) and [`orient_3d`](crate::orient_3d) on input
+//! without collinear or coplanar points.
+//!
+//! Use these in place of [`in_circle`](crate::in_circle) and [`in_sphere`](crate::in_sphere)
+//! where results need to agree with CGAL-based components. Non-degenerate input gives
+//! the same answers as the usual predicates.
+//!
+//! CGAL doesn't allow equal points. Here, equal points are ordered by index,
+//! and the lower index gets the larger perturbation.
+//! If every point is collinear or coplanar, no orientation decides the test,
+//! so this falls back to the usual predicate.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, cgal};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(0.0, 1.0),
+//! ];
+//! // The point at (1, 1) is the most perturbed, moving it outside the circle through the others
+//! assert!(!cgal::in_circle(&points, |l, i| l[i], 0, 1, 3, 2));
+//! // So the diagonal from (1, 0) to (0, 1) is Delaunay, and the other one isn't
+//! assert!(!cgal::in_circle(&points, |l, i| l[i], 1, 2, 3, 0));
+//! assert!(cgal::in_circle(&points, |l, i| l[i], 0, 1, 2, 3));
+//! ```
+
+use crate::{rg, scale, Vec2, Vec3};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// The positions of the points, from the most perturbed to the least.
+fn perturbation_order<Idx: Ord>(indexes: &[Idx], coords: &[&[f64]]) -> Vec<usize> {
+    let mut order = (0..indexes.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        let lex = coords[*b].partial_cmp(coords[*a]).unwrap_or(Ordering::Equal);
+        lex.then(indexes[*a].cmp(&indexes[*b]))
+    });
+    order
+}
+
+/// Decides a degenerate in-hypersphere test. The coefficient of the perturbation of the last point
+/// is minus the orientation of the others, and the coefficient for any other point is
+/// the orientation with that point replaced by the last point.
+fn decide<Idx: Ord, P: Copy>(indexes: &[Idx], points: &[P], coords: &[&[f64]], orient: impl Fn(&[P]) -> f64) -> Option<bool> {
+    let last = points.len() - 1;
+    perturbation_order(indexes, coords).into_iter().find_map(|n| {
+        let coeff = if n == last {
+            -orient(&points[..last])
+        } else {
+            let mut replaced = points[..last].to_vec();
+            replaced[n] = points[last];
+            orient(&replaced)
+        };
+        if coeff == 0.0 {
+            None
+        } else {
+            Some(coeff > 0.0)
+        }
+    })
+}
+
+/// Like [`in_circle`](crate::in_circle), but breaking ties the way CGAL does.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let indexes = [i, j, k, l];
+    let p = scale::prescaled(indexes.map(|i| index_fn(list, i)));
+    let val = rg::in_circle(p[0], p[1], p[2], p[3]);
+    if val != 0.0 {
+        return val > 0.0;
+    }
+
+    let coords = p.iter().map(|p| p.as_slice()).collect::<Vec<_>>();
+    decide(&indexes, &p, &coords, |q| rg::orient_2d(q[0], q[1], q[2]))
+        .unwrap_or_else(|| crate::in_circle(list, index_fn, i, j, k, l))
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but breaking ties the way CGAL does.
+/// The orientation of the first 3 points is still decided by [`orient_2d`](crate::orient_2d).
+pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::orient_2d(list, index_fn.clone(), i, j, k) == in_circle(list, index_fn, i, j, k, l)
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but breaking ties the way CGAL does.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    let indexes = [i, j, k, l, m];
+    let p = scale::prescaled(indexes.map(|i| index_fn(list, i)));
+    let val = rg::in_sphere(p[0], p[1], p[2], p[3], p[4]);
+    if val != 0.0 {
+        return val > 0.0;
+    }
+
+    let coords = p.iter().map(|p| p.as_slice()).collect::<Vec<_>>();
+    decide(&indexes, &p, &coords, |q| rg::orient_3d(q[0], q[1], q[2], q[3]))
+        .unwrap_or_else(|| crate::in_sphere(list, index_fn, i, j, k, l, m))
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but breaking ties the way CGAL does.
+/// The orientation of the first 4 points is still decided by [`orient_3d`](crate::orient_3d).
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    crate::orient_3d(list, index_fn.clone(), i, j, k, l) == in_sphere(list, index_fn, i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_3d};
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_cgal() {
+        // CGAL's side_of_oriented_circle with perturbation, for positively oriented i, j, k
+        fn cgal_in_circle(points: &[Vec2], i: usize, j: usize, k: usize, l: usize) -> bool {
+            let orient = |a: usize, b: usize, c: usize| rg::orient_2d(points[a], points[b], points[c]);
+            let val = rg::in_circle(points[i], points[j], points[k], points[l]);
+            if val != 0.0 {
+                return val > 0.0;
+            }
+            let mut sorted = [i, j, k, l];
+            sorted.sort_by(|a, b| (points[*a].x, points[*a].y).partial_cmp(&(points[*b].x, points[*b].y)).unwrap());
+            for q in sorted.iter().rev() {
+                let o = match *q {
+                    q if q == l => return false,
+                    q if q == k => orient(i, j, l),
+                    q if q == j => orient(i, l, k),
+                    _ => orient(l, j, k),
+                };
+                if o != 0.0 {
+                    return o > 0.0;
+                }
+            }
+            unreachable!()
+        }
+
+        let points = (0..9).map(|i| Vector2::new((i % 3) as f64, (i / 3) as f64)).collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    let orient = rg::orient_2d(points[i], points[j], points[k]);
+                    for l in (0..n).filter(|l| ![i, j, k].contains(l)) {
+                        let inside = super::in_circle(&points, |l, i| l[i], i, j, k, l);
+                        if orient > 0.0 {
+                            assert_eq!(inside, cgal_in_circle(&points, i, j, k, l));
+                        } else if orient < 0.0 {
+                            assert_eq!(inside, !cgal_in_circle(&points, j, i, k, l));
+                        }
+                        if rg::in_circle(points[i], points[j], points[k], points[l]) != 0.0 {
+                            assert_eq!(inside, in_circle(&points, |l, i| l[i], i, j, k, l));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Exactly 1 diagonal of each cocircular quadrilateral is Delaunay
+        for [a, b, c, d] in [[0, 1, 4, 3], [0, 2, 8, 6], [1, 5, 7, 3]] {
+            assert_ne!(
+                super::in_circle(&points, |l, i| l[i], a, b, c, d),
+                super::in_circle(&points, |l, i| l[i], a, b, d, c)
+            );
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        for i in 0..n {
+            let (j, k, l) = ((i + 1) % n, (i + 3) % n, (i + 4) % n);
+            for m in (0..n).filter(|m| ![i, j, k, l].contains(m)) {
+                let inside = super::in_sphere(&points, |l, i| l[i], i, j, k, l, m);
+                if rg::in_sphere(points[i], points[j], points[k], points[l], points[m]) != 0.0 {
+                    assert_eq!(inside, in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+                }
+                assert_eq!(
+                    super::in_sphere_unoriented(&points, |l, i| l[i], i, j, k, l, m),
+                    orient_3d(&points, |l, i| l[i], i, j, k, l) == inside
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Exact constructions of the implicit points of [`arrangement`].
+//!
+//! Circumcenters, crossings of segments, and intersections of bisectors are vertices of
+//! Voronoi diagrams and clipped polygons, but they usually can't be represented as `f64`s.
+//! A [`Construction`] keeps the [`Implicit`] point, which can be passed back into
+//! [`orient_implicit`](crate::arrangement::orient_implicit) to get exact answers about it,
+//! along with its exact homogeneous coordinates and bounds on where it is, for output.
+//!
+//! The coordinates are those of the unperturbed points. The perturbation moves the construction
+//! infinitesimally, so the bounds hold for it too, unless the construction is degenerate,
+//! like the crossing of 2 parallel segments, which the perturbation moves infinitely far.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, constructions, arrangement::{self, Implicit}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(3.0, 0.0),
+//!     Vector2::new(0.0, 3.0),
+//!     Vector2::new(1.0, 2.0),
+//!     Vector2::new(1.0, 4.0),
+//!     Vector2::new(0.0, 1.0),
+//! ];
+//! let center = constructions::circumcenter(&points, |l, i| l[i], [0, 1, 3]);
+//! // The center is at (1.5, 0.5), so it has exact bounds
+//! assert_eq!(center.bounds(), Some([1.5..=1.5, 0.5..=0.5]));
+//! // The crossing is at (0.6, 2.4), which isn't representable, but it's within bounds
+//! let crossing = constructions::intersection(&points, |l, i| l[i], [1, 2], [0, 4]);
+//! let [x, y] = crossing.bounds().unwrap();
+//! assert!(x.contains(&0.6) && y.contains(&2.4) && x.end() - x.start() < 1e-15);
+//! // And both feed back into the predicates
+//! assert!(arrangement::orient_implicit(&points, |l, i| l[i], Implicit::Point(5), center.implicit, crossing.implicit));
+//! ```
+
+use crate::arrangement::{self, Implicit};
+use crate::exact::Expansion;
+use crate::perturbed::Perturbed;
+use crate::Vec2;
+use core::ops::RangeInclusive;
+
+/// An implicit point with its exact coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Construction<Idx> {
+    /// The point, to pass to [`orient_implicit`](crate::arrangement::orient_implicit)
+    pub implicit: Implicit<Idx>,
+    /// The homogeneous coordinates (*x w*, *y w*, *w*) of the unperturbed point, exactly
+    pub homogeneous: [Expansion; 3],
+}
+
+impl<Idx> Construction<Idx> {
+    /// The smallest intervals of `f64`s that contain each coordinate of the point,
+    /// or `None` if the unperturbed construction is degenerate and has no coordinates.
+    pub fn bounds(&self) -> Option<[RangeInclusive<f64>; 2]> {
+        let [x, y, w] = &self.homogeneous;
+        if w.signum() == 0.0 {
+            return None;
+        }
+        Some([bound(x, w), bound(y, w)])
+    }
+
+    /// An approximation of the point, or `None` if the unperturbed construction is degenerate.
+    pub fn estimate(&self) -> Option<Vec2> {
+        let [x, y, w] = &self.homogeneous;
+        if w.signum() == 0.0 {
+            return None;
+        }
+        Some(Vec2::new(x.estimate() / w.estimate(), y.estimate() / w.estimate()))
+    }
+}
+
+/// The sign of `num / den - q`, exactly.
+fn cmp_fraction(num: &Expansion, den: &Expansion, q: f64) -> f64 {
+    (num.clone() - den.scale(q)).signum() * den.signum()
+}
+
+/// The smallest interval of `f64`s that contains `num / den`, which must be finite.
+fn bound(num: &Expansion, den: &Expansion) -> RangeInclusive<f64> {
+    let q = num.estimate() / den.estimate();
+    if !q.is_finite() {
+        return f64::NEG_INFINITY..=f64::INFINITY;
+    }
+    // Step from the estimate to the nearest representable values on either side
+    let step = |x: f64, up: bool| {
+        let bits = x.to_bits() as i64;
+        let away = (x >= 0.0) == up;
+        match (x == 0.0, away) {
+            (true, _) => if up { f64::from_bits(1) } else { -f64::from_bits(1) },
+            (false, true) => f64::from_bits((bits + 1) as u64),
+            (false, false) => f64::from_bits((bits - 1) as u64),
+        }
+    };
+    let (mut lo, mut hi) = (q, q);
+    while lo.is_finite() && cmp_fraction(num, den, lo) < 0.0 {
+        lo = step(lo, false);
+    }
+    while hi.is_finite() && cmp_fraction(num, den, hi) > 0.0 {
+        hi = step(hi, true);
+    }
+    // Tighten the side that's past the value
+    while lo < hi && cmp_fraction(num, den, step(lo, true)) >= 0.0 {
+        lo = step(lo, true);
+    }
+    while lo < hi && cmp_fraction(num, den, step(hi, false)) <= 0.0 {
+        hi = step(hi, false);
+    }
+    lo..=hi
+}
+
+/// Constructs any implicit point.
+///
+/// Takes a list of all the points, an indexing function, and the implicit point.
+pub fn construct<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, implicit: Implicit<Idx>) -> Construction<Idx> {
+    let mut perturbed = Perturbed::new(2, 3, &implicit.indexes(), |i| index_fn(list, i));
+    let mut coord = |c| perturbed.value(|perturbed| arrangement::homogeneous(perturbed, implicit).swap_remove(c));
+    let homogeneous = [coord(0), coord(1), coord(2)];
+    Construction { implicit, homogeneous }
+}
+
+/// Constructs the circumcenter of a triangle, given the indexes of its vertices in any order.
+pub fn circumcenter<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, triangle: [Idx; 3]) -> Construction<Idx> {
+    construct(list, index_fn, Implicit::Circumcenter(triangle))
+}
+
+/// Constructs the intersection of the lines through 2 segments,
+/// given the indexes of the endpoints of each.
+pub fn intersection<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, s: [Idx; 2], t: [Idx; 2]) -> Construction<Idx> {
+    construct(list, index_fn, Implicit::Crossing(s, t))
+}
+
+/// Constructs the intersection of the perpendicular bisectors of 2 segments,
+/// given the indexes of the endpoints of each.
+pub fn bisector_intersection<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    s: [Idx; 2],
+    t: [Idx; 2],
+) -> Construction<Idx> {
+    construct(list, index_fn, Implicit::Bisectors(s, t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rg, rng};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_constructions() {
+        use crate::arrangement::Implicit::{self, Point};
+
+        let mut rng = rng::SplitMix(1495);
+        let points = (0..12).map(|_| Vector2::new(rng.int(-8, 8) / 3.0, rng.int(-8, 8) / 7.0)).collect::<Vec<_>>();
+        let orient = |p, q, r| arrangement::orient_implicit(&points, |l, i| l[i], p, q, r);
+        let mut checked = 0;
+        for _ in 0..200 {
+            let all = [(); 6].map(|_| rng.below(points.len()));
+            if all.iter().enumerate().any(|(n, i)| all[..n].contains(i)) {
+                continue;
+            }
+            let [a, b, c, d, e, f] = all;
+            let constructions = [
+                super::circumcenter(&points, |l, i| l[i], [a, b, c]),
+                super::intersection(&points, |l, i| l[i], [a, b], [c, d]),
+                super::bisector_intersection(&points, |l, i| l[i], [a, b], [c, d]),
+            ];
+            for construction in &constructions {
+                let [x, y] = match construction.bounds() {
+                    Some(bounds) => bounds,
+                    None => continue,
+                };
+                // The bounds are as tight as they can be, around the estimate
+                let estimate = construction.estimate().unwrap();
+                assert!(x.start() - 1e-9 <= estimate.x && estimate.x <= x.end() + 1e-9);
+                for r in [&x, &y] {
+                    assert!(r.start() <= r.end());
+                    assert!(r.end() - r.start() <= f64::EPSILON * r.start().abs().max(r.end().abs()));
+                }
+                // A line that misses the box around the point is on the same side of it
+                let corners = [(x.start(), y.start()), (x.start(), y.end()), (x.end(), y.start()), (x.end(), y.end())];
+                let sides = corners.map(|(cx, cy)| rg::orient_2d(points[e], points[f], Vector2::new(*cx, *cy)));
+                if sides.iter().all(|s| *s > 0.0) || sides.iter().all(|s| *s < 0.0) {
+                    assert_eq!(orient(Point(e), Point(f), construction.implicit), sides[0] > 0.0);
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 0);
+
+        // Exactly representable points get exact bounds, and others don't
+        let points = vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(0.0, 3.0), Vector2::new(1.0, 3.0)];
+        let center = super::construct(&points, |l, i| l[i], Implicit::Circumcenter([0, 1, 2]));
+        assert_eq!(center.bounds(), Some([0.5..=0.5, 1.5..=1.5]));
+        let bisectors = super::bisector_intersection(&points, |l, i| l[i], [0, 3], [1, 2]);
+        assert_eq!(bisectors.bounds(), center.bounds());
+        let third = super::intersection(&points, |l, i| l[i], [0, 3], [1, 2]);
+        assert_eq!(third.estimate(), Some(Vector2::new(0.5, 1.5)));
+        let points = vec![Vector2::new(0.0, 0.0), Vector2::new(3.0, 1.0), Vector2::new(0.0, 1.0), Vector2::new(2.0, 0.0)];
+        let [x, _] = super::intersection(&points, |l, i| l[i], [0, 1], [2, 3]).bounds().unwrap();
+        assert!(x.start() < x.end() && x.contains(&1.2));
+        // Parallel segments have no unperturbed crossing
+        let parallel = super::intersection(&points, |l, i| l[i], [0, 3], [2, 1]);
+        assert_eq!(parallel.bounds(), None);
+    }
+}
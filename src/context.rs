@@ -0,0 +1,518 @@
+//! A context for evaluating many predicates on the same points.
+//!
+//! Algorithms like flip-based Delaunay triangulation evaluate predicates millions of times,
+//! on overlapping tuples of points that aren't known ahead of time, so they can't use
+//! the `_batch` variants of the predicates. A [`PredicateContext`] fetches each point
+//! from the indexing function once and keeps it, which pays off when the indexing function
+//! is expensive, like when it follows references through a mesh or converts coordinates.
+//! A query that's repeated can also be sorted once into a [`Sorted`] tuple,
+//! which keeps the order the points are perturbed in and the parity of the permutation.
+//!
+//! Points that aren't in the list, like ones a triangulator is inserting, can be appended
+//! to a context under new indexes. A [`Snapshot`] records the appended points, so a point can be
+//! inserted speculatively, its conflicts queried, and the context rolled back to how it was.
+//! Points can also be moved or removed, like in a kinetic triangulation, and rolled back the same way.
+//! Snapshots share the points with the context and only copy the ones that change afterward.
+//!
+//! The results are the same as those of the crate's predicates with the same indexes.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, context::{PredicateContext, Sorted}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! let mut context = PredicateContext::new(&points, |l: &Vec<Vector2<f64>>, i| l[i]);
+//! assert!(context.orient_2d(0, 1, 2));
+//! // Cocircular, so the perturbation decides, like in the crate's predicate
+//! assert_eq!(context.in_circle(0, 1, 2, 3), simplicity::in_circle(&points, |l, i| l[i], 0, 1, 2, 3));
+//! let edge = Sorted::new([3, 1, 2, 0]);
+//! assert_eq!(context.in_circle_sorted(&edge), context.in_circle(3, 1, 2, 0));
+//!
+//! let snapshot = context.snapshot();
+//! context.append(4, Vector2::new(0.5, 0.5));
+//! assert!(context.in_circle(0, 1, 2, 4));
+//! context.rollback(snapshot);
+//! assert_eq!(context.num_appended(), 0);
+//! ```
+
+use crate::{SosPoint, Vec2, Vec3};
+use alloc::sync::{Arc, Weak};
+use alloc::{vec, vec::Vec};
+
+/// A tuple of indexes, sorted in the order the points are perturbed in,
+/// along with the parity of the permutation that sorted them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sorted<Idx, const N: usize> {
+    indexes: [Idx; N],
+    odd: bool,
+}
+
+impl<Idx: Ord + Copy, const N: usize> Sorted<Idx, N> {
+    /// Sorts a tuple of indexes.
+    pub fn new(mut indexes: [Idx; N]) -> Self {
+        let mut odd = false;
+        for i in 1..N {
+            for j in (0..i).rev() {
+                if indexes[j] > indexes[j + 1] {
+                    indexes.swap(j, j + 1);
+                    odd = !odd;
+                } else {
+                    break;
+                }
+            }
+        }
+        Self { indexes, odd }
+    }
+
+    /// The sorted indexes.
+    pub fn indexes(&self) -> [Idx; N] {
+        self.indexes
+    }
+
+    /// Whether the permutation that sorted the indexes is odd.
+    pub fn is_odd(&self) -> bool {
+        self.odd
+    }
+}
+
+/// How many changes a context keeps apart from the ones its snapshots share before merging them in.
+const MAX_OVERLAY: usize = 16;
+
+/// A point that was changed from the list's, which snapshots record.
+#[derive(Clone, Copy, Debug)]
+enum Change<P> {
+    Unchanged,
+    Appended(P),
+    Updated(P),
+    Removed,
+}
+
+/// The points appended to or changed in a [`PredicateContext`] at some point, to roll back to.
+///
+/// It shares the changes made before it was taken with the context, and the context copies them
+/// the first time it has to change them while the snapshot is still around.
+#[derive(Clone, Debug)]
+pub struct Snapshot<P> {
+    /// Identifies the snapshot to the context it was taken from
+    token: Arc<()>,
+    base: Arc<Vec<Change<P>>>,
+    overlay: Vec<(usize, Change<P>)>,
+    num_appended: usize,
+}
+
+/// Caches the points of a list as they're fetched, for evaluating many predicates on them.
+///
+/// The cache is a vector indexed by the points' indexes, so they should be small,
+/// like the indexes of a list are. Cloning the context shares the changes made to it
+/// with the clone until either changes them again, like a [`Snapshot`] does.
+///
+/// The points can be of any [`SosPoint`] type that's `Copy`. They're cached as they are,
+/// and converted to nalgebra vectors when a predicate reads them.
+pub struct PredicateContext<'a, T: ?Sized, P, F> {
+    list: &'a T,
+    index_fn: F,
+    /// The points fetched from the list, which don't change, so rolling back keeps them
+    fetched: Vec<Option<P>>,
+    num_fetched: usize,
+    /// The changes from the list as of the last merge, shared with the snapshots taken since
+    base: Arc<Vec<Change<P>>>,
+    /// The changes since the last merge, by index
+    overlay: Vec<(usize, Change<P>)>,
+    num_appended: usize,
+    /// The snapshots that can be rolled back to, oldest first
+    snapshots: Vec<Weak<()>>,
+}
+
+impl<'a, T: ?Sized, P: Clone, F: Clone> Clone for PredicateContext<'a, T, P, F> {
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list,
+            index_fn: self.index_fn.clone(),
+            fetched: self.fetched.clone(),
+            num_fetched: self.num_fetched,
+            base: self.base.clone(),
+            overlay: self.overlay.clone(),
+            num_appended: self.num_appended,
+            snapshots: self.snapshots.clone(),
+        }
+    }
+}
+
+impl<'a, T: ?Sized, P: Copy, F: Fn(&T, usize) -> P> PredicateContext<'a, T, P, F> {
+    /// Makes a context for a list of all the points and an indexing function.
+    /// No points are fetched until a predicate needs them.
+    pub fn new(list: &'a T, index_fn: F) -> Self {
+        Self {
+            list,
+            index_fn,
+            fetched: vec![],
+            num_fetched: 0,
+            base: Arc::new(vec![]),
+            overlay: vec![],
+            num_appended: 0,
+            snapshots: vec![],
+        }
+    }
+
+    /// Returns the point at index `i`, fetching it if it hasn't been yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point was removed.
+    pub fn point(&mut self, i: usize) -> P {
+        match self.change(i) {
+            Change::Appended(point) | Change::Updated(point) => point,
+            Change::Removed => panic!("queried point {} after removing it from the context", i),
+            Change::Unchanged => {
+                if i >= self.fetched.len() {
+                    self.fetched.resize(i + 1, None);
+                }
+                let (list, index_fn, num_fetched) = (self.list, &self.index_fn, &mut self.num_fetched);
+                *self.fetched[i].get_or_insert_with(|| {
+                    *num_fetched += 1;
+                    index_fn(list, i)
+                })
+            }
+        }
+    }
+
+    /// Returns the number of points fetched so far.
+    pub fn num_fetched(&self) -> usize {
+        self.num_fetched
+    }
+
+    /// Appends a point that isn't in the list at index `i`, which must not be the index of a point
+    /// in the list or of another appended point, unless that point was removed.
+    /// It's perturbed like any point at that index.
+    pub fn append(&mut self, i: usize, point: P) {
+        let fetched = matches!(self.fetched.get(i), Some(Some(_)));
+        let free = match self.change(i) {
+            Change::Unchanged => !fetched,
+            Change::Removed => true,
+            Change::Appended(_) | Change::Updated(_) => false,
+        };
+        assert!(
+            free,
+            "appended a point at an index that's already in the context"
+        );
+        self.set(i, Change::Appended(point));
+        self.num_appended += 1;
+    }
+
+    /// Returns the number of points appended and not rolled back or removed.
+    pub fn num_appended(&self) -> usize {
+        self.num_appended
+    }
+
+    /// Moves the point at index `i`, in the list or appended, to `point`.
+    /// It's still perturbed like the point at that index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the point was removed.
+    pub fn update_point(&mut self, i: usize, point: P) {
+        match self.change(i) {
+            Change::Appended(_) => self.set(i, Change::Appended(point)),
+            Change::Removed => panic!("updated point {} after removing it from the context", i),
+            Change::Unchanged | Change::Updated(_) => self.set(i, Change::Updated(point)),
+        }
+    }
+
+    /// Removes the point at index `i`, in the list or appended, from the context.
+    /// Predicates on it panic afterward, until a point is appended at the index
+    /// or the context is rolled back to before the removal.
+    /// A point in the list loses its cached copy, so it's fetched again if it comes back.
+    pub fn remove_point(&mut self, i: usize) {
+        match self.change(i) {
+            Change::Removed => return,
+            Change::Appended(_) => self.num_appended -= 1,
+            Change::Unchanged | Change::Updated(_) => {}
+        }
+        self.set(i, Change::Removed);
+        if let Some(Some(_)) = self.fetched.get_mut(i).map(Option::take) {
+            self.num_fetched -= 1;
+        }
+    }
+
+    /// Records the points appended and changed so far, to roll back to.
+    /// This copies at most a few of the latest changes, and shares the rest.
+    pub fn snapshot(&mut self) -> Snapshot<P> {
+        // Snapshots that were dropped can't be rolled back to anymore
+        while self.snapshots.last().is_some_and(|s| s.strong_count() == 0) {
+            self.snapshots.pop();
+        }
+        let token = Arc::new(());
+        self.snapshots.push(Arc::downgrade(&token));
+        Snapshot { token, base: self.base.clone(), overlay: self.overlay.clone(), num_appended: self.num_appended }
+    }
+
+    /// Puts back the points as they were when `snapshot` was taken,
+    /// which removes the points appended since then and undoes the updates and removals.
+    /// Points fetched from the list since then stay cached, since they haven't changed.
+    ///
+    /// Snapshots taken after `snapshot` can't be rolled back to afterward, but `snapshot` can.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `snapshot` was taken from another context, or if the context was rolled back
+    /// to a snapshot taken before it since it was taken.
+    pub fn rollback(&mut self, snapshot: Snapshot<P>) {
+        let position = self.snapshots.iter().position(|s| s.as_ptr() == Arc::as_ptr(&snapshot.token));
+        let position = position.expect(
+            "rolled back to a snapshot that's out of date: it's from another context, \
+             or the context was rolled back past it",
+        );
+        self.snapshots.truncate(position + 1);
+        self.base = snapshot.base;
+        self.overlay = snapshot.overlay;
+        self.num_appended = snapshot.num_appended;
+    }
+
+    /// How the point at index `i` was changed from the list's.
+    fn change(&self, i: usize) -> Change<P> {
+        match self.overlay.iter().find(|(j, _)| *j == i) {
+            Some((_, change)) => *change,
+            None => self.base.get(i).copied().unwrap_or(Change::Unchanged),
+        }
+    }
+
+    /// Records a change to the point at index `i`.
+    fn set(&mut self, i: usize, change: Change<P>) {
+        match self.overlay.iter_mut().find(|(j, _)| *j == i) {
+            Some((_, old)) => *old = change,
+            None => self.overlay.push((i, change)),
+        }
+        if self.overlay.len() > MAX_OVERLAY {
+            // Copies the base if a snapshot or a clone still shares it
+            let base = Arc::make_mut(&mut self.base);
+            for (i, change) in self.overlay.drain(..) {
+                if i >= base.len() {
+                    base.resize(i + 1, Change::Unchanged);
+                }
+                base[i] = change;
+            }
+        }
+    }
+
+    /// Returns the points of a tuple, fetching the ones that haven't been yet.
+    fn fetch<const N: usize>(&mut self, sorted: &Sorted<usize, N>) -> [P; N] {
+        sorted.indexes.map(|i| self.point(i))
+    }
+}
+
+impl<'a, T: ?Sized, P: SosPoint<Vector = Vec2> + Copy, F: Fn(&T, usize) -> P> PredicateContext<'a, T, P, F> {
+    /// Like [`orient_2d`](crate::orient_2d), with the points in this context.
+    pub fn orient_2d(&mut self, i: usize, j: usize, k: usize) -> bool {
+        self.orient_2d_sorted(&Sorted::new([i, j, k]))
+    }
+
+    /// Like [`orient_2d`](Self::orient_2d), with indexes that are already sorted.
+    pub fn orient_2d_sorted(&mut self, sorted: &Sorted<usize, 3>) -> bool {
+        crate::orient_2d_cascade(self.fetch(sorted).map(|p| p.to_vector()), sorted.odd)
+    }
+
+    /// Like [`in_circle`](crate::in_circle), with the points in this context.
+    pub fn in_circle(&mut self, i: usize, j: usize, k: usize, l: usize) -> bool {
+        self.in_circle_sorted(&Sorted::new([i, j, k, l]))
+    }
+
+    /// Like [`in_circle`](Self::in_circle), with indexes that are already sorted.
+    pub fn in_circle_sorted(&mut self, sorted: &Sorted<usize, 4>) -> bool {
+        crate::in_circle_cascade(self.fetch(sorted).map(|p| p.to_vector()), sorted.odd)
+    }
+}
+
+impl<'a, T: ?Sized, P: SosPoint<Vector = Vec3> + Copy, F: Fn(&T, usize) -> P> PredicateContext<'a, T, P, F> {
+    /// Like [`orient_3d`](crate::orient_3d), with the points in this context.
+    pub fn orient_3d(&mut self, i: usize, j: usize, k: usize, l: usize) -> bool {
+        self.orient_3d_sorted(&Sorted::new([i, j, k, l]))
+    }
+
+    /// Like [`orient_3d`](Self::orient_3d), with indexes that are already sorted.
+    pub fn orient_3d_sorted(&mut self, sorted: &Sorted<usize, 4>) -> bool {
+        crate::orient_3d_cascade(self.fetch(sorted).map(|p| p.to_vector()), sorted.odd)
+    }
+
+    /// Like [`in_sphere`](crate::in_sphere), with the points in this context.
+    pub fn in_sphere(&mut self, i: usize, j: usize, k: usize, l: usize, m: usize) -> bool {
+        self.in_sphere_sorted(&Sorted::new([i, j, k, l, m]))
+    }
+
+    /// Like [`in_sphere`](Self::in_sphere), with indexes that are already sorted.
+    pub fn in_sphere_sorted(&mut self, sorted: &Sorted<usize, 5>) -> bool {
+        crate::in_sphere_cascade(self.fetch(sorted).map(|p| p.to_vector()), sorted.odd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d, rng};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_predicate_context() {
+        use super::{PredicateContext, Sorted};
+        use std::cell::Cell;
+
+        let points = grid_points_2d();
+        let fetches = Cell::new(0);
+        let mut context = PredicateContext::new(&points, |l: &Vec<Vec2>, i| {
+            fetches.set(fetches.get() + 1);
+            l[i]
+        });
+        let mut rng = rng::SplitMix(7);
+        for _ in 0..500 {
+            let [i, j, k, l] = rng.distinct(points.len());
+            assert_eq!(context.orient_2d(i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(context.in_circle_sorted(&Sorted::new([i, j, k, l])), context.in_circle(i, j, k, l));
+        }
+        assert_eq!(fetches.get(), points.len());
+        assert_eq!(context.num_fetched(), points.len());
+
+        let points = grid_points_3d();
+        let mut context = PredicateContext::new(&points, |l: &Vec<Vec3>, i| l[i]);
+        for _ in 0..500 {
+            let [i, j, k, l, m] = rng.distinct(points.len());
+            assert_eq!(context.orient_3d(i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(context.in_sphere(i, j, k, l, m), in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+        }
+
+        let sorted = Sorted::new([3, 1, 2, 0]);
+        assert_eq!(sorted.indexes(), [0, 1, 2, 3]);
+        assert!(sorted.is_odd());
+        assert!(!Sorted::new([1, 2, 0]).is_odd());
+    }
+
+    #[test]
+    fn test_context_snapshot() {
+
+        let points = grid_points_2d();
+        let n = points.len();
+        let mut context = PredicateContext::new(&points[..8], |l: &[Vec2], i| l[i]);
+        let empty = context.snapshot();
+        context.append(8, points[8]);
+        let one = context.snapshot();
+        context.append(9, points[9]);
+        context.append(10, points[10]);
+        assert_eq!(context.num_appended(), 3);
+        let mut rng = rng::SplitMix(13);
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(n);
+            assert_eq!(context.orient_2d(i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+        }
+        assert_eq!(context.num_fetched(), 8);
+
+        // Roll back the last 2 points, and insert a different point in their place
+        context.rollback(one);
+        assert_eq!(context.num_appended(), 1);
+        assert_eq!(context.num_fetched(), 8);
+        let mut moved = points[..9].to_vec();
+        moved.push(Vector2::new(0.5, 1.5));
+        context.append(9, moved[9]);
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(moved.len());
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&moved, |l, i| l[i], i, j, k, l));
+        }
+
+        context.rollback(empty.clone());
+        assert_eq!(context.num_appended(), 0);
+
+        // Enough points that the snapshot's changes have to be copied
+        let many = (0..40).map(|i| Vector2::new(i as f64 / 40.0, 2.0 + (i * i) as f64 / 1600.0)).collect::<Vec<_>>();
+        let mut all = points[..8].to_vec();
+        all.extend(&many);
+        let mut clone = context.clone();
+        for (i, p) in many.iter().enumerate() {
+            context.append(8 + i, *p);
+        }
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(all.len());
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&all, |l, i| l[i], i, j, k, l));
+        }
+        // The clone and the snapshot don't see the points appended since
+        assert_eq!(clone.num_appended(), 0);
+        clone.append(8, points[8]);
+        assert_eq!(clone.orient_2d(0, 8, 4), orient_2d(&points, |l, i| l[i], 0, 8, 4));
+        context.rollback(empty);
+        assert_eq!(context.num_appended(), 0);
+        context.append(8, points[9]);
+        assert_eq!(context.orient_2d(0, 8, 4), orient_2d(&points, |l, i| l[i], 0, 9, 4));
+    }
+
+    #[test]
+    fn test_context_update_remove() {
+        use std::cell::Cell;
+
+        let points = grid_points_2d();
+        let fetches = Cell::new(0);
+        let mut context = super::PredicateContext::new(&points[..8], |l: &[Vec2], i| {
+            fetches.set(fetches.get() + 1);
+            l[i]
+        });
+        context.append(8, points[8]);
+        let before = context.snapshot();
+
+        // Move a point in the list and the appended point
+        let mut moved = points[..9].to_vec();
+        moved[3] = Vector2::new(0.5, 1.5);
+        moved[8] = Vector2::new(-1.0, 0.5);
+        context.update_point(3, moved[3]);
+        context.update_point(8, moved[8]);
+        let mut rng = rng::SplitMix(17);
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(moved.len());
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&moved, |l, i| l[i], i, j, k, l));
+        }
+        assert_eq!(context.num_appended(), 1);
+
+        context.remove_point(8);
+        assert_eq!(context.num_appended(), 0);
+        context.append(8, moved[8]);
+        assert_eq!(context.num_appended(), 1);
+        context.remove_point(8);
+        context.remove_point(0);
+
+        // A point in the list is fetched again when it comes back
+        let fetched = fetches.get();
+        context.rollback(before);
+        assert_eq!(context.num_appended(), 1);
+        assert_eq!(context.point(0), points[0]);
+        assert_eq!(fetches.get(), fetched + 1);
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(9);
+            assert_eq!(context.in_circle(i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "after removing it")]
+    fn test_context_removed_point() {
+        let points = grid_points_2d();
+        let mut context = super::PredicateContext::new(&points[..8], |l: &[Vec2], i| l[i]);
+        context.orient_2d(0, 1, 2);
+        context.remove_point(1);
+        context.orient_2d(0, 1, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of date")]
+    fn test_context_stale_snapshot() {
+        let points = grid_points_2d();
+        let mut context = super::PredicateContext::new(&points[..8], |l: &[Vec2], i| l[i]);
+        let empty = context.snapshot();
+        context.append(8, points[8]);
+        let one = context.snapshot();
+        context.rollback(empty);
+        context.rollback(one);
+    }
+}
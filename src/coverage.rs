@@ -0,0 +1,165 @@
+//! Coverage of the ε-cascades, enabled by the `tracing` feature along with `std`.
+//!
+//! [`Coverage`] is a `tracing` subscriber that counts the events emitted by the predicates
+//! when their input is degenerate, by predicate and by the case of the cascade
+//! that decided the sign, as returned by the [`diagnostics`](crate::diagnostics) functions.
+//! Calls decided by the unperturbed determinant don't emit events, so they aren't counted.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, orient_2d, coverage::Coverage};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//! ];
+//! let coverage = Coverage::new();
+//! tracing::subscriber::with_default(coverage.clone(), || {
+//!     orient_2d(&points, |l, i| l[i], 0, 1, 2);
+//! });
+//! let report = coverage.report();
+//! assert!(report.reached("orient_2d", "[2, 3, 3]"));
+//! println!("{}", report);
+//! ```
+
+use alloc::collections::BTreeMap;
+use core::fmt::{self, Debug, Display, Formatter};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// A subscriber that counts the cases of the ε-cascades reached.
+/// Clones share their counts, so keep a clone to get the report
+/// after installing one as the subscriber.
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+    hits: Arc<Mutex<BTreeMap<(String, String), u64>>>,
+}
+
+impl Coverage {
+    /// Creates a subscriber that hasn't counted anything yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the counts so far.
+    pub fn report(&self) -> Report {
+        Report { hits: self.hits.lock().unwrap().clone() }
+    }
+}
+
+/// Extracts the predicate and case from an event.
+#[derive(Default)]
+struct CaseVisitor {
+    predicate: Option<String>,
+    case: Option<String>,
+}
+
+impl Visit for CaseVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "predicate" {
+            self.predicate = Some(value.to_owned());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "case" {
+            self.case = Some(format!("{:?}", value));
+        }
+    }
+}
+
+impl Subscriber for Coverage {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        !metadata.is_span() && metadata.target().starts_with("simplicity::")
+    }
+
+    fn new_span(&self, _span: &Attributes) -> Id {
+        // Spans are never enabled
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event) {
+        let mut visitor = CaseVisitor::default();
+        event.record(&mut visitor);
+        if let CaseVisitor { predicate: Some(predicate), case: Some(case) } = visitor {
+            *self.hits.lock().unwrap().entry((predicate, case)).or_insert(0) += 1;
+        }
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+/// The number of times each case of each predicate's ε-cascade was reached.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// The counts, keyed by predicate name and case, formatted with `Debug`
+    pub hits: BTreeMap<(String, String), u64>,
+}
+
+impl Report {
+    /// Whether a case of a predicate was reached.
+    pub fn reached(&self, predicate: &str, case: &str) -> bool {
+        self.hits.contains_key(&(predicate.to_owned(), case.to_owned()))
+    }
+
+    /// The cases of a predicate that were reached, with their counts.
+    pub fn cases<'a>(&'a self, predicate: &'a str) -> impl Iterator<Item = (&'a str, u64)> + 'a {
+        self.hits.iter().filter(move |((p, _), _)| p == predicate).map(|((_, case), count)| (case.as_str(), *count))
+    }
+}
+
+/// One line per predicate and case, like `in_circle 3: 12`.
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for ((predicate, case), count) in &self.hits {
+            writeln!(f, "{} {}: {}", predicate, case, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, orient_2d, trace};
+    use crate::diagnostics::{in_circle_case, orient_2d_case};
+    use crate::tests::grid_points_2d;
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_coverage() {
+        let points = grid_points_2d();
+        let coverage = super::Coverage::new();
+        let mut degenerate = 0;
+        tracing::subscriber::with_default(coverage.clone(), || {
+            for i in 0..points.len() {
+                for j in (0..points.len()).filter(|j| *j != i) {
+                    for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                        orient_2d(&points, |l, i| l[i], i, j, k);
+                        if orient_2d_case(&points, |l, i| l[i], i, j, k) != [3, 3, 3] {
+                            degenerate += 1;
+                        }
+                    }
+                }
+            }
+            in_circle(&points, |l, i| l[i], 0, 1, 3, 4);
+        });
+
+        let report = coverage.report();
+        assert!(report.reached("orient_2d", "[2, 3, 3]"));
+        assert!(!report.reached("orient_2d", "[3, 3, 3]"));
+        assert_eq!(report.cases("orient_2d").map(|(_, count)| count).sum::<u64>(), degenerate);
+        let case = in_circle_case(&points, |l, i| l[i], 0, 1, 3, 4).to_string();
+        assert_eq!(report.cases("in_circle").collect::<Vec<_>>(), vec![(case.as_str(), 1)]);
+        assert_eq!(trace::ranks([5, 2, 9, 0]), [2, 1, 3, 0]);
+    }
+}
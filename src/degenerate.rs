@@ -0,0 +1,188 @@
+//! Generators of point sets with exact degeneracies, for testing and benchmarking code
+//! built on the predicates.
+//!
+//! All coordinates are small integers, so the degeneracies are exact in floating point,
+//! and the same seed always generates the same points.
+//!
+//! # Example
+//!
+//! ```
+//! use simplicity::{in_circle_strict, degenerate::Generator};
+//!
+//! let mut gen = Generator::new(42);
+//! let points = gen.cocircular_2d(6);
+//! assert!(in_circle_strict(&points, |l, i| l[i], 0, 1, 2, 3).unwrap_err().is_degenerate());
+//! ```
+
+use crate::rng::SplitMix;
+use crate::{Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+
+/// Radii of circles with many integer points.
+const CIRCLE_RADII: [i32; 3] = [5, 25, 65];
+/// Radii of spheres with many integer points.
+const SPHERE_RADII: [i32; 3] = [3, 9, 11];
+
+/// The integer points at distance `radius` from the origin, in `dim` dimensions.
+fn lattice_points(dim: usize, radius: i32) -> Vec<Vec<i32>> {
+    let mut points = vec![vec![]];
+    for _ in 0..dim {
+        points = points.into_iter()
+            .flat_map(|p: Vec<i32>| (-radius..=radius).map(move |c| {
+                let mut p = p.clone();
+                p.push(c);
+                p
+            }))
+            .filter(|p| p.iter().map(|c| c * c).sum::<i32>() <= radius * radius)
+            .collect();
+    }
+    points.retain(|p| p.iter().map(|c| c * c).sum::<i32>() == radius * radius);
+    points
+}
+
+/// A seeded generator of degenerate point sets.
+pub struct Generator {
+    rng: SplitMix,
+}
+
+impl Generator {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { rng: SplitMix(seed) }
+    }
+
+    fn vec2(&mut self, lo: i32, hi: i32) -> Vec2 {
+        Vec2::new(self.rng.int(lo, hi), self.rng.int(lo, hi))
+    }
+
+    fn vec3(&mut self, lo: i32, hi: i32) -> Vec3 {
+        Vec3::new(self.rng.int(lo, hi), self.rng.int(lo, hi), self.rng.int(lo, hi))
+    }
+
+    /// Picks `n` of `points`, without repeats until they run out.
+    fn pick<V: Clone>(&mut self, points: &[V], n: usize) -> Vec<V> {
+        let mut picked = vec![];
+        while picked.len() < n {
+            let mut round = points.to_vec();
+            self.rng.shuffle(&mut round);
+            picked.extend(round.into_iter().take(n - picked.len()));
+        }
+        picked
+    }
+
+    /// `n` distinct points on a line, in random order.
+    pub fn collinear_2d(&mut self, n: usize) -> Vec<Vec2> {
+        let origin = self.vec2(-10, 10);
+        let dir = loop {
+            let dir = self.vec2(-3, 3);
+            if dir != Vec2::zeros() {
+                break dir;
+            }
+        };
+        let steps = (-(n as i32)..=n as i32).collect::<Vec<_>>();
+        self.pick(&steps, n).into_iter().map(|t| origin + dir * f64::from(t)).collect()
+    }
+
+    /// `n` points on a circle, distinct if there are at most 12, in random order.
+    pub fn cocircular_2d(&mut self, n: usize) -> Vec<Vec2> {
+        let center = self.vec2(-10, 10);
+        let radius = CIRCLE_RADII[self.rng.below(CIRCLE_RADII.len())];
+        let points = lattice_points(2, radius);
+        self.pick(&points, n).into_iter().map(|p| center + Vec2::new(p[0].into(), p[1].into())).collect()
+    }
+
+    /// `n` distinct points on a line in 3D, in random order.
+    pub fn collinear_3d(&mut self, n: usize) -> Vec<Vec3> {
+        let origin = self.vec3(-10, 10);
+        let dir = loop {
+            let dir = self.vec3(-3, 3);
+            if dir != Vec3::zeros() {
+                break dir;
+            }
+        };
+        let steps = (-(n as i32)..=n as i32).collect::<Vec<_>>();
+        self.pick(&steps, n).into_iter().map(|t| origin + dir * f64::from(t)).collect()
+    }
+
+    /// `n` points on a plane, in random order.
+    pub fn coplanar_3d(&mut self, n: usize) -> Vec<Vec3> {
+        let origin = self.vec3(-10, 10);
+        let u = self.vec3(-3, 3);
+        let v = self.vec3(-3, 3);
+        (0..n).map(|_| origin + u * self.rng.int(-5, 5) + v * self.rng.int(-5, 5)).collect()
+    }
+
+    /// `n` points on a sphere, distinct if there are at most 30, in random order.
+    pub fn cospherical_3d(&mut self, n: usize) -> Vec<Vec3> {
+        let center = self.vec3(-10, 10);
+        let radius = SPHERE_RADII[self.rng.below(SPHERE_RADII.len())];
+        let points = lattice_points(3, radius);
+        self.pick(&points, n).into_iter().map(|p| center + Vec3::new(p[0].into(), p[1].into(), p[2].into())).collect()
+    }
+
+    /// `clusters` clusters of `size` points each, snapped to a unit grid
+    /// around random centers, so there are many duplicate, collinear, and cocircular points.
+    pub fn grid_clusters_2d(&mut self, clusters: usize, size: usize) -> Vec<Vec2> {
+        let mut points = vec![];
+        for _ in 0..clusters {
+            let center = self.vec2(-20, 20);
+            points.extend((0..size).map(|_| center + self.vec2(-1, 1)));
+        }
+        points
+    }
+
+    /// `clusters` clusters of `size` points each, snapped to a unit grid
+    /// around random centers, so there are many duplicate, coplanar, and cospherical points.
+    pub fn grid_clusters_3d(&mut self, clusters: usize, size: usize) -> Vec<Vec3> {
+        let mut points = vec![];
+        for _ in 0..clusters {
+            let center = self.vec3(-20, 20);
+            points.extend((0..size).map(|_| center + self.vec3(-1, 1)));
+        }
+        points
+    }
+
+    /// Adds `copies` duplicates of random points in `points` and shuffles them.
+    pub fn with_duplicates<V: Clone>(&mut self, mut points: Vec<V>, copies: usize) -> Vec<V> {
+        if points.is_empty() {
+            return points;
+        }
+        for _ in 0..copies {
+            let point = points[self.rng.below(points.len())].clone();
+            points.push(point);
+        }
+        self.rng.shuffle(&mut points);
+        points
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle_strict, in_sphere_strict, orient_2d_strict, orient_3d_strict};
+    use test_case::test_case;
+
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    fn test_degenerate_generator(seed: u64) {
+        let mut gen = super::Generator::new(seed);
+        let points = gen.collinear_2d(5);
+        assert!(orient_2d_strict(&points, |l, i| l[i], 4, 0, 2).unwrap_err().is_degenerate());
+        let points = gen.cocircular_2d(5);
+        assert!(orient_2d_strict(&points, |l, i| l[i], 0, 1, 2).is_ok());
+        assert!(in_circle_strict(&points, |l, i| l[i], 0, 1, 2, 4).unwrap_err().is_degenerate());
+        let points = gen.coplanar_3d(5);
+        assert!(orient_3d_strict(&points, |l, i| l[i], 4, 0, 2, 3).unwrap_err().is_degenerate());
+        let points = gen.cospherical_3d(5);
+        assert!(in_sphere_strict(&points, |l, i| l[i], 0, 1, 2, 3, 4).unwrap_err().is_degenerate());
+
+        let points = gen.collinear_3d(4);
+        let points = gen.with_duplicates(points, 2);
+        assert_eq!(points.len(), 6);
+        assert!((0..6).any(|i| (0..i).any(|j| points[i] == points[j])));
+
+        assert_eq!(gen.grid_clusters_2d(3, 4).len(), 12);
+        assert_ne!(gen.grid_clusters_3d(2, 5), super::Generator::new(seed + 1).grid_clusters_3d(2, 5));
+    }
+}
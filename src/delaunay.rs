@@ -0,0 +1,214 @@
+//! Verifying triangulations built on the predicates.
+
+use crate::{Vec2, Vec3};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// A vertex inside the circumcircle of a triangle it shares an edge with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DelaunayViolation {
+    /// The index of the triangle in the list of triangles
+    pub triangle: usize,
+    /// The index of the vertex
+    pub vertex: usize,
+}
+
+/// Checks that a 2D triangulation is Delaunay after perturbing the points,
+/// returning the vertices inside the circumcircle of a triangle across one of its edges.
+///
+/// Takes a list of all the points, an indexing function, and the triangles,
+/// as triples of indexes in either orientation. For each edge shared by 2 triangles,
+/// this checks that the vertex of each triangle opposite the edge isn't inside the circumcircle
+/// of the other triangle. For a triangulation of the convex hull of the points, that's equivalent
+/// to every circumcircle being empty. Because of the perturbations, there's exactly 1 Delaunay
+/// triangulation even if 4 points are cocircular, so this also checks that cocircular points
+/// were triangulated consistently with [`in_circle`](crate::in_circle).
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, verify_delaunay_2d};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 0.0),
+///     Vector2::new(0.0, 1.0),
+///     Vector2::new(1.0, 1.0),
+/// ];
+/// // The points are cocircular, so the perturbations decide which diagonal is Delaunay
+/// let one = verify_delaunay_2d(&points, |l, i| l[i], &[[0, 1, 3], [0, 3, 2]]);
+/// let other = verify_delaunay_2d(&points, |l, i| l[i], &[[0, 1, 2], [1, 3, 2]]);
+/// assert!(one.is_empty() != other.is_empty());
+/// ```
+pub fn verify_delaunay_2d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+    triangles: &[[usize; 3]],
+) -> Vec<DelaunayViolation> {
+    // The triangles on each edge, with the vertex opposite it
+    let mut edges = BTreeMap::<[usize; 2], Vec<(usize, usize)>>::new();
+    for (t, tri) in triangles.iter().enumerate() {
+        for i in 0..3 {
+            let (a, b) = (tri[(i + 1) % 3], tri[(i + 2) % 3]);
+            edges.entry([a.min(b), a.max(b)]).or_default().push((t, tri[i]));
+        }
+    }
+
+    let mut violations = vec![];
+    for sharing in edges.values() {
+        for (t, _) in sharing {
+            let [i, j, k] = triangles[*t];
+            for (u, vertex) in sharing {
+                if u != t && crate::in_circle_unoriented(list, index_fn.clone(), i, j, k, *vertex) {
+                    violations.push(DelaunayViolation { triangle: *t, vertex: *vertex });
+                }
+            }
+        }
+    }
+    violations.sort_by_key(|v| (v.triangle, v.vertex));
+    violations
+}
+
+/// A problem with a tetrahedralization found by [`verify_delaunay_3d`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Delaunay3dViolation {
+    /// A tetrahedron isn't positively oriented
+    Orientation {
+        /// The index of the tetrahedron in the list of tetrahedra
+        tetrahedron: usize,
+    },
+    /// A vertex is inside the circumsphere of a tetrahedron it shares a face with
+    InSphere {
+        /// The index of the tetrahedron in the list of tetrahedra
+        tetrahedron: usize,
+        /// The index of the vertex
+        vertex: usize,
+    },
+}
+
+/// Checks that a 3D tetrahedralization is positively oriented and Delaunay
+/// after perturbing the points, returning the problems found.
+///
+/// Takes a list of all the points, an indexing function, and the tetrahedra,
+/// as quadruples of indexes that should be positively oriented by [`orient_3d`](crate::orient_3d).
+/// For each face shared by 2 tetrahedra, this checks that the vertex of each tetrahedron
+/// opposite the face isn't inside the circumsphere of the other tetrahedron, like
+/// [`verify_delaunay_2d`] does in 2D. Orientation problems are listed first.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, verify_delaunay_3d, Delaunay3dViolation};
+/// # use nalgebra::Vector3;
+/// let points = vec![
+///     Vector3::new(0.0, 0.0, 0.0),
+///     Vector3::new(1.0, 0.0, 0.0),
+///     Vector3::new(0.0, 1.0, 0.0),
+///     Vector3::new(0.0, 0.0, 1.0),
+///     Vector3::new(2.0, 2.0, 2.0),
+/// ];
+/// assert_eq!(verify_delaunay_3d(&points, |l, i| l[i], &[[0, 1, 3, 2], [4, 1, 2, 3]]), vec![]);
+/// assert_eq!(
+///     verify_delaunay_3d(&points, |l, i| l[i], &[[0, 1, 2, 3]]),
+///     vec![Delaunay3dViolation::Orientation { tetrahedron: 0 }],
+/// );
+/// ```
+pub fn verify_delaunay_3d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+    tetrahedra: &[[usize; 4]],
+) -> Vec<Delaunay3dViolation> {
+    let mut violations = tetrahedra.iter().enumerate()
+        .filter(|(_, [i, j, k, l])| !crate::orient_3d(list, &index_fn, *i, *j, *k, *l))
+        .map(|(tetrahedron, _)| Delaunay3dViolation::Orientation { tetrahedron })
+        .collect::<Vec<_>>();
+
+    // The tetrahedra on each face, with the vertex opposite it
+    let mut faces = BTreeMap::<[usize; 3], Vec<(usize, usize)>>::new();
+    for (t, tet) in tetrahedra.iter().enumerate() {
+        for i in 0..4 {
+            let mut face = [tet[(i + 1) % 4], tet[(i + 2) % 4], tet[(i + 3) % 4]];
+            face.sort_unstable();
+            faces.entry(face).or_default().push((t, tet[i]));
+        }
+    }
+
+    let mut in_sphere = vec![];
+    for sharing in faces.values() {
+        for (t, _) in sharing {
+            let [i, j, k, l] = tetrahedra[*t];
+            for (u, vertex) in sharing {
+                if u != t && crate::in_sphere_unoriented(list, index_fn.clone(), i, j, k, l, *vertex) {
+                    in_sphere.push((*t, *vertex));
+                }
+            }
+        }
+    }
+    in_sphere.sort_unstable();
+    violations.extend(
+        in_sphere
+            .into_iter()
+            .map(|(tetrahedron, vertex)| Delaunay3dViolation::InSphere { tetrahedron, vertex }),
+    );
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_verify_delaunay_2d() {
+        // A 3x3 grid, where every square is cocircular
+        let points = grid_points_2d()[..9].to_vec();
+        let square = |x: usize, y: usize| [3 * y + x, 3 * y + x + 1, 3 * y + x + 3, 3 * y + x + 4];
+        let diagonals = |[a, b, c, d]: [usize; 4]| [[[a, b, d], [a, d, c]], [[a, b, c], [b, d, c]]];
+
+        let mut triangles = vec![];
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)].iter() {
+            let valid = diagonals(square(*x, *y)).iter()
+                .filter(|tris| verify_delaunay_2d(&points, |l, i| l[i], &tris[..]).is_empty())
+                .copied()
+                .collect::<Vec<_>>();
+            assert_eq!(valid.len(), 1);
+            triangles.extend(valid[0].iter().copied());
+        }
+        assert_eq!(verify_delaunay_2d(&points, |l, i| l[i], &triangles), vec![]);
+
+        // Flip the diagonal of the first square
+        let flipped = diagonals(square(0, 0)).iter().find(|tris| tris[..] != triangles[..2]).unwrap().to_vec();
+        triangles.splice(..2, flipped);
+        let violations = verify_delaunay_2d(&points, |l, i| l[i], &triangles);
+        assert_eq!(violations.iter().map(|v| v.triangle).collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_verify_delaunay_3d() {
+        let mut points = vec![
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(2.0, 2.0, 2.0),
+        ];
+        let mut tetrahedra = [[0, 1, 3, 2], [4, 1, 2, 3]];
+        assert_eq!(verify_delaunay_3d(&points, |l, i| l[i], &tetrahedra), vec![]);
+
+        // Now each tetrahedron's circumsphere contains the other's opposite vertex
+        points[4] = Vector3::new(0.375, 0.375, 0.375);
+        assert_eq!(
+            verify_delaunay_3d(&points, |l, i| l[i], &tetrahedra),
+            vec![
+                Delaunay3dViolation::InSphere { tetrahedron: 0, vertex: 4 },
+                Delaunay3dViolation::InSphere { tetrahedron: 1, vertex: 0 },
+            ],
+        );
+
+        tetrahedra[0] = [0, 1, 2, 3];
+        assert_eq!(
+            verify_delaunay_3d(&points, |l, i| l[i], &tetrahedra)[0],
+            Delaunay3dViolation::Orientation { tetrahedron: 0 },
+        );
+    }
+}
@@ -0,0 +1,358 @@
+//! Incremental 2D Delaunay triangulation built only on the predicates.
+//!
+//! This is a reference implementation of the Bowyer–Watson algorithm. Because the predicates
+//! are perturbed, no 3 points are collinear and no 4 are cocircular, so the algorithm has
+//! no special cases for degenerate input, not even for duplicate points.
+//! The triangulation is the unique Delaunay triangulation of the perturbed points.
+//!
+//! The outside of the convex hull is covered by *ghost triangles*, which connect
+//! each hull edge to a vertex at infinity. A point conflicts with a ghost triangle if it's
+//! outside its hull edge, just like it conflicts with a real triangle if it's inside its
+//! circumcircle, so points outside the hull are inserted the same way as points inside it.
+//!
+//! Each point is located by jumping to the nearest of a few points inserted before it
+//! and walking toward it from there, which only takes predicates too, so the triangle
+//! it's located in always conflicts with it. [`Locator`] locates points in a finished
+//! triangulation the same way.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, delaunay2, verify_delaunay_2d};
+//! # use nalgebra::Vector2;
+//! // A grid, where every square is cocircular
+//! let points = (0..16).map(|i| Vector2::new((i % 4) as f64, (i / 4) as f64)).collect::<Vec<_>>();
+//! let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+//! // 18 triangles in the squares, plus 4 thin ones where the perturbation
+//! // moved a point on a side of the grid inward
+//! assert_eq!(triangles.len(), 22);
+//! assert!(verify_delaunay_2d(&points, |l, i| l[i], &triangles).is_empty());
+//! ```
+
+use crate::Vec2;
+use std::collections::HashMap;
+
+/// The vertex at infinity of the ghost triangles.
+const GHOST: usize = usize::MAX;
+
+struct Triangulation<'a, T: ?Sized, F> {
+    list: &'a T,
+    index_fn: F,
+    /// Triangles oriented positive, with `GHOST` last for ghost triangles
+    triangles: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    /// The triangle on the left of each directed edge
+    edges: HashMap<(usize, usize), usize>,
+    /// A real triangle at each vertex, to start walking from
+    incident: Vec<usize>,
+}
+
+/// Finds the nearest of about ∛n evenly spaced samples of the `n` vertices to the point,
+/// after perturbing them, returning its position among the vertices.
+fn jump<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    vertex: impl Fn(usize) -> usize,
+    n: usize,
+    point: usize,
+) -> usize {
+    let samples = ((n as f64).cbrt().ceil() as usize).max(1);
+    (0..samples)
+        .map(|s| s * n / samples)
+        .min_by(|v, w| crate::distance::distance_cmp_2d(list, &index_fn, vertex(*v), point, vertex(*w), point))
+        .unwrap()
+}
+
+impl<'a, T: ?Sized, F: Fn(&T, usize) -> Vec2> Triangulation<'a, T, F> {
+    fn orient(&self, i: usize, j: usize, k: usize) -> bool {
+        crate::orient_2d(self.list, &self.index_fn, i, j, k)
+    }
+
+    /// Whether the point is inside the circumcircle of the triangle,
+    /// or outside the hull edge of a ghost triangle.
+    fn conflicts(&self, tri: usize, point: usize) -> bool {
+        match self.triangles[tri] {
+            [i, j, GHOST] => self.orient(i, j, point),
+            [i, j, k] => crate::in_circle(self.list, &self.index_fn, i, j, k, point),
+        }
+    }
+
+    fn add(&mut self, tri: [usize; 3]) {
+        // Keep the ghost vertex last
+        let tri = match tri {
+            [GHOST, i, j] => [i, j, GHOST],
+            [i, GHOST, j] => [j, i, GHOST],
+            tri => tri,
+        };
+        let index = self.triangles.len();
+        for e in 0..3 {
+            self.edges.insert((tri[e], tri[(e + 1) % 3]), index);
+        }
+        self.triangles.push(tri);
+        self.alive.push(true);
+        if tri[2] != GHOST {
+            for v in tri.iter() {
+                self.incident[*v] = index;
+            }
+        }
+    }
+
+    fn remove(&mut self, tri: usize) {
+        self.alive[tri] = false;
+        let t = self.triangles[tri];
+        for e in 0..3 {
+            self.edges.remove(&(t[e], t[(e + 1) % 3]));
+        }
+    }
+
+    /// Jumps to a real triangle near the point and walks toward it,
+    /// returning a triangle that conflicts with it.
+    fn locate(&self, point: usize) -> usize {
+        // The points before this one have been inserted
+        let mut tri = self.incident[jump(self.list, &self.index_fn, |v| v, point, point)];
+        'walk: loop {
+            let t = self.triangles[tri];
+            if t[2] == GHOST {
+                return tri;
+            }
+            for e in 0..3 {
+                let (i, j) = (t[e], t[(e + 1) % 3]);
+                if !self.orient(i, j, point) {
+                    tri = self.edges[&(j, i)];
+                    continue 'walk;
+                }
+            }
+            return tri;
+        }
+    }
+
+    fn insert(&mut self, point: usize) {
+        let start = self.locate(point);
+
+        // Find the cavity of conflicting triangles, and its boundary
+        let mut cavity = vec![start];
+        let mut boundary = vec![];
+        self.alive[start] = false;
+        let mut n = 0;
+        while n < cavity.len() {
+            let t = self.triangles[cavity[n]];
+            for e in 0..3 {
+                let (i, j) = (t[e], t[(e + 1) % 3]);
+                let neighbor = self.edges[&(j, i)];
+                if !self.alive[neighbor] {
+                    // Already in the cavity
+                    continue;
+                }
+                if self.conflicts(neighbor, point) {
+                    self.alive[neighbor] = false;
+                    cavity.push(neighbor);
+                } else {
+                    boundary.push((i, j));
+                }
+            }
+            n += 1;
+        }
+
+        for tri in cavity {
+            self.remove(tri);
+        }
+        for (i, j) in boundary {
+            self.add([i, j, point]);
+        }
+    }
+}
+
+/// Computes the Delaunay triangulation of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the triangles as triples of indexes, oriented positive by [`orient_2d`](crate::orient_2d).
+/// Returns no triangles if there are fewer than 3 points.
+pub fn triangulate<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize) -> Vec<[usize; 3]> {
+    if len < 3 {
+        return vec![];
+    }
+
+    let mut tri = Triangulation {
+        list,
+        index_fn,
+        triangles: vec![],
+        alive: vec![],
+        edges: HashMap::new(),
+        incident: vec![GHOST; len],
+    };
+    let (i, j, k) = if tri.orient(0, 1, 2) { (0, 1, 2) } else { (1, 0, 2) };
+    tri.add([j, i, GHOST]);
+    tri.add([k, j, GHOST]);
+    tri.add([i, k, GHOST]);
+    tri.add([i, j, k]);
+
+    for point in 3..len {
+        tri.insert(point);
+    }
+
+    tri.triangles
+        .iter()
+        .zip(&tri.alive)
+        .filter(|(t, alive)| **alive && t[2] != GHOST)
+        .map(|(t, _)| *t)
+        .collect()
+}
+
+/// Where a point is in a triangulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// Inside the triangle with this index
+    Triangle(usize),
+    /// Outside the triangulation, on the negative side of this hull edge,
+    /// which is oriented like in its triangle
+    Outside([usize; 2]),
+}
+
+/// A structure for locating points in a finished triangulation.
+///
+/// Like during [`triangulate`], each point is located by jumping to the nearest of
+/// a few vertices and walking toward the point, using only perturbed predicates.
+/// So a point is located in a triangle exactly when [`in_circle`](crate::in_circle)
+/// and [`orient_2d`](crate::orient_2d) say it's inside it.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, delaunay2::{self, Location, Locator}};
+/// # use nalgebra::Vector2;
+/// let mut points = (0..9).map(|i| Vector2::new((i % 3) as f64, (i / 3) as f64)).collect::<Vec<_>>();
+/// let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+/// let locator = Locator::new(&triangles);
+///
+/// points.push(Vector2::new(0.25, 0.5));
+/// match locator.locate(&points, |l, i| l[i], 9) {
+///     Location::Triangle(t) => assert!(triangles[t].contains(&0) && triangles[t].contains(&3)),
+///     Location::Outside(_) => unreachable!(),
+/// }
+/// points.push(Vector2::new(1.0, -1.0));
+/// assert_eq!(locator.locate(&points, |l, i| l[i], 10), Location::Outside([0, 1]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Locator {
+    triangles: Vec<[usize; 3]>,
+    /// The triangle on the left of each directed edge
+    edges: HashMap<(usize, usize), usize>,
+    /// Each vertex, with a triangle at it
+    vertices: Vec<(usize, usize)>,
+}
+
+impl Locator {
+    /// Creates a locator for a triangulation, given as triples of indexes oriented positive.
+    /// The triangulation should be Delaunay, such as the one returned by [`triangulate`],
+    /// so that walking through it always reaches the point.
+    pub fn new(triangles: &[[usize; 3]]) -> Self {
+        let mut edges = HashMap::new();
+        let mut vertices = HashMap::new();
+        for (t, tri) in triangles.iter().enumerate() {
+            for e in 0..3 {
+                edges.insert((tri[e], tri[(e + 1) % 3]), t);
+                vertices.insert(tri[e], t);
+            }
+        }
+        let mut vertices = vertices.into_iter().collect::<Vec<_>>();
+        vertices.sort_unstable();
+        Self { triangles: triangles.to_vec(), edges, vertices }
+    }
+
+    /// Locates a point that isn't a vertex of the triangulation, given a list of all the points
+    /// and an indexing function.
+    pub fn locate<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2, point: usize) -> Location {
+        let (_, mut tri) = self.vertices[jump(list, &index_fn, |s| self.vertices[s].0, self.vertices.len(), point)];
+        'walk: loop {
+            let t = self.triangles[tri];
+            for e in 0..3 {
+                let (i, j) = (t[e], t[(e + 1) % 3]);
+                if !crate::orient_2d(list, &index_fn, i, j, point) {
+                    match self.edges.get(&(j, i)) {
+                        Some(next) => tri = *next,
+                        None => return Location::Outside([i, j]),
+                    }
+                    continue 'walk;
+                }
+            }
+            return Location::Triangle(tri);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, delaunay3, orient_2d, orient_3d, verify_delaunay_2d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::{Vector2, Vector3};
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    fn test_delaunay2(seed: u64) {
+        use std::collections::HashSet;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.cocircular_2d(12),
+            _ => {
+                let points = gen.grid_clusters_2d(3, 4);
+                gen.with_duplicates(points, 5)
+            }
+        };
+        let triangles = super::triangulate(&points, |l, i| l[i], points.len());
+        assert_eq!(verify_delaunay_2d(&points, |l, i| l[i], &triangles), vec![]);
+        for [i, j, k] in &triangles {
+            assert!(orient_2d(&points, |l, i| l[i], *i, *j, *k));
+        }
+
+        // Every point is used, and Euler's formula holds
+        let edges = triangles.iter().flat_map(|t| (0..3).map(move |e| (t[e], t[(e + 1) % 3]))).collect::<HashSet<_>>();
+        let hull = edges.iter().filter(|(i, j)| !edges.contains(&(*j, *i))).count();
+        assert_eq!(edges.iter().map(|(i, _)| *i).collect::<HashSet<_>>().len(), points.len());
+        assert_eq!(triangles.len(), 2 * points.len() - 2 - hull);
+    }
+
+    #[test]
+    fn test_locator() {
+        // Queries on the grid lines, at grid points, and outside
+        let mut points = (0..16).map(|i| Vector2::new((i % 4) as f64, (i / 4) as f64)).collect::<Vec<_>>();
+        let triangles = super::triangulate(&points, |l, i| l[i], points.len());
+        let locator = super::Locator::new(&triangles);
+        points.extend((0..64).map(|i| Vector2::new((i % 8) as f64 / 2.0 - 0.5, (i / 8) as f64 / 2.0 - 0.5)));
+        for p in 16..points.len() {
+            let inside = (0..triangles.len())
+                .filter(|t| (0..3).all(|e| orient_2d(&points, |l, i| l[i], triangles[*t][e], triangles[*t][(e + 1) % 3], p)))
+                .collect::<Vec<_>>();
+            match locator.locate(&points, |l, i| l[i], p) {
+                super::Location::Triangle(t) => assert_eq!(inside, vec![t]),
+                super::Location::Outside([i, j]) => {
+                    assert_eq!(inside, vec![]);
+                    assert!(!orient_2d(&points, |l, i| l[i], i, j, p));
+                    assert!(!triangles.iter().any(|t| (0..3).any(|e| [t[e], t[(e + 1) % 3]] == [j, i])));
+                }
+            }
+        }
+
+        let mut points = grid_points_3d();
+        let tetrahedra = delaunay3::tetrahedralize(&points, |l, i| l[i], points.len());
+        let locator = delaunay3::Locator::new(&tetrahedra);
+        let n = points.len();
+        points.extend((0..64).map(|i| Vector3::new((i % 4) as f64 / 2.0 - 0.5, (i / 4 % 4) as f64 / 2.0 - 0.5, (i / 16) as f64 / 2.0 - 0.5)));
+        for p in n..points.len() {
+            let inside = (0..tetrahedra.len())
+                .filter(|t| delaunay3::faces(tetrahedra[*t]).iter().all(|[i, j, k]| orient_3d(&points, |l, i| l[i], *i, *j, *k, p)))
+                .collect::<Vec<_>>();
+            match locator.locate(&points, |l, i| l[i], p) {
+                delaunay3::Location::Tetrahedron(t) => assert_eq!(inside, vec![t]),
+                delaunay3::Location::Outside([i, j, k]) => {
+                    assert_eq!(inside, vec![]);
+                    assert!(!orient_3d(&points, |l, i| l[i], i, j, k, p));
+                }
+            }
+        }
+    }
+}
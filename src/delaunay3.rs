@@ -0,0 +1,419 @@
+//! Incremental 3D Delaunay tetrahedralization built only on the predicates.
+//!
+//! This is the 3D counterpart of [`delaunay2`](crate::delaunay2), using only
+//! [`orient_3d`](crate::orient_3d) and [`in_sphere`](crate::in_sphere).
+//! Each point is located by jumping to the nearest of a few points inserted before it
+//! and walking toward it from there, and then the tetrahedra whose circumspheres contain it are replaced by tetrahedra connecting
+//! it to the boundary of their union. Because the predicates are perturbed, no 4 points
+//! are coplanar and no 5 are cospherical, so there are no special cases for degenerate input.
+//!
+//! The outside of the convex hull is covered by *ghost tetrahedra*, which connect each
+//! hull face to a vertex at infinity. A point conflicts with a ghost tetrahedron if it's
+//! outside its hull face, so points outside the hull are inserted like points inside it.
+//! [`Locator`] locates points in a finished tetrahedralization the same way.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, delaunay3, verify_delaunay_3d};
+//! # use nalgebra::Vector3;
+//! // A grid, where every cube is cospherical
+//! let points = (0..27)
+//!     .map(|i| Vector3::new((i % 3) as f64, (i / 3 % 3) as f64, (i / 9) as f64))
+//!     .collect::<Vec<_>>();
+//! let tetrahedra = delaunay3::tetrahedralize(&points, |l, i| l[i], points.len());
+//! assert!(verify_delaunay_3d(&points, |l, i| l[i], &tetrahedra).is_empty());
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed, Polynomial};
+use crate::Vec3;
+use std::collections::HashMap;
+
+/// The vertex at infinity of the ghost tetrahedra.
+const GHOST: usize = usize::MAX;
+
+/// The faces of a positively oriented tetrahedron, each oriented so that
+/// it's positive with the opposite vertex.
+pub(crate) fn faces([a, b, c, d]: [usize; 4]) -> [[usize; 3]; 4] {
+    [[a, b, c], [a, d, b], [a, c, d], [b, d, c]]
+}
+
+/// Rotates a face so its smallest index is first, without changing its orientation.
+pub(crate) fn key([a, b, c]: [usize; 3]) -> [usize; 3] {
+    if a < b && a < c {
+        [a, b, c]
+    } else if b < c {
+        [b, c, a]
+    } else {
+        [c, a, b]
+    }
+}
+
+struct Tetrahedralization<'a, T: ?Sized, F> {
+    list: &'a T,
+    index_fn: F,
+    /// Tetrahedra oriented positive, with `GHOST` last for ghost tetrahedra
+    tetrahedra: Vec<[usize; 4]>,
+    alive: Vec<bool>,
+    /// The tetrahedron on the positive side of each oriented face, keyed by [`key`]
+    faces: HashMap<[usize; 3], usize>,
+    /// A real tetrahedron at each vertex, to start walking from
+    incident: Vec<usize>,
+}
+
+/// Finds the nearest of about ∛n evenly spaced samples of the `n` vertices to the point,
+/// after perturbing them, returning its position among the vertices.
+fn jump<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3,
+    vertex: impl Fn(usize) -> usize,
+    n: usize,
+    point: usize,
+) -> usize {
+    let samples = ((n as f64).cbrt().ceil() as usize).max(1);
+    (0..samples)
+        .map(|s| s * n / samples)
+        .min_by(|v, w| crate::distance::distance_cmp_3d(list, &index_fn, vertex(*v), point, vertex(*w), point))
+        .unwrap()
+}
+
+impl<'a, T: ?Sized, F: Fn(&T, usize) -> Vec3> Tetrahedralization<'a, T, F> {
+    fn orient(&self, [i, j, k]: [usize; 3], l: usize) -> bool {
+        crate::orient_3d(self.list, &self.index_fn, i, j, k, l)
+    }
+
+    /// Whether the point is inside the circumsphere of the tetrahedron,
+    /// or outside the hull face of a ghost tetrahedron.
+    fn conflicts(&self, tet: usize, point: usize) -> bool {
+        match self.tetrahedra[tet] {
+            [i, j, k, GHOST] => self.orient([i, j, k], point),
+            [i, j, k, l] => crate::in_sphere(self.list, &self.index_fn, i, j, k, l, point),
+        }
+    }
+
+    /// The tetrahedron on the other side of a face of a tetrahedron.
+    fn neighbor(&self, [a, b, c]: [usize; 3]) -> usize {
+        self.faces[&key([a, c, b])]
+    }
+
+    /// Adds the tetrahedron with a face and a vertex on its positive side.
+    fn add(&mut self, face: [usize; 3], apex: usize) {
+        // Keep the ghost vertex last, with an even permutation
+        let tet = match face {
+            [a, b, GHOST] | [GHOST, a, b] | [b, GHOST, a] => [b, a, apex, GHOST],
+            [a, b, c] => [a, b, c, apex],
+        };
+        let index = self.tetrahedra.len();
+        for face in faces(tet).iter() {
+            self.faces.insert(key(*face), index);
+        }
+        self.tetrahedra.push(tet);
+        self.alive.push(true);
+        if tet[3] != GHOST {
+            for v in tet.iter() {
+                self.incident[*v] = index;
+            }
+        }
+    }
+
+    fn remove(&mut self, tet: usize) {
+        self.alive[tet] = false;
+        for face in faces(self.tetrahedra[tet]).iter() {
+            self.faces.remove(&key(*face));
+        }
+    }
+
+    /// Jumps to a real tetrahedron near the point and walks toward it,
+    /// returning a tetrahedron that conflicts with it.
+    fn locate(&self, point: usize) -> usize {
+        // The points before this one have been inserted
+        let mut tet = self.incident[jump(self.list, &self.index_fn, |v| v, point, point)];
+        'walk: loop {
+            let t = self.tetrahedra[tet];
+            if t[3] == GHOST {
+                return tet;
+            }
+            for face in faces(t).iter() {
+                if !self.orient(*face, point) {
+                    tet = self.neighbor(*face);
+                    continue 'walk;
+                }
+            }
+            return tet;
+        }
+    }
+
+    fn insert(&mut self, point: usize) {
+        let start = self.locate(point);
+
+        // Find the cavity of conflicting tetrahedra, and its boundary
+        let mut cavity = vec![start];
+        let mut boundary = vec![];
+        self.alive[start] = false;
+        let mut n = 0;
+        while n < cavity.len() {
+            for face in faces(self.tetrahedra[cavity[n]]).iter() {
+                let neighbor = self.neighbor(*face);
+                if !self.alive[neighbor] {
+                    // Already in the cavity
+                    continue;
+                }
+                if self.conflicts(neighbor, point) {
+                    self.alive[neighbor] = false;
+                    cavity.push(neighbor);
+                } else {
+                    boundary.push(*face);
+                }
+            }
+            n += 1;
+        }
+
+        for tet in cavity {
+            self.remove(tet);
+        }
+        for face in boundary {
+            self.add(face, point);
+        }
+    }
+}
+
+/// Computes the Delaunay tetrahedralization of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the tetrahedra as quadruples of indexes, oriented positive by [`orient_3d`](crate::orient_3d).
+/// Returns no tetrahedra if there are fewer than 4 points.
+pub fn tetrahedralize<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize) -> Vec<[usize; 4]> {
+    if len < 4 {
+        return vec![];
+    }
+
+    let mut tet = Tetrahedralization {
+        list,
+        index_fn,
+        tetrahedra: vec![],
+        alive: vec![],
+        faces: HashMap::new(),
+        incident: vec![GHOST; len],
+    };
+    let first = if tet.orient([0, 1, 2], 3) { [0, 1, 2, 3] } else { [1, 0, 2, 3] };
+    for [a, b, c] in faces(first).iter() {
+        tet.add([*a, *c, *b], GHOST);
+    }
+    tet.add([first[0], first[1], first[2]], first[3]);
+
+    for point in 4..len {
+        tet.insert(point);
+    }
+
+    tet.tetrahedra
+        .iter()
+        .zip(&tet.alive)
+        .filter(|(t, alive)| **alive && t[3] != GHOST)
+        .map(|(t, _)| *t)
+        .collect()
+}
+
+/// Returns whether `a`, `b`, `c`, and the circumcenter of tetrahedron `tet` are oriented positive,
+/// like [`orient_3d`](crate::orient_3d), after perturbing the points. The circumcenter is
+/// computed exactly as a fraction, so this decides which side of a plane a Voronoi vertex is on
+/// without rounding it. The vertices of `tet` can be in any order.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, delaunay3};
+/// # use nalgebra::Vector3;
+/// let points = vec![
+///     Vector3::new(0.0, 0.0, 0.0),
+///     Vector3::new(1.0, 0.0, 0.0),
+///     Vector3::new(0.0, 1.0, 0.0),
+///     Vector3::new(0.0, 0.0, 4.0),
+///     Vector3::new(0.0, 0.0, 1.0),
+///     Vector3::new(1.0, 0.0, 1.0),
+///     Vector3::new(0.0, 1.0, 1.0),
+/// ];
+/// // The circumcenter is at (0.5, 0.5, 2), above the plane z = 1
+/// let above = simplicity::orient_3d(&points, |l, i| l[i], 4, 5, 6, 3);
+/// assert_eq!(delaunay3::orient_circumcenter(&points, |l, i| l[i], 4, 5, 6, [0, 1, 2, 3]), above);
+/// ```
+pub fn orient_circumcenter<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    tet: [Idx; 4],
+) -> bool {
+    let [p, q, r, s] = tet;
+    // A point shared by the plane and the tetrahedron has degree at most 4
+    let mut perturbed = Perturbed::new(3, 5, &[a, b, c, p, q, r, s], |i| index_fn(list, i));
+    // Homogeneous coordinates of the circumcenter,
+    // p + (|u|² v × w + |v|² w × u + |w|² u × v) / (2 u · v × w) with u, v, w the edges from p
+    let center = |perturbed: &Perturbed<Idx, _>| {
+        let edge = |i| [0, 1, 2].map(|k| perturbed.diff(i, p, k));
+        let (u, v, w) = (edge(q), edge(r), edge(s));
+        let cross = |x: &[Polynomial; 3], y: &[Polynomial; 3]| {
+            [0, 1, 2].map(|k| {
+                let (k1, k2) = ((k + 1) % 3, (k + 2) % 3);
+                x[k1].clone() * y[k2].clone() - x[k2].clone() * y[k1].clone()
+            })
+        };
+        let dot = |x: &[Polynomial; 3], y: &[Polynomial; 3]| {
+            (0..3).fold(Polynomial::default(), |acc, k| acc + x[k].clone() * y[k].clone())
+        };
+        let (vw, wu, uv) = (cross(&v, &w), cross(&w, &u), cross(&u, &v));
+        let den = dot(&u, &vw).scale(2.0);
+        let (lu, lv, lw) = (dot(&u, &u), dot(&v, &v), dot(&w, &w));
+        let mut row = (0..3)
+            .map(|k| {
+                perturbed.coord(p, k) * den.clone()
+                    + lu.clone() * vw[k].clone()
+                    + lv.clone() * wu[k].clone()
+                    + lw.clone() * uv[k].clone()
+            })
+            .collect::<Vec<_>>();
+        row.push(den);
+        row
+    };
+    let orientation = perturbed.lazy_signum(|perturbed| {
+        let row = |i| {
+            let mut row = (0..3).map(|k| perturbed.coord(i, k)).collect::<Vec<_>>();
+            row.push(Polynomial::from(Expansion::from(1.0)));
+            row
+        };
+        perturbed::det(&[row(a), row(b), row(c), center(perturbed)])
+    });
+    let weight = perturbed.lazy_signum(|perturbed| center(perturbed).swap_remove(3));
+    orientation * weight > 0.0
+}
+
+/// Where a point is in a tetrahedralization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Location {
+    /// Inside the tetrahedron with this index
+    Tetrahedron(usize),
+    /// Outside the tetrahedralization, on the negative side of this hull face,
+    /// which is oriented positive with the rest of its tetrahedron
+    Outside([usize; 3]),
+}
+
+/// A structure for locating points in a finished tetrahedralization.
+///
+/// Like during [`tetrahedralize`], each point is located by jumping to the nearest of
+/// a few vertices and walking toward the point, using only perturbed predicates.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, delaunay3::{self, Location, Locator}};
+/// # use nalgebra::Vector3;
+/// let mut points = (0..8)
+///     .map(|i| Vector3::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4) as f64))
+///     .collect::<Vec<_>>();
+/// let tetrahedra = delaunay3::tetrahedralize(&points, |l, i| l[i], points.len());
+/// let locator = Locator::new(&tetrahedra);
+///
+/// points.push(Vector3::new(0.5, 0.5, 0.5));
+/// assert!(matches!(locator.locate(&points, |l, i| l[i], 8), Location::Tetrahedron(_)));
+/// points.push(Vector3::new(0.5, 0.5, 2.0));
+/// assert!(matches!(locator.locate(&points, |l, i| l[i], 9), Location::Outside(_)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Locator {
+    tetrahedra: Vec<[usize; 4]>,
+    /// The tetrahedron on the positive side of each oriented face, keyed by [`key`]
+    faces: HashMap<[usize; 3], usize>,
+    /// Each vertex, with a tetrahedron at it
+    vertices: Vec<(usize, usize)>,
+}
+
+impl Locator {
+    /// Creates a locator for a tetrahedralization, given as quadruples of indexes oriented positive.
+    /// The tetrahedralization should be Delaunay, such as the one returned by [`tetrahedralize`],
+    /// so that walking through it always reaches the point.
+    pub fn new(tetrahedra: &[[usize; 4]]) -> Self {
+        let mut faces_map = HashMap::new();
+        let mut vertices = HashMap::new();
+        for (t, tet) in tetrahedra.iter().enumerate() {
+            for face in faces(*tet).iter() {
+                faces_map.insert(key(*face), t);
+            }
+            for v in tet.iter() {
+                vertices.insert(*v, t);
+            }
+        }
+        let mut vertices = vertices.into_iter().collect::<Vec<_>>();
+        vertices.sort_unstable();
+        Self { tetrahedra: tetrahedra.to_vec(), faces: faces_map, vertices }
+    }
+
+    /// Locates a point that isn't a vertex of the tetrahedralization, given a list of all the points
+    /// and an indexing function.
+    pub fn locate<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec3, point: usize) -> Location {
+        let (_, mut tet) = self.vertices[jump(list, &index_fn, |s| self.vertices[s].0, self.vertices.len(), point)];
+        'walk: loop {
+            for face in faces(self.tetrahedra[tet]).iter() {
+                let [i, j, k] = *face;
+                if !crate::orient_3d(list, &index_fn, i, j, k, point) {
+                    match self.faces.get(&key([i, k, j])) {
+                        Some(next) => tet = *next,
+                        None => return Location::Outside(*face),
+                    }
+                    continue 'walk;
+                }
+            }
+            return Location::Tetrahedron(tet);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, verify_delaunay_3d};
+    use crate::tests::grid_points_3d;
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    fn test_delaunay3(seed: u64) {
+        use std::collections::HashSet;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_3d(),
+            1 => gen.cospherical_3d(12),
+            2 => gen.coplanar_3d(10),
+            _ => {
+                let points = gen.grid_clusters_3d(2, 3);
+                gen.with_duplicates(points, 5)
+            }
+        };
+        let tetrahedra = super::tetrahedralize(&points, |l, i| l[i], points.len());
+        assert_eq!(verify_delaunay_3d(&points, |l, i| l[i], &tetrahedra), vec![]);
+
+        // Every point is used, and no face is on the same side of 2 tetrahedra
+        let used = tetrahedra.iter().flatten().copied().collect::<HashSet<_>>();
+        assert_eq!(used.len(), points.len());
+        let mut faces = HashSet::new();
+        for [i, j, k, l] in &tetrahedra {
+            for face in [[*i, *j, *k], [*i, *l, *j], [*i, *k, *l], [*j, *l, *k]].iter() {
+                let mut face = *face;
+                let min = (0..3).min_by_key(|c| face[*c]).unwrap();
+                face.rotate_left(min);
+                assert!(faces.insert(face));
+            }
+        }
+
+        if seed == 0 {
+            // The tetrahedra fill the unit cube, although some are flat
+            let volume = tetrahedra.iter()
+                .map(|t| (points[t[1]] - points[t[0]]).cross(&(points[t[2]] - points[t[0]])).dot(&(points[t[3]] - points[t[0]])).abs())
+                .sum::<f64>();
+            assert_eq!(volume, 6.0);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Variants of the predicates that perturb higher indexes more.
+//!
+//! The usual predicates perturb lower indexes more, so coordinate *p\_(i,j)* is perturbed by
+//! ε^(3^(*d*\**i* - *j*)). Some descriptions of simulation of simplicity use the opposite
+//! convention, where the last point is the most significant, and these match them,
+//! so ports of pseudocode written against that convention break ties the same way.
+//! Within a point, later coordinates are still perturbed more.
+//!
+//! These just call the usual predicates with [`Reverse`] indexes, which works for any predicate
+//! in the crate, since the points are perturbed in the order of the indexes.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, orient_1d, descending};
+//! # use nalgebra::Vector1;
+//! let points = vec![Vector1::new(0.0), Vector1::new(0.0)];
+//! // Point 0 is perturbed more, so it's to the right of point 1
+//! assert!(orient_1d(&points, |l, i| l[i], 0, 1));
+//! // Point 1 is perturbed more
+//! assert!(descending::orient_1d(&points, |l, i| l[i], 1, 0));
+//! ```
+
+use crate::{Vec1, Vec2, Vec3};
+use core::cmp::Reverse;
+
+fn reverse_indexes<T: ?Sized, Idx, P>(index_fn: impl Fn(&T, Idx) -> P + Clone) -> impl Fn(&T, Reverse<Idx>) -> P + Clone {
+    move |list, Reverse(i)| index_fn(list, i)
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but perturbing higher indexes more.
+pub fn orient_1d<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec1 + Clone, i: Idx, j: Idx) -> bool {
+    crate::orient_1d(list, reverse_indexes(index_fn), Reverse(i), Reverse(j))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but perturbing higher indexes more.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    crate::orient_2d(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but perturbing higher indexes more.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::orient_3d(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k), Reverse(l))
+}
+
+/// Like [`in_circle`](crate::in_circle), but perturbing higher indexes more.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::in_circle(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k), Reverse(l))
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but perturbing higher indexes more.
+pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::in_circle_unoriented(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k), Reverse(l))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but perturbing higher indexes more.
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    crate::in_sphere(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k), Reverse(l), Reverse(m))
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but perturbing higher indexes more.
+pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    crate::in_sphere_unoriented(list, reverse_indexes(index_fn), Reverse(i), Reverse(j), Reverse(k), Reverse(l), Reverse(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_1d, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_descending() {
+        let vec1 = |l: &Vec<Vec1>, i: usize| l[i];
+        let ties = vec![Vector1::new(0.0); 3];
+        for i in 0..3 {
+            for j in (0..3).filter(|j| *j != i) {
+                assert_eq!(orient_1d(&ties, vec1, i, j), i < j);
+                assert_eq!(super::orient_1d(&ties, vec1, i, j), i > j);
+            }
+        }
+
+        // Same as reversing the list
+        let points = grid_points_2d();
+        let rev = points.iter().rev().copied().collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        super::orient_2d(&points, |l, i| l[i], i, j, k),
+                        orient_2d(&rev, |l, i| l[i], n - 1 - i, n - 1 - j, n - 1 - k)
+                    );
+                    for l in (0..n).filter(|l| ![i, j, k].contains(l)) {
+                        assert_eq!(
+                            super::in_circle(&points, |l, i| l[i], i, j, k, l),
+                            in_circle(&rev, |l, i| l[i], n - 1 - i, n - 1 - j, n - 1 - k, n - 1 - l)
+                        );
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d();
+        let rev = points.iter().rev().copied().collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+            assert_eq!(
+                super::orient_3d(&points, |l, i| l[i], i, j, k, l),
+                orient_3d(&rev, |l, i| l[i], n - 1 - i, n - 1 - j, n - 1 - k, n - 1 - l)
+            );
+            assert_eq!(
+                super::in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                in_sphere(&rev, |l, i| l[i], n - 1 - i, n - 1 - j, n - 1 - k, n - 1 - l, n - 1 - m)
+            );
+        }
+    }
+}
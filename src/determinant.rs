@@ -0,0 +1,112 @@
+//! Signs of small determinants with perturbed entries, for custom predicates.
+//!
+//! [`generate_sos_predicate`](crate::generate_sos_predicate) generates a predicate from
+//! the columns of its determinant at compile time. This takes the matrix at runtime instead,
+//! with each row tagged by the index of the point it comes from, and a choice of which columns
+//! are perturbed. The entry in the *k*-th perturbed column of a point's row is perturbed like
+//! coordinate *k* of that point, so with the coordinates as the perturbed columns and constants
+//! in the rest, the signs agree with the crate's predicates.
+//!
+//! Each perturbed entry is perturbed on its own, so a column that's a function of other columns,
+//! like the squared distances of in-circle tests, doesn't get the perturbation of that function.
+//! Determinants are expanded exactly, which takes time factorial in the size, so this is meant
+//! for matrices up to about 5×5.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, determinant};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//! ];
+//! // The orientation determinant, with rows (x, y, 1)
+//! let rows = [0, 1, 2].map(|i| (i, [points[i].x, points[i].y, 1.0]));
+//! let sign = determinant::signum(&rows, &[0, 1]);
+//! assert_eq!(sign > 0.0, simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed, Polynomial};
+use alloc::{vec, vec::Vec};
+
+/// Returns the sign of the determinant of a square matrix after perturbing the entries
+/// in the columns `perturbed`, as -1, 0, or 1.
+///
+/// Takes the rows, each tagged with the index of its point, and the positions of the perturbed
+/// columns, in the order of the coordinates they're perturbed like. Returns 0 only if
+/// the determinant is 0 even after perturbing, such as when 2 rows have the same index
+/// and the same entries.
+///
+/// # Panics
+///
+/// Panics if the matrix isn't square or a perturbed column is out of range.
+pub fn signum<Idx: Ord + Copy, R: AsRef<[f64]>>(rows: &[(Idx, R)], perturbed: &[usize]) -> f64 {
+    let n = rows.len();
+    assert!(rows.iter().all(|(_, row)| row.as_ref().len() == n), "the matrix isn't square");
+    assert!(perturbed.iter().all(|c| *c < n), "a perturbed column is out of range");
+
+    let indexes = rows.iter().map(|(i, _)| *i).collect::<Vec<_>>();
+    // Only the perturbations, which get added to the entries, since rows can share an index
+    let zeros = vec![0.0; perturbed.len()];
+    // Each perturbation is in 1 entry of its row, so it shows up at most once in a term
+    let mut perturbations = Perturbed::new(perturbed.len(), 1, &indexes, |_| zeros.clone());
+    perturbations.lazy_signum(|p| {
+        let matrix = rows
+            .iter()
+            .map(|(i, row)| {
+                let row = row.as_ref();
+                (0..n)
+                    .map(|c| {
+                        let entry = Polynomial::from(Expansion::from(row[c]));
+                        match perturbed.iter().position(|pc| *pc == c) {
+                            Some(k) => entry + p.coord(*i, k),
+                            None => entry,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        perturbed::det(&matrix)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_determinant() {
+        let points = grid_points_2d();
+        let n = points.len();
+        let row = |i: usize| (i, [points[i].x, points[i].y, 1.0]);
+        // The constant column first, which is an even permutation of the columns
+        let shifted = |i: usize| (i, [1.0, points[i].x, points[i].y]);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (j + 1..n).filter(|k| *k != i) {
+                    let orient = orient_2d(&points, |l, i| l[i], i, j, k);
+                    assert_eq!(super::signum(&[row(i), row(j), row(k)], &[0, 1]) > 0.0, orient);
+                    assert_eq!(super::signum(&[shifted(i), shifted(j), shifted(k)], &[1, 2]) > 0.0, orient);
+                }
+            }
+        }
+        // A repeated point stays degenerate
+        assert_eq!(super::signum(&[row(0), row(1), row(0)], &[0, 1]), 0.0);
+        // Rows that share an index are perturbed the same way, but the entries can differ
+        assert_ne!(super::signum(&[row(0), row(1), (0, [0.0, 0.0, 2.0])], &[0, 1]), 0.0);
+
+        let points = grid_points_3d();
+        let n = points.len();
+        let row = |i: usize| (i, vec![points[i].x, points[i].y, points[i].z, 1.0]);
+        for i in 0..n {
+            let (j, k, l) = ((i + 1) % n, (i + 3) % n, (i + 4) % n);
+            let sign = super::signum(&[row(i), row(j), row(k), row(l)], &[0, 1, 2]);
+            assert_eq!(sign > 0.0, orient_3d(&points, |l, i| l[i], i, j, k, l));
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Which case of a predicate's cascade decides its result.
+//!
+//! Each function takes the same arguments as the predicate of the same name,
+//! minus `_case`, and evaluates the same cascade, so test suites can check
+//! which degeneracy their fixtures exercise.
+
+use crate::{rg, scale, sorted_3, sorted_4, Vec2, Vec3};
+
+// Like the predicates' `case!`, but returns which case was reached
+macro_rules! case {
+    ($arr:expr => $pi:ident, $pj:ident, @ m2) => {
+        let val = rg::magnitude_cmp_2d($pi, $pj);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, @ m3) => {
+        let val = rg::magnitude_cmp_3d($pi, $pj);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident $(, @ $swiz:ident)?) => {
+        if $pi$(.$swiz)? != $pj$(.$swiz)? {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, @ $swiz:ident m2) => {
+        let val = rg::sign_det_x_x2y2($pi.$swiz(), $pj.$swiz(), $pk.$swiz());
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, @ $swiz:ident m3) => {
+        let val = rg::sign_det_x_x2y2z2($pi.$swiz(), $pj.$swiz(), $pk.$swiz());
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident $(, @ $swiz:ident)?) => {
+        let val = rg::orient_2d($pi$(.$swiz())?, $pj$(.$swiz())?, $pk$(.$swiz())?);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, @ xy m2) => {
+        let val = rg::in_circle($pi, $pj, $pk, $pl);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, @ $swiz:ident m3) => {
+        let val = rg::sign_det_x_y_x2y2z2($pi.$swiz(), $pj.$swiz(), $pk.$swiz(), $pl.$swiz());
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident $(, @ $swiz:ident)?) => {
+        let val = rg::orient_3d($pi$(.$swiz())?, $pj$(.$swiz())?, $pk$(.$swiz())?, $pl$(.$swiz())?);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+
+    ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, $pm:ident, @ xyz m3) => {
+        let val = rg::in_sphere($pi, $pj, $pk, $pl, $pm);
+        if val != 0.0 {
+            return $arr;
+        }
+    };
+}
+
+/// Returns which case of the cascade decides [`orient_2d`](crate::orient_2d).
+///
+/// The case has an entry per point, in increasing index order, and cases later in the cascade
+/// compare smaller when compared from the last entry to the first.
+/// `[3, 3, 3]` is the unperturbed determinant, and `[1, 2, 3]` is only reached
+/// if all 3 points are the same.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, diagnostics::orient_2d_case};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 0.0),
+///     Vector2::new(1.0, 1.0),
+///     Vector2::new(2.0, 2.0),
+/// ];
+/// assert_eq!(orient_2d_case(&points, |l, i| l[i], 0, 1, 2), [3, 3, 3]);
+/// // Collinear
+/// assert_eq!(orient_2d_case(&points, |l, i| l[i], 0, 2, 3), [2, 3, 3]);
+/// ```
+// Keep in sync with orient_2d
+pub fn orient_2d_case<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> [usize; 3] {
+    let ([i, j, k], _) = sorted_3([i, j, k]);
+    let [pi, pj, pk] = scale::prescaled([index_fn(list, i), index_fn(list, j), index_fn(list, k)]);
+
+    case!([3, 3, 3] => pi, pj, pk);
+    case!([2, 3, 3] => pk, pj, @ x);
+    case!([1, 3, 3] => pj, pk, @ y);
+    case!([2, 2, 3] => pi, pk, @ x);
+    [1, 2, 3]
+}
+
+/// Returns which case of the cascade decides [`orient_3d`](crate::orient_3d),
+/// in the same format as [`orient_2d_case`].
+// Keep in sync with orient_3d
+pub fn orient_3d_case<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> [usize; 4] {
+    let ([i, j, k, l], _) = sorted_4([i, j, k, l]);
+    let [pi, pj, pk, pl] =
+        scale::prescaled([index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)]);
+
+    case!([4, 4, 4, 4] => pi, pj, pk, pl);
+    case!([3, 4, 4, 4] => pj, pk, pl, @ xy);
+    case!([2, 4, 4, 4] => pj, pk, pl, @ zx);
+    case!([1, 4, 4, 4] => pj, pk, pl, @ yz);
+    case!([3, 3, 4, 4] => pi, pk, pl, @ yx);
+    case!([2, 3, 4, 4] => pk, pl, @ x);
+    case!([1, 3, 4, 4] => pl, pk, @ y);
+    case!([2, 2, 4, 4] => pi, pk, pl, @ xz);
+    case!([1, 2, 4, 4] => pk, pl, @ z);
+    //case!([1, 1, 4, 4] => pi, pk, pl, @ zy); Impossible
+    case!([3, 3, 3, 4] => pi, pj, pl, @ xy);
+    case!([2, 3, 3, 4] => pl, pj, @ x);
+    case!([1, 3, 3, 4] => pj, pl, @ y);
+    case!([2, 2, 3, 4] => pi, pl, @ x);
+    [1, 2, 3, 4]
+}
+
+/// Returns the index of the case of the cascade that decides [`in_circle`](crate::in_circle).
+/// Case 0 is the unperturbed determinant, and the last case is only reached
+/// if every determinant in the cascade is 0.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, diagnostics::in_circle_case};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 0.0),
+///     Vector2::new(0.0, 1.0),
+///     Vector2::new(1.0, 1.0),
+///     Vector2::new(0.2, 0.2),
+/// ];
+/// assert_eq!(in_circle_case(&points, |l, i| l[i], 0, 1, 2, 4), 0);
+/// // Cocircular
+/// assert_ne!(in_circle_case(&points, |l, i| l[i], 0, 1, 2, 3), 0);
+/// ```
+pub fn in_circle_case<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> usize {
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l]);
+    simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l; case}
+}
+
+/// Returns the index of the case of the cascade that decides [`in_sphere`](crate::in_sphere),
+/// in the same format as [`in_circle_case`].
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_case<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> usize {
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l, m]);
+    simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l, m; case}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_in_circle_case() {
+        let points = grid_points_2d();
+        let num_cases = (0..points.len())
+            .flat_map(|i| (0..points.len()).map(move |j| [i, j]))
+            .filter(|[i, j]| i != j)
+            .map(|[i, j]| in_circle_case(&points, |l, i| l[i], i, j, 4, 10))
+            .max();
+        assert!(num_cases > Some(1));
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                let case = in_circle_case(&points, |l, i| l[i], i, j, 2, 6);
+                let general = rg::in_circle(points[i], points[j], points[2], points[6]) != 0.0;
+                assert_eq!(case == 0, general);
+            }
+        }
+    }
+}
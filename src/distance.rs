@@ -0,0 +1,171 @@
+//! Comparisons of distances between perturbed points.
+//!
+//! These predicates are signs of sums of squared distances, which are quadratic polynomials
+//! in the perturbations instead of determinants. When the unperturbed sum is exactly 0,
+//! the sign is decided by expanding the polynomial in ε, with coordinate *j* of the point
+//! with the *i*-th smallest index perturbed by ε^(3^(*d*\**i* + *d* - 1 - *j*)),
+//! which orders the perturbations the same way as the rest of the crate.
+//! So equal distances between different pairs of points never tie.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, distance};
+//! # use nalgebra::Vector2;
+//! # use std::cmp::Ordering;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(2.0, 2.0),
+//! ];
+//! let cmp = distance::distance_cmp_2d(&points, |l, i| l[i], 0, 1, 0, 3);
+//! assert_eq!(cmp, Ordering::Less);
+//! // The same length, but the perturbation decides
+//! let cmp = distance::distance_cmp_2d(&points, |l, i| l[i], 0, 1, 0, 2);
+//! assert_eq!(cmp, distance::distance_cmp_2d(&points, |l, i| l[i], 0, 2, 0, 1).reverse());
+//! assert_ne!(cmp, Ordering::Equal);
+//! ```
+
+use crate::exact::Expansion;
+use crate::scale::Prescaled;
+use crate::{Vec2, Vec3};
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::ops::Index;
+
+/// Returns the sign of a sum of squared distances `s * |p - q|²` for each `(s, p, q)` in `terms`,
+/// after perturbing the `dim`-dimensional points.
+/// Returns `Ordering::Equal` only if the sum is identically 0.
+pub(crate) fn sign_of_squared_distances<Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    dim: usize,
+    terms: &[(i32, Idx, Idx)],
+    point: impl Fn(Idx) -> P,
+) -> Ordering {
+    let mut indexes = terms.iter().flat_map(|(_, p, q)| vec![*p, *q]).collect::<Vec<_>>();
+    indexes.sort_unstable();
+    indexes.dedup();
+    let points = Prescaled::new(dim, 0, &indexes, &[], point);
+
+    let mut value = Expansion::zero();
+    // Coefficients of the perturbations of each coordinate, and of the products of 2 of them
+    let mut linear = BTreeMap::<(Idx, usize), Expansion>::new();
+    let mut quadratic = BTreeMap::<[(Idx, usize); 2], i32>::new();
+    for (s, p, q) in terms.iter().copied() {
+        let (pp, pq) = (points.point(p), points.point(q));
+        for c in 0..dim {
+            let diff = Expansion::from(pp[c]) - Expansion::from(pq[c]);
+            value = value + (diff.clone() * diff.clone()).scale(f64::from(s));
+            for (v, scale) in [((p, c), 2 * s), ((q, c), -2 * s)].iter() {
+                let coeff = linear.entry(*v).or_default();
+                *coeff = core::mem::take(coeff) + diff.scale(f64::from(*scale));
+            }
+            let pair = [(p, c).min((q, c)), (p, c).max((q, c))];
+            for (vars, coeff) in [([(p, c), (p, c)], s), ([(q, c), (q, c)], s), (pair, -2 * s)].iter() {
+                *quadratic.entry(*vars).or_default() += coeff;
+            }
+        }
+    }
+    if value.signum() != 0.0 {
+        return value.signum().partial_cmp(&0.0).unwrap();
+    }
+
+    // Exponents of different monomials are different numbers in base 3, so they never tie
+    let exponent = |(i, c): (Idx, usize)| {
+        let rank = indexes.binary_search(&i).unwrap();
+        3u64.pow((dim * rank + dim - 1 - c) as u32)
+    };
+    let mut monomials = linear
+        .into_iter()
+        .map(|(v, coeff)| (exponent(v), coeff.signum()))
+        .chain(quadratic.into_iter().map(|([v, w], coeff)| (exponent(v) + exponent(w), f64::from(coeff))))
+        .filter(|(_, coeff)| *coeff != 0.0)
+        .collect::<Vec<_>>();
+    monomials.sort_by_key(|(e, _)| *e);
+    monomials.first().map_or(Ordering::Equal, |(_, coeff)| coeff.partial_cmp(&0.0).unwrap())
+}
+
+/// Compares the distance between points `i` and `j` with the distance between points `k` and `l`
+/// after perturbing them. Returns `Ordering::Equal` only if they're the same pair of points.
+pub fn distance_cmp_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Ordering {
+    sign_of_squared_distances(2, &[(1, i, j), (-1, k, l)], |i| index_fn(list, i))
+}
+
+/// Compares the distance between points `i` and `j` with the distance between points `k` and `l`
+/// after perturbing them. Returns `Ordering::Equal` only if they're the same pair of points.
+pub fn distance_cmp_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Ordering {
+    sign_of_squared_distances(3, &[(1, i, j), (-1, k, l)], |i| index_fn(list, i))
+}
+
+/// Returns whether point `p` is strictly inside the circle with diameter `ab` after perturbing the points.
+/// The result is never degenerate unless `p` is `a` or `b`, which gives `false`.
+pub fn in_diametral_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    a: Idx,
+    b: Idx,
+    p: Idx,
+) -> bool {
+    // By Thales, p is inside exactly when |a - p|² + |b - p|² < |a - b|²
+    sign_of_squared_distances(2, &[(1, a, p), (1, b, p), (-1, a, b)], |i| index_fn(list, i)) == Ordering::Less
+}
+
+/// Returns whether point `p` is strictly inside the sphere with diameter `ab` after perturbing the points.
+/// The result is never degenerate unless `p` is `a` or `b`, which gives `false`.
+pub fn in_diametral_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    a: Idx,
+    b: Idx,
+    p: Idx,
+) -> bool {
+    sign_of_squared_distances(3, &[(1, a, p), (1, b, p), (-1, a, b)], |i| index_fn(list, i)) == Ordering::Less
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cdt;
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_distance_cmp_consistent() {
+        use std::cmp::Ordering;
+
+        // Lots of equal distances
+        let points = grid_points_2d();
+        let n = points.len();
+        let pairs = (0..n).flat_map(|i| ((i + 1)..n).map(move |j| [i, j])).collect::<Vec<_>>();
+        let cmp = |[i, j]: [usize; 2], [k, l]: [usize; 2]| distance_cmp_2d(&points, |l, i| l[i], i, j, k, l);
+        for e in &pairs {
+            assert_eq!(cmp(*e, *e), Ordering::Equal);
+            assert_eq!(cmp(*e, [e[1], e[0]]), Ordering::Equal);
+            for f in pairs.iter().filter(|f| *f != e) {
+                assert_ne!(cmp(*e, *f), Ordering::Equal);
+                assert_eq!(cmp(*e, *f), cmp(*f, *e).reverse());
+            }
+        }
+
+        // Encroachment is the sign of |a - p|² + |b - p|² - |a - b|²
+        for [a, b] in &pairs {
+            for p in (0..n).filter(|p| p != a && p != b) {
+                let sign = super::sign_of_squared_distances(2, &[(1, *a, p), (1, *b, p), (-1, *a, *b)], |i| points[i]);
+                assert_eq!(cdt::encroaches(&points, |l, i| l[i], *a, *b, p), sign == Ordering::Less);
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Euclidean minimum spanning trees.
+//!
+//! The minimum spanning tree of a point set is a subgraph of its Delaunay triangulation, so
+//! this runs Kruskal's algorithm on the edges of the triangulations from
+//! [`delaunay2`](crate::delaunay2) and [`delaunay3`](crate::delaunay3). Edges are compared with
+//! [`distance`](crate::distance), so lengths never tie and the tree is unique, even on a grid
+//! where most edges have exactly the same length.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, emst};
+//! # use nalgebra::Vector2;
+//! let points = (0..9).map(|i| Vector2::new((i % 3) as f64, (i / 3) as f64)).collect::<Vec<_>>();
+//! let tree = emst::spanning_tree_2d(&points, |l, i| l[i], points.len());
+//! assert_eq!(tree.len(), 8);
+//! // All 12 edges of the grid have length 1, and the perturbation picks 8 of them
+//! assert!(tree.iter().all(|[i, j]| (points[*i] - points[*j]).norm() == 1.0));
+//! ```
+
+use crate::{Vec2, Vec3};
+use core::cmp::Ordering;
+
+/// Finds the root of an element's set, compressing the path to it.
+fn find(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+/// Runs Kruskal's algorithm on the edges, returning the edges of the tree in increasing order of length.
+fn kruskal(len: usize, mut edges: Vec<[usize; 2]>, cmp: impl Fn([usize; 2], [usize; 2]) -> Ordering) -> Vec<[usize; 2]> {
+    edges.sort_unstable();
+    edges.dedup();
+    edges.sort_by(|e, f| cmp(*e, *f));
+
+    let mut parents = (0..len).collect::<Vec<_>>();
+    let mut tree = vec![];
+    for [i, j] in edges {
+        let (ri, rj) = (find(&mut parents, i), find(&mut parents, j));
+        if ri != rj {
+            parents[ri] = rj;
+            tree.push([i, j]);
+        }
+    }
+    tree
+}
+
+/// Computes the Euclidean minimum spanning tree of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the edges of the tree, with the smaller index first, in increasing order of length.
+pub fn spanning_tree_2d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize) -> Vec<[usize; 2]> {
+    if len == 2 {
+        return vec![[0, 1]];
+    }
+    let edges = crate::delaunay2::triangulate(list, &index_fn, len)
+        .into_iter()
+        .flat_map(|t| (0..3).map(move |e| [t[e].min(t[(e + 1) % 3]), t[e].max(t[(e + 1) % 3])]))
+        .collect();
+    kruskal(len, edges, |[i, j], [k, l]| crate::distance::distance_cmp_2d(list, &index_fn, i, j, k, l))
+}
+
+/// Computes the Euclidean minimum spanning tree of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the edges of the tree, with the smaller index first, in increasing order of length.
+pub fn spanning_tree_3d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize) -> Vec<[usize; 2]> {
+    if len == 2 || len == 3 {
+        let edges = if len == 2 { vec![[0, 1]] } else { vec![[0, 1], [0, 2], [1, 2]] };
+        return kruskal(len, edges, |[i, j], [k, l]| crate::distance::distance_cmp_3d(list, &index_fn, i, j, k, l));
+    }
+    let edges = crate::delaunay3::tetrahedralize(list, &index_fn, len)
+        .into_iter()
+        .flat_map(|t| {
+            (0..4).flat_map(move |a| ((a + 1)..4).map(move |b| [t[a].min(t[b]), t[a].max(t[b])]))
+        })
+        .collect();
+    kruskal(len, edges, |[i, j], [k, l]| crate::distance::distance_cmp_3d(list, &index_fn, i, j, k, l))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, distance};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    fn test_emst(seed: u64) {
+        // Kruskal's algorithm on all the edges
+        fn brute_force(len: usize, cmp: impl Fn([usize; 2], [usize; 2]) -> std::cmp::Ordering) -> Vec<[usize; 2]> {
+            let mut edges = (0..len).flat_map(|i| ((i + 1)..len).map(move |j| [i, j])).collect::<Vec<_>>();
+            edges.sort_by(|e, f| cmp(*e, *f));
+            let mut component = (0..len).collect::<Vec<_>>();
+            let mut tree = vec![];
+            for [i, j] in edges {
+                let (ci, cj) = (component[i], component[j]);
+                if ci != cj {
+                    component.iter_mut().filter(|c| **c == ci).for_each(|c| *c = cj);
+                    tree.push([i, j]);
+                }
+            }
+            tree
+        }
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.cocircular_2d(10),
+            _ => {
+                let points = gen.grid_clusters_2d(3, 4);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let tree = super::spanning_tree_2d(&points, |l, i| l[i], points.len());
+        let expected = brute_force(points.len(), |[i, j], [k, l]| distance::distance_cmp_2d(&points, |l, i| l[i], i, j, k, l));
+        assert_eq!(tree, expected);
+
+        let points = match seed {
+            0 => grid_points_3d(),
+            1 => gen.cospherical_3d(10),
+            _ => {
+                let points = gen.grid_clusters_3d(2, 3);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let tree = super::spanning_tree_3d(&points, |l, i| l[i], points.len());
+        let expected = brute_force(points.len(), |[i, j], [k, l]| distance::distance_cmp_3d(&points, |l, i| l[i], i, j, k, l));
+        assert_eq!(tree, expected);
+    }
+}
@@ -0,0 +1,150 @@
+//! Minimum enclosing circles with Welzl's algorithm, and the predicates it needs.
+//!
+//! Welzl's algorithm keeps the circle determined by 2 or 3 *support points* on its boundary,
+//! and replaces it whenever a point is outside. With floating-point tests, a point that's
+//! on the circle can be found outside of it over and over, and with 3 collinear support points,
+//! there's no circle at all. After perturbing the points, neither can happen: only
+//! the support points are on the circle, and no 3 points are collinear, so the algorithm
+//! always finishes with the minimum enclosing circle of the perturbed points.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, enclosing};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(1.0, -1.0),
+//!     Vector2::new(1.0, 0.5),
+//! ];
+//! // Points 0, 1, 2, and 3 are all on the same circle, so the perturbation picks the support
+//! let support = enclosing::min_enclosing_circle(&points, |l, i| l[i], &[0, 1, 2, 3, 4]);
+//! assert!(support.len() >= 2);
+//! assert!(!support.contains(&4));
+//! ```
+
+use crate::Vec2;
+use alloc::{vec, vec::Vec};
+
+/// Returns whether `p` is inside the smallest circle through `a` and `b`, which has
+/// its center halfway between them, after perturbing the points.
+/// This is [`cdt::encroaches`](crate::cdt::encroaches).
+pub fn in_circle_2<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, a: Idx, b: Idx, p: Idx) -> bool {
+    crate::cdt::encroaches(list, index_fn, a, b, p)
+}
+
+/// Returns whether `p` is inside the circle through `a`, `b`, and `c`, in any order,
+/// after perturbing the points. This is [`in_circle_unoriented`](crate::in_circle_unoriented),
+/// so it's decided even if the 3 points are collinear.
+pub fn in_circle_3<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    p: Idx,
+) -> bool {
+    crate::in_circle_unoriented(list, index_fn, a, b, c, p)
+}
+
+/// Returns whether `p` is inside or on the circle determined by `support` after perturbing the points.
+/// A single support point determines a circle with radius 0.
+fn encloses<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2 + Clone, support: &[Idx], p: Idx) -> bool {
+    if support.contains(&p) {
+        return true;
+    }
+    match *support {
+        [a, b] => in_circle_2(list, index_fn, a, b, p),
+        [a, b, c] => in_circle_3(list, index_fn, a, b, c, p),
+        _ => false,
+    }
+}
+
+/// Computes the minimum enclosing circle of the points at `indexes` after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the indexes of the points
+/// to enclose, which should be distinct. Returns the indexes of the 1 to 3 support points
+/// on the circle, or none if there are no points. Every other point is strictly inside
+/// the circle through them, which is the one with its center halfway between them if there are 2.
+///
+/// This is the iterative form of Welzl's algorithm, which takes expected linear time if
+/// `indexes` is in random order. Shuffle them first if they might be sorted.
+pub fn min_enclosing_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    indexes: &[Idx],
+) -> Vec<Idx> {
+    let encloses = |support: &[Idx], p| encloses(list, index_fn.clone(), support, p);
+
+    let mut support = indexes.iter().take(1).copied().collect::<Vec<_>>();
+    for (n, i) in indexes.iter().copied().enumerate() {
+        if encloses(&support, i) {
+            continue;
+        }
+        // i is on the circle of the first n + 1 points
+        support = vec![i];
+        for (m, j) in indexes[..n].iter().copied().enumerate() {
+            if encloses(&support, j) {
+                continue;
+            }
+            // And so is j, for the first n + 1 points with the first m + 1 of them
+            support = vec![i, j];
+            for k in indexes[..m].iter().copied() {
+                if !encloses(&support, k) {
+                    support = vec![i, j, k];
+                }
+            }
+        }
+    }
+    support
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, rng};
+    use crate::tests::grid_points_2d;
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    fn test_min_enclosing_circle(seed: u64) {
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.collinear_2d(8),
+            2 => gen.cocircular_2d(8),
+            _ => {
+                let points = gen.cocircular_2d(8);
+                gen.with_duplicates(points, 4)
+            }
+        };
+        let mut rng = rng::SplitMix(seed);
+        let mut indexes = (0..points.len()).collect::<Vec<_>>();
+        for _ in 0..10 {
+            for i in (1..indexes.len()).rev() {
+                indexes.swap(i, rng.below(i + 1));
+            }
+            let support = super::min_enclosing_circle(&points, |l, i| l[i], &indexes);
+            assert!(support.len() == 2 || support.len() == 3);
+            for p in indexes.iter().filter(|p| !support.contains(p)) {
+                let inside = match support[..] {
+                    [a, b] => super::in_circle_2(&points, |l, i| l[i], a, b, *p),
+                    [a, b, c] => super::in_circle_3(&points, |l, i| l[i], a, b, c, *p),
+                    _ => unreachable!(),
+                };
+                assert!(inside);
+            }
+            // No smaller circle through 2 of 3 support points encloses the third
+            if let [a, b, c] = support[..] {
+                assert!(!super::in_circle_2(&points, |l, i| l[i], a, b, c));
+                assert!(!super::in_circle_2(&points, |l, i| l[i], b, c, a));
+                assert!(!super::in_circle_2(&points, |l, i| l[i], c, a, b));
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! The error type of the fallible predicates.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+/// The fallible predicate that returned an error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PredicateKind {
+    /// [`orient_1d_strict`](crate::orient_1d_strict) and the other `orient_1d` variants
+    Orient1d,
+    /// [`orient_2d_strict`](crate::orient_2d_strict) and the other `orient_2d` variants
+    Orient2d,
+    /// [`orient_3d_strict`](crate::orient_3d_strict) and the other `orient_3d` variants
+    Orient3d,
+    /// [`in_circle_strict`](crate::in_circle_strict) and the other `in_circle` variants
+    InCircle,
+    /// [`in_sphere_strict`](crate::in_sphere_strict) and the other `in_sphere` variants
+    InSphere,
+    /// [`weighted::in_power_circle`](crate::weighted::in_power_circle)
+    InPowerCircle,
+    /// [`weighted::in_power_sphere`](crate::weighted::in_power_sphere)
+    InPowerSphere,
+}
+
+impl PredicateKind {
+    /// The name of the predicate's function.
+    pub fn name(self) -> &'static str {
+        match self {
+            PredicateKind::Orient1d => "orient_1d",
+            PredicateKind::Orient2d => "orient_2d",
+            PredicateKind::Orient3d => "orient_3d",
+            PredicateKind::InCircle => "in_circle",
+            PredicateKind::InSphere => "in_sphere",
+            PredicateKind::InPowerCircle => "in_power_circle",
+            PredicateKind::InPowerSphere => "in_power_sphere",
+        }
+    }
+}
+
+/// Why a fallible predicate failed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorReason {
+    /// The unperturbed determinant is exactly 0, so the result would depend on the perturbation
+    Degenerate,
+    /// A point has an infinite or NaN coordinate
+    NonFinite,
+    /// The same index was passed more than once
+    DuplicateIndex,
+}
+
+impl Display for ErrorReason {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ErrorReason::Degenerate => write!(f, "the points are degenerate"),
+            ErrorReason::NonFinite => write!(f, "a coordinate is not finite"),
+            ErrorReason::DuplicateIndex => write!(f, "an index is repeated"),
+        }
+    }
+}
+
+/// The error returned by the fallible predicates, such as the `_strict` ones.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d_strict, ErrorReason, PredicateKind, SimplicityError};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(f64::NAN, 0.0),
+///     Vector2::new(1.0, 1.0),
+/// ];
+/// let err = orient_2d_strict(&points, |l, i| l[i], 0, 1, 2).unwrap_err();
+/// assert_eq!(err, SimplicityError { predicate: PredicateKind::Orient2d, indexes: vec![1], reason: ErrorReason::NonFinite });
+/// assert_eq!(err.to_string(), "orient_2d on [1]: a coordinate is not finite");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SimplicityError<Idx = usize> {
+    /// The predicate that failed
+    pub predicate: PredicateKind,
+    /// The indexes responsible for the failure.
+    /// For degenerate points, these are all the indexes passed.
+    pub indexes: Vec<Idx>,
+    /// Why the predicate failed
+    pub reason: ErrorReason,
+}
+
+impl<Idx> SimplicityError<Idx> {
+    /// Whether the predicate failed because the points are degenerate.
+    pub fn is_degenerate(&self) -> bool {
+        self.reason == ErrorReason::Degenerate
+    }
+}
+
+impl<Idx: Debug> Display for SimplicityError<Idx> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} on {:?}: {}", self.predicate.name(), self.indexes, self.reason)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Idx: Debug> std::error::Error for SimplicityError<Idx> {}
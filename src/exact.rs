@@ -0,0 +1,389 @@
+//! Exact arithmetic on floating-point expansions,
+//! following [Shewchuk's robust predicates paper](https://people.eecs.berkeley.edu/~jrs/papers/robustr.pdf).
+//!
+//! An expansion is a sum of nonoverlapping `f64` components
+//! sorted by increasing magnitude, so its sign is the sign of
+//! its largest component. Sums, differences, and products of
+//! expansions are computed without any rounding error.
+//!
+//! This is the backend of the cascades generated by
+//! `simplicity_derive::generate_sos_predicate`.
+
+use crate::scale::pow2;
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Mul, Neg, Sub};
+
+/// 2^ceil(53 / 2) + 1, used to split a double into two halves.
+const SPLITTER: f64 = 134217729.0;
+
+/// Returns `(x, y)` such that `x + y = a + b` exactly
+/// and `x` is `a + b` rounded.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virt = x - a;
+    let a_virt = x - b_virt;
+    let b_round = b - b_virt;
+    let a_round = a - a_virt;
+    (x, a_round + b_round)
+}
+
+/// Splits `a` into two halves with at most 26 significant bits each.
+fn split(a: f64) -> (f64, f64) {
+    let c = SPLITTER * a;
+    let big = c - a;
+    let hi = c - big;
+    (hi, a - hi)
+}
+
+/// Returns `(x, y)` such that `x + y = a * b` exactly
+/// and `x` is `a * b` rounded.
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    let (a_hi, a_lo) = split(a);
+    let (b_hi, b_lo) = split(b);
+    let err1 = x - a_hi * b_hi;
+    let err2 = err1 - a_lo * b_hi;
+    let err3 = err2 - a_hi * b_lo;
+    (x, a_lo * b_lo - err3)
+}
+
+/// An exact sum of nonoverlapping doubles, in increasing order of magnitude.
+/// Zero components are never stored, so zero is the empty expansion.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Expansion(Vec<f64>);
+
+impl Expansion {
+    /// The zero expansion.
+    pub fn zero() -> Self {
+        Self(vec![])
+    }
+
+    /// The exact product of a coefficient and some factors.
+    pub fn product(coeff: f64, factors: &[f64]) -> Self {
+        factors
+            .iter()
+            .fold(Self::from(coeff), |acc, f| acc.scale(*f))
+    }
+
+    /// The components, in increasing order of magnitude.
+    pub fn components(&self) -> &[f64] {
+        &self.0
+    }
+
+    /// Adds a single double to this expansion.
+    fn grow(&self, b: f64) -> Self {
+        let mut res = Vec::with_capacity(self.0.len() + 1);
+        let mut q = b;
+        for e in &self.0 {
+            let (sum, err) = two_sum(q, *e);
+            if err != 0.0 {
+                res.push(err);
+            }
+            q = sum;
+        }
+        if q != 0.0 {
+            res.push(q);
+        }
+        Self(res)
+    }
+
+    /// Multiplies this expansion by a single double.
+    pub fn scale(&self, b: f64) -> Self {
+        let mut res = Vec::with_capacity(2 * self.0.len());
+        let mut iter = self.0.iter();
+        let mut q = match iter.next() {
+            Some(e) => {
+                let (prod, err) = two_product(*e, b);
+                if err != 0.0 {
+                    res.push(err);
+                }
+                prod
+            }
+            None => return Self::zero(),
+        };
+
+        for e in iter {
+            let (prod_hi, prod_lo) = two_product(*e, b);
+            let (sum, err) = two_sum(q, prod_lo);
+            if err != 0.0 {
+                res.push(err);
+            }
+            let (sum, err) = two_sum(prod_hi, sum);
+            if err != 0.0 {
+                res.push(err);
+            }
+            q = sum;
+        }
+        if q != 0.0 {
+            res.push(q);
+        }
+        Self(res)
+    }
+
+    /// Returns 1.0, -1.0, or 0.0 depending on the sign of the expansion.
+    pub fn signum(&self) -> f64 {
+        match self.0.last() {
+            Some(e) if *e > 0.0 => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+
+    /// An approximation of the value of the expansion.
+    pub fn estimate(&self) -> f64 {
+        self.0.iter().sum()
+    }
+}
+
+impl From<f64> for Expansion {
+    fn from(value: f64) -> Self {
+        if value == 0.0 {
+            Self::zero()
+        } else {
+            Self(vec![value])
+        }
+    }
+}
+
+impl Add for Expansion {
+    type Output = Expansion;
+
+    fn add(self, rhs: Expansion) -> Expansion {
+        let (small, big) = if self.0.len() < rhs.0.len() { (self, rhs) } else { (rhs, self) };
+        small.0.iter().fold(big, |acc, e| acc.grow(*e))
+    }
+}
+
+impl Neg for Expansion {
+    type Output = Expansion;
+
+    fn neg(self) -> Expansion {
+        Self(self.0.into_iter().map(|e| -e).collect())
+    }
+}
+
+impl Sub for Expansion {
+    type Output = Expansion;
+
+    fn sub(self, rhs: Expansion) -> Expansion {
+        self + -rhs
+    }
+}
+
+impl Mul for Expansion {
+    type Output = Expansion;
+
+    fn mul(self, rhs: Expansion) -> Expansion {
+        let (small, big) = if self.0.len() < rhs.0.len() { (self, rhs) } else { (rhs, self) };
+        small.0.iter().map(|e| big.scale(*e)).fold(Expansion::zero(), Add::add)
+    }
+}
+
+/// Calculates the determinant of a square matrix of expansions exactly.
+///
+/// Uses Laplace expansion along the rows, memoizing the minors
+/// of the bottom rows by their column set.
+pub fn det(matrix: Vec<Vec<Expansion>>) -> Expansion {
+    let n = matrix.len();
+    if n == 0 {
+        return Expansion::from(1.0);
+    }
+
+    // minors[cols] = determinant of the bottom |cols| rows restricted to columns `cols`
+    let mut minors = vec![Expansion::zero(); 1 << n];
+    minors[0] = Expansion::from(1.0);
+
+    for cols in 1usize..1 << n {
+        let size = cols.count_ones() as usize;
+        let row = &matrix[n - size];
+        let mut sum = Expansion::zero();
+        for (pos, col) in (0..n).filter(|c| cols & (1 << c) != 0).enumerate() {
+            if row[col].0.is_empty() {
+                continue;
+            }
+            let term = row[col].clone() * minors[cols & !(1 << col)].clone();
+            sum = if pos % 2 == 0 { sum + term } else { sum - term };
+        }
+        minors[cols] = sum;
+    }
+
+    minors.pop().unwrap()
+}
+
+/// Calculates the determinant of a square matrix exactly,
+/// given as rows of indexes into a table of entries.
+pub fn det_from_table(entries: &[Expansion], rows: &[&[u16]]) -> Expansion {
+    det(rows.iter().map(|row| row.iter().map(|e| entries[*e as usize].clone()).collect()).collect())
+}
+
+/// Whether the coordinates are 0 or within 2^(±900 / `degree`),
+/// so terms of at most that degree in them don't underflow or overflow.
+#[inline]
+fn in_filter_range(coords: &[f64], degree: u32) -> bool {
+    let bound = 900 / degree.max(1) as i32;
+    let (min, max) = (pow2(-bound), pow2(bound));
+    coords.iter().all(|c| *c == 0.0 || (min..=max).contains(&c.abs()))
+}
+
+/// The largest matrix [`filtered_det_signum`] handles.
+const MAX_FILTERED: usize = 8;
+
+/// The sign of the determinant of a square matrix whose entries are polynomials in some coordinates,
+/// as -1, 0, or 1, from their values in floating point, or 0 if that can't be sure of it.
+///
+/// `entries` are the rows of the matrix, each entry with the sum of the absolute values of its terms.
+/// Each term has degree at most `degree` in `coords` and takes at most `entry_ops` roundings to sum up.
+/// It gives up unless the coordinates are 0 or within 2^(±900 / `degree`), so that the terms
+/// don't underflow or overflow and the rounding errors are bounded relative to the permanent of those sums.
+/// This is how the generated cascades avoid the exact arithmetic in the common case.
+pub fn filtered_det_signum(coords: &[f64], entries: &[(f64, f64)], degree: u32, entry_ops: u32) -> f64 {
+    let n = (1..=MAX_FILTERED).find(|n| n * n == entries.len());
+    let n = match n {
+        Some(n) => n,
+        None => return 0.0,
+    };
+    if !in_filter_range(coords, degree) {
+        return 0.0;
+    }
+    // Keeps the products of n sums finite
+    let max = entries.iter().fold(0.0, |max: f64, (_, m)| max.max(*m));
+    if max > pow2(100) || !entries.iter().all(|(v, m)| v.is_finite() && m.is_finite()) {
+        return 0.0;
+    }
+
+    // Like `det`: the minors of the bottom rows by their column set, and their permanents
+    let mut minors = [(0.0, 0.0); 1 << MAX_FILTERED];
+    minors[0] = (1.0, 1.0);
+    for cols in 1usize..1 << n {
+        let row = &entries[(n - cols.count_ones() as usize) * n..];
+        let (mut det, mut perm) = (0.0, 0.0);
+        for (pos, col) in (0..n).filter(|c| cols & (1 << c) != 0).enumerate() {
+            let (minor, minor_perm) = minors[cols & !(1 << col)];
+            let term = row[col].0 * minor;
+            det = if pos % 2 == 0 { det + term } else { det - term };
+            perm += row[col].1 * minor_perm;
+        }
+        minors[cols] = (det, perm);
+    }
+    let (det, perm) = minors[(1 << n) - 1];
+
+    // Each entry is off by at most entry_ops roundings, and each product in the expansion
+    // takes at most n(n + 1) / 2 more. f64::EPSILON is twice the unit roundoff,
+    // which makes room for the higher order terms and for rounding the bound itself.
+    let ops = (n as u32 * entry_ops + (n * (n + 1) / 2) as u32) as f64;
+    let relative = ops * f64::EPSILON * perm;
+    // A product in the expansion that underflows loses less than the smallest normal double,
+    // which is bounded with normal doubles since subnormal arithmetic is slow,
+    // and the rows above can multiply that by at most max^n
+    let absolute = (n << n) as f64 * f64::MIN_POSITIVE * max.max(1.0).powi(n as i32);
+    if det.abs() > relative + absolute {
+        det.signum()
+    } else {
+        0.0
+    }
+}
+
+/// Calculates the cofactors of the last row of a square matrix
+/// given all the other rows, so that the determinant of the matrix
+/// is the dot product of the last row and the cofactors.
+pub fn last_row_cofactors(rows: &[Vec<Expansion>]) -> Vec<Expansion> {
+    let n = rows.len() + 1;
+    (0..n).map(|col| {
+        let minor = rows.iter()
+            .map(|row| row.iter().enumerate().filter(|(c, _)| *c != col).map(|(_, e)| e.clone()).collect())
+            .collect();
+        let minor = det(minor);
+        if (n - 1 + col) % 2 == 1 { -minor } else { minor }
+    }).collect()
+}
+
+/// Rounds each expansion to a double, along with a bound on the rounding error,
+/// for [`filtered_dot_signum`].
+pub fn estimates(values: &[Expansion]) -> Vec<(f64, f64)> {
+    // The components don't overlap, so summing them is off by at most one rounding per component
+    values.iter().map(|v| {
+        let estimate = v.estimate();
+        (estimate, estimate.abs() * v.0.len() as f64 * f64::EPSILON)
+    }).collect()
+}
+
+/// The sign of the dot product of some exact values and some polynomials in some coordinates,
+/// as -1, 0, or 1, from their values in floating point, or 0 if that can't be sure of it.
+///
+/// `values` are the exact values rounded by [`estimates`]. `entries` are the polynomials
+/// in the same format as for [`filtered_det_signum`], which gives up in the same cases.
+#[inline]
+pub fn filtered_dot_signum(values: &[(f64, f64)], coords: &[f64], entries: &[(f64, f64)], degree: u32, entry_ops: u32) -> f64 {
+    if !in_filter_range(coords, degree) {
+        return 0.0;
+    }
+    // The dot product rounds each term at most n times more than the entries,
+    // and each product that underflows loses less than the smallest normal double
+    let n = values.len() as f64;
+    let relative = (entry_ops as f64 + n) * f64::EPSILON;
+    let (mut dot, mut error) = (0.0, n * f64::MIN_POSITIVE);
+    for ((value, value_error), (entry, magnitude)) in values.iter().zip(entries) {
+        dot += value * entry;
+        error += (value.abs() * relative + value_error) * magnitude;
+    }
+    if dot.is_finite() && error.is_finite() && dot.abs() > error {
+        dot.signum()
+    } else {
+        0.0
+    }
+}
+
+/// Calculates the dot product of two vectors of expansions exactly.
+pub fn dot(a: &[Expansion], b: Vec<Expansion>) -> Expansion {
+    a.iter().zip(b).map(|(a, b)| a.clone() * b).fold(Expansion::zero(), Add::add)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_is_exact() {
+        let sum = Expansion::from(1e300) + Expansion::from(1.0) - Expansion::from(1e300);
+        assert_eq!(sum, Expansion::from(1.0));
+    }
+
+    #[test]
+    fn test_product_is_exact() {
+        let a = 1.0 + f64::EPSILON;
+        // (1 + e)² - 1 - 2e = e², which rounds away in plain doubles
+        let val = Expansion::product(1.0, &[a, a]) - Expansion::from(1.0) - Expansion::from(2.0 * f64::EPSILON);
+        assert_eq!(val.signum(), 1.0);
+        assert_eq!(val.estimate(), f64::EPSILON * f64::EPSILON);
+    }
+
+    #[test]
+    fn test_det() {
+        let matrix = [[2.0, 0.0, 1.0], [1.0, 3.0, 1.0], [0.0, 1.0, 1.0]];
+        let matrix = matrix.iter().map(|row| row.iter().map(|e| Expansion::from(*e)).collect()).collect();
+        assert_eq!(det(matrix).estimate(), 5.0);
+    }
+
+    #[test]
+    fn test_filtered_det_signum() {
+        let entries = |matrix: [[f64; 3]; 3]| matrix.iter().flatten().map(|e| (*e, e.abs())).collect::<Vec<_>>();
+        let matrix = [[2.0, 0.0, 1.0], [1.0, 3.0, 1.0], [0.0, 1.0, 1.0]];
+        assert_eq!(filtered_det_signum(&[2.0, 3.0], &entries(matrix), 1, 0), 1.0);
+
+        // Nearly collinear, so rounding could flip the sign; the filter has to give up
+        let a = 1.0 + f64::EPSILON;
+        let matrix = [[0.0, 0.0, 1.0], [a, a, 1.0], [3.0, 3.0 * a, 1.0]];
+        assert_eq!(filtered_det_signum(&[a, 3.0], &entries(matrix), 1, 0), 0.0);
+        assert_eq!(filtered_det_signum(&[1e-300], &entries([[2.0, 0.0, 1.0], [1.0, 3.0, 1.0], [0.0, 1.0, 1.0]]), 1, 0), 0.0);
+    }
+
+    #[test]
+    fn test_filtered_dot_signum() {
+        // 1e300 + 1 - 1e300, where the 1 rounds away in doubles
+        let values = estimates(&[Expansion::from(1e300) + Expansion::from(1.0), Expansion::from(1.0)]);
+        let entries = [(1.0, 1.0), (-1e300, 1e300)];
+        assert_eq!(filtered_dot_signum(&values, &[1.0], &entries, 1, 0), 0.0);
+        let entries = [(1.0, 1.0), (-1e299, 1e299)];
+        assert_eq!(filtered_dot_signum(&values, &[1.0], &entries, 1, 0), 1.0);
+    }
+}
@@ -0,0 +1,292 @@
+//! Exact signs of arbitrary polynomials in the coordinates of points.
+//!
+//! Many one-off predicates are just the sign of a small polynomial. An [`Expression`] describes one
+//! as a DAG of sums and products of coordinates and constants, built up node by node, so shared
+//! subexpressions are only written and evaluated once. Its sign can then be evaluated exactly
+//! with [`Expression::signum`], which is 0 when the polynomial is, or with the points perturbed
+//! the same way as in the rest of the crate with [`Expression::perturbed_signum`],
+//! which is only 0 when the polynomial is identically 0 after perturbing.
+//!
+//! Constants are never perturbed. The coordinates of the points are perturbed by the rank of
+//! their index among the points in the expression, and a coordinate's perturbation is the same
+//! no matter which nodes it appears in.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, expression::Expression};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 2.0),
+//! ];
+//! // The 2D orientation of points 0, 1, and 2
+//! let mut expr = Expression::new();
+//! let [dx1, dy1, dx2, dy2] = [(1, 0), (1, 1), (2, 0), (2, 1)].map(|(i, c)| {
+//!     let (x, x0) = (expr.coord(i, c), expr.coord(0, c));
+//!     expr.sub(x, x0)
+//! });
+//! let (left, right) = (expr.mul(dx1, dy2), expr.mul(dy1, dx2));
+//! let orient = expr.sub(left, right);
+//! // The points are collinear, but the perturbation decides
+//! assert_eq!(expr.signum(orient, &points, |l, i| l[i]), 0.0);
+//! let sign = expr.perturbed_signum(orient, &points, |l, i| l[i]);
+//! assert_eq!(sign > 0.0, simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{Perturbed, Polynomial};
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Index, Mul, Neg, Sub};
+
+/// A handle to a node of an [`Expression`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Node(usize);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Op<Idx> {
+    Constant(f64),
+    Coord(Idx, usize),
+    Add(Node, Node),
+    Sub(Node, Node),
+    Mul(Node, Node),
+    Neg(Node),
+}
+
+/// A polynomial in the coordinates of points, as a DAG of operations.
+/// Each node can only refer to nodes made before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression<Idx> {
+    ops: Vec<Op<Idx>>,
+    /// A bound on the degree of each node in any one coordinate
+    degrees: Vec<u64>,
+}
+
+impl<Idx> Default for Expression<Idx> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Idx> Expression<Idx> {
+    /// Makes an expression with no nodes.
+    pub fn new() -> Self {
+        Self { ops: vec![], degrees: vec![] }
+    }
+
+    fn push(&mut self, op: Op<Idx>, degree: u64) -> Node {
+        self.ops.push(op);
+        self.degrees.push(degree);
+        Node(self.ops.len() - 1)
+    }
+
+    fn degree(&self, node: Node) -> u64 {
+        *self.degrees.get(node.0).expect("node from another expression")
+    }
+
+    /// Adds a constant, which isn't perturbed.
+    pub fn constant(&mut self, value: f64) -> Node {
+        self.push(Op::Constant(value), 0)
+    }
+
+    /// Adds coordinate `c` of the point with index `point`.
+    pub fn coord(&mut self, point: Idx, c: usize) -> Node {
+        self.push(Op::Coord(point, c), 1)
+    }
+
+    /// Adds the sum of 2 nodes.
+    pub fn add(&mut self, a: Node, b: Node) -> Node {
+        let degree = self.degree(a).max(self.degree(b));
+        self.push(Op::Add(a, b), degree)
+    }
+
+    /// Adds the difference of 2 nodes.
+    pub fn sub(&mut self, a: Node, b: Node) -> Node {
+        let degree = self.degree(a).max(self.degree(b));
+        self.push(Op::Sub(a, b), degree)
+    }
+
+    /// Adds the product of 2 nodes.
+    pub fn mul(&mut self, a: Node, b: Node) -> Node {
+        let degree = self.degree(a) + self.degree(b);
+        self.push(Op::Mul(a, b), degree)
+    }
+
+    /// Adds the negation of a node.
+    pub fn neg(&mut self, a: Node) -> Node {
+        let degree = self.degree(a);
+        self.push(Op::Neg(a), degree)
+    }
+
+    /// Which nodes `root` depends on, including itself.
+    fn dependencies(&self, root: Node) -> Vec<bool> {
+        let mut needed = vec![false; root.0 + 1];
+        needed[root.0] = true;
+        for n in (0..=root.0).rev() {
+            if !needed[n] {
+                continue;
+            }
+            match self.ops[n] {
+                Op::Add(a, b) | Op::Sub(a, b) | Op::Mul(a, b) => {
+                    needed[a.0] = true;
+                    needed[b.0] = true;
+                }
+                Op::Neg(a) => needed[a.0] = true,
+                Op::Constant(_) | Op::Coord(..) => {}
+            }
+        }
+        needed
+    }
+
+    /// Evaluates the nodes `root` depends on, given the value of a constant and of a coordinate.
+    fn evaluate<V: Clone + Add<Output = V> + Sub<Output = V> + Mul<Output = V> + Neg<Output = V>>(
+        &self,
+        root: Node,
+        constant: impl Fn(f64) -> V,
+        coord: impl Fn(&Idx, usize) -> V,
+    ) -> V {
+        let needed = self.dependencies(root);
+        let mut values: Vec<Option<V>> = Vec::with_capacity(root.0 + 1);
+        for (op, needed) in self.ops.iter().zip(needed) {
+            let value = |node: &Node| values[node.0].clone().unwrap();
+            let value = needed.then(|| match op {
+                Op::Constant(x) => constant(*x),
+                Op::Coord(i, c) => coord(i, *c),
+                Op::Add(a, b) => value(a) + value(b),
+                Op::Sub(a, b) => value(a) - value(b),
+                Op::Mul(a, b) => value(a) * value(b),
+                Op::Neg(a) => -value(a),
+            });
+            values.push(value);
+        }
+        values.pop().unwrap().unwrap()
+    }
+}
+
+impl<Idx: Ord + Copy> Expression<Idx> {
+    /// The sign of node `root` as -1, 0, or 1, evaluated exactly without perturbing the points.
+    ///
+    /// Takes the node, a list of all the points, and an indexing function.
+    pub fn signum<T: ?Sized, P: Index<usize, Output = f64>>(&self, root: Node, list: &T, index_fn: impl Fn(&T, Idx) -> P) -> f64 {
+        self.evaluate(root, Expansion::from, |i, c| Expansion::from(index_fn(list, *i)[c])).signum()
+    }
+
+    /// The sign of node `root` as -1, 0, or 1, after perturbing the points.
+    /// Returns 0 only if the polynomial is identically 0, like `x - x`.
+    ///
+    /// Takes the node, a list of all the points, and an indexing function.
+    /// The points have as many dimensions as the largest coordinate in the expression.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomial is 0 without perturbing the points, and it has too high a degree
+    /// in too many coordinates to expand in the perturbations. Polynomials of degree up to 24
+    /// in up to 6 points in 3 dimensions are always fine.
+    pub fn perturbed_signum<T: ?Sized, P: Index<usize, Output = f64>>(
+        &self,
+        root: Node,
+        list: &T,
+        index_fn: impl Fn(&T, Idx) -> P,
+    ) -> f64 {
+        let sign = self.signum(root, list, &index_fn);
+        if sign != 0.0 {
+            return sign;
+        }
+
+        let needed = self.dependencies(root);
+        let coords = self.ops.iter().zip(needed).filter_map(|(op, needed)| match op {
+            Op::Coord(i, c) if needed => Some((*i, *c)),
+            _ => None,
+        });
+        let (mut indexes, mut dim) = (vec![], 0);
+        for (i, c) in coords {
+            indexes.push(i);
+            dim = dim.max(c + 1);
+        }
+        indexes.sort_unstable();
+        indexes.dedup();
+        let degree = self.degree(root);
+        assert!(
+            (degree as u128 + 1).checked_pow((dim * indexes.len()) as u32).is_some(),
+            "the polynomial's degree of {} is too high to perturb {} coordinates",
+            degree,
+            dim * indexes.len()
+        );
+
+        let mut perturbed = Perturbed::new(dim, degree, &indexes, |i| index_fn(list, i));
+        perturbed.lazy_signum(|perturbed| {
+            self.evaluate(root, |value| Polynomial::from(Expansion::from(value)), |i, c| perturbed.coord(*i, c))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, rg, rng};
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_expression() {
+        use super::Expression;
+
+        // The in-circle determinant, translated so the last point is at the origin
+        let in_circle_expr = |[a, b, c, d]: [usize; 4]| {
+            let mut expr = Expression::new();
+            let rows = [a, b, c].map(|i| {
+                let [x, y] = [0, 1].map(|k| {
+                    let (p, q) = (expr.coord(i, k), expr.coord(d, k));
+                    expr.sub(p, q)
+                });
+                let (xx, yy) = (expr.mul(x, x), expr.mul(y, y));
+                [x, y, expr.add(xx, yy)]
+            });
+            let mut det = expr.constant(0.0);
+            for n in 0..3 {
+                let [r0, r1, r2] = [n, (n + 1) % 3, (n + 2) % 3].map(|r| rows[r]);
+                let minor = [expr.mul(r1[1], r2[2]), expr.mul(r1[2], r2[1])];
+                let minor = expr.sub(minor[0], minor[1]);
+                let term = expr.mul(r0[0], minor);
+                det = expr.add(det, term);
+            }
+            (expr, det)
+        };
+
+        let points = grid_points_2d();
+        let mut rng = rng::SplitMix(1498);
+        let mut ties = 0;
+        for _ in 0..300 {
+            let indexes = [(); 4].map(|_| rng.below(points.len()));
+            if indexes.iter().enumerate().any(|(n, i)| indexes[..n].contains(i)) {
+                continue;
+            }
+            let (expr, det) = in_circle_expr(indexes);
+            let [a, b, c, d] = indexes;
+            let unperturbed = expr.signum(det, &points, |l, i| l[i]);
+            let exact = rg::in_circle(points[a], points[b], points[c], points[d]);
+            assert_eq!(unperturbed, if exact == 0.0 { 0.0 } else { exact.signum() });
+            ties += (unperturbed == 0.0) as usize;
+            let sign = expr.perturbed_signum(det, &points, |l, i| l[i]);
+            assert_eq!(sign > 0.0, in_circle(&points, |l, i| l[i], a, b, c, d), "{:?}", indexes);
+        }
+        assert!(ties > 0);
+
+        // Constants aren't perturbed, identically 0 polynomials stay 0,
+        // and nodes the root doesn't depend on don't matter
+        let mut expr = Expression::new();
+        let x = expr.coord(0, 0);
+        let zero = expr.sub(x, x);
+        let mut huge = x;
+        for _ in 0..40 {
+            huge = expr.mul(huge, huge);
+        }
+        let one = expr.constant(1.0);
+        let neg = expr.neg(one);
+        let sum = expr.add(zero, neg);
+        assert_eq!(expr.perturbed_signum(zero, &points, |l, i| l[i]), 0.0);
+        assert_eq!(expr.perturbed_signum(sum, &points, |l, i| l[i]), -1.0);
+        let y = expr.coord(1, 1);
+        let diff = expr.sub(x, y);
+        assert_eq!(expr.perturbed_signum(diff, &points, |l, i| l[i]), 1.0);
+    }
+}
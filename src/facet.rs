@@ -0,0 +1,145 @@
+//! Facets of 3D convex hulls, and the points that can see them.
+//!
+//! An incremental hull algorithm adds a point by removing the faces it can see, which is just
+//! [`orient_3d`](crate::orient_3d), but the sign is easy to get backwards. Here, a facet's vertices
+//! go counterclockwise when seen from outside the hull, so the hull is on the side where
+//! `orient_3d` of the vertices and a point is `true`, and a point sees the facet when it's `false`.
+//! [`Facet`] keeps that convention in one place. Because the points are perturbed,
+//! no point is on the plane of a facet, so every point either sees it or doesn't,
+//! and exactly one of a facet and its [flip](Facet::flipped) is visible from a point.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, facet::{self, Facet}};
+//! # use nalgebra::Vector3;
+//! let points = vec![
+//!     Vector3::new(0.0, 0.0, 0.0),
+//!     Vector3::new(1.0, 0.0, 0.0),
+//!     Vector3::new(0.0, 1.0, 0.0),
+//!     Vector3::new(0.0, 0.0, 1.0),
+//!     Vector3::new(1.0, 1.0, 0.0),
+//! ];
+//! // Counterclockwise seen from above
+//! assert!(facet::above_plane(&points, |l, i| l[i], 0, 1, 2, 3));
+//! // The bottom of a tetrahedron, facing away from its top
+//! let bottom = Facet::oriented(&points, |l, i| l[i], [0, 1, 2], 3);
+//! assert_eq!(bottom, Facet::new([0, 2, 1]));
+//! assert!(!bottom.visible_from(&points, |l, i| l[i], 3));
+//! // Coplanar, so the perturbation decides, but it's visible from one side only
+//! assert_ne!(
+//!     bottom.visible_from(&points, |l, i| l[i], 4),
+//!     bottom.flipped().visible_from(&points, |l, i| l[i], 4),
+//! );
+//! ```
+
+use crate::Vec3;
+
+/// Returns whether point `m` is above the plane through points `i`, `j`, and `k`
+/// after perturbing them, where above is the side from which `i`, `j`, and `k` go counterclockwise.
+///
+/// This is [`orient_3d`](crate::orient_3d) of `i, j, k, m`, negated.
+pub fn above_plane<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    m: Idx,
+) -> bool {
+    !crate::orient_3d(list, index_fn, i, j, k, m)
+}
+
+/// A triangular face of a 3D convex hull, with vertices that go counterclockwise when seen from outside.
+///
+/// The vertices are rotated so the smallest comes first, which keeps the orientation,
+/// so facets are equal when they have the same vertices in the same cyclic order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Facet<Idx> {
+    vertices: [Idx; 3],
+}
+
+impl<Idx: Ord + Copy> Facet<Idx> {
+    /// Makes a facet from vertices that go counterclockwise when seen from outside.
+    pub fn new(vertices: [Idx; 3]) -> Self {
+        let [i, j, k] = vertices;
+        let vertices = if i < j && i < k {
+            [i, j, k]
+        } else if j < k {
+            [j, k, i]
+        } else {
+            [k, i, j]
+        };
+        Self { vertices }
+    }
+
+    /// Makes a facet from vertices in either order, facing away from point `inside`,
+    /// like a face of a tetrahedron facing away from the opposite vertex.
+    pub fn oriented<T: ?Sized>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec3, vertices: [Idx; 3], inside: Idx) -> Self {
+        let facet = Self::new(vertices);
+        if facet.visible_from(list, index_fn, inside) {
+            facet.flipped()
+        } else {
+            facet
+        }
+    }
+
+    /// The vertices, counterclockwise when seen from outside, starting with the smallest.
+    pub fn vertices(&self) -> [Idx; 3] {
+        self.vertices
+    }
+
+    /// The same facet, facing the other way.
+    pub fn flipped(&self) -> Self {
+        let [i, j, k] = self.vertices;
+        Self { vertices: [i, k, j] }
+    }
+
+    /// Returns whether point `m` can see this facet after perturbing the points;
+    /// that is, whether it's [above the plane](above_plane) of the facet, outside of it.
+    ///
+    /// Takes a list of all the points in consideration, an indexing function, and the index of the point.
+    pub fn visible_from<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, Idx) -> Vec3, m: Idx) -> bool {
+        let [i, j, k] = self.vertices;
+        above_plane(list, index_fn, i, j, k, m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hull3, orient_3d};
+    use crate::tests::grid_points_3d;
+
+    #[test]
+    fn test_facet() {
+
+        let points = grid_points_3d();
+        let index_fn = |l: &Vec<Vec3>, i| l[i];
+        assert_eq!(Facet::new([2, 0, 1]), Facet::new([0, 1, 2]));
+        assert_eq!(Facet::new([1, 2, 0]).vertices(), [0, 1, 2]);
+        assert_ne!(Facet::new([0, 2, 1]), Facet::new([0, 1, 2]));
+        assert_eq!(Facet::new([0, 1, 2]).flipped().flipped(), Facet::new([0, 1, 2]));
+
+        for [i, j, k] in [[0, 1, 2], [0, 3, 5], [1, 7, 4], [2, 6, 3]] {
+            for m in 0..points.len() {
+                if [i, j, k].contains(&m) {
+                    continue;
+                }
+                let facet = Facet::new([i, j, k]);
+                let visible = facet.visible_from(&points, index_fn, m);
+                assert_eq!(visible, !orient_3d(&points, index_fn, i, j, k, m));
+                assert_eq!(visible, super::above_plane(&points, index_fn, j, k, i, m));
+                assert_ne!(visible, facet.flipped().visible_from(&points, index_fn, m));
+                assert!(!Facet::oriented(&points, index_fn, [i, j, k], m).visible_from(&points, index_fn, m));
+            }
+        }
+
+        // No point sees a face of the hull
+        let faces = hull3::convex_hull(&points, index_fn, points.len());
+        for face in faces {
+            let facet = Facet::new(face);
+            assert!((0..points.len()).all(|m| face.contains(&m) || !facet.visible_from(&points, index_fn, m)));
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Legality of the flips that flip-based 3D Delaunay algorithms are made of.
+//!
+//! A flip replaces some tetrahedra with others filling the same space. It can be done if
+//! the tetrahedra after it are all oriented positive, which for a valid mesh means the union
+//! is convex in the right way, and it brings the mesh closer to Delaunay if the tetrahedra
+//! before it aren't locally Delaunay. Both are decided with [`orient_3d`](crate::orient_3d)
+//! and [`in_sphere`](crate::in_sphere), so because of the perturbations, there's never
+//! a tie: a flip can't be on the boundary of applicable, and of the triangulations that
+//! a flip switches between, exactly one is Delaunay.
+//!
+//! Because no 4 points are coplanar, a 4-4 flip is an edge removal with a ring of 4,
+//! and [`edge_removal`] generalizes it to other small rings.
+//!
+//! The tetrahedra are all real; ghost tetrahedra aren't handled here.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, flip::{self, Flip}};
+//! # use nalgebra::Vector3;
+//! let points = vec![
+//!     Vector3::new(0.0, 0.0, 0.0),
+//!     Vector3::new(1.0, 0.0, 0.0),
+//!     Vector3::new(0.0, 1.0, 0.0),
+//!     Vector3::new(0.2, 0.2, 1.0),
+//!     Vector3::new(0.2, 0.2, -0.1),
+//!     Vector3::new(2.0, 2.0, -0.1),
+//! ];
+//! // Tetrahedra [0, 2, 1, 3] and [0, 1, 2, 4], with a flat one below the shared face
+//! // that reaches into the circumsphere of the other
+//! assert_eq!(flip::flip_23(&points, |l, i| l[i], [0, 2, 1], 3, 4), Flip::Improving);
+//! // And back
+//! assert_eq!(flip::flip_32(&points, |l, i| l[i], [0, 2, 1], 3, 4), Flip::NotImproving);
+//! // The edge from 3 to 5 misses the face, so [0, 2, 1, 3] and [0, 1, 2, 5] can't be flipped
+//! assert_eq!(flip::flip_23(&points, |l, i| l[i], [0, 2, 1], 3, 5), Flip::Inapplicable);
+//! ```
+
+use crate::{verify_delaunay_3d, Vec3};
+use alloc::{vec, vec::Vec};
+
+/// Whether a flip can be done, and whether it helps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Flip {
+    /// Some tetrahedron after the flip would be oriented negative
+    Inapplicable,
+    /// The flip can be done, but it doesn't replace tetrahedra that aren't locally Delaunay
+    /// with ones that are. For 2-3 and 3-2 flips, that means they already are.
+    NotImproving,
+    /// The flip can be done, and the tetrahedra after it are locally Delaunay
+    /// while the ones before it aren't
+    Improving,
+}
+
+impl Flip {
+    /// Whether the flip can be done.
+    pub fn is_applicable(self) -> bool {
+        self != Flip::Inapplicable
+    }
+
+    /// Whether the flip can be done and brings the mesh closer to Delaunay.
+    pub fn is_improving(self) -> bool {
+        self == Flip::Improving
+    }
+}
+
+/// Whether the tetrahedra are all oriented positive.
+fn all_positive<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, tetrahedra: &[[usize; 4]]) -> bool {
+    tetrahedra.iter().all(|[i, j, k, l]| crate::orient_3d(list, &index_fn, *i, *j, *k, *l))
+}
+
+/// Classifies a flip, only checking whether it improves the mesh if it can be done.
+fn classify(applicable: bool, improving: impl FnOnce() -> bool) -> Flip {
+    if !applicable {
+        Flip::Inapplicable
+    } else if improving() {
+        Flip::Improving
+    } else {
+        Flip::NotImproving
+    }
+}
+
+/// Classifies the 2-3 flip of the tetrahedra [`a`, `b`, `c`, `d`] and [`a`, `c`, `b`, `e`],
+/// which share the face [`a`, `b`, `c`], into the 3 tetrahedra [`a`, `b`, `e`, `d`],
+/// [`b`, `c`, `e`, `d`], and [`c`, `a`, `e`, `d`] around the edge from `d` to `e`.
+///
+/// It can be done if that edge goes through the face, and it improves the mesh
+/// if `e` is inside the circumsphere of [`a`, `b`, `c`, `d`].
+pub fn flip_23<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, [a, b, c]: [usize; 3], d: usize, e: usize) -> Flip {
+    classify(all_positive(list, &index_fn, &[[a, b, e, d], [b, c, e, d], [c, a, e, d]]), || {
+        crate::in_sphere(list, &index_fn, a, b, c, d, e)
+    })
+}
+
+/// Classifies the 3-2 flip of the 3 tetrahedra [`a`, `b`, `e`, `d`], [`b`, `c`, `e`, `d`],
+/// and [`c`, `a`, `e`, `d`] around the edge from `d` to `e` into [`a`, `b`, `c`, `d`] and
+/// [`a`, `c`, `b`, `e`], which share the face [`a`, `b`, `c`]. This undoes [`flip_23`].
+///
+/// It can be done if the edge goes through the face, and it improves the mesh
+/// if `e` is outside the circumsphere of [`a`, `b`, `c`, `d`].
+pub fn flip_32<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, [a, b, c]: [usize; 3], d: usize, e: usize) -> Flip {
+    classify(all_positive(list, &index_fn, &[[a, b, c, d], [a, c, b, e]]), || {
+        !crate::in_sphere(list, &index_fn, a, b, c, d, e)
+    })
+}
+
+/// The tetrahedra around the edge from `d` to `e` with the ring of vertices around it, in order.
+fn around_edge(ring: &[usize], d: usize, e: usize) -> Vec<[usize; 4]> {
+    (0..ring.len()).map(|i| [ring[i], ring[(i + 1) % ring.len()], e, d]).collect()
+}
+
+/// The tetrahedra connecting triangles oriented like the ring to `d` and `e`.
+fn connecting(triangles: &[[usize; 3]], d: usize, e: usize) -> Vec<[usize; 4]> {
+    triangles.iter().flat_map(|[x, y, z]| vec![[*x, *y, *z, d], [*x, *z, *y, e]]).collect()
+}
+
+/// Classifies replacing the tetrahedra around an edge with the triangles of its ring connected to its ends.
+fn classify_removal<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3,
+    ring: &[usize],
+    d: usize,
+    e: usize,
+    triangles: &[[usize; 3]],
+) -> Flip {
+    let after = connecting(triangles, d, e);
+    classify(all_positive(list, &index_fn, &after), || {
+        verify_delaunay_3d(list, &index_fn, &after).is_empty()
+            && !verify_delaunay_3d(list, &index_fn, &around_edge(ring, d, e)).is_empty()
+    })
+}
+
+/// Classifies the 4-4 flip of the 4 tetrahedra [`a`, `b`, `e`, `d`], [`b`, `c`, `e`, `d`],
+/// [`c`, `f`, `e`, `d`], and [`f`, `a`, `e`, `d`] around the edge from `d` to `e` into the 4 tetrahedra
+/// [`a`, `b`, `c`, `d`], [`a`, `c`, `b`, `e`], [`a`, `c`, `f`, `d`], and [`a`, `f`, `c`, `e`] around
+/// the edge from `a` to `c`. To flip to the edge from `b` to `f` instead, rotate the ring.
+///
+/// It can be done if the tetrahedra after it are all oriented positive, and it improves the mesh
+/// if they're locally Delaunay and the ones before it aren't.
+pub fn flip_44<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, [a, b, c, f]: [usize; 4], d: usize, e: usize) -> Flip {
+    classify_removal(list, index_fn, &[a, b, c, f], d, e, &[[a, b, c], [a, c, f]])
+}
+
+/// All the triangulations of a convex polygon with the vertices of `ring` in order,
+/// with triangles oriented like it.
+fn triangulations(ring: &[usize]) -> Vec<Vec<[usize; 3]>> {
+    if ring.len() < 3 {
+        return vec![vec![]];
+    }
+    let last = ring.len() - 1;
+    (1..last)
+        .flat_map(|k| {
+            let right = triangulations(&ring[k..]);
+            triangulations(&ring[..=k])
+                .into_iter()
+                .flat_map(|left| {
+                    right.iter().map(move |right| {
+                        let mut triangles = left.clone();
+                        triangles.push([ring[0], ring[k], ring[last]]);
+                        triangles.extend(right);
+                        triangles
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Finds a way to remove the edge from `d` to `e`, given the ring of vertices around it,
+/// in the order that makes [`ring[i]`, `ring[i + 1]`, `e`, `d`] the tetrahedra around it.
+/// Returns how the best one is classified, and the triangles to connect to `d` and `e`
+/// with the tetrahedra [`x`, `y`, `z`, `d`] and [`x`, `z`, `y`, `e`] for each triangle
+/// [`x`, `y`, `z`], which are empty if the edge can't be removed.
+///
+/// This tries every triangulation of the ring, and there are exponentially many,
+/// so it's meant for rings of up to about 7 vertices, like the ones mesh improvement
+/// passes try to remove. With 3 vertices, it's a [`flip_32`], and with 4, a [`flip_44`].
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, flip::{self, Flip}};
+/// # use nalgebra::Vector3;
+/// // A long edge through the middle of a pentagon
+/// let points = vec![
+///     Vector3::new(2.0, 0.0, 0.0),
+///     Vector3::new(1.0, 2.5, 0.0),
+///     Vector3::new(-1.0, 2.0, 0.0),
+///     Vector3::new(-2.0, 0.0, 0.0),
+///     Vector3::new(0.0, -2.0, 0.0),
+///     Vector3::new(0.0, 0.5, -3.0),
+///     Vector3::new(0.0, 0.5, 3.0),
+/// ];
+/// let (flip, triangles) = flip::edge_removal(&points, |l, i| l[i], &[0, 1, 2, 3, 4], 5, 6);
+/// assert_eq!(flip, Flip::Improving);
+/// assert_eq!(triangles.len(), 3);
+/// ```
+pub fn edge_removal<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3,
+    ring: &[usize],
+    d: usize,
+    e: usize,
+) -> (Flip, Vec<[usize; 3]>) {
+    let mut best = (Flip::Inapplicable, vec![]);
+    for triangles in triangulations(ring) {
+        match classify_removal(list, &index_fn, ring, d, e, &triangles) {
+            Flip::Improving => return (Flip::Improving, triangles),
+            Flip::NotImproving if best.0 == Flip::Inapplicable => best = (Flip::NotImproving, triangles),
+            _ => {}
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{orient_3d, rng};
+    use crate::tests::grid_points_3d;
+
+    #[test]
+    fn test_flip_23_32() {
+        let points = grid_points_3d();
+        let n = points.len();
+        let mut counts = [0; 3];
+        for a in 0..n {
+            for b in (0..n).filter(|b| *b != a) {
+                for c in (0..n).filter(|c| *c != a && *c != b) {
+                    let d = (a + b + c) % n;
+                    let e = (a * b + c + 1) % n;
+                    if [a, b, c].contains(&d) || [a, b, c, d].contains(&e)
+                        || !orient_3d(&points, |l, i| l[i], a, b, c, d)
+                        || !orient_3d(&points, |l, i| l[i], a, c, b, e)
+                    {
+                        continue;
+                    }
+                    let two = [[a, b, c, d], [a, c, b, e]];
+                    let three = [[a, b, e, d], [b, c, e, d], [c, a, e, d]];
+                    let flip = super::flip_23(&points, |l, i| l[i], [a, b, c], d, e);
+                    counts[flip as usize] += 1;
+                    if flip.is_applicable() {
+                        assert!(three.iter().all(|[i, j, k, l]| orient_3d(&points, |l, i| l[i], *i, *j, *k, *l)));
+                        // Exactly one side of the flip is Delaunay, and the flip back undoes it
+                        let two_delaunay = verify_delaunay_3d(&points, |l, i| l[i], &two).is_empty();
+                        let three_delaunay = verify_delaunay_3d(&points, |l, i| l[i], &three).is_empty();
+                        assert_ne!(two_delaunay, three_delaunay);
+                        assert_eq!(flip.is_improving(), three_delaunay);
+                        let back = super::flip_32(&points, |l, i| l[i], [a, b, c], d, e);
+                        assert!(back.is_applicable());
+                        assert_eq!(back.is_improving(), two_delaunay);
+                    } else {
+                        assert!(!three.iter().all(|[i, j, k, l]| orient_3d(&points, |l, i| l[i], *i, *j, *k, *l)));
+                    }
+                }
+            }
+        }
+        assert!(counts.iter().all(|c| *c > 0));
+    }
+
+    #[test]
+    fn test_flip_44_edge_removal() {
+        let points = grid_points_3d();
+        let n = points.len();
+        let positive = |tets: &[[usize; 4]]| tets.iter().all(|[i, j, k, l]| orient_3d(&points, |l, i| l[i], *i, *j, *k, *l));
+        let mut rng = rng::SplitMix(0x44);
+        let mut counts = [[0; 3]; 2];
+        for _ in 0..3000 {
+            let mut v = vec![];
+            while v.len() < 6 {
+                let i = rng.below(n);
+                if !v.contains(&i) {
+                    v.push(i);
+                }
+            }
+            let (d, e) = (v[4], v[5]);
+            for ring in [&v[..3], &v[..4]] {
+                let before = (0..ring.len()).map(|i| [ring[i], ring[(i + 1) % ring.len()], e, d]).collect::<Vec<_>>();
+                if !positive(&before) {
+                    continue;
+                }
+                let (flip, triangles) = super::edge_removal(&points, |l, i| l[i], ring, d, e);
+                counts[ring.len() - 3][flip as usize] += 1;
+                if let [a, b, c] = *ring {
+                    assert_eq!(flip, super::flip_32(&points, |l, i| l[i], [a, b, c], d, e));
+                } else if let [a, b, c, f] = *ring {
+                    let flips = [
+                        super::flip_44(&points, |l, i| l[i], [a, b, c, f], d, e),
+                        super::flip_44(&points, |l, i| l[i], [b, c, f, a], d, e),
+                    ];
+                    assert_eq!(flip.is_improving(), flips.iter().any(|f| f.is_improving()));
+                    assert_eq!(flip.is_applicable(), flips.iter().any(|f| f.is_applicable()));
+                }
+                if flip.is_applicable() {
+                    let after = triangles.iter().flat_map(|[x, y, z]| vec![[*x, *y, *z, d], [*x, *z, *y, e]]).collect::<Vec<_>>();
+                    assert!(positive(&after));
+                    assert_eq!(flip.is_improving(), verify_delaunay_3d(&points, |l, i| l[i], &after).is_empty());
+                } else {
+                    assert!(triangles.is_empty());
+                }
+            }
+        }
+        assert!(counts.iter().all(|c| c.iter().all(|c| *c > 0)), "{:?}", counts);
+    }
+}
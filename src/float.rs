@@ -0,0 +1,17 @@
+//! Rounding of floats, which `core` doesn't have, from `std` or from `libm`.
+
+/// Rounds to the nearest integer, with halfway cases away from 0.
+pub(crate) fn round(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.round();
+    #[cfg(not(feature = "std"))]
+    return libm::round(x);
+}
+
+/// Rounds toward 0.
+pub(crate) fn trunc(x: f64) -> f64 {
+    #[cfg(feature = "std")]
+    return x.trunc();
+    #[cfg(not(feature = "std"))]
+    return libm::trunc(x);
+}
@@ -0,0 +1,126 @@
+//! Gabriel graphs.
+//!
+//! Two points are connected in the Gabriel graph if no other point is inside the circle
+//! or sphere with the segment between them as a diameter. Every such edge is an edge of the
+//! Delaunay triangulation, and if some point is inside the diametral ball of a Delaunay edge,
+//! then so is a vertex of a triangle or tetrahedron on the edge. So only those vertices are
+//! tested, with [`distance::in_diametral_circle`](crate::distance::in_diametral_circle)
+//! and [`distance::in_diametral_sphere`](crate::distance::in_diametral_sphere).
+//! The perturbation decides points exactly on a diametral boundary, so the graph is
+//! the same every time for the same input.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, gabriel};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(4.0, 0.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(2.0, 5.0),
+//! ];
+//! let graph = gabriel::gabriel_graph_2d(&points, |l, i| l[i], points.len());
+//! // Point 2 is inside the diametral circle of the bottom edge
+//! assert_eq!(graph, vec![[0, 2], [1, 2], [2, 3]]);
+//! ```
+
+use crate::{Vec2, Vec3};
+use alloc::collections::BTreeMap;
+
+/// Keeps the edges that none of their witnesses encroach on.
+fn filter(witnesses: BTreeMap<[usize; 2], Vec<usize>>, encroaches: impl Fn(usize, usize, usize) -> bool) -> Vec<[usize; 2]> {
+    witnesses
+        .into_iter()
+        .filter(|([a, b], witnesses)| !witnesses.iter().any(|p| encroaches(*a, *b, *p)))
+        .map(|(edge, _)| edge)
+        .collect()
+}
+
+/// Maps each edge of the simplices to the vertices of the simplices on it that aren't on the edge.
+/// If there are no simplices because there are too few points, every pair of points is an edge.
+fn witnesses<const N: usize>(simplices: &[[usize; N]], len: usize) -> BTreeMap<[usize; 2], Vec<usize>> {
+    let mut witnesses = BTreeMap::<[usize; 2], Vec<usize>>::new();
+    if simplices.is_empty() {
+        for a in 0..len {
+            for b in (a + 1)..len {
+                witnesses.insert([a, b], (0..len).filter(|p| *p != a && *p != b).collect());
+            }
+        }
+    }
+    for simplex in simplices {
+        for i in 0..N {
+            for j in (i + 1)..N {
+                let edge = [simplex[i].min(simplex[j]), simplex[i].max(simplex[j])];
+                let others = simplex.iter().filter(|v| !edge.contains(v));
+                witnesses.entry(edge).or_default().extend(others);
+            }
+        }
+    }
+    witnesses
+}
+
+/// Computes the Gabriel graph of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the edges of the graph, with the smaller index first, sorted.
+pub fn gabriel_graph_2d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize) -> Vec<[usize; 2]> {
+    let triangles = crate::delaunay2::triangulate(list, &index_fn, len);
+    filter(witnesses(&triangles, len), |a, b, p| crate::distance::in_diametral_circle(list, &index_fn, a, b, p))
+}
+
+/// Computes the Gabriel graph of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the edges of the graph, with the smaller index first, sorted.
+pub fn gabriel_graph_3d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize) -> Vec<[usize; 2]> {
+    let tetrahedra = crate::delaunay3::tetrahedralize(list, &index_fn, len);
+    filter(witnesses(&tetrahedra, len), |a, b, p| crate::distance::in_diametral_sphere(list, &index_fn, a, b, p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, distance};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    fn test_gabriel(seed: u64) {
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.cocircular_2d(10),
+            _ => {
+                let points = gen.grid_clusters_2d(3, 4);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let n = points.len();
+        let expected = (0..n)
+            .flat_map(|a| ((a + 1)..n).map(move |b| [a, b]))
+            .filter(|[a, b]| !(0..n).any(|p| distance::in_diametral_circle(&points, |l, i| l[i], *a, *b, p)))
+            .collect::<Vec<_>>();
+        assert_eq!(super::gabriel_graph_2d(&points, |l, i| l[i], n), expected);
+
+        let points = match seed {
+            0 => grid_points_3d(),
+            1 => gen.cospherical_3d(10),
+            _ => {
+                let points = gen.grid_clusters_3d(2, 3);
+                gen.with_duplicates(points, 3)
+            }
+        };
+        let expected = |n: usize| {
+            (0..n)
+                .flat_map(|a| ((a + 1)..n).map(move |b| [a, b]))
+                .filter(|[a, b]| !(0..n).any(|p| distance::in_diametral_sphere(&points, |l, i| l[i], *a, *b, p)))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(super::gabriel_graph_3d(&points, |l, i| l[i], points.len()), expected(points.len()));
+        // Too few points to tetrahedralize
+        assert_eq!(super::gabriel_graph_3d(&points, |l, i| l[i], 3), expected(3));
+    }
+}
@@ -0,0 +1,193 @@
+//! Variants of the predicates that perturb points in an order derived from their coordinates.
+//!
+//! The usual predicates perturb points in the order of their indexes, so the same points
+//! assembled in a different order can break ties differently. These perturb them in the order
+//! of a hash of their exact coordinates instead, so the results only depend on the geometry.
+//! The hash is computed the same way on every machine, and it scrambles the order, so sorted
+//! input isn't perturbed in a structured way.
+//!
+//! Points with different coordinates but the same hash are ordered by their coordinates,
+//! lexicographically by their bits, so collisions never bring the indexes back in.
+//! Only points with the same coordinates, which `-0.0` and `0.0` count as, fall back to
+//! comparing indexes, so predicates on duplicate points still depend on which comes first.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, hashed};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//! ];
+//! let shuffled = vec![points[2], points[0], points[1]];
+//! assert_eq!(
+//!     hashed::orient_2d(&points, |l, i| l[i], 0, 1, 2),
+//!     hashed::orient_2d(&shuffled, |l, i| l[i], 1, 2, 0),
+//! );
+//! ```
+
+use crate::rng::SplitMix;
+use crate::{Keyed, SosPoint, Vec1, Vec2, Vec3};
+use core::ops::Index;
+
+/// The key of a point: a hash of its coordinates, then the coordinates themselves.
+pub type Key<const N: usize> = (u64, [u64; N]);
+
+/// The key that decides where the point at index `i` goes in the perturbation order.
+pub fn key<P: Index<usize, Output = f64>, const N: usize>(point: &P, i: usize) -> Keyed<Key<N>> {
+    // Adding 0 turns -0 into 0
+    let mut bits = [0; N];
+    for (c, b) in bits.iter_mut().enumerate() {
+        *b = (point[c] + 0.0).to_bits();
+    }
+    let hash = bits.iter().fold(0, |h, b| SplitMix(h ^ b).next());
+    Keyed::new((hash, bits), i)
+}
+
+fn keyed<'a, T: ?Sized + 'a, P, const N: usize>(index_fn: impl Fn(&'a T, usize) -> P + Clone) -> impl Fn(&'a T, Keyed<Key<N>>) -> P + Clone {
+    move |list, k| index_fn(list, k.index)
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but perturbing in the order of the hashes of the coordinates.
+pub fn orient_1d<'a, T: ?Sized, P: SosPoint<Vector = Vec1>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P + Clone, i: usize, j: usize) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 1>(&index_fn(list, i), i);
+    crate::orient_1d(list, keyed(index_fn.clone()), key(i), key(j))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but perturbing in the order of the hashes of the coordinates.
+pub fn orient_2d<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P + Clone, i: usize, j: usize, k: usize) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 2>(&index_fn(list, i), i);
+    crate::orient_2d(list, keyed(index_fn.clone()), key(i), key(j), key(k))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but perturbing in the order of the hashes of the coordinates.
+pub fn orient_3d<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, usize) -> P + Clone,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 3>(&index_fn(list, i), i);
+    crate::orient_3d(list, keyed(index_fn.clone()), key(i), key(j), key(k), key(l))
+}
+
+/// Like [`in_circle`](crate::in_circle), but perturbing in the order of the hashes of the coordinates.
+pub fn in_circle<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, usize) -> P + Clone,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 2>(&index_fn(list, i), i);
+    crate::in_circle(list, keyed(index_fn.clone()), key(i), key(j), key(k), key(l))
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but perturbing in the order of the hashes
+/// of the coordinates.
+pub fn in_circle_unoriented<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, usize) -> P + Clone,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 2>(&index_fn(list, i), i);
+    crate::in_circle_unoriented(list, keyed(index_fn.clone()), key(i), key(j), key(k), key(l))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but perturbing in the order of the hashes of the coordinates.
+pub fn in_sphere<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, usize) -> P + Clone,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+    m: usize,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 3>(&index_fn(list, i), i);
+    crate::in_sphere(list, keyed(index_fn.clone()), key(i), key(j), key(k), key(l), key(m))
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but perturbing in the order of the hashes
+/// of the coordinates.
+pub fn in_sphere_unoriented<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, usize) -> P + Clone,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+    m: usize,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let key = |i| key::<_, 3>(&index_fn(list, i), i);
+    crate::in_sphere_unoriented(list, keyed(index_fn.clone()), key(i), key(j), key(k), key(l), key(m))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_hashed() {
+        // Drop the duplicates, since those still fall back to the indexes
+        let points = grid_points_2d()[..9].to_vec();
+        let n = points.len();
+        let reversed = points.iter().rev().copied().collect::<Vec<_>>();
+        let r = |i: usize| n - 1 - i;
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        super::orient_2d(&points, |l, i| l[i], i, j, k),
+                        super::orient_2d(&reversed, |l, i| l[i], r(i), r(j), r(k))
+                    );
+                    let l = (k + 1) % n;
+                    if l != i && l != j {
+                        assert_eq!(
+                            super::in_circle(&points, |l, i| l[i], i, j, k, l),
+                            super::in_circle(&reversed, |l, i| l[i], r(i), r(j), r(k), r(l))
+                        );
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d()[..8].to_vec();
+        let n = points.len();
+        let reversed = points.iter().rev().copied().collect::<Vec<_>>();
+        let r = |i: usize| n - 1 - i;
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 7) % n);
+            assert_eq!(
+                super::orient_3d(&points, |l, i| l[i], i, j, k, l),
+                super::orient_3d(&reversed, |l, i| l[i], r(i), r(j), r(k), r(l))
+            );
+            assert_eq!(
+                super::in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                super::in_sphere(&reversed, |l, i| l[i], r(i), r(j), r(k), r(l), r(m))
+            );
+        }
+
+        // -0 is the same point as 0, so only the index tells them apart
+        let a = super::key::<_, 2>(&Vector2::new(-0.0, 1.0), 3);
+        let b = super::key::<_, 2>(&Vector2::new(0.0, 1.0), 5);
+        assert_eq!(a.key, b.key);
+        assert!(a < b);
+    }
+}
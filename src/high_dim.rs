@@ -0,0 +1,313 @@
+//! Predicates in 4 and 5 dimensions, generated with [`generate_sos_predicate`](crate::generate_sos_predicate).
+//!
+//! Their cascades are large (the 5-dimensional in-hypersphere has thousands of cases),
+//! so they're behind the `high-dim` feature. Building them is much faster with
+//! an optimized proc macro:
+//!
+//! ```toml
+//! [profile.dev.build-override]
+//! opt-level = 3
+//! ```
+//!
+//! Like the other generated predicates, they evaluate the unperturbed determinant
+//! in floating point first, with an error bound, and only fall back to the exact cascade
+//! when that bound doesn't decide the sign, so calls on non-degenerate points are
+//! about as cheap as a floating-point determinant. Run `cargo bench --features high-dim`
+//! for timings.
+
+use crate::{scale, SosPoint};
+use nalgebra::{Vector4, Vector5};
+type Vec4 = Vector4<f64>;
+type Vec5 = Vector5<f64>;
+
+/// Returns whether the orientation of 5 points in 4-dimensional space
+/// is positive after perturbing them.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 5 indexes to the points to calculate the orientation of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_4d};
+/// # use nalgebra::Vector4;
+/// let points = vec![
+///     Vector4::new(0.0, 0.0, 0.0, 0.0),
+///     Vector4::new(1.0, 0.0, 0.0, 0.0),
+///     Vector4::new(0.0, 1.0, 0.0, 0.0),
+///     Vector4::new(0.0, 0.0, 1.0, 0.0),
+///     Vector4::new(0.0, 0.0, 0.0, 1.0),
+/// ];
+/// let positive = orient_4d(&points, |l, i| l[i], 0, 1, 2, 3, 4);
+/// assert!(positive);
+/// let positive = orient_4d(&points, |l, i| l[i], 1, 0, 2, 3, 4);
+/// assert!(!positive);
+/// ```
+pub fn orient_4d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec4>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l, m]);
+    simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l, m; x, y, z, w}
+}
+
+/// Returns whether the orientation of 6 points in 5-dimensional space
+/// is positive after perturbing them.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 6 indexes to the points to calculate the orientation of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_5d};
+/// # use nalgebra::Vector5;
+/// let points = vec![
+///     Vector5::new(1.0, 0.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 1.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 1.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 1.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 1.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 0.0),
+/// ];
+/// let positive = orient_5d(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5);
+/// assert!(positive);
+/// let positive = orient_5d(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5);
+/// assert!(!positive);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn orient_5d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec5>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+    n: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l, m, n]);
+    simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l, m, n; x, y, z, w, p[4]}
+}
+
+/// Returns whether the last point is inside the oriented hypersphere that goes through
+/// the first 5 points in 4-dimensional space after perturbing them.
+/// The first 5 points should be oriented positive or the result will be flipped.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 6 indexes to the points to calculate the in-hypersphere of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, in_hypersphere_4d};
+/// # use nalgebra::Vector4;
+/// let points = vec![
+///     Vector4::new(0.0, 0.0, 0.0, 0.0),
+///     Vector4::new(1.0, 0.0, 0.0, 0.0),
+///     Vector4::new(0.0, 1.0, 0.0, 0.0),
+///     Vector4::new(0.0, 0.0, 1.0, 0.0),
+///     Vector4::new(0.0, 0.0, 0.0, 1.0),
+///     Vector4::new(0.5, 0.5, 0.5, 0.5),
+///     Vector4::new(2.0, 2.0, 2.0, 2.0),
+/// ];
+/// let inside = in_hypersphere_4d(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5);
+/// assert!(inside);
+/// let inside = in_hypersphere_4d(&points, |l, i| l[i], 0, 1, 2, 3, 4, 6);
+/// assert!(!inside);
+/// // Negatively oriented, so the result is flipped
+/// let inside = in_hypersphere_4d(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5);
+/// assert!(!inside);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn in_hypersphere_4d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec4>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+    n: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l, m, n]);
+    simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l, m, n; x, y, z, w, x*x + y*y + z*z + w*w}
+}
+
+/// Returns whether the last point is inside the hypersphere that goes through
+/// the first 5 points in 4-dimensional space after perturbing them.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 6 indexes to the points to calculate the in-hypersphere of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, in_hypersphere_4d_unoriented};
+/// # use nalgebra::Vector4;
+/// let points = vec![
+///     Vector4::new(0.0, 0.0, 0.0, 0.0),
+///     Vector4::new(1.0, 0.0, 0.0, 0.0),
+///     Vector4::new(0.0, 1.0, 0.0, 0.0),
+///     Vector4::new(0.0, 0.0, 1.0, 0.0),
+///     Vector4::new(0.0, 0.0, 0.0, 1.0),
+///     Vector4::new(0.5, 0.5, 0.5, 0.5),
+/// ];
+/// let inside = in_hypersphere_4d_unoriented(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5);
+/// assert!(inside);
+/// let inside = in_hypersphere_4d_unoriented(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5);
+/// assert!(inside);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn in_hypersphere_4d_unoriented<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec4>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+    n: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    orient_4d(list, index_fn.clone(), i, j, k, l, m) == in_hypersphere_4d(list, index_fn, i, j, k, l, m, n)
+}
+
+/// Returns whether the last point is inside the oriented hypersphere that goes through
+/// the first 6 points in 5-dimensional space after perturbing them.
+/// The first 6 points should be oriented positive or the result will be flipped.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 7 indexes to the points to calculate the in-hypersphere of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, in_hypersphere_5d};
+/// # use nalgebra::Vector5;
+/// let points = vec![
+///     Vector5::new(1.0, 0.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 1.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 1.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 1.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 1.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.5, 0.5, 0.5, 0.5, 0.5),
+///     Vector5::new(2.0, 2.0, 2.0, 2.0, 2.0),
+/// ];
+/// let inside = in_hypersphere_5d(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5, 6);
+/// assert!(inside);
+/// let inside = in_hypersphere_5d(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5, 7);
+/// assert!(!inside);
+/// // Negatively oriented, so the result is flipped
+/// let inside = in_hypersphere_5d(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5, 6);
+/// assert!(!inside);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn in_hypersphere_5d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec5>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+    n: Idx,
+    o: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let index_fn = scale::prefetched(list, index_fn, [i, j, k, l, m, n, o]);
+    simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l, m, n, o; x, y, z, w, p[4], x*x + y*y + z*z + w*w + p[4]*p[4]}
+}
+
+/// Returns whether the last point is inside the hypersphere that goes through
+/// the first 6 points in 5-dimensional space after perturbing them.
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 7 indexes to the points to calculate the in-hypersphere of.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, in_hypersphere_5d_unoriented};
+/// # use nalgebra::Vector5;
+/// let points = vec![
+///     Vector5::new(1.0, 0.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 1.0, 0.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 1.0, 0.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 1.0, 0.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 1.0),
+///     Vector5::new(0.0, 0.0, 0.0, 0.0, 0.0),
+///     Vector5::new(2.0, 2.0, 2.0, 2.0, 2.0),
+/// ];
+/// let inside = in_hypersphere_5d_unoriented(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5, 6);
+/// assert!(!inside);
+/// let inside = in_hypersphere_5d_unoriented(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5, 6);
+/// assert!(!inside);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn in_hypersphere_5d_unoriented<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec5>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+    n: Idx,
+    o: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    orient_5d(list, index_fn.clone(), i, j, k, l, m, n) == in_hypersphere_5d(list, index_fn, i, j, k, l, m, n, o)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "high-dim")]
+    #[test]
+    fn test_high_dim_swap_flips() {
+        use nalgebra::Vector4;
+        // Corners of a hypercube and the origin again, so that most tuples are degenerate
+        let mut points = (0..16).map(|i| Vector4::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4 % 2) as f64, (i / 8) as f64)).collect::<Vec<_>>();
+        points.push(Vector4::new(0.0, 0.0, 0.0, 0.0));
+
+        for a in 0..points.len() - 5 {
+            let t = [a, 16, a + 1, a + 2, a + 3, a + 5];
+            let orient = orient_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4]);
+            assert_ne!(orient, orient_4d(&points, |l, i| l[i], t[1], t[0], t[2], t[3], t[4]));
+            assert_ne!(orient, orient_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[4], t[3]));
+
+            let inside = in_hypersphere_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4], t[5]);
+            assert_ne!(inside, in_hypersphere_4d(&points, |l, i| l[i], t[1], t[0], t[2], t[3], t[4], t[5]));
+            assert_ne!(inside, in_hypersphere_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[5], t[4]));
+        }
+    }
+
+    #[cfg(feature = "high-dim")]
+    #[test]
+    fn test_in_hypersphere_5d_unoriented() {
+        use nalgebra::Vector5;
+        let mut points = (0..5).map(|i| {
+            let mut p = Vector5::zeros();
+            p[i] = 1.0;
+            p
+        }).collect::<Vec<_>>();
+        points.push(Vector5::repeat(-1.0));
+        points.push(Vector5::zeros());
+        points.push(Vector5::repeat(2.0));
+
+        assert!(in_hypersphere_5d_unoriented(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5, 6));
+        assert!(in_hypersphere_5d_unoriented(&points, |l, i| l[i], 1, 0, 2, 3, 4, 5, 6));
+        assert!(!in_hypersphere_5d_unoriented(&points, |l, i| l[i], 0, 1, 2, 3, 4, 5, 7));
+    }
+}
@@ -0,0 +1,119 @@
+//! 2D convex hulls built only on the predicates.
+//!
+//! This is Andrew's monotone chain algorithm. The points are sorted by their perturbed
+//! x coordinates, which never tie, and the chains are built with [`orient_2d`](crate::orient_2d),
+//! which never finds 3 points collinear. So the hull is always a simple polygon with
+//! at least 3 vertices when there are at least 3 points, even if all the points
+//! are collinear or equal.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, hull2};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(1.0, 0.5),
+//! ];
+//! let hull = hull2::convex_hull(&points, |l, i| l[i], points.len());
+//! assert_eq!(hull, vec![0, 1, 2, 3]);
+//!
+//! // All collinear, but the perturbation still makes a polygon
+//! let hull = hull2::convex_hull(&points, |l, i| l[i], 3);
+//! assert_eq!(hull.len(), 3);
+//! ```
+
+use crate::Vec2;
+use nalgebra::Vector1;
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+
+/// Computes the convex hull of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the indexes of the hull's vertices in counterclockwise order,
+/// so every 3 consecutive vertices are oriented positive by [`orient_2d`](crate::orient_2d),
+/// starting with the leftmost point.
+pub fn convex_hull<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize) -> Vec<usize> {
+    let right_of = |i, j| crate::orient_1d(list, |l, i| Vector1::new(index_fn(l, i).x), i, j);
+    let orient = |i, j, k| crate::orient_2d(list, &index_fn, i, j, k);
+
+    let mut sorted = (0..len).collect::<Vec<_>>();
+    sorted.sort_unstable_by(|i, j| match (i == j, right_of(*i, *j)) {
+        (true, _) => Ordering::Equal,
+        (false, true) => Ordering::Greater,
+        (false, false) => Ordering::Less,
+    });
+    if len < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<usize> = vec![];
+    // The lower chain, from left to right
+    for point in &sorted {
+        while hull.len() >= 2 && !orient(hull[hull.len() - 2], hull[hull.len() - 1], *point) {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    // The upper chain, from right to left
+    let lower = hull.len();
+    for point in sorted.iter().rev().skip(1) {
+        while hull.len() > lower && !orient(hull[hull.len() - 2], hull[hull.len() - 1], *point) {
+            hull.pop();
+        }
+        hull.push(*point);
+    }
+    // The leftmost point ends both chains
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, delaunay2, orient_2d};
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    fn test_hull2(seed: u64) {
+        use std::collections::HashSet;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_2d(),
+            1 => gen.collinear_2d(8),
+            2 => vec![Vector2::new(1.0, 2.0); 6],
+            _ => {
+                let points = gen.cocircular_2d(8);
+                gen.with_duplicates(points, 4)
+            }
+        };
+        let hull = super::convex_hull(&points, |l, i| l[i], points.len());
+        assert!(hull.len() >= 3);
+        assert_eq!(hull.iter().collect::<HashSet<_>>().len(), hull.len());
+
+        // Strictly convex, with every other point inside
+        for n in 0..hull.len() {
+            let (i, j) = (hull[n], hull[(n + 1) % hull.len()]);
+            assert!(orient_2d(&points, |l, i| l[i], i, j, hull[(n + 2) % hull.len()]));
+            for k in (0..points.len()).filter(|k| !hull.contains(k)) {
+                assert!(orient_2d(&points, |l, i| l[i], i, j, k));
+            }
+        }
+
+        // The same hull as the Delaunay triangulation's
+        let triangles = delaunay2::triangulate(&points, |l, i| l[i], points.len());
+        let edges = triangles.iter().flat_map(|t| (0..3).map(move |e| (t[e], t[(e + 1) % 3]))).collect::<HashSet<_>>();
+        let hull_edges = edges.iter().filter(|(i, j)| !edges.contains(&(*j, *i))).copied().collect::<HashSet<_>>();
+        assert_eq!(hull_edges, (0..hull.len()).map(|n| (hull[n], hull[(n + 1) % hull.len()])).collect());
+    }
+}
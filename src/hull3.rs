@@ -0,0 +1,144 @@
+//! 3D convex hulls built only on the predicates.
+//!
+//! This is an incremental algorithm: it starts with a tetrahedron, and adds each point outside
+//! the current hull by removing the faces it can see and connecting it to the horizon.
+//! Because [`orient_3d`](crate::orient_3d) is perturbed, no 4 points are coplanar,
+//! so every face is a triangle and a point either sees a face or it doesn't. Coplanar points
+//! on the hull become a triangulated facet, triangulated the way the perturbation decides,
+//! and the hull is a closed surface even if all the points are coplanar, collinear, or equal.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, hull3};
+//! # use nalgebra::Vector3;
+//! // A cube, whose square sides are split into 2 triangles each
+//! let points = (0..8)
+//!     .map(|i| Vector3::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4) as f64))
+//!     .collect::<Vec<_>>();
+//! let faces = hull3::convex_hull(&points, |l, i| l[i], points.len());
+//! assert_eq!(faces.len(), 12);
+//! ```
+
+use crate::delaunay3::{faces, key};
+use crate::facet::Facet;
+use crate::Vec3;
+use std::collections::HashMap;
+
+/// Computes the convex hull of the first `len` points after perturbing them.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// Returns the faces of the hull as triples of indexes, oriented so that the
+/// hull is on their positive side: for each face `[i, j, k]` and every other point `l`,
+/// [`orient_3d`](crate::orient_3d) of `i, j, k, l` is `true`. That is, each face is counterclockwise
+/// when seen from outside, like a [`Facet`], which no other point can see.
+/// Returns no faces if there are fewer than 4 points.
+pub fn convex_hull<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize) -> Vec<[usize; 3]> {
+    if len < 4 {
+        return vec![];
+    }
+    let orient = |[i, j, k]: [usize; 3], l| crate::orient_3d(list, &index_fn, i, j, k, l);
+
+    let first = if orient([0, 1, 2], 3) { [0, 1, 2, 3] } else { [1, 0, 2, 3] };
+    let mut hull = faces(first).to_vec();
+    let mut alive = vec![true; 4];
+    // The face with each directed edge
+    let mut edges = HashMap::new();
+    for (f, face) in hull.iter().enumerate() {
+        for e in 0..3 {
+            edges.insert((face[e], face[(e + 1) % 3]), f);
+        }
+    }
+
+    for point in 4..len {
+        let visible =
+            (0..hull.len()).filter(|f| alive[*f] && Facet::new(hull[*f]).visible_from(list, &index_fn, point)).collect::<Vec<_>>();
+        if visible.is_empty() {
+            continue;
+        }
+        for f in &visible {
+            alive[*f] = false;
+        }
+
+        // The edges between visible and invisible faces, directed like in the visible faces
+        let mut horizon = vec![];
+        for f in &visible {
+            let face = hull[*f];
+            for e in 0..3 {
+                let (u, v) = (face[e], face[(e + 1) % 3]);
+                if alive[edges[&(v, u)]] {
+                    horizon.push((u, v));
+                }
+            }
+        }
+        for f in visible {
+            let face = hull[f];
+            for e in 0..3 {
+                edges.remove(&(face[e], face[(e + 1) % 3]));
+            }
+        }
+        for (u, v) in horizon {
+            let face = [u, v, point];
+            for e in 0..3 {
+                edges.insert((face[e], face[(e + 1) % 3]), hull.len());
+            }
+            hull.push(face);
+            alive.push(true);
+        }
+    }
+
+    hull.into_iter()
+        .zip(alive)
+        .filter(|(_, alive)| *alive)
+        .map(|(face, _)| key(face))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{degenerate, delaunay3, orient_3d};
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector3;
+    use test_case::test_case;
+
+    #[test_case(0)]
+    #[test_case(1)]
+    #[test_case(2)]
+    #[test_case(3)]
+    #[test_case(4)]
+    fn test_hull3(seed: u64) {
+        use std::collections::HashSet;
+
+        let mut gen = degenerate::Generator::new(seed);
+        let points = match seed {
+            0 => grid_points_3d(),
+            1 => gen.coplanar_3d(8),
+            2 => gen.collinear_3d(6),
+            3 => vec![Vector3::new(1.0, 2.0, 3.0); 6],
+            _ => {
+                let points = gen.cospherical_3d(10);
+                gen.with_duplicates(points, 4)
+            }
+        };
+        let faces = super::convex_hull(&points, |l, i| l[i], points.len());
+
+        // A closed surface, with every point inside
+        let edges = faces.iter().flat_map(|f| (0..3).map(move |e| (f[e], f[(e + 1) % 3]))).collect::<HashSet<_>>();
+        assert_eq!(edges.len(), 3 * faces.len());
+        assert!(edges.iter().all(|(i, j)| edges.contains(&(*j, *i))));
+        let vertices = faces.iter().flatten().collect::<HashSet<_>>();
+        assert_eq!(faces.len(), 2 * vertices.len() - 4);
+        for [i, j, k] in &faces {
+            for l in (0..points.len()).filter(|l| ![*i, *j, *k].contains(l)) {
+                assert!(orient_3d(&points, |l, i| l[i], *i, *j, *k, l));
+            }
+        }
+
+        // The same hull as the Delaunay tetrahedralization's
+        let tetrahedra = delaunay3::tetrahedralize(&points, |l, i| l[i], points.len());
+        let tet_faces = tetrahedra.iter().flat_map(|t| delaunay3::faces(*t).to_vec()).map(delaunay3::key).collect::<HashSet<_>>();
+        let hull_faces = tet_faces.iter().filter(|[i, j, k]| !tet_faces.contains(&delaunay3::key([*i, *k, *j]))).copied().collect::<HashSet<_>>();
+        assert_eq!(hull_faces, faces.into_iter().collect());
+    }
+}
@@ -0,0 +1,263 @@
+//! Predicates for hyperbolic Delaunay triangulations, in the Poincaré and Klein disk models.
+//!
+//! Both models put the hyperbolic plane in the open unit disk. In the Klein model, geodesics
+//! are straight chords, so orientation is the usual [`orient_2d`](crate::orient_2d). In the Poincaré
+//! model, they're arcs of circles orthogonal to the boundary, and the orientation of 3 points
+//! is the sign of the determinant with rows (*x*, *y*, 1 + *x*² + *y*²), which is the orientation
+//! of their images in the Klein model, 2**p** / (1 + |**p**|²), times positive factors.
+//!
+//! Hyperbolic circles are Euclidean circles in the Poincaré model, so the in-circle test there
+//! is the Euclidean one. The circle through 3 points can also be a horocycle or a hypercycle,
+//! when the Euclidean circle isn't inside the disk, and then the inside is the part of the disk
+//! inside the Euclidean circle. These tests don't depend on the orientation of the 3 points,
+//! like [`in_circle_unoriented`](crate::in_circle_unoriented), since the orientation of a triangle
+//! and of its circle don't always agree in the hyperbolic plane.
+//!
+//! In the Klein model, the in-circle test is the sign of a sum of square roots, which is
+//! computed exactly by squaring it. The points are perturbed in the coordinates of the model
+//! they're given in, so points on a geodesic and points on a circle still get consistent answers.
+//! Points should be strictly inside the disk.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, hyperbolic};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(-0.5, 0.5),
+//!     Vector2::new(0.0, 0.45),
+//!     Vector2::new(0.5, 0.5),
+//! ];
+//! // The geodesic from the first point to the last bends toward the center, past the middle one
+//! assert!(simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! assert!(!hyperbolic::orient_poincare(&points, |l, i| l[i], 0, 1, 2));
+//!
+//! // The same points in both models
+//! let poincare = vec![
+//!     Vector2::new(0.5, 0.0),
+//!     Vector2::new(0.0, 0.5),
+//!     Vector2::new(-0.5, 0.0),
+//!     Vector2::new(0.0, 0.0),
+//! ];
+//! let klein = poincare.iter().map(|p| p * 2.0 / (1.0 + p.norm_squared())).collect::<Vec<_>>();
+//! assert!(hyperbolic::in_circle_poincare(&poincare, |l, i| l[i], 0, 1, 2, 3));
+//! assert!(hyperbolic::in_circle_klein(&klein, |l, i| l[i], 0, 1, 2, 3));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed, Polynomial};
+use crate::Vec2;
+use alloc::{vec, vec::Vec};
+
+/// The highest degree of the polynomials that the Klein in-circle test takes the signs of.
+const KLEIN_DEGREE: u64 = 24;
+
+/// Terms c√w of a sum, with each w positive.
+type Terms = Vec<(Polynomial, Polynomial)>;
+
+fn constant(x: f64) -> Polynomial {
+    Polynomial::from(Expansion::from(x))
+}
+
+/// The square of a sum of terms, as a sum of terms, with the terms without square roots first.
+fn square(terms: Terms) -> Terms {
+    let rational = terms.iter().fold(Polynomial::default(), |acc, (c, w)| acc + c.clone() * c.clone() * w.clone());
+    let mut result = vec![(rational, constant(1.0))];
+    for (n, (c, w)) in terms.iter().enumerate() {
+        for (c2, w2) in &terms[n + 1..] {
+            result.push(((c.clone() * c2.clone()).scale(2.0), w.clone() * w2.clone()));
+        }
+    }
+    result
+}
+
+/// The sign of the sum of the `n` terms that `terms` computes from the perturbed points.
+/// The sign of a sum of 2 parts with opposite signs is the sign of the first times the sign of
+/// the difference of their squares, which has fewer square roots when the parts are small enough.
+fn sqrt_sum_signum<Idx: Ord + Copy, F: Fn(Idx) -> Vec2>(
+    perturbed: &mut Perturbed<Idx, F>,
+    n: usize,
+    terms: &dyn Fn(&Perturbed<Idx, F>) -> Terms,
+) -> f64 {
+    if n == 1 {
+        return perturbed.lazy_signum(|p| terms(p).swap_remove(0).0);
+    }
+    let half = n / 2;
+    let first = |p: &Perturbed<Idx, F>| terms(p)[..half].to_vec();
+    let second = |p: &Perturbed<Idx, F>| terms(p)[half..].to_vec();
+    let (a, b) = (sqrt_sum_signum(perturbed, half, &first), sqrt_sum_signum(perturbed, n - half, &second));
+    if b == 0.0 || a == b {
+        return a;
+    }
+    if a == 0.0 {
+        return b;
+    }
+    let difference = |p: &Perturbed<Idx, F>| {
+        let (mut first, second) = (square(first(p)), square(second(p)));
+        first[0].0 = core::mem::take(&mut first[0].0) - second[0].0.clone();
+        first.extend(second.into_iter().skip(1).map(|(c, w)| (-c, w)));
+        first
+    };
+    let pairs = |n: usize| n * (n - 1) / 2;
+    a * sqrt_sum_signum(perturbed, 1 + pairs(half) + pairs(n - half), &difference)
+}
+
+/// Returns whether the geodesic triangle `ijk` in the Poincaré disk is oriented positive
+/// after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, and 3 indexes.
+/// The points are perturbed in their Poincaré coordinates.
+pub fn orient_poincare<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, i: Idx, j: Idx, k: Idx) -> bool {
+    let perturbed = Perturbed::new(2, 4, &[i, j, k], |i| index_fn(list, i));
+    let row = |i| {
+        let (x, y) = (perturbed.coord(i, 0), perturbed.coord(i, 1));
+        let lift = constant(1.0) + x.clone() * x.clone() + y.clone() * y.clone();
+        vec![x, y, lift]
+    };
+    perturbed::det(&[row(i), row(j), row(k)]).signum() > 0.0
+}
+
+/// Returns whether the geodesic triangle `ijk` in the Klein disk is oriented positive
+/// after perturbing the points. Geodesics are straight in the Klein model,
+/// so this is [`orient_2d`](crate::orient_2d).
+pub fn orient_klein<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, i: Idx, j: Idx, k: Idx) -> bool {
+    crate::orient_2d(list, index_fn, i, j, k)
+}
+
+/// Returns whether `l` is inside the circle, horocycle, or hypercycle through `i`, `j`, and `k`
+/// in the Poincaré disk after perturbing the points. That's the Euclidean circle through them,
+/// so this is [`in_circle_unoriented`](crate::in_circle_unoriented).
+///
+/// Takes a list of all the points, an indexing function, and 4 indexes.
+pub fn in_circle_poincare<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::in_circle_unoriented(list, index_fn, i, j, k, l)
+}
+
+/// Returns whether `l` is inside the circle, horocycle, or hypercycle through `i`, `j`, and `k`
+/// in the Klein disk after perturbing the points. It's inside if it's inside
+/// in the Poincaré disk, with the points mapped there, so this agrees with
+/// [`in_circle_poincare`] on non-degenerate input.
+///
+/// Takes a list of all the points, an indexing function, and 4 indexes.
+pub fn in_circle_klein<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    // A point k of the Klein disk maps to k / (1 + s) in the Poincaré disk, with s = √(1 - |k|²),
+    // and the squared norm of that is (1 - s) / (1 + s). Multiplying each row of the in-circle
+    // determinant there by 1 + s and combining the last 2 columns makes the rows (k_x, k_y, 1, s),
+    // and the orientation there has rows (k_x, k_y, 1 + s).
+    let mut perturbed = Perturbed::new(2, KLEIN_DEGREE, &[i, j, k, l], |i| index_fn(list, i));
+    let radicand = |p: &Perturbed<Idx, _>, i| {
+        let (x, y) = (p.coord(i, 0), p.coord(i, 1));
+        constant(1.0) - x.clone() * x - y.clone() * y
+    };
+    // The cofactors of the last column of the matrix with rows (k_x, k_y, 1, ...) or (k_x, k_y, ...)
+    let cofactors = |p: &Perturbed<Idx, _>, points: &[Idx]| {
+        let n = points.len();
+        let row = |i| vec![p.coord(i, 0), p.coord(i, 1), constant(1.0)][..n - 1].to_vec();
+        (0..n)
+            .map(|m| {
+                let minor = perturbed::det(&points.iter().enumerate().filter(|(r, _)| *r != m).map(|(_, i)| row(*i)).collect::<Vec<_>>());
+                if (n - 1 + m) % 2 == 1 {
+                    -minor
+                } else {
+                    minor
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let in_circle = sqrt_sum_signum(&mut perturbed, 4, &|p| {
+        let points = [i, j, k, l];
+        cofactors(p, &points).into_iter().zip(points.iter()).map(|(c, i)| (c, radicand(p, *i))).collect()
+    });
+    let orientation = sqrt_sum_signum(&mut perturbed, 4, &|p| {
+        let points = [i, j, k];
+        let cofactors = cofactors(p, &points);
+        let rational = cofactors.iter().fold(Polynomial::default(), |acc, c| acc + c.clone());
+        let mut terms = vec![(rational, constant(1.0))];
+        terms.extend(cofactors.into_iter().zip(points.iter()).map(|(c, i)| (c, radicand(p, *i))));
+        terms
+    });
+    in_circle * orientation > 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{rg, rng};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_hyperbolic() {
+        let klein = |p: Vec2| p * 2.0 / (1.0 + p.norm_squared());
+        let distance = |u: Vec2, v: Vec2| (1.0 + 2.0 * (u - v).norm_squared() / ((1.0 - u.norm_squared()) * (1.0 - v.norm_squared()))).acosh();
+        // The isometry of the Poincaré disk that takes 0 to c
+        let translate = |z: Vec2, c: Vec2| {
+            let (num, den) = (z + c, Vector2::new(1.0 + c.x * z.x + c.y * z.y, c.x * z.y - c.y * z.x));
+            Vector2::new(num.x * den.x + num.y * den.y, num.y * den.x - num.x * den.y) / den.norm_squared()
+        };
+
+        let mut rng = rng::SplitMix(1479);
+        let mut random = |scale: f64| (rng.next() as f64 / u64::MAX as f64 * 2.0 - 1.0) * scale;
+        for _ in 0..40 {
+            // 3 points on a circle around c, and 1 more
+            let (c, radius) = (Vector2::new(random(0.5), random(0.5)), random(1.0).abs() + 0.1);
+            let mut points = (0..3)
+                .map(|_| {
+                    let angle = random(std::f64::consts::PI);
+                    translate(Vector2::new(angle.cos(), angle.sin()) * (radius / 2.0).tanh(), c)
+                })
+                .collect::<Vec<_>>();
+            points.push(Vector2::new(random(0.6), random(0.6)));
+            let images = points.iter().map(|p| klein(*p)).collect::<Vec<_>>();
+            let on_circle = points[..3].iter().map(|p| distance(c, *p)).sum::<f64>() / 3.0;
+            if (distance(c, points[3]) - on_circle).abs() < 1e-6 {
+                continue;
+            }
+            let inside = distance(c, points[3]) < on_circle;
+            assert_eq!(super::in_circle_poincare(&points, |l, i| l[i], 0, 1, 2, 3), inside);
+            assert_eq!(super::in_circle_klein(&images, |l, i| l[i], 0, 1, 2, 3), inside);
+            assert_eq!(super::in_circle_klein(&images, |l, i| l[i], 1, 0, 2, 3), inside);
+        }
+        for _ in 0..40 {
+            // Horocycles and hypercycles too
+            let points = (0..4).map(|_| Vector2::new(random(0.7), random(0.7))).collect::<Vec<_>>();
+            let images = points.iter().map(|p| klein(*p)).collect::<Vec<_>>();
+            if rg::orient_2d(images[0], images[1], images[2]).abs() > 1e-9 {
+                let ccw = rg::orient_2d(images[0], images[1], images[2]) > 0.0;
+                assert_eq!(super::orient_poincare(&points, |l, i| l[i], 0, 1, 2), ccw);
+                assert_eq!(super::orient_klein(&images, |l, i| l[i], 0, 1, 2), ccw);
+            }
+            if rg::in_circle(points[0], points[1], points[2], points[3]).abs() > 1e-9 {
+                let inside = super::in_circle_poincare(&points, |l, i| l[i], 0, 1, 2, 3);
+                assert_eq!(super::in_circle_klein(&images, |l, i| l[i], 0, 1, 2, 3), inside);
+            }
+        }
+
+        // On a diameter, and on a circle around the center, in both models
+        let points = vec![Vector2::new(-0.5, 0.0), Vector2::new(0.0, 0.0), Vector2::new(0.5, 0.0), Vector2::new(0.0, 0.5), Vector2::new(0.0, -0.5)];
+        let images = points.iter().map(|p| klein(*p)).collect::<Vec<_>>();
+        for (i, j, k) in [(0, 1, 2), (3, 1, 4), (0, 3, 2)].iter().copied() {
+            let ccw = super::orient_poincare(&points, |l, i| l[i], i, j, k);
+            assert_eq!(super::orient_poincare(&points, |l, i| l[i], j, k, i), ccw);
+            assert_ne!(super::orient_poincare(&points, |l, i| l[i], j, i, k), ccw);
+        }
+        let inside = super::in_circle_klein(&images, |l, i| l[i], 0, 2, 3, 4);
+        assert_eq!(super::in_circle_klein(&images, |l, i| l[i], 2, 0, 3, 4), inside);
+        assert_eq!(super::in_circle_klein(&images, |l, i| l[i], 2, 3, 0, 4), inside);
+        assert!(super::in_circle_klein(&images, |l, i| l[i], 0, 2, 3, 1));
+    }
+}
@@ -0,0 +1,389 @@
+//! `const fn` predicates for points with small integer coordinates.
+//!
+//! These take the points as `i16` arrays and compute with `i128`, which fits every determinant
+//! exactly, so they can run at compile time to build tables, like the cases of marching squares,
+//! with the same results the usual predicates give for the same points at runtime.
+//! Degenerate cases are broken the same way too, in the order of the indexes.
+//!
+//! Without closures in `const fn`, the points come as a slice and the indexes are positions in it.
+//!
+//! The perturbed determinant is a polynomial in the perturbations of the coordinates,
+//! and each of its coefficients is the determinant with each point's row replaced by
+//! a derivative of it, so these look for the first nonzero one in the order of the monomials.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, integer};
+//! # use nalgebra::Vector2;
+//! const SQUARE: [[i16; 2]; 4] = [[0, 0], [1, 0], [1, 1], [0, 1]];
+//! // Whether each corner is inside the circle through the other 3, decided at compile time
+//! const INSIDE: [bool; 4] = [
+//!     integer::in_circle(&SQUARE, 1, 2, 3, 0),
+//!     integer::in_circle(&SQUARE, 0, 2, 3, 1),
+//!     integer::in_circle(&SQUARE, 0, 1, 3, 2),
+//!     integer::in_circle(&SQUARE, 0, 1, 2, 3),
+//! ];
+//! let square = SQUARE.map(|[x, y]| Vector2::new(x.into(), y.into()));
+//! assert_eq!(INSIDE[3], simplicity::in_circle(&square, |l, i| l[i], 0, 1, 2, 3));
+//! ```
+//!
+//! Points computed at runtime can use these predicates too, if their coordinates are on a common
+//! grid. [`compress`] finds the coarsest such grid and maps the points to it, or reports why
+//! it can't. Translating and scaling points by a positive factor doesn't change any predicate
+//! results, so the compressed points get the same results as the original ones.
+
+use crate::exact::Expansion;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::ops::Index;
+
+/// The most points a predicate takes.
+const MAX: usize = 4;
+
+type Matrix = [[i128; MAX]; MAX];
+
+/// The determinant of the first `n` rows and columns of `m`, leaving out the rows before `row`
+/// and the columns in `used`, by Laplace expansion along the first row.
+const fn det(m: &Matrix, n: usize, row: usize, used: u32) -> i128 {
+    if row == n {
+        return 1;
+    }
+    let mut sum = 0;
+    let mut sign = 1;
+    let mut c = 0;
+    while c < n {
+        if used & (1 << c) == 0 {
+            if m[row][c] != 0 {
+                sum += sign * m[row][c] * det(m, n, row + 1, used | (1 << c));
+            }
+            sign = -sign;
+        }
+        c += 1;
+    }
+    sum
+}
+
+/// The sign of the determinant with a row (**p**, 1) for each point, or (**p**, |**p**|², 1)
+/// if `lifted`, after perturbing the points, as -1 or 1, or 0 if an index is repeated.
+const fn evaluate<const D: usize>(points: &[[i16; D]], indexes: &[usize], lifted: bool) -> i128 {
+    let n = indexes.len();
+    // Each perturbation shows up squared at most in lifted rows, and only once otherwise
+    let base: usize = if lifted { 3 } else { 2 };
+    let monomials = base.pow((D * n) as u32);
+    let mut monomial = 0;
+    while monomial < monomials {
+        let mut matrix = [[0i128; MAX]; MAX];
+        let mut nonzero = true;
+        let mut a = 0;
+        while a < n {
+            let mut rank = 0;
+            let mut b = 0;
+            while b < n {
+                if indexes[b] < indexes[a] {
+                    rank += 1;
+                }
+                b += 1;
+            }
+
+            // The coordinate whose perturbation is in the monomial, and its power
+            let (mut coord, mut power) = (D, 0);
+            let mut c = 0;
+            while c < D {
+                let digit = monomial / base.pow((D * rank + D - 1 - c) as u32) % base;
+                if digit != 0 {
+                    // The derivative of the row by 2 perturbations is 0
+                    nonzero &= coord == D;
+                    coord = c;
+                    power = digit;
+                }
+                c += 1;
+            }
+
+            let p = points[indexes[a]];
+            let mut c = 0;
+            while c < D {
+                let x = p[c] as i128;
+                if coord == D {
+                    matrix[a][c] = x;
+                    if lifted {
+                        matrix[a][D] += x * x;
+                    }
+                } else if c == coord && power == 1 {
+                    matrix[a][c] = 1;
+                    if lifted {
+                        matrix[a][D] = 2 * x;
+                    }
+                } else if c == coord {
+                    matrix[a][D] = 1;
+                }
+                c += 1;
+            }
+            matrix[a][n - 1] = if coord == D { 1 } else { 0 };
+            a += 1;
+        }
+
+        if nonzero {
+            let d = det(&matrix, n, 0, 0);
+            if d != 0 {
+                return d.signum();
+            }
+        }
+        monomial += 1;
+    }
+    0
+}
+
+/// Like [`orient_1d`](crate::orient_1d), for integer points given as a slice.
+pub const fn orient_1d(points: &[[i16; 1]], i: usize, j: usize) -> bool {
+    evaluate(points, &[i, j], false) > 0
+}
+
+/// Like [`orient_2d`](crate::orient_2d), for integer points given as a slice.
+pub const fn orient_2d(points: &[[i16; 2]], i: usize, j: usize, k: usize) -> bool {
+    evaluate(points, &[i, j, k], false) > 0
+}
+
+/// Like [`orient_3d`](crate::orient_3d), for integer points given as a slice.
+pub const fn orient_3d(points: &[[i16; 3]], i: usize, j: usize, k: usize, l: usize) -> bool {
+    evaluate(points, &[i, j, k, l], false) > 0
+}
+
+/// Like [`in_circle`](crate::in_circle), for integer points given as a slice.
+pub const fn in_circle(points: &[[i16; 2]], i: usize, j: usize, k: usize, l: usize) -> bool {
+    evaluate(points, &[i, j, k, l], true) > 0
+}
+
+/// Points mapped to a grid by [`compress`]. Coordinate *c* of point *i* of the original points
+/// is exactly `offset[c] + scale * points[i][c]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<const D: usize> {
+    /// The smallest coordinate along each axis
+    pub offset: [f64; D],
+    /// The spacing of the grid, the same along every axis
+    pub scale: f64,
+    /// The points, as steps along the grid from the offset
+    pub points: Vec<[i16; D]>,
+}
+
+/// Why points couldn't be compressed, with the index of the first point responsible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CompressError {
+    /// A point has an infinite or NaN coordinate
+    NonFinite(usize),
+    /// A point is too many steps of the grid away from the offset to fit in an `i16`
+    OutOfRange(usize),
+}
+
+impl Display for CompressError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CompressError::NonFinite(i) => write!(f, "point {} has a coordinate that is not finite", i),
+            CompressError::OutOfRange(i) => write!(f, "point {} is too far from the others to fit on a grid", i),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CompressError {}
+
+/// Splits a positive finite number into an odd integer and a power of 2.
+fn odd_part(x: f64) -> (u64, i32) {
+    let bits = x.to_bits();
+    let (mantissa, exponent) = match (bits >> 52) as i32 {
+        0 => (bits, -1074),
+        e => (bits & ((1 << 52) - 1) | 1 << 52, e - 1075),
+    };
+    let zeros = mantissa.trailing_zeros();
+    (mantissa >> zeros, exponent + zeros as i32)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Maps points to the coarsest grid their coordinates are on, for the predicates in this module.
+///
+/// Takes a list of all the points, an indexing function, and the number of points.
+/// The grid has the same spacing along every axis, since scaling axes by different factors
+/// changes in-circle results, and each axis starts at its smallest coordinate.
+/// The predicates in this module on the compressed points give the same results as the usual
+/// predicates on the original points with the same indexes. Fails if a coordinate isn't finite
+/// or a point is more than 32767 steps from the offset along some axis.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, integer::{self, CompressError}};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(100.25, -3.0),
+///     Vector2::new(100.75, -2.5),
+///     Vector2::new(101.5, -2.0),
+/// ];
+/// let grid = integer::compress(&points, |l, i| l[i], points.len()).unwrap();
+/// assert_eq!(grid.scale, 0.25);
+/// assert_eq!(grid.points, vec![[0, 0], [2, 2], [5, 4]]);
+/// assert_eq!(integer::orient_2d(&grid.points, 0, 1, 2), simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+///
+/// // 0.1 isn't a dyadic fraction, so its grid is too fine to reach 1
+/// let points = vec![Vector2::new(0.0, 0.0), Vector2::new(0.1, 1.0)];
+/// let result: Result<integer::Grid<2>, _> = integer::compress(&points, |l, i| l[i], points.len());
+/// assert_eq!(result, Err(CompressError::OutOfRange(1)));
+/// ```
+pub fn compress<T: ?Sized, P: Index<usize, Output = f64>, const D: usize>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> P,
+    len: usize,
+) -> Result<Grid<D>, CompressError> {
+    let mut offset = [f64::INFINITY; D];
+    for i in 0..len {
+        let p = index_fn(list, i);
+        for c in 0..D {
+            if !p[c].is_finite() {
+                return Err(CompressError::NonFinite(i));
+            }
+            offset[c] = offset[c].min(p[c]);
+        }
+    }
+
+    // The differences from the offset, which are out of range if they aren't exact
+    let mut diffs = Vec::with_capacity(len);
+    for i in 0..len {
+        let p = index_fn(list, i);
+        let mut diff = [0.0; D];
+        for c in 0..D {
+            diff[c] = match (Expansion::from(p[c]) + Expansion::from(-offset[c])).components() {
+                [] => 0.0,
+                [d] => *d,
+                _ => return Err(CompressError::OutOfRange(i)),
+            };
+        }
+        diffs.push(diff);
+    }
+
+    // Every nonzero difference is an odd integer times a power of 2, so the spacing is
+    // the gcd of the odd integers times the smallest power of 2
+    let (odd, exponent) = diffs
+        .iter()
+        .flatten()
+        .filter(|d| **d != 0.0)
+        .map(|d| odd_part(*d))
+        .fold((0, i32::MAX), |(g, e), (m, f)| (gcd(g, m), e.min(f)));
+    let mut scale = 1.0;
+    let mut points = Vec::with_capacity(len);
+    for (i, diff) in diffs.iter().enumerate() {
+        let mut point = [0; D];
+        for c in 0..D {
+            if diff[c] == 0.0 {
+                continue;
+            }
+            let (m, e) = odd_part(diff[c]);
+            let shift = (e - exponent) as u32;
+            let steps = (m / odd).checked_shl(shift).filter(|s| shift < 16 && *s <= i16::MAX as u64);
+            point[c] = steps.ok_or(CompressError::OutOfRange(i))? as i16;
+            // Exact, since the spacing is representable and divides the difference
+            scale = diff[c] / point[c] as f64;
+        }
+        points.push(point);
+    }
+
+    if len == 0 {
+        offset = [0.0; D];
+    }
+    Ok(Grid { offset, scale, points })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, orient_1d, orient_2d, orient_3d, rng};
+    use crate::tests::grid_points_3d;
+    use nalgebra::{Vector1, Vector2, Vector3};
+
+    #[test]
+    fn test_integer() {
+        let mut rng = rng::SplitMix(1488);
+        // Few distinct coordinates, so there are duplicates and every kind of degeneracy
+        let ints_2d = (0..8).map(|_| [rng.below(3) as i16 - 1, rng.below(3) as i16 - 1]).collect::<Vec<_>>();
+        let points = ints_2d.iter().map(|[x, y]| Vector2::new(f64::from(*x), f64::from(*y))).collect::<Vec<_>>();
+        let ints_1d = ints_2d.iter().map(|[x, _]| [*x]).collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                assert_eq!(
+                    super::orient_1d(&ints_1d, i, j),
+                    orient_1d(&points, |l, i| Vector1::new(l[i].x), i, j)
+                );
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    assert_eq!(super::orient_2d(&ints_2d, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+                    for l in (0..n).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(super::in_circle(&ints_2d, i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+                    }
+                }
+            }
+        }
+
+        let ints_3d = (0..10).map(|_| [rng.below(2) as i16, rng.below(2) as i16, rng.below(3) as i16 - 1]).collect::<Vec<_>>();
+        let points = ints_3d.iter().map(|p| Vector3::new(f64::from(p[0]), f64::from(p[1]), f64::from(p[2]))).collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    for l in (k + 1..n).filter(|l| *l != i && *l != j) {
+                        assert_eq!(super::orient_3d(&ints_3d, i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+                    }
+                }
+            }
+        }
+
+        // Large coordinates still fit
+        let far = [[i16::MIN, i16::MIN], [i16::MAX, i16::MIN], [i16::MAX, i16::MAX], [i16::MIN, i16::MAX]];
+        let points = far.iter().map(|[x, y]| Vector2::new(f64::from(*x), f64::from(*y))).collect::<Vec<_>>();
+        assert_eq!(super::in_circle(&far, 0, 1, 2, 3), in_circle(&points, |l, i| l[i], 0, 1, 2, 3));
+        assert_eq!(super::in_circle(&far, 3, 1, 2, 0), in_circle(&points, |l, i| l[i], 3, 1, 2, 0));
+    }
+
+    #[test]
+    fn test_integer_compress() {
+        let mut rng = rng::SplitMix(1492);
+        // A grid with spacing 3/8, off the origin, with the smallest step along x
+        let steps = (0..8).map(|i| [i.min(1) + 3 * rng.below(4) as i16, 2 * rng.below(4) as i16]).collect::<Vec<_>>();
+        let points = steps
+            .iter()
+            .map(|[x, y]| Vector2::new(100.5 + 0.375 * f64::from(*x), -7.25 + 0.375 * f64::from(*y)))
+            .collect::<Vec<_>>();
+        let grid = super::compress(&points, |l, i| l[i], points.len()).unwrap();
+        assert_eq!(grid.scale, 0.375);
+        for (p, q) in points.iter().zip(&grid.points) {
+            assert_eq!(*p, Vector2::new(grid.offset[0] + grid.scale * f64::from(q[0]), grid.offset[1] + grid.scale * f64::from(q[1])));
+        }
+        for _ in 0..200 {
+            let (i, j, k, l) = (rng.below(8), rng.below(8), rng.below(8), rng.below(8));
+            if i == j || i == k || i == l || j == k || j == l || k == l {
+                continue;
+            }
+            assert_eq!(super::orient_2d(&grid.points, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+            assert_eq!(super::in_circle(&grid.points, i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+        }
+
+        let points = grid_points_3d();
+        let grid = super::compress(&points, |l, i| l[i], points.len()).unwrap();
+        assert_eq!(super::orient_3d(&grid.points, 0, 1, 2, 3), orient_3d(&points, |l, i| l[i], 0, 1, 2, 3));
+
+        // Points that aren't on a common grid, or don't fit
+        let mut points = vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 1.0), Vector2::new(f64::NAN, 0.0)];
+        let compress = |points: &Vec<Vector2<f64>>| super::compress::<_, _, 2>(points, |l, i| l[i], points.len());
+        assert_eq!(compress(&points), Err(super::CompressError::NonFinite(2)));
+        points[2] = Vector2::new(0.0, 32768.0);
+        assert_eq!(compress(&points), Err(super::CompressError::OutOfRange(2)));
+        points[2] = Vector2::new(1e-300, 0.0);
+        assert_eq!(compress(&points), Err(super::CompressError::OutOfRange(1)));
+        points[2] = Vector2::new(0.0, 32767.0);
+        assert_eq!(compress(&points).unwrap().points[2], [0, 32767]);
+    }
+}
@@ -0,0 +1,122 @@
+//! Splitting points for k-d trees consistently with the predicates.
+//!
+//! A k-d tree splits its points at the median along an axis, and a point with the same
+//! coordinate as the splitting point could go on either side. Breaking that tie arbitrarily,
+//! or by putting it on both sides, can disagree with the predicates, which see every point
+//! perturbed off the splitting plane to one definite side. [`axis_cmp`] breaks ties the way
+//! the perturbation does, like [`orient_1d`](crate::orient_1d): a lower index is perturbed
+//! further in the positive direction. So a point is on the side of every splitting plane
+//! that the predicates would put it on, and only the splitting point itself is on its plane.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, kdtree};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(1.0, 2.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! let mut indexes = vec![0, 1, 2, 3, 4];
+//! // Points 0, 2, and 4 tie along x, and the lowest index is perturbed the most in +x
+//! let median = kdtree::median_split(&points, |l, i| l[i], 0, &mut indexes);
+//! assert_eq!(indexes[median], 2);
+//! assert_eq!(indexes[..median].iter().filter(|i| [1, 4].contains(i)).count(), 2);
+//! ```
+
+use core::cmp::Ordering;
+use core::ops::Index;
+
+/// Compares coordinate `axis` of points `i` and `j` after perturbing them.
+/// Returns `Ordering::Equal` only if `i` and `j` are the same index.
+///
+/// Takes a list of all the points, an indexing function that returns something that can be
+/// indexed by axis, like a vector, the axis, and 2 indexes. Panics if a coordinate is NaN.
+pub fn axis_cmp<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    axis: usize,
+    i: Idx,
+    j: Idx,
+) -> Ordering {
+    let (pi, pj) = (index_fn(list, i)[axis], index_fn(list, j)[axis]);
+    pi.partial_cmp(&pj).expect("coordinates shouldn't be NaN").then_with(|| j.cmp(&i))
+}
+
+/// Partitions `indexes` around the median of the points along `axis` after perturbing them,
+/// and returns its position. Every point before it is less than it along `axis`,
+/// and every point after it is greater, by [`axis_cmp`].
+///
+/// Panics if `indexes` is empty.
+pub fn median_split<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    axis: usize,
+    indexes: &mut [Idx],
+) -> usize {
+    assert!(!indexes.is_empty(), "there are no points to split");
+    let median = indexes.len() / 2;
+    indexes.select_nth_unstable_by(median, |i, j| axis_cmp(list, &index_fn, axis, *i, *j));
+    median
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec1, orient_1d, rng};
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_kdtree() {
+        use std::cmp::Ordering;
+
+        let points = grid_points_3d();
+        for axis in 0..3 {
+            let coord = |l: &Vec<Vector3<f64>>, i: usize| Vec1::new(l[i][axis]);
+            for i in 0..points.len() {
+                for j in 0..points.len() {
+                    let expected = if i == j {
+                        Ordering::Equal
+                    } else if orient_1d(&points, coord, i, j) {
+                        Ordering::Greater
+                    } else {
+                        Ordering::Less
+                    };
+                    assert_eq!(super::axis_cmp(&points, |l, i| l[i], axis, i, j), expected);
+                }
+            }
+        }
+
+        // Build a whole tree, and check every point against every splitting point above it
+        fn build(points: &[Vector3<f64>], indexes: &mut [usize], depth: usize, splits: &mut Vec<(usize, usize, Vec<usize>, Vec<usize>)>) {
+            if indexes.is_empty() {
+                return;
+            }
+            let axis = depth % 3;
+            let median = super::median_split(points, |l, i| l[i], axis, indexes);
+            let (below, rest) = indexes.split_at_mut(median);
+            let (split, above) = rest.split_first_mut().unwrap();
+            splits.push((*split, axis, below.to_vec(), above.to_vec()));
+            build(points, below, depth + 1, splits);
+            build(points, above, depth + 1, splits);
+        }
+
+        let mut rng = rng::SplitMix(1475);
+        let points = (0..40).map(|_| grid_points_3d()[rng.below(10)]).collect::<Vec<_>>();
+        let mut indexes = (0..points.len()).collect::<Vec<_>>();
+        let mut splits = vec![];
+        build(&points, &mut indexes, 0, &mut splits);
+        assert_eq!(splits.len(), points.len());
+        for (split, axis, below, above) in splits {
+            for i in below {
+                assert_eq!(super::axis_cmp(&points, |l, i| l[i], axis, i, split), Ordering::Less);
+            }
+            for i in above {
+                assert_eq!(super::axis_cmp(&points, |l, i| l[i], axis, i, split), Ordering::Greater);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+/// An index with a key that decides where its point goes in the perturbation order.
+///
+/// The predicates perturb points in the order of their indexes, so the answers for degenerate
+/// input change if the points get renumbered, such as when a mesh compacts its vertices.
+/// Keyed indexes compare by key first, so passing them instead of plain indexes
+/// ties the perturbation to something stable, like vertex IDs or a rank array.
+/// Equal keys fall back to comparing indexes, so keys can also be the ranks of groups of points.
+/// Then ties are broken by group first and by index within each group, and the points of
+/// a group with a lower rank are perturbed more than all the points of groups with higher ranks.
+///
+/// # Examples
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d, Keyed};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 0.0),
+///     Vector2::new(2.0, 0.0),
+/// ];
+/// let ids = [30, 20, 10];
+/// let key = |i| Keyed::new(ids[i], i);
+/// // The same as reversing the list, since the IDs are in reverse
+/// let reversed = points.iter().rev().copied().collect::<Vec<_>>();
+/// assert_eq!(
+///     orient_2d(&points, |l, k: Keyed<i32>| l[k.index], key(0), key(1), key(2)),
+///     orient_2d(&reversed, |l, i| l[i], 2, 1, 0),
+/// );
+/// ```
+///
+/// Merging a background grid with refinement points, so the grid points decide every tie
+/// between a grid point and a refinement point:
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d, Keyed};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(1.0, 0.0), // Refinement
+///     Vector2::new(0.0, 0.0), // Grid
+///     Vector2::new(2.0, 0.0), // Grid
+/// ];
+/// let group = |i| Keyed::new(if i == 0 { 1 } else { 0 }, i);
+/// let grouped = |l: &Vec<_>, k: Keyed<u8>| l[k.index];
+/// // The same as putting the grid points first
+/// let grid_first = vec![points[1], points[2], points[0]];
+/// assert_eq!(
+///     orient_2d(&points, grouped, group(0), group(1), group(2)),
+///     orient_2d(&grid_first, |l, i| l[i], 2, 0, 1),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Keyed<K> {
+    pub key: K,
+    pub index: usize,
+}
+
+impl<K> Keyed<K> {
+    pub fn new(key: K, index: usize) -> Self {
+        Self { key, index }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Vec2, in_circle, orient_2d};
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_keyed() {
+        // Keys that reorder the points the same way as sorting them by key
+        let points = grid_points_2d();
+        let n = points.len();
+        let keys = (0..n).map(|i| (i * 7 % n) as u64).collect::<Vec<_>>();
+        let mut sorted = vec![Vector2::zeros(); n];
+        for i in 0..n {
+            sorted[keys[i] as usize] = points[i];
+        }
+        let key = |i| Keyed::new(keys[i], i);
+        let rank = |i| keys[i] as usize;
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        orient_2d(&points, |l, k: Keyed<u64>| l[k.index], key(i), key(j), key(k)),
+                        orient_2d(&sorted, |l, i| l[i], rank(i), rank(j), rank(k))
+                    );
+                    for l in (0..n).filter(|l| *l != i && *l != j && *l != k).take(3) {
+                        assert_eq!(
+                            in_circle(&points, |l, k: Keyed<u64>| l[k.index], key(i), key(j), key(k), key(l)),
+                            in_circle(&sorted, |l, i| l[i], rank(i), rank(j), rank(k), rank(l))
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keyed_groups() {
+        // Every third point is in a refinement group, which goes after the grid
+        let points = grid_points_2d();
+        let n = points.len();
+        let group = |i: usize| u8::from(i % 3 == 1);
+        let mut order = (0..n).collect::<Vec<_>>();
+        order.sort_by_key(|i| (group(*i), *i));
+        let mut rank = vec![0; n];
+        for (r, i) in order.iter().enumerate() {
+            rank[*i] = r;
+        }
+        let sorted = order.iter().map(|i| points[*i]).collect::<Vec<_>>();
+
+        let key = |i| Keyed::new(group(i), i);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        orient_2d(&points, |l, k: Keyed<u8>| l[k.index], key(i), key(j), key(k)),
+                        orient_2d(&sorted, |l, i| l[i], rank[i], rank[j], rank[k])
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_composite_indexes() {
+        // Merging 2 degenerate point sets keeps the answers within each
+        let sets = vec![grid_points_2d(), grid_points_2d().into_iter().rev().collect::<Vec<_>>()];
+        let merged = |l: &Vec<Vec<Vec2>>, (d, i): (usize, usize)| l[d][i];
+        let n = sets[0].len();
+        for (d, set) in sets.iter().enumerate() {
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        assert_eq!(
+                            orient_2d(&sets, merged, (d, i), (d, j), (d, k)),
+                            orient_2d(set, |l, i| l[i], i, j, k)
+                        );
+                    }
+                }
+            }
+        }
+
+        // Across sets, the first set's points are perturbed more, like earlier indexes
+        let concatenated = sets.concat();
+        for i in 0..n {
+            for j in 0..n {
+                for k in (0..n).filter(|k| *k != j) {
+                    assert_eq!(
+                        orient_2d(&sets, merged, (0, i), (1, j), (1, k)),
+                        orient_2d(&concatenated, |l, i| l[i], i, n + j, n + k)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -7,6 +7,9 @@
 //! in ε, a sufficiently small positive number.
 //! Specifically, coordinate *p\_(i,j)* is perturbed by ε^(3^(*d*\**i* - *j*)),
 //! where *d* is more than the number of dimensions.
+//! The signs don't depend on *d*. Any choice orders the ε-monomials the same way,
+//! by the index of the point and then the coordinate, so the predicates agree with other
+//! implementations that chose a different *d* and don't need it as a parameter.
 //!
 //! # Predicates
 //!
@@ -21,7 +24,8 @@
 //! from **p**_0. In particular, the orientation of 3 points in 2-dimensional space
 //! is positive iff they form a left turn.
 //!
-//! Orientation predicates for 1, 2, and 3 dimensions are implemented.
+//! Orientation predicates for 1, 2, and 3 dimensions are implemented,
+//! and for 4 and 5 dimensions with the `high-dim` feature.
 //! They return whether the orientation is positive.
 //!
 //! ## In Hypersphere
@@ -34,6 +38,63 @@
 //! the sphere that goes through the first 4 points. Those 4 points
 //! are not coplanar because of the perturbations.
 //!
+//! With the `high-dim` feature, in-hypersphere predicates for 4 and 5 dimensions
+//! are implemented too.
+//!
+//! # Tiny Coordinates
+//!
+//! The exact arithmetic behind the predicates multiplies coordinate differences together,
+//! and products that underflow lose bits, so points with subnormal coordinates or coordinates
+//! around 1e-300 could look degenerate when they aren't. The predicates scale their points
+//! by a power of 2 first when the largest coordinate is below 2^-64. That's exact, and
+//! it doesn't change any signs, so such inputs get the same results as the scaled-up points.
+//! So do the `_strict` predicates, the [`diagnostics`], [`cgal`], and [`analysis`],
+//! which report a tie only when the scaled-up points have one.
+//! The scale comes from the largest coordinate, so a predicate on points that are mostly tiny
+//! but include one far from the origin can still underflow.
+//!
+//! # Other Tie-Breaking Schemes
+//!
+//! Some modules have variants of the predicates that resolve degeneracies differently,
+//! but just as consistently:
+//!
+//! * [`reversed`] perturbs by -ε, which flips every tie decided by a term of odd degree in ε,
+//!   such as a tie between 2 equal numbers or between distinct collinear points.
+//! * [`descending`] perturbs higher indexes more, and [`shuffled`] perturbs the points in
+//!   a pseudorandom order, which change which point decides a tie.
+//! * [`axes`] perturbs the axes in a chosen order.
+//! * [`hashed`] perturbs the points in the order of a hash of their coordinates,
+//!   so the ties don't depend on the order the points are listed in.
+//!
+//! No consistent scheme flips every tie, since flipping the sign of ε flips the ties between
+//! distinct collinear points but not among 3 equal points, and reflecting the perturbations
+//! does the opposite. To check that a structure doesn't depend on the tie-breaking, build it
+//! with [`reversed`] and with [`descending`] too, and compare.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature, `orient_2d`, `orient_3d`, `in_circle`, `in_sphere`,
+//! and their variants emit a `DEBUG` event when their input is degenerate
+//! and the sign is decided by the perturbation, recording the predicate,
+//! the points, and the deciding case as returned by the [`diagnostics`] functions.
+//! Indexes aren't required to implement `Debug`, and requiring it only with the feature
+//! would make turning it on break callers, so the events don't record the indexes themselves.
+//! They record the rank of each index among the call's indexes instead, which is the order
+//! the points are perturbed in.
+//! The `coverage` module has a subscriber that counts which cases were reached.
+//!
+//! # `no_std`
+//!
+//! Targets without `std` aren't supported yet. `robust-geo` 0.1.7, which the predicates call
+//! for their leading determinants, links `std` and turns on `nalgebra`'s default features,
+//! so those targets are blocked on a `no_std` release of it.
+//! Turning off the default `std` feature only keeps this crate's own code to `core` and `alloc`,
+//! which CI checks, so that support can follow once `robust-geo` allows it.
+//! Without `std`, the crate leaves out the incremental triangulations
+//! in `delaunay2` and `delaunay3`, which keep hash maps, and `emst`, `gabriel`, and `hull3`,
+//! which are built on them. `shrink` and `preprocess` round coordinates, so they need either
+//! `std` or the `libm` feature. The `reference`, `testing`, and `validate` features turn `std` on.
+//!
 //! # Usage
 //!
 //! ```rust
@@ -77,9 +138,216 @@
 //!
 //! let result = orient_2d(&points, |l, i| l[i].0, 0, 1, 2);
 //! ```
+//!
+//! For lists that can be indexed, [`sos_orient!`], [`sos_in_circle!`], and [`sos_in_sphere!`]
+//! write the indexing function, so `sos_orient!(points.0, 0, 1, 2)` is the call above.
+//!
+//! The indexing function can return any [`SosPoint`] with the right number of coordinates,
+//! like `glam`, `cgmath`, or `mint` points with their features, arrays, tuples, or columns of
+//! a matrix, and the predicates read its coordinates once, without copying them first:
+//!
+//! ```rust
+//! # use simplicity::orient_2d;
+//! let points = vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+//! assert!(orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! ```
+//!
+//! That goes for the predicates here and their `_sign`, `_with_det`, `_strict`, and `_certified`
+//! variants, the ones in [`hashed`] and [`context`], [`weighted::power_in_circle`] and
+//! [`weighted::power_in_sphere`], the 4 and 5-dimensional predicates, and the `_batch` and
+//! `_multi` variants. The algorithms built on them, like the triangulations, still take
+//! `nalgebra` vectors.
+//!
+//! The indexes don't have to be `usize`. Any `Ord + Copy` type works, and the points are
+//! perturbed in its order, so [`Keyed`] indexes can tie the perturbation to stable keys
+//! instead of positions in the list.
+//!
+//! Tuples compare lexicographically, so composite indexes like `(dataset, index)` merge
+//! point sets without disturbing them. A predicate on points from 1 dataset only depends on
+//! the order of their local indexes, so it gives the same answer as before the merge,
+//! and only predicates on points from different datasets see the new order:
+//!
+//! ```rust
+//! # use simplicity::{nalgebra, orient_2d};
+//! # use nalgebra::Vector2;
+//! let datasets = vec![
+//!     vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(2.0, 0.0)],
+//!     vec![Vector2::new(3.0, 0.0), Vector2::new(4.0, 0.0)],
+//! ];
+//! let merged = |l: &Vec<Vec<Vector2<f64>>>, (d, i): (usize, usize)| l[d][i];
+//!
+//! let before = orient_2d(&datasets[0], |l, i| l[i], 2, 1, 0);
+//! assert_eq!(orient_2d(&datasets, merged, (0, 2), (0, 1), (0, 0)), before);
+//! ```
+//!
+//! Lower indexes are perturbed more. For the opposite convention, where the last point
+//! is the most significant, wrap the indexes in [`Reverse`](std::cmp::Reverse).
+//! The [`descending`] module has the usual predicates with that done already.
+//!
+//! # Custom Predicates
+//!
+//! Other predicates that are the sign of a determinant with one row per point
+//! can be generated with [`generate_sos_predicate`]. The generated code only
+//! refers to items through `::simplicity`, so this works from any crate
+//! that depends on `simplicity`:
+//!
+//! ```rust
+//! use simplicity::{nalgebra, generate_sos_predicate};
+//! use nalgebra::Vector2;
+//!
+//! /// Whether `l` is above the parabola through `i`, `j`, `k`, when they're in order.
+//! fn above_parabola<T: ?Sized>(
+//!     list: &T,
+//!     index_fn: impl Fn(&T, usize) -> Vector2<f64>,
+//!     i: usize,
+//!     j: usize,
+//!     k: usize,
+//!     l: usize,
+//! ) -> bool {
+//!     generate_sos_predicate!{list, index_fn, i, j, k, l; x, x*x, y}
+//! }
+//!
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 4.0),
+//!     Vector2::new(3.0, 9.0),
+//! ];
+//! // Degenerate, tie broken by perturbance
+//! let above = above_parabola(&points, |l, i| l[i], 0, 1, 2, 3);
+//! assert_ne!(above, above_parabola(&points, |l, i| l[i], 1, 0, 2, 3));
+//! ```
+//!
+//! For matrices that are only known at runtime, [`determinant::signum`] takes the rows
+//! tagged with their indexes and perturbs the entries of the chosen columns.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// The generated code refers to this crate by name, since proc macros can't use `$crate`.
+extern crate self as simplicity;
 
 use robust_geo as rg;
+#[cfg(feature = "cgmath")]
+pub use cgmath;
+#[cfg(feature = "glam")]
+pub use glam;
+#[cfg(feature = "kurbo")]
+pub use kurbo;
+#[cfg(feature = "mint")]
+pub use mint;
 pub use nalgebra;
+#[cfg(feature = "ultraviolet")]
+pub use ultraviolet;
+
+pub mod aabb;
+pub mod affine;
+pub mod alpha;
+pub mod analysis;
+pub mod angle;
+pub mod apollonius;
+pub mod arrangement;
+pub mod axes;
+pub mod boolean;
+pub mod calipers;
+pub mod cdt;
+mod certified;
+pub mod cgal;
+pub mod constructions;
+pub mod context;
+#[cfg(all(feature = "tracing", feature = "std"))]
+pub mod coverage;
+pub mod degenerate;
+mod delaunay;
+#[cfg(feature = "std")]
+pub mod delaunay2;
+#[cfg(feature = "std")]
+pub mod delaunay3;
+pub mod descending;
+pub mod determinant;
+pub mod diagnostics;
+pub mod distance;
+#[cfg(feature = "std")]
+pub mod emst;
+pub mod enclosing;
+mod error;
+pub mod exact;
+pub mod expression;
+pub mod facet;
+pub mod flip;
+#[cfg(any(feature = "std", feature = "libm"))]
+mod float;
+#[cfg(feature = "std")]
+pub mod gabriel;
+pub mod hashed;
+pub mod hull2;
+#[cfg(feature = "std")]
+pub mod hull3;
+pub mod hyperbolic;
+pub mod integer;
+pub mod kdtree;
+mod keyed;
+mod macros;
+pub mod mesh;
+mod perturbed;
+pub mod pinned;
+mod point;
+pub mod polygon;
+#[cfg(any(feature = "std", feature = "libm"))]
+pub mod preprocess;
+pub mod projected;
+pub mod quadtree;
+pub mod query;
+#[cfg(feature = "reference")]
+pub mod reference;
+pub mod restricted;
+pub mod reversed;
+mod rng;
+pub mod scalar;
+mod scale;
+#[cfg(any(feature = "std", feature = "libm"))]
+pub mod shrink;
+pub mod shuffled;
+mod sign;
+pub mod slice;
+pub mod soa;
+mod strict;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "tracing")]
+mod trace;
+pub mod transcript;
+#[cfg(all(feature = "validate", debug_assertions))]
+mod validate;
+pub mod visibility;
+pub mod weighted;
+mod with_det;
+
+pub use certified::*;
+pub use delaunay::*;
+pub use error::*;
+pub use keyed::Keyed;
+pub use point::SosPoint;
+pub use sign::*;
+pub use strict::*;
+pub use with_det::*;
+pub use simplicity_derive::{batch, generate_sos_predicate, SosPoint};
+
+#[cfg(feature = "high-dim")]
+mod high_dim;
+#[cfg(feature = "high-dim")]
+pub use high_dim::*;
+
+/// Items the code generated by `simplicity_derive` depends on.
+/// Not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::collections::BTreeMap;
+    pub use alloc::{vec, vec::Vec};
+    pub use robust_geo as rg;
+    pub use crate::scale::{prescale_exponent, prescaled, scaled};
+}
 
 use nalgebra::{Vector1, Vector2, Vector3};
 type Vec1 = Vector1<f64>;
@@ -131,17 +399,52 @@ sorted_fn!(sorted_5, 5);
 /// // points[1] gets perturbed farther to the right than points[3]
 /// assert!(positive);
 /// ```
-pub fn orient_1d<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec1,
+pub fn orient_1d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
     i: Idx,
     j: Idx,
 ) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    #[cfg(all(feature = "validate", debug_assertions))]
+    validate::check(transcript::Predicate::Orient1d, 1, list, &index_fn, &[i, j]);
+
     let pi = index_fn(list, i);
     let pj = index_fn(list, j);
     pi > pj || (pi == pj && i < j)
 }
 
+/// Returns whether point `p` is strictly between points `a` and `b`, in either order,
+/// in 1-dimensional space after perturbing them; that is, if it's to the right of one of them
+/// and to the left of the other, by [`orient_1d`].
+///
+/// Takes a list of all the points in consideration, an indexing function,
+/// and 3 indexes. The perturbation decides ties, so `p` can be between points at the same
+/// coordinate as it, but it's never between `a` and itself, or between `a` and `a`.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, in_interval};
+/// # use nalgebra::Vector1;
+/// let points = vec![0.0, 1.0, 2.0, 1.0, 1.0];
+/// assert!(in_interval(&points, |l, i| Vector1::new(l[i]), 0, 2, 1));
+/// assert!(in_interval(&points, |l, i| Vector1::new(l[i]), 2, 0, 1));
+/// // points[1] gets perturbed farther to the right than points[3], and points[3] farther than points[4]
+/// assert!(in_interval(&points, |l, i| Vector1::new(l[i]), 1, 4, 3));
+/// assert!(!in_interval(&points, |l, i| Vector1::new(l[i]), 1, 3, 4));
+/// ```
+pub fn in_interval<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    a: Idx,
+    b: Idx,
+    p: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    p != a && p != b && orient_1d(list, &index_fn, p, a) != orient_1d(list, &index_fn, p, b)
+}
+
 macro_rules! case {
     (2: $pi:ident, $pj:ident, @ m2, != $odd:expr) => {
         let val = rg::magnitude_cmp_2d($pi, $pj);
@@ -237,17 +540,28 @@ macro_rules! case {
 /// let positive = orient_2d(&points, |l, i| l[i], 0, 3, 2);
 /// assert!(!positive);
 /// ```
-pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec2,
+#[simplicity_derive::batch(x, y)]
+pub fn orient_2d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
     i: Idx,
     j: Idx,
     k: Idx,
 ) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    #[cfg(feature = "tracing")]
+    trace::orient_2d(list, &index_fn, i, j, k);
+    #[cfg(all(feature = "validate", debug_assertions))]
+    validate::check(transcript::Predicate::Orient2d, 2, list, &index_fn, &[i, j, k]);
+
     let ([i, j, k], odd) = sorted_3([i, j, k]);
-    let pi = index_fn(list, i);
-    let pj = index_fn(list, j);
-    let pk = index_fn(list, k);
+    orient_2d_cascade([index_fn(list, i), index_fn(list, j), index_fn(list, k)], odd)
+}
+
+/// The cascade of [`orient_2d`] on points sorted by index,
+/// where `odd` is the parity of the permutation that sorted them.
+pub(crate) fn orient_2d_cascade(points: [Vec2; 3], odd: bool) -> bool {
+    let [pi, pj, pk] = scale::prescaled(points);
 
     case!(3: pi, pj, pk, != odd);
     case!(2: pk, pj, @ x, != odd);
@@ -283,19 +597,29 @@ pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
 /// let positive = orient_3d(&points, |l, i| l[i], 7, 4, 0, 2);
 /// assert!(positive);
 /// ```
-pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec3,
+#[simplicity_derive::batch(x, y, z)]
+pub fn orient_3d<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
     i: Idx,
     j: Idx,
     k: Idx,
     l: Idx,
 ) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    #[cfg(feature = "tracing")]
+    trace::orient_3d(list, &index_fn, i, j, k, l);
+    #[cfg(all(feature = "validate", debug_assertions))]
+    validate::check(transcript::Predicate::Orient3d, 3, list, &index_fn, &[i, j, k, l]);
+
     let ([i, j, k, l], odd) = sorted_4([i, j, k, l]);
-    let pi = index_fn(list, i);
-    let pj = index_fn(list, j);
-    let pk = index_fn(list, k);
-    let pl = index_fn(list, l);
+    orient_3d_cascade([index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)], odd)
+}
+
+/// The cascade of [`orient_3d`] on points sorted by index,
+/// where `odd` is the parity of the permutation that sorted them.
+pub(crate) fn orient_3d_cascade(points: [Vec3; 4], odd: bool) -> bool {
+    let [pi, pj, pk, pl] = scale::prescaled(points);
 
     case!(4: pi, pj, pk, pl, != odd);
     case!(3: pj, pk, pl, @ xy, != odd);
@@ -338,15 +662,23 @@ pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
 /// let inside = in_circle(&points, |l, i| l[i], 2, 1, 3, 4);
 /// assert!(!inside);
 /// ```
-pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+#[simplicity_derive::batch(x, y, x*x + y*y)]
+pub fn in_circle<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
     i: Idx,
     j: Idx,
     k: Idx,
     l: Idx,
 ) -> bool {
-    simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l}
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    #[cfg(feature = "tracing")]
+    trace::in_circle(list, &index_fn, i, j, k, l);
+    #[cfg(all(feature = "validate", debug_assertions))]
+    validate::check(transcript::Predicate::InCircle, 2, list, &index_fn, &[i, j, k, l]);
+
+    let ([i, j, k, l], odd) = sorted_4([i, j, k, l]);
+    in_circle_cascade([index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)], odd)
     // let flip = !orient_2d(list, index_fn.clone(), i, j, k);
     // let ([i, j, k, l], odd) = sorted_4([i, j, k, l]);
     // let odd = odd != flip;
@@ -373,6 +705,18 @@ pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
     // !odd
 }
 
+/// The cascade of [`in_circle`] on points sorted by index,
+/// where `odd` is the parity of the permutation that sorted them.
+pub(crate) fn in_circle_cascade(points: [Vec2; 4], odd: bool) -> bool {
+    // The generated cascade sorts its indexes again, which takes a few comparisons when they're in order
+    fn cascade(list: &[Vec2; 4]) -> bool {
+        let index_fn = |list: &[Vec2; 4], i: usize| list[i];
+        let [i, j, k, l] = [0, 1, 2, 3];
+        simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l}
+    }
+    cascade(&scale::prescaled(points)) != odd
+}
+
 /// Returns whether the last point is inside the circle that goes through
 /// the first 3 points after perturbing them.
 ///
@@ -396,14 +740,15 @@ pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
 /// let inside = in_circle_unoriented(&points, |l, i| l[i], 2, 3, 1, 4);
 /// assert!(!inside);
 /// ```
-pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+pub fn in_circle_unoriented<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
     i: Idx,
     j: Idx,
     k: Idx,
     l: Idx,
 ) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
     orient_2d(list, index_fn.clone(), i, j, k) == in_circle(list, index_fn, i, j, k, l)
 }
 
@@ -430,16 +775,24 @@ pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
 /// let inside = in_sphere(&points, |l, i| l[i], 2, 3, 1, 4, 0);
 /// assert!(!inside);
 /// ```
-pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+#[simplicity_derive::batch(x, y, z, x*x + y*y + z*z)]
+pub fn in_sphere<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
     i: Idx,
     j: Idx,
     k: Idx,
     l: Idx,
     m: Idx,
 ) -> bool {
-    simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l, m}
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    #[cfg(feature = "tracing")]
+    trace::in_sphere(list, &index_fn, i, j, k, l, m);
+    #[cfg(all(feature = "validate", debug_assertions))]
+    validate::check(transcript::Predicate::InSphere, 3, list, &index_fn, &[i, j, k, l, m]);
+
+    let ([i, j, k, l, m], odd) = sorted_5([i, j, k, l, m]);
+    in_sphere_cascade([index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l), index_fn(list, m)], odd)
     // let flip = !orient_3d(list, index_fn.clone(), i, j, k, l);
     // let ([i, j, k, l, m], odd) = sorted_5([i, j, k, l, m]);
     // let odd = odd != flip;
@@ -502,6 +855,18 @@ pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
     // !odd
 }
 
+/// The cascade of [`in_sphere`] on points sorted by index,
+/// where `odd` is the parity of the permutation that sorted them.
+pub(crate) fn in_sphere_cascade(points: [Vec3; 5], odd: bool) -> bool {
+    // The generated cascade sorts its indexes again, which takes a few comparisons when they're in order
+    fn cascade(list: &[Vec3; 5]) -> bool {
+        let index_fn = |list: &[Vec3; 5], i: usize| list[i];
+        let [i, j, k, l, m] = [0, 1, 2, 3, 4];
+        simplicity_derive::generate_in_hypersphere!{list, index_fn, i, j, k, l, m}
+    }
+    cascade(&scale::prescaled(points)) != odd
+}
+
 /// Returns whether the last point is inside the sphere that goes through
 /// the first 4 points after perturbing them.
 /// The first 4 points must be oriented positive or the result will be flipped.
@@ -526,15 +891,16 @@ pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
 /// let inside = in_sphere_unoriented(&points, |l, i| l[i], 2, 3, 1, 4, 0);
 /// assert!(!inside);
 /// ```
-pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
-    list: &T,
-    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+pub fn in_sphere_unoriented<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
     i: Idx,
     j: Idx,
     k: Idx,
     l: Idx,
     m: Idx,
 ) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
     orient_3d(list, index_fn.clone(), i, j, k, l) == in_sphere(list, index_fn, i, j, k, l, m)
 }
 
@@ -573,130 +939,271 @@ pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use diagnostics::*;
     use test_case::test_case;
 
-    // Test-specific to determine case reached
-    macro_rules! case {
-        ($arr:expr => $pi:ident, $pj:ident, @ m2) => {
-            let val = rg::magnitude_cmp_2d($pi, $pj);
-            if val != 0.0 {
-                return $arr;
-            }
-        };
+    // Cascades generated from the lifting functions alone
+    fn orient_2d_generated<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P, i: usize, j: usize, k: usize) -> bool {
+        let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+        simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k; x, y}
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, @ m3) => {
-            let val = rg::magnitude_cmp_3d($pi, $pj);
-            if val != 0.0 {
-                return $arr;
+    fn orient_3d_generated<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P, i: usize, j: usize, k: usize, l: usize) -> bool {
+        let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+        simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l; x, y, z}
+    }
+
+    fn in_circle_generated<'a, T: ?Sized, P: SosPoint<Vector = Vec2>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P, i: usize, j: usize, k: usize, l: usize) -> bool {
+        let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+        simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l; x, y, x*x + y*y}
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn in_sphere_generated<'a, T: ?Sized, P: SosPoint<Vector = Vec3>>(list: &'a T, index_fn: impl Fn(&'a T, usize) -> P, i: usize, j: usize, k: usize, l: usize, m: usize) -> bool {
+        let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+        simplicity_derive::generate_sos_predicate!{list, index_fn, i, j, k, l, m; x, y, z, x*x + y*y + z*z}
+    }
+
+    /// Points on a small grid, with duplicates, so that most tuples are degenerate.
+    pub(crate) fn grid_points_2d() -> Vec<Vec2> {
+        let mut points = (0..9).map(|i| Vector2::new((i % 3) as f64, (i / 3) as f64)).collect::<Vec<_>>();
+        points.push(Vector2::new(1.0, 1.0));
+        points.push(Vector2::new(0.0, 0.0));
+        points
+    }
+
+    pub(crate) fn grid_points_3d() -> Vec<Vec3> {
+        let mut points = (0..8).map(|i| Vector3::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4) as f64)).collect::<Vec<_>>();
+        points.push(Vector3::new(1.0, 0.0, 1.0));
+        points.push(Vector3::new(0.0, 0.0, 0.0));
+        points
+    }
+
+    #[test]
+    fn test_generated_orient_2d() {
+        let points = grid_points_2d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        orient_2d_generated(&points, |l, i| l[i], i, j, k),
+                        orient_2d(&points, |l, i| l[i], i, j, k),
+                    );
+                }
             }
-        };
+        }
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident $(, @ $swiz:ident)?) => {
-            if $pi$(.$swiz)? != $pj$(.$swiz)? {
-                return $arr;
+    #[test]
+    fn test_generated_orient_3d() {
+        let points = grid_points_3d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(
+                            orient_3d_generated(&points, |l, i| l[i], i, j, k, l),
+                            orient_3d(&points, |l, i| l[i], i, j, k, l),
+                        );
+                    }
+                }
             }
-        };
+        }
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, @ $swiz:ident m2) => {
-            let val = rg::sign_det_x_x2y2($pi.$swiz(), $pj.$swiz(), $pk.$swiz());
-            if val != 0.0 {
-                return $arr;
+    #[test]
+    fn test_generated_in_circle() {
+        let points = grid_points_2d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(
+                            in_circle_generated(&points, |l, i| l[i], i, j, k, l),
+                            in_circle(&points, |l, i| l[i], i, j, k, l),
+                        );
+                    }
+                }
             }
-        };
+        }
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, @ $swiz:ident m3) => {
-            let val = rg::sign_det_x_x2y2z2($pi.$swiz(), $pj.$swiz(), $pk.$swiz());
-            if val != 0.0 {
-                return $arr;
+    #[test]
+    fn test_generated_in_sphere() {
+        // Both cascades sort the indexes first, so the sphere's points can go in order
+        let points = grid_points_3d();
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                for k in j + 1..points.len() {
+                    for l in k + 1..points.len() {
+                        for m in (0..points.len()).filter(|m| ![i, j, k, l].contains(m)) {
+                            assert_eq!(
+                                in_sphere_generated(&points, |l, i| l[i], i, j, k, l, m),
+                                in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                            );
+                        }
+                    }
+                }
             }
-        };
+        }
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident $(, @ $swiz:ident)?) => {
-            let val = rg::orient_2d($pi$(.$swiz())?, $pj$(.$swiz())?, $pk$(.$swiz())?);
-            if val != 0.0 {
-                return $arr;
+    #[test]
+    fn test_schemes_consistent() {
+        // Swapping 2 points flips the orientation and the in-circle test
+        type Orient<'a> = fn(&'a Vec<Vec2>, fn(&Vec<Vec2>, usize) -> Vec2, usize, usize, usize) -> bool;
+        type InCircle<'a> = fn(&'a Vec<Vec2>, fn(&Vec<Vec2>, usize) -> Vec2, usize, usize, usize, usize) -> bool;
+        let schemes: [(Orient<'_>, InCircle<'_>); 3] = [
+            (orient_2d, in_circle),
+            (reversed::orient_2d, reversed::in_circle),
+            (descending::orient_2d, descending::in_circle),
+        ];
+        let points = grid_points_2d();
+        let n = points.len();
+        let f: fn(&Vec<Vec2>, usize) -> Vec2 = |l, i| l[i];
+        for (orient, in_circle) in schemes.iter() {
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        let o = orient(&points, f, i, j, k);
+                        assert_eq!(orient(&points, f, j, k, i), o);
+                        assert_ne!(orient(&points, f, j, i, k), o);
+                        for l in (k + 1..n).filter(|l| *l != i && *l != j) {
+                            let c = in_circle(&points, f, i, j, k, l);
+                            assert_ne!(in_circle(&points, f, j, i, k, l), c);
+                            assert_eq!(in_circle(&points, f, j, k, i, l), c);
+                            assert_ne!(in_circle(&points, f, i, j, l, k), c);
+                        }
+                    }
+                }
             }
+        }
+    }
+
+    // The debug hooks fetch the points themselves
+    #[cfg(not(any(feature = "tracing", feature = "validate")))]
+    #[test]
+    fn test_fetches_each_point_once() {
+        use std::cell::Cell;
+
+        let fetches = Cell::new(0);
+        let points = grid_points_2d();
+        let counted = |l: &Vec<Vec2>, i: usize| {
+            fetches.set(fetches.get() + 1);
+            l[i]
         };
+        // Cocircular, so the cascade goes past the first case
+        in_circle(&points, counted, 0, 1, 4, 3);
+        assert_eq!(fetches.replace(0), 4);
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, @ xy m2) => {
-            let val = rg::in_circle($pi, $pj, $pk, $pl);
-            if val != 0.0 {
-                return $arr;
-            }
+        let points = grid_points_3d();
+        let counted = |l: &Vec<Vec3>, i: usize| {
+            fetches.set(fetches.get() + 1);
+            l[i]
         };
+        in_sphere(&points, counted, 0, 1, 2, 3, 4);
+        assert_eq!(fetches.get(), 5);
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, @ $swiz:ident m3) => {
-            let val = rg::sign_det_x_y_x2y2z2($pi.$swiz(), $pj.$swiz(), $pk.$swiz(), $pl.$swiz());
-            if val != 0.0 {
-                return $arr;
+    #[test]
+    fn test_in_interval() {
+        let points = [0.0, 1.0, 1.0, 2.0, 1.0, 0.0, 2.0];
+        let vec1 = |l: &[f64; 7], i: usize| Vector1::new(l[i]);
+        // The order after perturbing, where a lower index is further right on a tie
+        let mut order = (0..points.len()).collect::<Vec<_>>();
+        order.sort_by(|i, j| points[*i].partial_cmp(&points[*j]).unwrap().then(j.cmp(i)));
+        let rank = |i| order.iter().position(|j| *j == i).unwrap();
+        for a in 0..points.len() {
+            for b in 0..points.len() {
+                for p in 0..points.len() {
+                    let (lo, hi) = (rank(a).min(rank(b)), rank(a).max(rank(b)));
+                    assert_eq!(in_interval(&points, vec1, a, b, p), lo < rank(p) && rank(p) < hi, "{} {} {}", a, b, p);
+                }
             }
-        };
+        }
+    }
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident $(, @ $swiz:ident)?) => {
-            let val = rg::orient_3d($pi$(.$swiz())?, $pj$(.$swiz())?, $pk$(.$swiz())?, $pl$(.$swiz())?);
-            if val != 0.0 {
-                return $arr;
+    #[test]
+    fn test_batch_in_circle() {
+        let points = grid_points_2d();
+        let tuples = (0..points.len()).flat_map(|i| (0..points.len()).map(move |j| [i, j, (i + 1) % 11, (j + 3) % 11]))
+            .filter(|[i, j, k, l]| i != j && i != k && i != l && j != k && j != l && k != l)
+            .collect::<Vec<_>>();
+        let expected = tuples.iter().map(|[i, j, k, l]| in_circle(&points, |l, i| l[i], *i, *j, *k, *l)).collect::<Vec<_>>();
+        assert_eq!(in_circle_batch(&points, |l, i| l[i], &tuples), expected);
+    }
+
+    #[test]
+    fn test_batch_async_in_circle() {
+        use std::cell::RefCell;
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+        // A point that arrives on the second poll, like one from a remote store
+        struct Fetch(Option<Vector2<f64>>, bool);
+        impl Future for Fetch {
+            type Output = Vector2<f64>;
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Vector2<f64>> {
+                if std::mem::replace(&mut self.1, true) {
+                    Poll::Ready(self.0.take().unwrap())
+                } else {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
             }
-        };
+        }
+        fn block_on<F: Future>(future: F) -> F::Output {
+            const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RawWaker::new(std::ptr::null(), &VTABLE), |_| {}, |_| {}, |_| {});
+            let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+            let mut future = Box::pin(future);
+            loop {
+                if let Poll::Ready(output) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                    return output;
+                }
+            }
+        }
+
+        let points = grid_points_2d();
+        let tuples = (0..points.len()).flat_map(|i| (0..points.len()).map(move |j| [i, j, (i + 1) % 11, (j + 3) % 11]))
+            .filter(|[i, j, k, l]| i != j && i != k && i != l && j != k && j != l && k != l)
+            .collect::<Vec<_>>();
+        let fetched = RefCell::new(vec![]);
+        let results = block_on(in_circle_batch_async(|i| {
+            fetched.borrow_mut().push(i);
+            Fetch(Some(points[i]), false)
+        }, &tuples));
+        assert_eq!(results, in_circle_batch(&points, |l, i| l[i], &tuples));
 
-        ($arr:expr => $pi:ident, $pj:ident, $pk:ident, $pl:ident, $pm:ident, @ xyz m3) => {
-            let val = rg::in_sphere($pi, $pj, $pk, $pl, $pm);
-            if val != 0.0 {
-                return $arr;
+        // Each point is fetched once, when the first tuple with it gets evaluated,
+        // so none is dropped before the last tuple that uses it
+        let mut expected = vec![];
+        for index in tuples.iter().flatten() {
+            if !expected.contains(index) {
+                expected.push(*index);
             }
-        };
+        }
+        assert_eq!(fetched.into_inner(), expected);
     }
 
-    // Copied from orient_2d
-    pub fn orient_2d_case<T: ?Sized>(
-        list: &T,
-        index_fn: impl Fn(&T, usize) -> Vec2,
-        i: usize,
-        j: usize,
-        k: usize,
-    ) -> [usize; 3] {
-        let ([i, j, k], _) = sorted_3([i, j, k]);
-        let pi = index_fn(list, i);
-        let pj = index_fn(list, j);
-        let pk = index_fn(list, k);
-
-        case!([3, 3, 3] => pi, pj, pk);
-        case!([2, 3, 3] => pk, pj, @ x);
-        case!([1, 3, 3] => pj, pk, @ y);
-        case!([2, 2, 3] => pi, pk, @ x);
-        [1, 2, 3]
+    #[test]
+    fn test_multi_in_circle() {
+        let points = grid_points_2d();
+        for (i, j, k) in [(0, 1, 3), (0, 4, 8), (4, 9, 1), (10, 2, 6)].iter().copied() {
+            let queries = (0..points.len()).filter(|l| *l != i && *l != j && *l != k).collect::<Vec<_>>();
+            let expected = queries.iter().map(|l| in_circle(&points, |l, i| l[i], i, j, k, *l)).collect::<Vec<_>>();
+            assert_eq!(in_circle_multi(&points, |l, i| l[i], i, j, k, &queries), expected);
+        }
     }
 
-    // Copied from orient_3d
-    pub fn orient_3d_case<T: ?Sized>(
-        list: &T,
-        index_fn: impl Fn(&T, usize) -> Vec3,
-        i: usize,
-        j: usize,
-        k: usize,
-        l: usize,
-    ) -> [usize; 4] {
-        let ([i, j, k, l], _) = sorted_4([i, j, k, l]);
-        let pi = index_fn(list, i);
-        let pj = index_fn(list, j);
-        let pk = index_fn(list, k);
-        let pl = index_fn(list, l);
-
-        case!([4, 4, 4, 4] => pi, pj, pk, pl);
-        case!([3, 4, 4, 4] => pj, pk, pl, @ xy);
-        case!([2, 4, 4, 4] => pj, pk, pl, @ zx);
-        case!([1, 4, 4, 4] => pj, pk, pl, @ yz);
-        case!([3, 3, 4, 4] => pi, pk, pl, @ yx);
-        case!([2, 3, 4, 4] => pk, pl, @ x);
-        case!([1, 3, 4, 4] => pl, pk, @ y);
-        case!([2, 2, 4, 4] => pi, pk, pl, @ xz);
-        case!([1, 2, 4, 4] => pk, pl, @ z);
-        //case!([1, 1, 4, 4] => pi, pk, pl, @ zy); Impossible
-        case!([3, 3, 3, 4] => pi, pj, pl, @ xy);
-        case!([2, 3, 3, 4] => pl, pj, @ x);
-        case!([1, 3, 3, 4] => pj, pl, @ y);
-        case!([2, 2, 3, 4] => pi, pl, @ x);
-        [1, 2, 3, 4]
+    #[test]
+    fn test_multi_orient_3d() {
+        let points = grid_points_3d();
+        for (i, j, k) in [(0, 1, 2), (0, 3, 9), (9, 8, 5), (1, 2, 4)].iter().copied() {
+            let queries = (0..points.len()).filter(|l| *l != i && *l != j && *l != k).collect::<Vec<_>>();
+            let expected = queries.iter().map(|l| orient_3d(&points, |l, i| l[i], i, j, k, *l)).collect::<Vec<_>>();
+            assert_eq!(orient_3d_multi(&points, |l, i| l[i], i, j, k, &queries), expected);
+        }
     }
 
     #[test]
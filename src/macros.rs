@@ -0,0 +1,93 @@
+//! Shorthands for calling the predicates on lists that can be indexed.
+
+/// Calls [`orient_1d`](crate::orient_1d), [`orient_2d`](crate::orient_2d),
+/// or [`orient_3d`](crate::orient_3d), depending on the number of indexes, on a list
+/// that can be indexed, with the indexing function `|l, i| l[i]`.
+///
+/// The list can be followed by a path of fields to get the point out of an element,
+/// like `points.pos` for `|l, i| l[i].pos`.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, sos_orient};
+/// # use nalgebra::Vector2;
+/// struct Vertex {
+///     pos: Vector2<f64>,
+/// }
+///
+/// let points = vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), Vector2::new(1.0, 1.0)];
+/// assert!(sos_orient!(points, 0, 1, 2));
+/// let vertices = points.iter().map(|p| Vertex { pos: *p }).collect::<Vec<_>>();
+/// assert!(!sos_orient!(vertices.pos, 0, 2, 1));
+/// ```
+#[macro_export]
+macro_rules! sos_orient {
+    ($list:ident $(. $field:tt)*, $i:expr, $j:expr $(,)?) => {
+        $crate::orient_1d(&$list, |l, i| l[i]$(.$field)*, $i, $j)
+    };
+    ($list:ident $(. $field:tt)*, $i:expr, $j:expr, $k:expr $(,)?) => {
+        $crate::orient_2d(&$list, |l, i| l[i]$(.$field)*, $i, $j, $k)
+    };
+    ($list:ident $(. $field:tt)*, $i:expr, $j:expr, $k:expr, $l:expr $(,)?) => {
+        $crate::orient_3d(&$list, |l, i| l[i]$(.$field)*, $i, $j, $k, $l)
+    };
+}
+
+/// Calls [`in_circle`](crate::in_circle) on a list that can be indexed, with the indexing
+/// function `|l, i| l[i]`, or a path of fields after that, like [`sos_orient!`].
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, sos_in_circle};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     (0, Vector2::new(0.0, 0.0)),
+///     (1, Vector2::new(2.0, 0.0)),
+///     (2, Vector2::new(0.0, 2.0)),
+///     (3, Vector2::new(1.0, 1.0)),
+/// ];
+/// assert!(sos_in_circle!(points.1, 0, 1, 2, 3));
+/// ```
+#[macro_export]
+macro_rules! sos_in_circle {
+    ($list:ident $(. $field:tt)*, $i:expr, $j:expr, $k:expr, $l:expr $(,)?) => {
+        $crate::in_circle(&$list, |l, i| l[i]$(.$field)*, $i, $j, $k, $l)
+    };
+}
+
+/// Calls [`in_sphere`](crate::in_sphere) on a list that can be indexed, with the indexing
+/// function `|l, i| l[i]`, or a path of fields after that, like [`sos_orient!`].
+#[macro_export]
+macro_rules! sos_in_sphere {
+    ($list:ident $(. $field:tt)*, $i:expr, $j:expr, $k:expr, $l:expr, $m:expr $(,)?) => {
+        $crate::in_sphere(&$list, |l, i| l[i]$(.$field)*, $i, $j, $k, $l, $m)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec3, in_circle, in_sphere, orient_1d, orient_2d, orient_3d};
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_macros() {
+        struct Vertex {
+            pos: Vec3,
+        }
+
+        let points = grid_points_3d();
+        let vertices = points.iter().map(|p| Vertex { pos: *p }).collect::<Vec<_>>();
+        let flat = points.iter().map(|p| (p.xy(), Vector1::new(p.x))).collect::<Vec<_>>();
+        for (i, j, k, l, m) in [(0, 1, 2, 4, 7), (3, 1, 0, 8, 9), (5, 6, 7, 2, 1)].iter().copied() {
+            assert_eq!(crate::sos_orient!(flat.1, i, j), orient_1d(&flat, |l, i| l[i].1, i, j));
+            assert_eq!(crate::sos_orient!(flat.0, i, j, k), orient_2d(&flat, |l, i| l[i].0, i, j, k));
+            assert_eq!(crate::sos_orient!(points, i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(crate::sos_orient!(vertices.pos, i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(crate::sos_in_circle!(flat.0, i, j, k, l), in_circle(&flat, |l, i| l[i].0, i, j, k, l));
+            assert_eq!(crate::sos_in_sphere!(vertices.pos, i, j, k, l, m), in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+        }
+    }
+}
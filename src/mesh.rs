@@ -0,0 +1,146 @@
+//! Classifying points against closed triangle meshes.
+//!
+//! A point is inside a closed surface if a ray from it crosses the surface an odd number of times.
+//! The ray here goes in the +x direction, and whether it crosses a triangle is decided by
+//! the signs of polynomials in the perturbed coordinates, so it never passes exactly through
+//! an edge or a vertex, and it never lies in the plane of a triangle. Triangles that share
+//! an edge agree on which side of it the ray passes, so the count is right for any closed
+//! surface, no matter how the triangles are oriented or how degenerate they are.
+//!
+//! Besides input points, this can classify the centroid of several points, such as
+//! the barycenter of a tetrahedron, without computing it.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, hull3, mesh};
+//! # use nalgebra::Vector3;
+//! let mut points = (0..8)
+//!     .map(|i| Vector3::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4) as f64))
+//!     .collect::<Vec<_>>();
+//! let triangles = hull3::convex_hull(&points, |l, i| l[i], points.len());
+//!
+//! points.push(Vector3::new(0.5, 0.5, 0.5));
+//! points.push(Vector3::new(1.5, 0.5, 0.5));
+//! assert!(mesh::point_inside(&points, |l, i| l[i], &triangles, 8));
+//! assert!(!mesh::point_inside(&points, |l, i| l[i], &triangles, 9));
+//! // The barycenter of a tetrahedron between 3 corners of the cube and the point outside
+//! assert!(mesh::tetrahedron_inside(&points, |l, i| l[i], &triangles, [0, 1, 3, 8]));
+//! assert!(!mesh::tetrahedron_inside(&points, |l, i| l[i], &triangles, [1, 3, 5, 9]));
+//! ```
+
+use crate::perturbed::{Perturbed, Polynomial};
+use crate::scale::Prescaled;
+use crate::Vec3;
+use alloc::vec::Vec;
+use core::ops::Add;
+
+/// Whether the ray in the +x direction from the centroid of the query points crosses triangle `abc`.
+fn crosses<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec3, [a, b, c]: [Idx; 3], query: &[Idx]) -> bool {
+    let indexes = [a, b, c].iter().chain(query).copied().collect::<Vec<_>>();
+    let points = Prescaled::new(3, 0, &indexes, &[], |i| index_fn(list, i));
+    let perturbed = Perturbed::new(3, 3, &indexes, |i| points.point(i));
+    // Scale everything by the number of query points, so the centroid is a sum
+    let k = query.len() as f64;
+    let q = |c| query.iter().map(|i| perturbed.coord(*i, c)).fold(Polynomial::default(), Add::add);
+    let v = |i, c| perturbed.coord(i, c).scale(k);
+
+    // The ray passes on the same side of each edge in the yz plane, the side of the triangle
+    let cross_yz = |i, j| (v(j, 1) - v(i, 1)) * (q(2) - v(i, 2)) - (v(j, 2) - v(i, 2)) * (q(1) - v(i, 1));
+    let sign = cross_yz(a, b).signum();
+    if sign == 0.0 || cross_yz(b, c).signum() != sign || cross_yz(c, a).signum() != sign {
+        return false;
+    }
+
+    // The x component of the triangle's normal has that sign too, so the centroid is behind the triangle
+    // if it's on the negative side of the normal
+    let normal = |c1, c2| {
+        perturbed.diff(b, a, c1) * perturbed.diff(c, a, c2) - perturbed.diff(b, a, c2) * perturbed.diff(c, a, c1)
+    };
+    let side = (0..3).fold(Polynomial::default(), |acc, c| acc + normal((c + 1) % 3, (c + 2) % 3) * (q(c) - v(a, c)));
+    side.signum() * sign < 0.0
+}
+
+/// Returns whether the centroid of some points is inside a closed surface after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the triangles of the surface as triples of indexes,
+/// and the indexes of the points to take the centroid of.
+/// The triangles can be oriented either way, but every edge must be on an even number of them.
+/// If the centroid is on the surface even after perturbing, such as when it's a vertex of the surface,
+/// the result is unspecified.
+pub fn centroid_inside<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    triangles: &[[Idx; 3]],
+    query: &[Idx],
+) -> bool {
+    triangles.iter().filter(|tri| crosses(list, &index_fn, **tri, query)).count() % 2 == 1
+}
+
+/// Returns whether point `p` is inside a closed surface after perturbing the points.
+/// See [`centroid_inside`] for the requirements.
+pub fn point_inside<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    triangles: &[[Idx; 3]],
+    p: Idx,
+) -> bool {
+    centroid_inside(list, index_fn, triangles, &[p])
+}
+
+/// Returns whether the barycenter of a tetrahedron is inside a closed surface after perturbing the points.
+/// See [`centroid_inside`] for the requirements.
+pub fn tetrahedron_inside<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    triangles: &[[Idx; 3]],
+    tetrahedron: [Idx; 4],
+) -> bool {
+    centroid_inside(list, index_fn, triangles, &tetrahedron)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{delaunay3, hull3};
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_mesh() {
+        // A cube from 0 to 2, with triangles oriented both ways
+        let mut points = (0..8)
+            .map(|i| Vector3::new((i % 2 * 2) as f64, (i / 2 % 2 * 2) as f64, (i / 4 * 2) as f64))
+            .collect::<Vec<_>>();
+        let mut triangles = hull3::convex_hull(&points, |l, i| l[i], points.len());
+        for tri in triangles.iter_mut().step_by(2) {
+            tri.swap(0, 1);
+        }
+
+        // Points exactly on the surface can go either way, so only check the others
+        let grid = (0..125).map(|i| Vector3::new((i % 5) as f64 - 1.0, (i / 5 % 5) as f64 - 1.0, (i / 25) as f64 - 1.0));
+        points.extend(grid);
+        for p in 8..points.len() {
+            let inside = points[p].iter().all(|x| *x > 0.0 && *x < 2.0);
+            let outside = points[p].iter().any(|x| *x < 0.0 || *x > 2.0);
+            let result = super::point_inside(&points, |l, i| l[i], &triangles, p);
+            assert!(!inside || result);
+            assert!(!outside || !result);
+        }
+
+        // Tetrahedra of the cube and a point outside it, skipping flat ones on the surface
+        let outside = points.iter().position(|p| *p == Vector3::new(3.0, 1.0, 1.0)).unwrap();
+        let indexes = (0..8).chain(std::iter::once(outside)).collect::<Vec<_>>();
+        let tetrahedra = delaunay3::tetrahedralize(&indexes, |l, i| points[l[i]], indexes.len());
+        let mut checked = 0;
+        for tet in tetrahedra {
+            let tet = [indexes[tet[0]], indexes[tet[1]], indexes[tet[2]], indexes[tet[3]]];
+            let sum = tet.iter().fold(Vector3::zeros(), |acc, i| acc + points[*i]);
+            if sum.iter().any(|x| *x == 0.0 || *x == 8.0) {
+                continue;
+            }
+            assert_eq!(super::tetrahedron_inside(&points, |l, i| l[i], &triangles, tet), !tet.contains(&outside));
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
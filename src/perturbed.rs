@@ -0,0 +1,237 @@
+//! Polynomials in the perturbed coordinates, for predicates that aren't determinants.
+//!
+//! Coordinate *c* of the point with the *r*-th smallest index among the points of a predicate
+//! is perturbed by ε^(*b*^(*d*\**r* + *d* - 1 - *c*)), in the same order as the rest of the crate,
+//! and any polynomial in the perturbed coordinates is then a polynomial in ε.
+//! Its sign for infinitesimal ε is the sign of its lowest order nonzero coefficient.
+//!
+//! The base *b* is more than the degree of the polynomial, so different monomials never get
+//! the same exponent, and they're ordered lexicographically by their powers of the coordinates,
+//! whatever the base and whichever other points there are. For determinants, that's the order
+//! the generated predicates use, so all of these signs are consistent with each other and with them.
+//!
+//! Expanding a polynomial of high degree in all the perturbations at once can take a long time,
+//! but the lowest order terms only involve the most perturbed coordinates, since every monomial
+//! in the first *k* of them has a lower exponent than any monomial with another one.
+//! [`Perturbed::lazy_signum`] perturbs more of them until the sign is decided.
+
+use crate::exact::Expansion;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+use core::ops::{Add, Index, Mul, Neg, Sub};
+
+/// A polynomial in ε with exact coefficients, keyed by exponent.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Polynomial(BTreeMap<u128, Expansion>);
+
+impl Polynomial {
+    /// The sign of the polynomial for infinitesimal ε, as -1, 0, or 1.
+    pub(crate) fn signum(&self) -> f64 {
+        self.0.values().map(Expansion::signum).find(|s| *s != 0.0).unwrap_or(0.0)
+    }
+
+    /// Multiplies the polynomial by a constant.
+    pub(crate) fn scale(&self, b: f64) -> Self {
+        Self(self.0.iter().map(|(e, coeff)| (*e, coeff.scale(b))).collect())
+    }
+}
+
+impl From<Expansion> for Polynomial {
+    fn from(value: Expansion) -> Self {
+        Self(core::iter::once((0, value)).collect())
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        for (e, coeff) in other.0 {
+            let sum = self.0.entry(e).or_insert_with(Expansion::zero);
+            *sum = core::mem::take(sum) + coeff;
+        }
+        self
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.into_iter().map(|(e, coeff)| (e, -coeff)).collect())
+    }
+}
+
+impl Sub for Polynomial {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut product = Self::default();
+        for (e, a) in &self.0 {
+            for (f, b) in &other.0 {
+                let sum = product.0.entry(e + f).or_insert_with(Expansion::zero);
+                *sum = core::mem::take(sum) + a.clone() * b.clone();
+            }
+        }
+        product
+    }
+}
+
+/// The determinant of a square matrix of polynomials, by Laplace expansion along the first row.
+pub(crate) fn det(matrix: &[Vec<Polynomial>]) -> Polynomial {
+    if matrix.is_empty() {
+        return Polynomial::from(Expansion::from(1.0));
+    }
+    (0..matrix.len()).fold(Polynomial::default(), |acc, col| {
+        let minor = matrix[1..]
+            .iter()
+            .map(|row| row.iter().enumerate().filter(|(c, _)| *c != col).map(|(_, e)| e.clone()).collect())
+            .collect::<Vec<_>>();
+        let term = matrix[0][col].clone() * det(&minor);
+        if col % 2 == 0 {
+            acc + term
+        } else {
+            acc - term
+        }
+    })
+}
+
+/// The perturbed coordinates of the points of a predicate.
+pub(crate) struct Perturbed<Idx, F> {
+    dim: usize,
+    base: u128,
+    /// Sorted and deduplicated, without the pinned points
+    indexes: Vec<Idx>,
+    pinned: Vec<Idx>,
+    /// How many of the coordinates are perturbed, most perturbed first
+    perturbed: usize,
+    point: F,
+}
+
+impl<Idx: Ord + Copy, C: Clone + Into<Expansion>, P: Index<usize, Output = C>, F: Fn(Idx) -> P> Perturbed<Idx, F> {
+    /// Takes the number of dimensions, the degree of the polynomials to compute,
+    /// the indexes of the points of the predicate, and a function that gets a point,
+    /// whose coordinates can be `f64`s or exact expansions.
+    /// The points aren't scaled, so tiny ones can underflow; predicates whose polynomials are
+    /// homogeneous should fetch them through [`Prescaled`](crate::scale::Prescaled).
+    pub(crate) fn new(dim: usize, degree: u64, indexes: &[Idx], point: F) -> Self {
+        let mut indexes = indexes.to_vec();
+        indexes.sort_unstable();
+        indexes.dedup();
+        Self { dim, base: degree as u128 + 1, indexes, pinned: vec![], perturbed: usize::MAX, point }
+    }
+
+    /// Like [`new`](Self::new), but leaves the points that satisfy `pinned` unperturbed.
+    pub(crate) fn with_pinned(dim: usize, degree: u64, indexes: &[Idx], pinned: impl Fn(Idx) -> bool, point: F) -> Self {
+        let (pinned, perturbed) = indexes.iter().partition::<Vec<Idx>, _>(|i| pinned(**i));
+        Self { pinned, ..Self::new(dim, degree, &perturbed, point) }
+    }
+
+    /// Coordinate `c` of point `i`, perturbed unless it's pinned.
+    pub(crate) fn coord(&self, i: Idx, c: usize) -> Polynomial {
+        let mut poly = Polynomial::from((self.point)(i)[c].clone().into());
+        if self.pinned.contains(&i) {
+            return poly;
+        }
+        let rank = self.indexes.binary_search(&i).expect("point not in the predicate");
+        let slot = self.dim * rank + self.dim - 1 - c;
+        if slot < self.perturbed {
+            poly.0.insert(self.base.pow(slot as u32), Expansion::from(1.0));
+        }
+        poly
+    }
+
+    /// The difference of coordinate `c` of points `i` and `j`, perturbed.
+    pub(crate) fn diff(&self, i: Idx, j: Idx, c: usize) -> Polynomial {
+        self.coord(i, c) - self.coord(j, c)
+    }
+
+    /// The sign of the polynomial that `poly` computes from the perturbed coordinates,
+    /// for infinitesimal ε, as -1, 0, or 1. Perturbs more and more of the coordinates,
+    /// most perturbed first, until the terms so far decide it.
+    pub(crate) fn lazy_signum(&mut self, poly: impl Fn(&Self) -> Polynomial) -> f64 {
+        let mut sign = 0.0;
+        for perturbed in 0..=self.dim * self.indexes.len() {
+            self.perturbed = perturbed;
+            sign = poly(self).signum();
+            if sign != 0.0 {
+                break;
+            }
+        }
+        self.perturbed = usize::MAX;
+        sign
+    }
+
+    /// The value of the polynomial that `poly` computes from the coordinates, without perturbing them.
+    pub(crate) fn value(&mut self, poly: impl Fn(&Self) -> Polynomial) -> Expansion {
+        self.perturbed = 0;
+        let value = poly(self).0.remove(&0).unwrap_or_default();
+        self.perturbed = usize::MAX;
+        value
+    }
+
+    /// The 2D cross product of `j - i` and `k - i`, which is positive if `ijk` is oriented positive.
+    pub(crate) fn cross_2d(&self, i: Idx, j: Idx, k: Idx) -> Polynomial {
+        self.diff(j, i, 0) * self.diff(k, i, 1) - self.diff(j, i, 1) * self.diff(k, i, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, orient_2d};
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_perturbed_cross_2d() {
+        // Agrees with orient_2d even on collinear and repeated points
+        let points = grid_points_2d();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    let perturbed = super::Perturbed::new(2, 2, &[i, j, k], |i| points[i]);
+                    let sign = perturbed.cross_2d(i, j, k).signum();
+                    assert_eq!(sign > 0.0, orient_2d(&points, |l, i| l[i], i, j, k));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exponent_parameter() {
+        let points = grid_points_2d();
+        let n = points.len();
+        // Polynomials in coordinates perturbed with d = 2, which is what the predicates use, and with bigger d
+        for d in [2, 4] {
+            for i in 0..n {
+                for j in i + 1..n {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        let perturbed = Perturbed::new(d, 4, &[i, j, k], |i| points[i]);
+                        let orient = perturbed.cross_2d(i, j, k).signum() > 0.0;
+                        assert_eq!(orient, orient_2d(&points, |l, i| l[i], i, j, k));
+
+                        // The first point from a pseudorandom start that isn't already taken
+                        let l = (i + j + k..).map(|l| l % n).find(|l| ![i, j, k].contains(l)).unwrap();
+                        let perturbed = Perturbed::new(d, 4, &[i, j, k, l], |i| points[i]);
+                        let diff = |i, c| perturbed.diff(i, l, c);
+                        let lift = |i| (0..2).fold(Polynomial::default(), |a, c| a + diff(i, c) * diff(i, c));
+                        let det = super::det(&[
+                            vec![diff(i, 0), diff(i, 1), lift(i)],
+                            vec![diff(j, 0), diff(j, 1), lift(j)],
+                            vec![diff(k, 0), diff(k, 1), lift(k)],
+                        ]);
+                        assert_eq!(det.signum() > 0.0, in_circle(&points, |l, i| l[i], i, j, k, l));
+                    }
+                }
+            }
+        }
+    }
+}
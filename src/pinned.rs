@@ -0,0 +1,218 @@
+//! Variants of the predicates that leave some points unperturbed.
+//!
+//! Points can be pinned so they never move, even symbolically, such as the corners of a domain
+//! or the endpoints of constraints that a mesh has to respect. Only the other points are perturbed,
+//! in the order of their indexes, the same way as usual. A point that isn't pinned on the line
+//! through 2 pinned points is moved off it, but 3 pinned points on a line stay there, so then
+//! the predicate fails with [`ErrorReason::Degenerate`].
+//!
+//! When these succeed, they agree with the usual predicates called with indexes that put
+//! every pinned point after every other point, such as `(pinned(i), i)`.
+//! So pinning points is like perturbing them infinitely less than the rest.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, pinned, ErrorReason};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//! ];
+//! let corner = |i| i < 2;
+//! // The point in the middle of the edge moves off it, and the corners stay put
+//! assert_eq!(pinned::orient_2d(&points, |l, i| l[i], corner, 0, 1, 2), Ok(true));
+//! let err = pinned::orient_2d(&points, |l, i| l[i], |_| true, 0, 1, 2).unwrap_err();
+//! assert_eq!(err.reason, ErrorReason::Degenerate);
+//! ```
+
+use crate::perturbed::{self, Perturbed};
+use crate::scale::Prescaled;
+use crate::{ErrorReason, PredicateKind, SimplicityError, Vec1, Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::ops::Index;
+
+/// Evaluates the sign of a determinant with a row for each point but the last, relative to the last,
+/// and with the squared distance to the last point as an extra column if `lifted`.
+fn evaluate<Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    predicate: PredicateKind,
+    dim: usize,
+    lifted: bool,
+    indexes: Vec<Idx>,
+    pinned: impl Fn(Idx) -> bool,
+    point: impl Fn(Idx) -> P,
+) -> Result<bool, SimplicityError<Idx>> {
+    let degree = if lifted { dim as u64 + 1 } else { dim as u64 };
+    let points = Prescaled::new(dim, 0, &indexes, &[], point);
+    let perturbed = Perturbed::with_pinned(dim, degree, &indexes, pinned, |i| points.point(i));
+    let (last, rest) = indexes.split_last().unwrap();
+    let matrix = rest
+        .iter()
+        .map(|i| {
+            let mut row = (0..dim).map(|c| perturbed.diff(*i, *last, c)).collect::<Vec<_>>();
+            if lifted {
+                row.push(row.iter().map(|d| d.clone() * d.clone()).fold(Default::default(), |a, b| a + b));
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let sign = perturbed::det(&matrix).signum();
+    if sign == 0.0 {
+        Err(SimplicityError { predicate, indexes, reason: ErrorReason::Degenerate })
+    } else {
+        Ok(sign > 0.0)
+    }
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but leaves the points that satisfy `pinned` unperturbed.
+/// Returns an error with [`ErrorReason::Degenerate`] if the points are equal and both pinned.
+pub fn orient_1d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec1,
+    pinned: impl Fn(Idx) -> bool,
+    i: Idx,
+    j: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    evaluate(PredicateKind::Orient1d, 1, false, vec![i, j], pinned, |i| index_fn(list, i))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but leaves the points that satisfy `pinned` unperturbed.
+/// Returns an error with [`ErrorReason::Degenerate`] if the points are collinear
+/// even after perturbing the ones that aren't pinned.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    pinned: impl Fn(Idx) -> bool,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    evaluate(PredicateKind::Orient2d, 2, false, vec![i, j, k], pinned, |i| index_fn(list, i))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but leaves the points that satisfy `pinned` unperturbed.
+/// Returns an error with [`ErrorReason::Degenerate`] if the points are coplanar
+/// even after perturbing the ones that aren't pinned.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    pinned: impl Fn(Idx) -> bool,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    evaluate(PredicateKind::Orient3d, 3, false, vec![i, j, k, l], pinned, |i| index_fn(list, i))
+}
+
+/// Like [`in_circle`](crate::in_circle), but leaves the points that satisfy `pinned` unperturbed.
+/// Returns an error with [`ErrorReason::Degenerate`] if the in-circle determinant is 0
+/// even after perturbing the points that aren't pinned.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    pinned: impl Fn(Idx) -> bool,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    evaluate(PredicateKind::InCircle, 2, true, vec![i, j, k, l], pinned, |i| index_fn(list, i))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but leaves the points that satisfy `pinned` unperturbed.
+/// Returns an error with [`ErrorReason::Degenerate`] if the in-sphere determinant is 0
+/// even after perturbing the points that aren't pinned.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    pinned: impl Fn(Idx) -> bool,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    evaluate(PredicateKind::InSphere, 3, true, vec![i, j, k, l, m], pinned, |i| index_fn(list, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d, rg};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_pinned() {
+        let vec1 = |l: &Vec<Vec1>, i: usize| l[i];
+        let ties = vec![Vector1::new(0.0); 2];
+        assert_eq!(super::orient_1d(&ties, vec1, |i| i == 0, 0, 1), Ok(false));
+        assert_eq!(super::orient_1d(&ties, vec1, |i| i == 1, 0, 1), Ok(true));
+        assert!(super::orient_1d(&ties, vec1, |_| true, 0, 1).unwrap_err().is_degenerate());
+
+        let points = grid_points_2d();
+        let n = points.len();
+        for pins in [0usize, 0b101, 0b1_0001_0010, (1 << n) - 1] {
+            let pin = |i: usize| pins & (1 << i) != 0;
+            // Pinned points go last
+            let last = |l: &Vec<Vec2>, (_, i): (bool, usize)| l[i];
+            // Pinned points sort after the others, so the indexes can go in order
+            for i in 0..n {
+                for j in i + 1..n {
+                    for k in j + 1..n {
+                        let (pi, pj, pk) = ((pin(i), i), (pin(j), j), (pin(k), k));
+                        let result = super::orient_2d(&points, |l, i| l[i], pin, i, j, k);
+                        let exact = rg::orient_2d(points[i], points[j], points[k]);
+                        match &result {
+                            Ok(result) => assert_eq!(*result, orient_2d(&points, last, pi, pj, pk)),
+                            Err(err) => assert!(err.is_degenerate() && exact == 0.0 && pin(i) && pin(j) && pin(k)),
+                        }
+                        if pins == 0 || exact != 0.0 {
+                            assert_eq!(result, Ok(orient_2d(&points, |l, i| l[i], i, j, k)));
+                        }
+
+                        for l in (k + 1..n).filter(|l| *l != i && *l != j) {
+                            let result = super::in_circle(&points, |l, i| l[i], pin, i, j, k, l);
+                            match &result {
+                                Ok(result) => {
+                                    assert_eq!(*result, in_circle(&points, last, pi, pj, pk, (pin(l), l)))
+                                }
+                                Err(err) => assert!(err.is_degenerate() && pin(i) && pin(j) && pin(k) && pin(l)),
+                            }
+                            if pins == 0 {
+                                assert_eq!(result, Ok(in_circle(&points, |l, i| l[i], i, j, k, l)));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        for pins in [0usize, 0b11, 0b10_1001_0110] {
+            let pin = |i: usize| pins & (1 << i) != 0;
+            let last = |l: &Vec<Vec3>, (_, i): (bool, usize)| l[i];
+            for i in 0..n {
+                let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+                let keys = [i, j, k, l, m].iter().map(|i| (pin(*i), *i)).collect::<Vec<_>>();
+                if let Ok(result) = super::orient_3d(&points, |l, i| l[i], pin, i, j, k, l) {
+                    assert_eq!(result, orient_3d(&points, last, keys[0], keys[1], keys[2], keys[3]));
+                }
+                if let Ok(result) = super::in_sphere(&points, |l, i| l[i], pin, i, j, k, l, m) {
+                    assert_eq!(result, in_sphere(&points, last, keys[0], keys[1], keys[2], keys[3], keys[4]));
+                }
+                if pins == 0 {
+                    assert_eq!(
+                        super::in_sphere(&points, |l, i| l[i], pin, i, j, k, l, m),
+                        Ok(in_sphere(&points, |l, i| l[i], i, j, k, l, m))
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,357 @@
+//! Access to the coordinates of user point types.
+
+use nalgebra::storage::Storage;
+use nalgebra::{Matrix, Vector1, Vector2, Vector3, Vector4, Vector5, U1, U2, U3, U4, U5};
+
+/// A point whose coordinates the predicates can read.
+///
+/// The predicates' indexing functions can return any point of the right dimension,
+/// including references to points and views into the list, so nothing has to be copied
+/// out of the list before the predicates read the coordinates.
+///
+/// Can be derived for structs by annotating each coordinate field
+/// with `#[sos(x)]`, `#[sos(y)]`, `#[sos(z)]`, `#[sos(w)]`, or `#[sos(n)]`
+/// for coordinate *n*. Fields must convert to `f64` with `f64::from`.
+///
+/// # Example
+///
+/// ```
+/// use simplicity::{orient_2d, SosPoint};
+///
+/// #[derive(SosPoint)]
+/// struct Vertex {
+///     #[sos(x)]
+///     px: f64,
+///     #[sos(y)]
+///     py: f32,
+///     id: u32,
+/// }
+///
+/// let vertices = vec![
+///     Vertex { px: 0.0, py: 0.0, id: 5 },
+///     Vertex { px: 1.0, py: 0.0, id: 6 },
+///     Vertex { px: 1.0, py: 1.0, id: 7 },
+/// ];
+/// assert!(orient_2d(&vertices, |l, i| &l[i], 0, 1, 2));
+/// ```
+///
+/// Vectors with any storage and the right number of rows are points too, such as
+/// the columns of a matrix that holds one point per column. The indexing function can
+/// return the column views themselves, and the predicates read the coordinates out of the matrix:
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d};
+/// # use nalgebra::{DMatrix, Matrix2xX, U1, U2};
+/// let points = Matrix2xX::from_column_slice(&[0.0, 0.0, 1.0, 0.0, 1.0, 1.0]);
+/// assert!(orient_2d(&points, |m, i| m.column(i), 0, 1, 2));
+/// // A matrix with a dynamic number of rows needs a slice with a fixed number of them
+/// let points = DMatrix::from_column_slice(2, 3, &[0.0, 0.0, 1.0, 0.0, 1.0, 1.0]);
+/// assert!(orient_2d(&points, |m, i| m.fixed_slice::<U2, U1>(0, i), 0, 1, 2));
+/// ```
+///
+/// Arrays and tuples of `f64`s are points, and so are the points and vectors of `mint`, `glam`,
+/// `cgmath`, and `ultraviolet`, with the features of the same names. The predicates in
+/// [`slice`](crate::slice) take slices of any points, so they need no closure at all:
+///
+/// ```
+/// # use simplicity::slice;
+/// let points = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)];
+/// assert!(slice::orient_2d(&points, 0, 1, 2));
+/// ```
+pub trait SosPoint {
+    /// The vector type the predicates take for points of this many coordinates.
+    type Vector;
+
+    /// The number of coordinates.
+    const DIM: usize;
+
+    /// Returns coordinate `i`, where `i < DIM`.
+    fn coord(&self, i: usize) -> f64;
+
+    /// Converts the point to the vector type the predicates take.
+    fn to_vector(&self) -> Self::Vector;
+}
+
+impl<P: SosPoint + ?Sized> SosPoint for &P {
+    type Vector = P::Vector;
+
+    const DIM: usize = P::DIM;
+
+    fn coord(&self, i: usize) -> f64 {
+        (**self).coord(i)
+    }
+
+    fn to_vector(&self) -> P::Vector {
+        (**self).to_vector()
+    }
+}
+
+// Vectors with any storage, like owned vectors, column slices of a matrix with any strides,
+// or columns of a matrix with one point per column
+macro_rules! impl_sos_point {
+    ($vector:ident, $rows:ident, $dim:expr) => {
+        impl<S: Storage<f64, $rows, U1>> SosPoint for Matrix<f64, $rows, U1, S> {
+            type Vector = $vector<f64>;
+
+            const DIM: usize = $dim;
+
+            fn coord(&self, i: usize) -> f64 {
+                self[i]
+            }
+
+            fn to_vector(&self) -> $vector<f64> {
+                $vector::from_fn(|i, _| self[i])
+            }
+        }
+    };
+}
+
+impl_sos_point!(Vector1, U1, 1);
+impl_sos_point!(Vector2, U2, 2);
+impl_sos_point!(Vector3, U3, 3);
+impl_sos_point!(Vector4, U4, 4);
+impl_sos_point!(Vector5, U5, 5);
+
+// kurbo's points and vectors, for 2D graphics code
+#[cfg(feature = "kurbo")]
+macro_rules! impl_sos_point_kurbo {
+    ($(#[$attr:meta])* $point:ident) => {
+        $(#[$attr])*
+        impl SosPoint for kurbo::$point {
+            type Vector = Vector2<f64>;
+
+            const DIM: usize = 2;
+
+            fn coord(&self, i: usize) -> f64 {
+                [self.x, self.y][i]
+            }
+
+            fn to_vector(&self) -> Vector2<f64> {
+                Vector2::new(self.x, self.y)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "kurbo")]
+impl_sos_point_kurbo!(
+    /// With the `kurbo` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use simplicity::{in_circle, kurbo, orient_2d};
+    /// use kurbo::Point;
+    ///
+    /// let points = [Point::new(0.0, 0.0), Point::new(2.0, 0.0), Point::new(0.0, 2.0), Point::new(1.0, 1.0)];
+    /// assert!(orient_2d(&points, |l, i| l[i], 0, 1, 2));
+    /// assert!(in_circle(&points, |l, i| l[i], 0, 1, 2, 3));
+    /// ```
+    Point
+);
+#[cfg(feature = "kurbo")]
+impl_sos_point_kurbo!(
+    /// With the `kurbo` feature.
+    Vec2
+);
+
+// Plain arrays and tuples of coordinates
+macro_rules! impl_sos_point_array {
+    ($vector:ident, $dim:expr) => {
+        impl SosPoint for [f64; $dim] {
+            type Vector = $vector<f64>;
+
+            const DIM: usize = $dim;
+
+            fn coord(&self, i: usize) -> f64 {
+                self[i]
+            }
+
+            fn to_vector(&self) -> $vector<f64> {
+                $vector::from(*self)
+            }
+        }
+    };
+}
+
+impl_sos_point_array!(Vector1, 1);
+impl_sos_point_array!(Vector2, 2);
+impl_sos_point_array!(Vector3, 3);
+impl_sos_point_array!(Vector4, 4);
+impl_sos_point_array!(Vector5, 5);
+
+impl SosPoint for (f64, f64) {
+    type Vector = Vector2<f64>;
+
+    const DIM: usize = 2;
+
+    fn coord(&self, i: usize) -> f64 {
+        [self.0, self.1][i]
+    }
+
+    fn to_vector(&self) -> Vector2<f64> {
+        Vector2::new(self.0, self.1)
+    }
+}
+
+impl SosPoint for (f64, f64, f64) {
+    type Vector = Vector3<f64>;
+
+    const DIM: usize = 3;
+
+    fn coord(&self, i: usize) -> f64 {
+        [self.0, self.1, self.2][i]
+    }
+
+    fn to_vector(&self) -> Vector3<f64> {
+        Vector3::new(self.0, self.1, self.2)
+    }
+}
+
+// Other math libraries' points and vectors, which have a public field for each coordinate
+#[cfg(any(feature = "mint", feature = "glam", feature = "cgmath", feature = "ultraviolet"))]
+macro_rules! impl_sos_point_fields {
+    ($feature:tt: $($ty:ty),* => $vector:ident, $dim:expr, $fields:tt) => {
+        $(impl_sos_point_fields!(@impl $feature, $ty, $vector, $dim, $fields);)*
+    };
+    (@impl $feature:tt, $ty:ty, $vector:ident, $dim:expr, ($($field:ident),*)) => {
+        #[doc = concat!("With the `", $feature, "` feature.")]
+        impl SosPoint for $ty {
+            type Vector = $vector<f64>;
+
+            const DIM: usize = $dim;
+
+            fn coord(&self, i: usize) -> f64 {
+                [$(f64::from(self.$field)),*][i]
+            }
+
+            fn to_vector(&self) -> $vector<f64> {
+                $vector::new($(f64::from(self.$field)),*)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mint")]
+impl_sos_point_fields!("mint":
+    mint::Point2<f64>, mint::Vector2<f64>, mint::Point2<f32>, mint::Vector2<f32>
+    => Vector2, 2, (x, y)
+);
+#[cfg(feature = "mint")]
+impl_sos_point_fields!("mint":
+    mint::Point3<f64>, mint::Vector3<f64>, mint::Point3<f32>, mint::Vector3<f32>
+    => Vector3, 3, (x, y, z)
+);
+#[cfg(feature = "glam")]
+impl_sos_point_fields!("glam": glam::DVec2, glam::Vec2 => Vector2, 2, (x, y));
+#[cfg(feature = "glam")]
+impl_sos_point_fields!("glam": glam::DVec3, glam::Vec3 => Vector3, 3, (x, y, z));
+#[cfg(feature = "cgmath")]
+impl_sos_point_fields!("cgmath":
+    cgmath::Point2<f64>, cgmath::Vector2<f64>, cgmath::Point2<f32>, cgmath::Vector2<f32>
+    => Vector2, 2, (x, y)
+);
+#[cfg(feature = "cgmath")]
+impl_sos_point_fields!("cgmath":
+    cgmath::Point3<f64>, cgmath::Vector3<f64>, cgmath::Point3<f32>, cgmath::Vector3<f32>
+    => Vector3, 3, (x, y, z)
+);
+#[cfg(feature = "ultraviolet")]
+impl_sos_point_fields!("ultraviolet": ultraviolet::DVec2, ultraviolet::Vec2 => Vector2, 2, (x, y));
+#[cfg(feature = "ultraviolet")]
+impl_sos_point_fields!("ultraviolet": ultraviolet::DVec3, ultraviolet::Vec3 => Vector3, 3, (x, y, z));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        SosPoint, context, hashed, in_circle, in_circle_batch, in_circle_multi, in_circle_sign,
+        in_sphere, orient_2d, orient_3d, slice,
+    };
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[derive(SosPoint)]
+    struct TupleVertex(u8, #[sos(z)] f32, #[sos(x)] f64, #[sos(1)] i32);
+
+    #[test]
+    fn test_derive_sos_point() {
+        let points = [(0, 0.0, 0.0, 0), (1, 0.0, 1.0, 0), (2, 0.0, 0.0, 1), (3, 1.0, 0.0, 0)]
+            .iter()
+            .map(|&(id, z, x, y)| TupleVertex(id, z, x, y))
+            .collect::<Vec<_>>();
+        assert_eq!(points[2].0, 2);
+        assert_eq!(TupleVertex::DIM, 3);
+        assert_eq!(points[3].coord(2), 1.0);
+        assert_eq!(points[1].to_vector(), Vector3::new(1.0, 0.0, 0.0));
+        let vectors = points.iter().map(SosPoint::to_vector).collect::<Vec<_>>();
+        assert_eq!(
+            orient_3d(&points, |l, i| &l[i], 0, 1, 2, 3),
+            orient_3d(&vectors, |l, i| l[i], 0, 1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_slice_sos_point() {
+        use nalgebra::{DMatrix, Dynamic, VectorSlice3};
+
+        // One point per row, so the coordinates of a point are a column apart
+        fn row(m: &DMatrix<f64>, i: usize) -> VectorSlice3<'_, f64, Dynamic, Dynamic> {
+            VectorSlice3::from_slice_with_strides(&m.as_slice()[i..], m.nrows(), 1)
+        }
+        let vectors = grid_points_3d();
+        let matrix = DMatrix::from_fn(vectors.len(), 3, |i, c| vectors[i][c]);
+        assert_eq!(row(&matrix, 8).coord(2), 1.0);
+        assert_eq!(row(&matrix, 5).to_vector(), vectors[5]);
+        for [i, j, k, l] in [[0, 1, 2, 4], [0, 1, 2, 3], [9, 0, 1, 2], [8, 5, 4, 1]] {
+            assert_eq!(
+                orient_3d(&matrix, row, i, j, k, l),
+                orient_3d(&vectors, |l, i| l[i], i, j, k, l)
+            );
+        }
+
+        // One point per column, read through views of the columns
+        let columns = nalgebra::Matrix3xX::from_columns(&vectors);
+        let tuples = [[0, 1, 2, 4, 5], [0, 1, 2, 3, 4], [9, 0, 1, 2, 3], [8, 5, 4, 1, 0]];
+        let expected = tuples.iter().map(|[i, j, k, l, m]| in_sphere(&vectors, |l, i| l[i], *i, *j, *k, *l, *m));
+        for ([i, j, k, l, m], expected) in tuples.iter().copied().zip(expected) {
+            assert_eq!(in_sphere(&columns, |c, i| c.column(i), i, j, k, l, m), expected);
+        }
+    }
+
+    #[test]
+    fn test_array_sos_point() {
+        let vectors = grid_points_2d();
+        let arrays = vectors.iter().map(|p| [p.x, p.y]).collect::<Vec<_>>();
+        let tuples = vectors.iter().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(<[f64; 2]>::DIM, 2);
+        assert_eq!(tuples[8].coord(1), 2.0);
+        assert_eq!(arrays[5].to_vector(), vectors[5]);
+        for [i, j, k, l] in [[0, 1, 2, 3], [0, 1, 3, 4], [8, 4, 0, 10], [1, 5, 7, 3]] {
+            assert_eq!(slice::orient_2d(&arrays, i, j, k), orient_2d(&vectors, |l, i| l[i], i, j, k));
+            assert_eq!(slice::in_circle(&tuples, i, j, k, l), in_circle(&vectors, |l, i| l[i], i, j, k, l));
+        }
+
+        // The indexing functions can return them too
+        let mut context = context::PredicateContext::new(&tuples, |l: &Vec<(f64, f64)>, i| l[i]);
+        let tuples_4 = [[0, 1, 2, 3], [0, 1, 3, 4], [8, 4, 0, 10], [1, 5, 7, 3]];
+        for [i, j, k, l] in tuples_4 {
+            let expected = in_circle(&vectors, |l, i| l[i], i, j, k, l);
+            assert_eq!(in_circle(&arrays, |l, i| l[i], i, j, k, l), expected);
+            assert_eq!(in_circle_sign(&tuples, |l, i| &l[i], i, j, k, l).is_positive(), expected);
+            assert_eq!(context.in_circle(i, j, k, l), expected);
+            assert_eq!(hashed::orient_2d(&arrays, |l, i| l[i], i, j, k), hashed::orient_2d(&vectors, |l, i| l[i], i, j, k));
+        }
+        let expected = tuples_4.iter().map(|[i, j, k, l]| in_circle(&vectors, |l, i| l[i], *i, *j, *k, *l)).collect::<Vec<_>>();
+        assert_eq!(in_circle_batch(&arrays, |l, i| l[i], &tuples_4), expected);
+        let queries = (3..vectors.len()).collect::<Vec<_>>();
+        assert_eq!(
+            in_circle_multi(&tuples, |l, i| l[i], 0, 1, 2, &queries),
+            in_circle_multi(&vectors, |l, i| l[i], 0, 1, 2, &queries)
+        );
+
+        let vectors = grid_points_3d();
+        let tuples = vectors.iter().map(|p| (p.x, p.y, p.z)).collect::<Vec<_>>();
+        assert_eq!(tuples[8].to_vector(), vectors[8]);
+        assert_eq!(slice::orient_3d(&tuples, 0, 1, 2, 4), orient_3d(&vectors, |l, i| l[i], 0, 1, 2, 4));
+    }
+}
@@ -0,0 +1,240 @@
+//! Triangulating simple polygons by ear clipping.
+//!
+//! An *ear* of a polygon is a vertex where the polygon turns left, such that no other
+//! vertex is inside the triangle it makes with its neighbors. Cutting off an ear leaves a smaller
+//! polygon, and every simple polygon with more than 3 vertices has at least 2 ears.
+//! The tests use [`orient_2d`](crate::orient_2d), so vertices in collinear runs turn slightly
+//! left or right, and a vertex is never exactly on an edge of a triangle. Repeated vertices,
+//! such as the ends of a bridge to a hole, are never inside triangles they're corners of.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, polygon};
+//! # use nalgebra::Vector2;
+//! // A square with collinear runs, and a notch in the top
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(2.0, 2.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(0.0, 2.0),
+//!     Vector2::new(0.0, 1.0),
+//! ];
+//! let polygon = (0..points.len()).collect::<Vec<_>>();
+//! let triangles = polygon::triangulate(&points, |l, i| l[i], &polygon);
+//! assert_eq!(triangles.len(), points.len() - 2);
+//! assert!(!polygon::is_ear(&points, |l, i| l[i], &polygon, 4));
+//! ```
+
+use crate::Vec2;
+use nalgebra::Vector1;
+use alloc::{vec, vec::Vec};
+
+/// Returns whether point `p` is inside triangle `abc` after perturbing the points.
+/// The triangle must be oriented positive, and `p` is never inside if it's one of the corners.
+pub fn in_triangle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    a: Idx,
+    b: Idx,
+    c: Idx,
+    p: Idx,
+) -> bool {
+    let orient = |i, j| crate::orient_2d(list, &index_fn, i, j, p);
+    p != a && p != b && p != c && orient(a, b) && orient(b, c) && orient(c, a)
+}
+
+/// Whether `v` is an ear between `u` and `w`, given the other vertices.
+fn ear<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    [u, v, w]: [Idx; 3],
+    mut others: impl Iterator<Item = Idx>,
+) -> bool {
+    crate::orient_2d(list, &index_fn, u, v, w) && !others.any(|p| in_triangle(list, &index_fn, u, v, w, p))
+}
+
+/// Returns whether a simple polygon is counterclockwise after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, and the polygon as a sequence of indexes
+/// into the list. This is decided by whether the polygon turns left at its leftmost vertex.
+pub fn is_counterclockwise<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, polygon: &[Idx]) -> bool {
+    let n = polygon.len();
+    let leftmost = (1..n).fold(0, |l, i| {
+        if crate::orient_1d(list, |l, i| Vector1::new(index_fn(l, i).x), polygon[l], polygon[i]) {
+            i
+        } else {
+            l
+        }
+    });
+    let corners = [polygon[(leftmost + n - 1) % n], polygon[leftmost], polygon[(leftmost + 1) % n]];
+    crate::orient_2d(list, &index_fn, corners[0], corners[1], corners[2])
+}
+
+/// Returns whether point `p` is inside a polygon after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the polygon as a sequence of indexes
+/// into the list, in either orientation, and the point. This counts the edges that a ray
+/// from `p` in the +x direction crosses, so the polygon doesn't need to be simple.
+/// A vertex of the polygon is never inside it.
+pub fn contains<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, polygon: &[Idx], p: Idx) -> bool {
+    let n = polygon.len();
+    let above = |i, j| crate::orient_1d(list, |l, i| Vector1::new(index_fn(l, i).y), i, j);
+    let crossings = (0..n)
+        .filter(|e| {
+            let (u, v) = (polygon[*e], polygon[(e + 1) % n]);
+            if u == p || v == p || above(u, p) == above(v, p) {
+                return false;
+            }
+            // The edge is to the right of p if p is on the left going up
+            crate::orient_2d(list, &index_fn, u, v, p) == above(v, p)
+        })
+        .count();
+    !polygon.contains(&p) && crossings % 2 == 1
+}
+
+/// Returns whether vertex `i` of a counterclockwise polygon is an ear after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the polygon as a sequence of indexes
+/// into the list, and the position of the vertex in the polygon.
+pub fn is_ear<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, polygon: &[Idx], i: usize) -> bool {
+    let n = polygon.len();
+    let corners = [polygon[(i + n - 1) % n], polygon[i], polygon[(i + 1) % n]];
+    ear(list, index_fn, corners, polygon.iter().copied())
+}
+
+/// Triangulates a polygon by ear clipping after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, and the polygon as a sequence of indexes
+/// into the list, in either orientation. Returns `n - 2` triangles.
+/// If the polygon is simple after perturbing, possibly with repeated vertices where it touches itself,
+/// they're oriented positive and triangulate it. Otherwise there may be no ears, so vertices where
+/// the polygon turns left, or failing that, any vertices, get cut off instead, and this still
+/// terminates. Either way, it takes O(n²) tests.
+pub fn triangulate<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec2, polygon: &[Idx]) -> Vec<[Idx; 3]> {
+    let n = polygon.len();
+    if n < 3 {
+        return vec![];
+    }
+
+    let mut polygon = polygon.to_vec();
+    if !is_counterclockwise(list, &index_fn, &polygon) {
+        polygon.reverse();
+    }
+
+    let mut prev = (0..n).map(|i| (i + n - 1) % n).collect::<Vec<_>>();
+    let mut next = (0..n).map(|i| (i + 1) % n).collect::<Vec<_>>();
+    let mut triangles = vec![];
+    let (mut i, mut remaining) = (0, n);
+    // How long since the last cut, and how strict to be about what to cut
+    let (mut tested, mut strictness) = (0, 0);
+    while remaining > 3 {
+        let (u, w) = (prev[i], next[i]);
+        let corners = [polygon[u], polygon[i], polygon[w]];
+        let cut = match strictness {
+            0 => {
+                let others = core::iter::successors(Some(next[w]), |j| Some(next[*j])).take_while(|j| *j != u);
+                ear(list, &index_fn, corners, others.map(|j| polygon[j]))
+            }
+            1 => crate::orient_2d(list, &index_fn, corners[0], corners[1], corners[2]),
+            _ => true,
+        };
+
+        if cut {
+            triangles.push(corners);
+            next[u] = w;
+            prev[w] = u;
+            remaining -= 1;
+            tested = 0;
+            strictness = 0;
+            i = u;
+        } else {
+            tested += 1;
+            if tested == remaining {
+                tested = 0;
+                strictness += 1;
+            }
+            i = w;
+        }
+    }
+    triangles.push([polygon[prev[i]], polygon[i], polygon[next[i]]]);
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Keyed, enclosing, orient_2d};
+    use crate::tests::grid_points_2d;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_polygon_triangulate() {
+        let area = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp(&(c - a));
+        let check = |points: &[Vec2], polygon: &[usize]| {
+            let triangles = super::triangulate(points, |l, i| l[i], polygon);
+            assert_eq!(triangles.len(), polygon.len() - 2);
+            let n = polygon.len();
+            let expected = (0..n).map(|i| area(Vector2::zeros(), points[polygon[i]], points[polygon[(i + 1) % n]])).sum::<f64>();
+            let mut total = 0.0;
+            for [a, b, c] in triangles {
+                assert!(orient_2d(points, |l, i| l[i], a, b, c));
+                assert!(!polygon.iter().any(|p| super::in_triangle(points, |l, i| l[i], a, b, c, *p)));
+                total += area(points[a], points[b], points[c]);
+            }
+            assert_eq!(total, expected.abs());
+        };
+
+        // The boundary of the grid, with collinear runs, in both orientations
+        let points = grid_points_2d();
+        let mut polygon = vec![0, 1, 2, 5, 8, 7, 6, 3];
+        check(&points, &polygon);
+        polygon.reverse();
+        check(&points, &polygon);
+
+        // A square with a square hole, connected by a bridge, so 2 vertices repeat
+        let points = [(0, 0), (6, 0), (6, 6), (0, 6), (2, 2), (2, 4), (4, 4), (4, 2)]
+            .iter()
+            .map(|(x, y)| Vector2::new(*x as f64, *y as f64))
+            .collect::<Vec<_>>();
+        check(&points, &[0, 1, 2, 3, 0, 4, 5, 6, 7, 4]);
+
+        // Not simple, but it still terminates
+        let polygon = [0, 2, 1, 3, 4, 6, 5, 7];
+        assert_eq!(super::triangulate(&points, |l, i| l[i], &polygon).len(), polygon.len() - 2);
+    }
+
+    #[test]
+    fn test_polygon_keyed() {
+        // Removing points by moving the last one into their place reorders the rest,
+        // but keying them by their old indexes keeps every decision
+        let points = grid_points_2d();
+        let mut ids = (0..points.len()).collect::<Vec<_>>();
+        ids.swap_remove(4);
+        ids.swap_remove(9);
+        let compacted = ids.iter().map(|i| points[*i]).collect::<Vec<_>>();
+        let key = |i: usize| Keyed::new(ids[i], i);
+        let index_fn = |l: &Vec<Vec2>, k: Keyed<usize>| l[k.index];
+
+        let polygon = [0, 1, 2, 5, 8, 7, 6, 3];
+        let keyed = polygon.iter().map(|i| key(ids.iter().position(|id| id == i).unwrap())).collect::<Vec<_>>();
+        let triangles = super::triangulate(&compacted, index_fn, &keyed)
+            .iter()
+            .map(|tri| tri.map(|k| k.key))
+            .collect::<Vec<_>>();
+        assert_eq!(triangles, super::triangulate(&points, |l, i| l[i], &polygon));
+        for (p, id) in ids.iter().enumerate() {
+            let inside = super::contains(&points, |l, i| l[i], &polygon, *id);
+            assert_eq!(super::contains(&compacted, index_fn, &keyed, key(p)), inside);
+        }
+        assert_eq!(
+            enclosing::min_enclosing_circle(&compacted, index_fn, &(0..compacted.len()).map(key).collect::<Vec<_>>())
+                .iter()
+                .map(|k| k.key)
+                .collect::<Vec<_>>(),
+            enclosing::min_enclosing_circle(&points, |l, i| l[i], &ids)
+        );
+    }
+}
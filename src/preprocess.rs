@@ -0,0 +1,206 @@
+//! Cleaning up input before using the predicates, with a report of what changed.
+//!
+//! Simulation of simplicity makes every input usable, but sometimes it's better to clean up
+//! the input first, such as by merging duplicate points or snapping coordinates to a grid.
+//! That changes which subsets of the points are degenerate, so the functions here
+//! [analyze](crate::analysis) the points before and after, and report exactly which
+//! degeneracies were removed and which were introduced. Both are given in terms of
+//! the indexes of the input points. When several input points become 1 output point,
+//! the smallest of their indexes stands for it.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, preprocess, analysis::{Kind, Options}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.1),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//! ];
+//! let deduped = preprocess::dedup_2d(&points, |l, i| l[i], points.len(), &Options::default());
+//! assert_eq!(deduped.points.len(), 3);
+//! assert_eq!(deduped.map, vec![0, 1, 2, 2]);
+//! assert_eq!(deduped.removed.len(), 1);
+//! assert_eq!(deduped.removed[0].kind, Kind::Duplicate);
+//!
+//! let snapped = preprocess::snap_2d(&deduped.points, |l, i| l[i], 3, 0.5, &Options::default());
+//! assert_eq!(snapped.introduced.len(), 1);
+//! assert_eq!(snapped.introduced[0].kind, Kind::Collinear);
+//! ```
+
+use crate::analysis::{self, Degeneracy, Options, Report};
+use crate::float;
+use crate::{Vec2, Vec3};
+use alloc::{collections::BTreeSet, vec, vec::Vec};
+
+/// Points after preprocessing, with the degeneracies that changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preprocessed<P> {
+    pub points: Vec<P>,
+    /// The index of the output point that each input point became
+    pub map: Vec<usize>,
+    /// Degeneracies of the input that the output doesn't have
+    pub removed: Vec<Degeneracy>,
+    /// Degeneracies of the output that the input doesn't have
+    pub introduced: Vec<Degeneracy>,
+}
+
+/// Compares the reports before and after preprocessing.
+fn preprocessed<P>(points: Vec<P>, map: Vec<usize>, before: Report, after: Report) -> Preprocessed<P> {
+    // The smallest input index of each output point
+    let mut representatives = vec![usize::MAX; points.len()];
+    for (i, o) in map.iter().enumerate().rev() {
+        representatives[*o] = i;
+    }
+
+    // An input degeneracy in terms of the output, unless some of its points merged
+    let mapped = |d: &Degeneracy| {
+        let mut indexes = d.indexes.iter().map(|i| map[*i]).collect::<Vec<_>>();
+        indexes.sort_unstable();
+        let merged = indexes.windows(2).any(|w| w[0] == w[1]);
+        Some(Degeneracy { kind: d.kind, indexes }).filter(|_| !merged)
+    };
+    let after_set = after.degeneracies.iter().collect::<BTreeSet<_>>();
+    let before_mapped = before.degeneracies.iter().filter_map(mapped).collect::<BTreeSet<_>>();
+
+    let removed = before
+        .degeneracies
+        .iter()
+        .filter(|d| !mapped(d).is_some_and(|m| after_set.contains(&m)))
+        .cloned()
+        .collect();
+    let introduced = after
+        .degeneracies
+        .iter()
+        .filter(|d| !before_mapped.contains(d))
+        .map(|d| {
+            let mut indexes = d.indexes.iter().map(|o| representatives[*o]).collect::<Vec<_>>();
+            indexes.sort_unstable();
+            Degeneracy { kind: d.kind, indexes }
+        })
+        .collect();
+    Preprocessed { points, map, removed, introduced }
+}
+
+/// Merges exactly equal points, keeping the first of each, in order.
+fn dedup<P: Copy + PartialEq>(points: &[P]) -> (Vec<P>, Vec<usize>) {
+    let mut unique = vec![];
+    let map = points
+        .iter()
+        .map(|p| {
+            unique.iter().position(|q| q == p).unwrap_or_else(|| {
+                unique.push(*p);
+                unique.len() - 1
+            })
+        })
+        .collect();
+    (unique, map)
+}
+
+/// Rounds a coordinate to the nearest multiple of the spacing.
+fn snap(x: f64, spacing: f64) -> f64 {
+    float::round(x / spacing) * spacing + 0.0
+}
+
+/// Merges the exactly equal points among the first `len` points in a list.
+/// The `options` are passed to the analysis.
+pub fn dedup_2d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec2, len: usize, options: &Options) -> Preprocessed<Vec2> {
+    let input = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let (points, map) = dedup(&input);
+    let before = analysis::analyze_2d(&input, |l, i| l[i], len, options);
+    let after = analysis::analyze_2d(&points, |l, i| l[i], points.len(), options);
+    preprocessed(points, map, before, after)
+}
+
+/// Merges the exactly equal points among the first `len` points in a list.
+/// The `options` are passed to the analysis.
+pub fn dedup_3d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec3, len: usize, options: &Options) -> Preprocessed<Vec3> {
+    let input = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let (points, map) = dedup(&input);
+    let before = analysis::analyze_3d(&input, |l, i| l[i], len, options);
+    let after = analysis::analyze_3d(&points, |l, i| l[i], points.len(), options);
+    preprocessed(points, map, before, after)
+}
+
+/// Rounds the coordinates of the first `len` points in a list to the nearest multiples of `spacing`.
+/// Points that become equal aren't merged, but are reported as introduced duplicates.
+/// The `options` are passed to the analysis.
+pub fn snap_2d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    len: usize,
+    spacing: f64,
+    options: &Options,
+) -> Preprocessed<Vec2> {
+    let input = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let points = input.iter().map(|p| p.map(|x| snap(x, spacing))).collect::<Vec<_>>();
+    let before = analysis::analyze_2d(&input, |l, i| l[i], len, options);
+    let after = analysis::analyze_2d(&points, |l, i| l[i], len, options);
+    preprocessed(points, (0..len).collect(), before, after)
+}
+
+/// Rounds the coordinates of the first `len` points in a list to the nearest multiples of `spacing`.
+/// Points that become equal aren't merged, but are reported as introduced duplicates.
+/// The `options` are passed to the analysis.
+pub fn snap_3d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3,
+    len: usize,
+    spacing: f64,
+    options: &Options,
+) -> Preprocessed<Vec3> {
+    let input = (0..len).map(|i| index_fn(list, i)).collect::<Vec<_>>();
+    let points = input.iter().map(|p| p.map(|x| snap(x, spacing))).collect::<Vec<_>>();
+    let before = analysis::analyze_3d(&input, |l, i| l[i], len, options);
+    let after = analysis::analyze_3d(&points, |l, i| l[i], len, options);
+    preprocessed(points, (0..len).collect(), before, after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_preprocess() {
+        use crate::analysis::{Kind, Options};
+
+        // Only the duplicates go away, since the other degeneracies still hold for the merged points
+        let points = grid_points_2d();
+        let deduped = super::dedup_2d(&points, |l, i| l[i], points.len(), &Options::default());
+        assert_eq!(deduped.points.len(), 9);
+        assert!(deduped.map.iter().enumerate().all(|(i, o)| deduped.points[*o] == points[i]));
+        assert_eq!(deduped.removed.len(), 2);
+        assert!(deduped.removed.iter().all(|d| d.kind == Kind::Duplicate));
+        assert_eq!(deduped.introduced, vec![]);
+
+        let points = grid_points_3d();
+        let deduped = super::dedup_3d(&points, |l, i| l[i], points.len(), &Options::default());
+        assert_eq!(deduped.points.len(), 8);
+        assert!(deduped.removed.iter().all(|d| d.kind == Kind::Duplicate));
+        assert_eq!(deduped.introduced, vec![]);
+
+        // Snapping a jittered grid back to the grid brings back its degeneracies
+        let grid = (0..9).map(|i| Vector2::new((i % 3) as f64, (i / 3) as f64)).collect::<Vec<_>>();
+        let jittered = grid
+            .iter()
+            .enumerate()
+            .map(|(i, p)| p + Vector2::new(0.01 * i as f64, -0.003 * (i * i) as f64))
+            .collect::<Vec<_>>();
+        let snapped = super::snap_2d(&jittered, |l, i| l[i], jittered.len(), 1.0, &Options::default());
+        assert_eq!(snapped.points, grid);
+        let before = analysis::analyze_2d(&jittered, |l, i| l[i], jittered.len(), &Options::default());
+        let after = analysis::analyze_2d(&grid, |l, i| l[i], grid.len(), &Options::default());
+        let introduced = after.degeneracies.iter().filter(|d| !before.degeneracies.contains(d)).cloned().collect::<Vec<_>>();
+        assert!(!introduced.is_empty());
+        assert_eq!(snapped.introduced, introduced);
+        assert!(snapped.removed.iter().all(|d| !after.degeneracies.contains(d)));
+
+        // Snapping to a coarse grid merges points
+        let snapped = super::snap_2d(&jittered, |l, i| l[i], jittered.len(), 4.0, &Options::default());
+        assert!(snapped.introduced.iter().any(|d| d.kind == Kind::Duplicate));
+    }
+}
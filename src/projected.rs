@@ -0,0 +1,131 @@
+//! 2D predicates on projections of 3D points, consistent with the 3D predicates.
+//!
+//! Projecting perturbed 3D points to a coordinate plane gives perturbed 2D points, and the
+//! predicates here are the signs of the 2D determinants of those, so they can never contradict
+//! the 3D predicates on the same points. For example, if 3 points are collinear, the sign of
+//! their orientation in the xy plane is the sign of the z component of the normal that
+//! [`orient_3d`](crate::orient_3d) sees.
+//!
+//! Within a point, the 3D predicates perturb z the most and x the least, so in the xy and yz
+//! planes, the second axis is perturbed more, like y in 2D, and these are the usual 2D
+//! predicates on the projected coordinates. In the zx plane, x is perturbed less than z,
+//! so these use the xz plane and flip the result, since swapping the axes flips the determinants.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, projected::{self, Plane}};
+//! # use nalgebra::Vector3;
+//! let points = vec![
+//!     Vector3::new(0.0, 0.0, 0.0),
+//!     Vector3::new(1.0, 1.0, 1.0),
+//!     Vector3::new(2.0, 2.0, 2.0),
+//! ];
+//! // All the projections of the diagonal are degenerate
+//! let xy = projected::orient_2d(&points, |l, i| l[i], Plane::Xy, 0, 1, 2);
+//! let yz = projected::orient_2d(&points, |l, i| l[i], Plane::Yz, 0, 1, 2);
+//! let zx = projected::orient_2d(&points, |l, i| l[i], Plane::Zx, 0, 1, 2);
+//! // They're the signs of the components of the normal of the perturbed triangle,
+//! // which is perpendicular to the diagonal, so they can't all be the same
+//! assert!(!(xy && yz && zx) && (xy || yz || zx));
+//! ```
+
+use crate::{Vec2, Vec3};
+use nalgebra::Vector2;
+
+/// A coordinate plane to project to, with its axes in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Plane {
+    /// The xy plane, viewed from +z
+    Xy,
+    /// The yz plane, viewed from +x
+    Yz,
+    /// The zx plane, viewed from +y
+    Zx,
+}
+
+impl Plane {
+    /// The axes to project to, in order of how much they're perturbed,
+    /// and whether that's the opposite of the order of the plane.
+    fn axes(self) -> ([usize; 2], bool) {
+        match self {
+            Plane::Xy => ([0, 1], false),
+            Plane::Yz => ([1, 2], false),
+            Plane::Zx => ([0, 2], true),
+        }
+    }
+
+    /// Projects a point to the plane, with the axes in order of how much they're perturbed.
+    fn project(self, p: Vec3) -> Vec2 {
+        let ([a, b], _) = self.axes();
+        Vector2::new(p[a], p[b])
+    }
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but on the projections of 3D points to a coordinate plane.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    plane: Plane,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    let (_, flip) = plane.axes();
+    crate::orient_2d(list, |l, i| plane.project(index_fn(l, i)), i, j, k) != flip
+}
+
+/// Like [`in_circle`](crate::in_circle), but on the projections of 3D points to a coordinate plane.
+/// The first 3 points should be oriented positive in the plane, according to [`orient_2d`].
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    plane: Plane,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let (_, flip) = plane.axes();
+    crate::in_circle(list, |l, i| plane.project(index_fn(l, i)), i, j, k, l) != flip
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::grid_points_3d;
+
+    #[test]
+    fn test_projected() {
+        use crate::perturbed::Perturbed;
+        use super::Plane;
+
+        // The signs of the projections of the 3D perturbed points
+        let points = grid_points_3d();
+        let n = points.len();
+        for (plane, [a, b]) in [(Plane::Xy, [0, 1]), (Plane::Yz, [1, 2]), (Plane::Zx, [2, 0])] {
+            for i in 0..n {
+                for j in i + 1..n {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        let perturbed = Perturbed::new(3, 4, &[i, j, k], |i| points[i]);
+                        let d = |i, c| perturbed.diff(i, k, c);
+                        let det = d(i, a) * d(j, b) - d(i, b) * d(j, a);
+                        assert_eq!(super::orient_2d(&points, |l, i| l[i], plane, i, j, k), det.signum() > 0.0);
+
+                        let l = (k + 1) % n;
+                        if l != i && l != j {
+                            let perturbed = Perturbed::new(3, 4, &[i, j, k, l], |i| points[i]);
+                            let d = |i, c| perturbed.diff(i, l, c);
+                            let lift = |i| d(i, a) * d(i, a) + d(i, b) * d(i, b);
+                            let det = crate::perturbed::det(&[
+                                vec![d(i, a), d(i, b), lift(i)],
+                                vec![d(j, a), d(j, b), lift(j)],
+                                vec![d(k, a), d(k, b), lift(k)],
+                            ]);
+                            assert_eq!(super::in_circle(&points, |l, i| l[i], plane, i, j, k, l), det.signum() > 0.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! Quadtree and octree decisions consistent with the predicates.
+//!
+//! A quadtree or octree splits each cell at a center into 2^*N* children, and Delaunay
+//! triangulation accelerated by one looks up which cells a simplex's circumcircle or circumsphere
+//! overlaps to find the points that conflict with it. If a point exactly on a splitting plane
+//! goes to a child the predicates wouldn't put it in, or a circumcircle that only touches a cell
+//! is decided differently than the in-circle test of a point in that cell, the search misses
+//! conflicts. Here the splitting planes and the faces of cells are given by points on them,
+//! the same way as in [`aabb`](crate::aabb), and everything is decided after perturbing the points.
+//! A child contains exactly the points [`aabb::contains`](crate::aabb::contains) puts in its box,
+//! and a circumcircle overlaps every cell that contains a point inside it.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, quadtree};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(0.0, 2.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(1.0, 0.5),
+//!     Vector2::new(3.0, 3.0),
+//!     Vector2::new(4.0, 4.0),
+//! ];
+//! // Point 4 is on the splitting plane x = 1, and point 3 is perturbed further in +x,
+//! // so it goes in the children with smaller x
+//! let children = quadtree::assign(&points, |l, i| l[i], [3, 3], &[0, 1, 2, 4, 5]);
+//! assert_eq!(children, vec![vec![0, 4], vec![1], vec![2], vec![5]]);
+//! // The circumcircle of the triangle is centered at (1, 1) with radius sqrt(2)
+//! assert!(quadtree::circumcircle_overlaps(&points, |l, i| l[i], [0, 1, 2], [3, 3], [6, 6]));
+//! assert!(!quadtree::circumcircle_overlaps(&points, |l, i| l[i], [0, 1, 2], [5, 5], [6, 6]));
+//! ```
+
+use crate::kdtree::axis_cmp;
+use crate::perturbed::{Perturbed, Polynomial};
+use crate::{Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::ops::Index;
+
+/// Returns which child of a cell split at `center` point `p` goes in, after perturbing the points.
+/// Bit *k* of the result is set if `p` isn't less than the center along axis *k*,
+/// by [`kdtree::axis_cmp`](crate::kdtree::axis_cmp), so a point on a splitting plane goes on
+/// the side the predicates would put it on, and the center itself goes in the last child.
+///
+/// Takes a list of all the points, an indexing function that returns something that can be
+/// indexed by axis, like a vector, the indexes of points on the splitting planes along each axis,
+/// and the index of the point.
+pub fn child<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>, const N: usize>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    center: [Idx; N],
+    p: Idx,
+) -> usize {
+    (0..N)
+        .filter(|axis| axis_cmp(list, &index_fn, *axis, p, center[*axis]) != Ordering::Less)
+        .fold(0, |acc, axis| acc | 1 << axis)
+}
+
+/// Assigns each of `indexes` to a child of a cell split at `center`, by [`child`],
+/// and returns the 2^*N* children's points, in the order they're given.
+pub fn assign<T: ?Sized, Idx: Ord + Copy, P: Index<usize, Output = f64>, const N: usize>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    center: [Idx; N],
+    indexes: &[Idx],
+) -> Vec<Vec<Idx>> {
+    let mut children = vec![vec![]; 1 << N];
+    for i in indexes {
+        children[child(list, &index_fn, center, *i)].push(*i);
+    }
+    children
+}
+
+/// The circumcenter of a simplex relative to its first point, as a numerator for each axis
+/// and a common denominator, which is 0 only if the simplex is degenerate.
+fn circumcenter<Idx: Ord + Copy, P: Index<usize, Output = f64>, F: Fn(Idx) -> P>(
+    perturbed: &Perturbed<Idx, F>,
+    simplex: &[Idx],
+) -> (Vec<Polynomial>, Polynomial) {
+    let edges = simplex[1..]
+        .iter()
+        .map(|i| (0..simplex.len() - 1).map(|k| perturbed.diff(*i, simplex[0], k)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let squared = |e: &[Polynomial]| e.iter().fold(Polynomial::default(), |acc, x| acc + x.clone() * x.clone());
+    if let [u, v] = &edges[..] {
+        // (|u|² v⊥ - |v|² u⊥) / (2 u × v), with (x, y)⊥ = (y, -x)
+        let (lu, lv) = (squared(u), squared(v));
+        let x = lu.clone() * v[1].clone() - lv.clone() * u[1].clone();
+        let y = lv * u[0].clone() - lu * v[0].clone();
+        let w = (u[0].clone() * v[1].clone() - u[1].clone() * v[0].clone()).scale(2.0);
+        return (vec![x, y], w);
+    }
+    // (|u|² v × w + |v|² w × u + |w|² u × v) / (2 u · v × w)
+    let cross = |x: &[Polynomial], y: &[Polynomial]| {
+        [0, 1, 2].map(|k| {
+            let (k1, k2) = ((k + 1) % 3, (k + 2) % 3);
+            x[k1].clone() * y[k2].clone() - x[k2].clone() * y[k1].clone()
+        })
+    };
+    let (u, v, w) = (&edges[0], &edges[1], &edges[2]);
+    let (vw, wu, uv) = (cross(v, w), cross(w, u), cross(u, v));
+    let den = (0..3).fold(Polynomial::default(), |acc, k| acc + u[k].clone() * vw[k].clone()).scale(2.0);
+    let (lu, lv, lw) = (squared(u), squared(v), squared(w));
+    let center = (0..3)
+        .map(|k| lu.clone() * vw[k].clone() + lv.clone() * wu[k].clone() + lw.clone() * uv[k].clone())
+        .collect();
+    (center, den)
+}
+
+/// Returns whether the open circumball of `simplex`, in `simplex.len() - 1` dimensions,
+/// overlaps the box with faces `lo` and `hi`, after perturbing the points.
+fn circumball_overlaps<Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    simplex: &[Idx],
+    lo: &[Idx],
+    hi: &[Idx],
+    point: impl Fn(Idx) -> P,
+) -> bool {
+    assert!(
+        !simplex.iter().enumerate().any(|(n, i)| simplex[..n].contains(i)),
+        "an index of the simplex is repeated"
+    );
+    let dim = simplex.len() - 1;
+    let a = simplex[0];
+    let mut indexes = simplex.to_vec();
+    indexes.extend(lo.iter().chain(hi));
+    // The squared distance from the circumcenter, times the squared denominator, has degree 8
+    let mut perturbed = Perturbed::new(dim, 8, &indexes, point);
+
+    // With o = a + C / W, W (o - f) = C - W (f - a) along an axis
+    let offset = |perturbed: &Perturbed<Idx, _>, axis: usize, face: Idx| {
+        let (center, w) = circumcenter(perturbed, simplex);
+        center[axis].clone() - w * perturbed.diff(face, a, axis)
+    };
+    let den = perturbed.lazy_signum(|perturbed| circumcenter(perturbed, simplex).1);
+    // The nearest face along each axis the center is outside of
+    let nearest = (0..dim)
+        .map(|axis| {
+            if perturbed.lazy_signum(|perturbed| offset(perturbed, axis, lo[axis])) * den < 0.0 {
+                Some(lo[axis])
+            } else if perturbed.lazy_signum(|perturbed| offset(perturbed, axis, hi[axis])) * den > 0.0 {
+                Some(hi[axis])
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // |o - f|² < |o - a|² for the nearest point f of the box
+    let sign = perturbed.lazy_signum(|perturbed| {
+        let (center, _) = circumcenter(perturbed, simplex);
+        let radius = center.iter().fold(Polynomial::default(), |acc, x| acc + x.clone() * x.clone());
+        nearest.iter().enumerate().fold(-radius, |acc, (axis, face)| match face {
+            Some(face) => {
+                let offset = offset(perturbed, axis, *face);
+                acc + offset.clone() * offset
+            }
+            None => acc,
+        })
+    });
+    sign < 0.0
+}
+
+/// Returns whether the open circumcircle of a triangle overlaps a cell, after perturbing the points.
+/// If the cell contains a point, by [`aabb::contains`](crate::aabb::contains),
+/// that's inside the circumcircle, by [`in_circle_unoriented`](crate::in_circle_unoriented),
+/// this returns `true`.
+///
+/// Takes a list of all the points, an indexing function, the indexes of the vertices of
+/// the triangle in any order, and the indexes of points on the lower and upper faces of the cell
+/// along each axis. Panics if an index of the triangle is repeated.
+pub fn circumcircle_overlaps<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    triangle: [Idx; 3],
+    lo: [Idx; 2],
+    hi: [Idx; 2],
+) -> bool {
+    circumball_overlaps(&triangle, &lo, &hi, |i| index_fn(list, i))
+}
+
+/// Returns whether the open circumsphere of a tetrahedron overlaps a cell, after perturbing the points.
+/// This is like [`circumcircle_overlaps`], for octrees.
+///
+/// Panics if an index of the tetrahedron is repeated.
+pub fn circumsphere_overlaps<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    tetrahedron: [Idx; 4],
+    lo: [Idx; 3],
+    hi: [Idx; 3],
+) -> bool {
+    circumball_overlaps(&tetrahedron, &lo, &hi, |i| index_fn(list, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aabb, in_circle_unoriented, in_sphere_unoriented, rg, rng};
+    use crate::tests::grid_points_2d;
+    use nalgebra::{Vector2, Vector3};
+
+    #[test]
+    fn test_quadtree() {
+        use nalgebra::{DMatrix, DVector};
+
+        // Children agree with the boxes they're in
+        let points = grid_points_2d();
+        let all = (0..points.len()).collect::<Vec<_>>();
+        for center in 0..points.len() {
+            let children = super::assign(&points, |l, i| l[i], [center, center], &all);
+            assert!(children[3].contains(&center));
+            for (n, child) in children.iter().enumerate() {
+                let lo = [0, 1].map(|k| if n >> k & 1 == 1 { center } else { 10 });
+                let hi = [0, 1].map(|k| if n >> k & 1 == 1 { 8 } else { center });
+                for p in child {
+                    assert_eq!(super::child(&points, |l, i| l[i], [center, center], *p), n);
+                    if aabb::contains(&points, |l, i| l[i], [10, 10], [8, 8], *p) {
+                        assert!(aabb::contains(&points, |l, i| l[i], lo, hi, *p), "{} {}", center, p);
+                    }
+                }
+            }
+        }
+
+        // The squared distance from the circumcenter of a simplex to a box, and the squared radius
+        fn distances(simplex: &[&[f64]], lo: &[f64], hi: &[f64]) -> (f64, f64) {
+            let dim = lo.len();
+            let a = DVector::from_column_slice(simplex[0]);
+            let m = DMatrix::from_fn(dim, dim, |i, k| 2.0 * (simplex[i + 1][k] - a[k]));
+            let b = DVector::from_fn(dim, |i, _| (DVector::from_column_slice(simplex[i + 1]) - &a).norm_squared());
+            let offset = m.lu().solve(&b).unwrap();
+            let center = &a + &offset;
+            let dist = (0..dim).map(|k| (lo[k] - center[k]).max(center[k] - hi[k]).max(0.0)).map(|d| d * d).sum();
+            (dist, offset.norm_squared())
+        }
+
+        // A cell that contains a point inside a circumcircle overlaps it, even with ties everywhere,
+        // and away from ties, this agrees with floating point
+        let mut rng = rng::SplitMix(1499);
+        let points_2d = (0..10).map(|_| Vector2::new(rng.int(0, 4), rng.int(0, 4))).collect::<Vec<_>>();
+        let points_3d = (0..10).map(|_| Vector3::new(rng.int(0, 3), rng.int(0, 3), rng.int(0, 3))).collect::<Vec<_>>();
+        let (mut contained, mut compared) = (0, 0);
+        for _ in 0..300 {
+            let all = [(); 11].map(|_| rng.below(10));
+            let [a, b, c, d, p] = [all[0], all[1], all[2], all[3], all[4]];
+            if [a, b, c, d].iter().enumerate().any(|(n, i)| [a, b, c, d][..n].contains(i)) || [a, b, c, d].contains(&p) {
+                continue;
+            }
+
+            let (lo, hi) = ([all[5], all[6]], [all[7], all[8]]);
+            let overlap = super::circumcircle_overlaps(&points_2d, |l, i| l[i], [a, b, c], lo, hi);
+            if in_circle_unoriented(&points_2d, |l, i| l[i], a, b, c, p) && aabb::contains(&points_2d, |l, i| l[i], lo, hi, p) {
+                assert!(overlap, "{:?}", all);
+                contained += 1;
+            }
+            let [lo, hi] = [lo, hi].map(|face| [points_2d[face[0]].x, points_2d[face[1]].y]);
+            if rg::orient_2d(points_2d[a], points_2d[b], points_2d[c]) != 0.0 && lo[0] < hi[0] && lo[1] < hi[1] {
+                let (dist, radius) = distances(&[a, b, c].map(|i| points_2d[i].as_slice()), &lo, &hi);
+                if (dist - radius).abs() > 1e-9 {
+                    assert_eq!(overlap, dist < radius, "{:?}", all);
+                    compared += 1;
+                }
+            }
+
+            let (lo, hi) = ([all[5], all[6], all[7]], [all[8], all[9], all[10]]);
+            let overlap = super::circumsphere_overlaps(&points_3d, |l, i| l[i], [a, b, c, d], lo, hi);
+            if in_sphere_unoriented(&points_3d, |l, i| l[i], a, b, c, d, p) && aabb::contains(&points_3d, |l, i| l[i], lo, hi, p) {
+                assert!(overlap, "{:?}", all);
+                contained += 1;
+            }
+            let [lo, hi] = [lo, hi].map(|face| [0, 1, 2].map(|k| points_3d[face[k]][k]));
+            if rg::orient_3d(points_3d[a], points_3d[b], points_3d[c], points_3d[d]) != 0.0 && (0..3).all(|k| lo[k] < hi[k]) {
+                let (dist, radius) = distances(&[a, b, c, d].map(|i| points_3d[i].as_slice()), &lo, &hi);
+                if (dist - radius).abs() > 1e-9 {
+                    assert_eq!(overlap, dist < radius, "{:?}", all);
+                    compared += 1;
+                }
+            }
+        }
+        assert!(contained > 0 && compared > 0);
+    }
+}
@@ -0,0 +1,150 @@
+//! Variants of the predicates that only perturb the query point.
+//!
+//! Locating points in a fixed triangulation that's already been checked, the triangles shouldn't
+//! move, even symbolically. These leave every point but the last one, the query point, exactly
+//! where it is, and perturb the query point the usual way, so the query is never exactly
+//! on an edge or a circle but the edges and circles themselves stay put.
+//! They're [`pinned`] predicates with every other point pinned.
+//!
+//! If the other points are degenerate themselves, moving the query point can't always decide
+//! the predicate. Distinct collinear points still define a line, and an in-circle test against
+//! them is decided by which side of that line the query point is on, but if 2 of them are equal,
+//! these fail with [`ErrorReason::Degenerate`](crate::ErrorReason::Degenerate).
+//! In 3D, coplanar points still define a plane, unless they're on a circle, since then
+//! every point is on a sphere through them.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, query, ErrorReason};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(0.0, 2.0),
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(0.0, 0.0),
+//! ];
+//! // The query point on the hypotenuse moves off it, away from the corner at the origin
+//! assert_eq!(query::orient_2d(&points, |l, i| l[i], 1, 2, 3), Ok(false));
+//! assert_eq!(query::orient_2d(&points, |l, i| l[i], 2, 1, 3), Ok(true));
+//! // Equal points don't define a line
+//! let err = query::orient_2d(&points, |l, i| l[i], 0, 4, 3).unwrap_err();
+//! assert_eq!(err.reason, ErrorReason::Degenerate);
+//! ```
+
+use crate::{pinned, SimplicityError, Vec1, Vec2, Vec3};
+
+/// Like [`orient_1d`](crate::orient_1d), but only perturbs `j`.
+pub fn orient_1d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec1,
+    i: Idx,
+    j: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    pinned::orient_1d(list, index_fn, |x| x != j, i, j)
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but only perturbs `k`.
+/// Fails if the points at `i` and `j` are equal.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    pinned::orient_2d(list, index_fn, |x| x != k, i, j, k)
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but only perturbs `l`.
+/// Fails if the points at `i`, `j`, and `k` are collinear.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    pinned::orient_3d(list, index_fn, |x| x != l, i, j, k, l)
+}
+
+/// Like [`in_circle`](crate::in_circle), but only perturbs `l`.
+/// Fails if 2 of the points at `i`, `j`, and `k` are equal.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    pinned::in_circle(list, index_fn, |x| x != l, i, j, k, l)
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but only perturbs `m`.
+/// Fails if 2 of the points at `i`, `j`, `k`, and `l` are equal, or they're all on a circle or a line.
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    pinned::in_sphere(list, index_fn, |x| x != m, i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_query() {
+        let points = grid_points_2d();
+        let n = points.len();
+        // The query point goes first, and the rest are pinned
+        let key = |q: usize, i: usize| (i != q, i);
+        let keyed = |l: &Vec<Vec2>, (_, i): (bool, usize)| l[i];
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    match super::orient_2d(&points, |l, i| l[i], i, j, k) {
+                        Ok(result) => assert_eq!(result, orient_2d(&points, keyed, key(k, i), key(k, j), key(k, k))),
+                        Err(err) => assert!(err.is_degenerate() && points[i] == points[j]),
+                    }
+
+                    let l = (k + 1) % n;
+                    if l != i && l != j {
+                        let (pi, pj, pk) = (points[i], points[j], points[k]);
+                        match super::in_circle(&points, |l, i| l[i], i, j, k, l) {
+                            Ok(result) => {
+                                assert_eq!(result, in_circle(&points, keyed, key(l, i), key(l, j), key(l, k), key(l, l)))
+                            }
+                            Err(err) => assert!(err.is_degenerate() && (pi == pj || pj == pk || pk == pi)),
+                        }
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        let keyed = |l: &Vec<Vec3>, (_, i): (bool, usize)| l[i];
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+            let collinear = (points[j] - points[i]).cross(&(points[k] - points[i])) == Vec3::zeros();
+            match super::orient_3d(&points, |l, i| l[i], i, j, k, l) {
+                Ok(result) => assert_eq!(result, orient_3d(&points, keyed, key(l, i), key(l, j), key(l, k), key(l, l))),
+                Err(err) => assert!(err.is_degenerate() && collinear),
+            }
+            if let Ok(result) = super::in_sphere(&points, |l, i| l[i], i, j, k, l, m) {
+                assert_eq!(result, in_sphere(&points, keyed, key(m, i), key(m, j), key(m, k), key(m, l), key(m, m)));
+            }
+        }
+    }
+}
@@ -0,0 +1,426 @@
+//! Slow reference implementations of the predicates, enabled by the `reference` feature.
+//!
+//! These don't use any case tables or floating-point filters. They treat ε symbolically
+//! and find the first nonzero coefficient of the fully expanded perturbed determinant
+//! with exact rational arithmetic, following the definition of the perturbation:
+//! coordinate *c* of the point with rank *r* among the sorted indexes,
+//! in *d* dimensions, is perturbed by ε^(3^(d·r + d - 1 - c)).
+//!
+//! They're meant for checking the real predicates and auditing suspicious results,
+//! and take the same arguments as them. [`verify`] checks a predicate against
+//! its expansion on many small degenerate configurations.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, in_circle, reference};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! assert_eq!(
+//!     reference::in_circle(&points, |l, i| l[i], 0, 1, 2, 3),
+//!     in_circle(&points, |l, i| l[i], 0, 1, 2, 3),
+//! );
+//! ```
+
+use crate::rng::SplitMix;
+use crate::transcript::Predicate;
+use crate::{Vec1, Vec2, Vec3};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+use std::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+/// A polynomial in the coordinates of a point, as a list of coefficients and exponents.
+type Poly = Vec<(i64, Vec<u32>)>;
+
+/// The lifting functions: the coordinates, and the squared magnitude if `lifted`.
+fn columns(dim: usize, lifted: bool) -> Vec<Poly> {
+    let unit = |c: usize, e: u32| (0..dim).map(|i| if i == c { e } else { 0 }).collect::<Vec<_>>();
+    let mut columns = (0..dim).map(|c| vec![(1, unit(c, 1))]).collect::<Vec<_>>();
+    if lifted {
+        columns.push((0..dim).map(|c| (1, unit(c, 2))).collect());
+    }
+    columns
+}
+
+fn binomial(n: u32, k: u32) -> i64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as i64 / (i + 1) as i64)
+}
+
+/// The α-th Taylor coefficient of a polynomial at a point.
+fn taylor(poly: &Poly, alpha: &[u32], point: &[BigRational]) -> BigRational {
+    poly.iter()
+        .filter(|(_, mono)| mono.iter().zip(alpha).all(|(m, a)| a <= m))
+        .map(|(coeff, mono)| {
+            mono.iter().zip(alpha).zip(point).fold(
+                BigRational::from_integer(BigInt::from(*coeff)),
+                |acc, ((m, a), p)| acc * BigRational::from_integer(BigInt::from(binomial(*m, *a))) * num_traits::pow(p.clone(), (m - a) as usize),
+            )
+        })
+        .fold(BigRational::zero(), |acc, term| acc + term)
+}
+
+/// Row-reduces a matrix, returning its rank and, if it's square, its determinant.
+fn reduce(mut rows: Vec<Vec<BigRational>>) -> (usize, BigRational) {
+    let num_cols = rows.first().map_or(0, |row| row.len());
+    let mut det = BigRational::one();
+    let mut rank = 0;
+
+    for col in 0..num_cols {
+        let pivot = match (rank..rows.len()).find(|r| !rows[*r][col].is_zero()) {
+            Some(pivot) => pivot,
+            None => {
+                det = BigRational::zero();
+                continue;
+            }
+        };
+        if pivot != rank {
+            rows.swap(pivot, rank);
+            det = -det;
+        }
+        det *= rows[rank][col].clone();
+
+        let (pivot_rows, rest) = rows.split_at_mut(rank + 1);
+        let pivot_row = &pivot_rows[rank];
+        for row in rest {
+            let factor = row[col].clone() / pivot_row[col].clone();
+            for (entry, pivot_entry) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *entry -= pivot_entry.clone() * factor.clone();
+            }
+        }
+        rank += 1;
+        if rank == rows.len() {
+            break;
+        }
+    }
+    (rank, det)
+}
+
+/// The first nonzero coefficient of the perturbed determinant, choosing multi-indexes
+/// from the last point, the most significant, to the first.
+/// `chosen` has the rows already chosen and their multi-indexes, starting with the last point's.
+/// Returns the coefficient and the multi-index of each point.
+fn first_nonzero(
+    points: &[Vec<BigRational>],
+    columns: &[Poly],
+    alphas: &[Vec<u32>],
+    chosen: &mut Vec<(Vec<BigRational>, Vec<u32>)>,
+) -> Option<(BigRational, Vec<Vec<u32>>)> {
+    let point = points.len() - 1 - chosen.len();
+
+    for alpha in alphas {
+        let mut row = columns.iter().map(|col| taylor(col, alpha, &points[point])).collect::<Vec<_>>();
+        row.push(if alpha.iter().all(|e| *e == 0) { BigRational::one() } else { BigRational::zero() });
+        chosen.push((row, alpha.clone()));
+
+        if reduce(chosen.iter().map(|(row, _)| row.clone()).collect()).0 == chosen.len() {
+            let coeff = if point == 0 {
+                let det = reduce(chosen.iter().rev().map(|(row, _)| row.clone()).collect()).1;
+                Some((det, chosen.iter().rev().map(|(_, alpha)| alpha.clone()).collect()))
+                    .filter(|(det, _)| !det.is_zero())
+            } else {
+                first_nonzero(points, columns, alphas, chosen)
+            };
+            if coeff.is_some() {
+                chosen.pop();
+                return coeff;
+            }
+        }
+        chosen.pop();
+    }
+    None
+}
+
+/// Whether the perturbed determinant with one row per point,
+/// with the lifting functions and a column of 1's, is positive.
+fn perturbed_sign<Idx: Ord + Copy>(indexes: &[Idx], points: Vec<Vec<f64>>, lifted: bool) -> bool {
+    expansion(indexes, points, lifted).0
+}
+
+/// The sign of the perturbed determinant, and the ε-exponents of the coordinates
+/// of each point, sorted by index, in the first nonzero term of its expansion.
+fn expansion<Idx: Ord + Copy>(indexes: &[Idx], points: Vec<Vec<f64>>, lifted: bool) -> (bool, Vec<Vec<u32>>) {
+    let dim = points[0].len();
+    let mut points = indexes.iter().copied().zip(points).collect::<Vec<_>>();
+
+    // Sort by index, keeping track of the parity
+    let mut odd = false;
+    for i in 1..points.len() {
+        for j in (0..i).rev() {
+            if points[j].0 > points[j + 1].0 {
+                points.swap(j, j + 1);
+                odd = !odd;
+            } else {
+                break;
+            }
+        }
+    }
+    let points = points.into_iter()
+        .map(|(_, p)| p.into_iter().map(|c| BigRational::from_float(c).expect("coordinates must be finite")).collect())
+        .collect::<Vec<Vec<_>>>();
+
+    let columns = columns(dim, lifted);
+    // Multi-indexes in increasing order of their ε-exponent
+    let mut alphas = vec![vec![]];
+    for _ in 0..dim {
+        alphas = alphas.into_iter()
+            .flat_map(|alpha: Vec<u32>| (0..=2).map(move |e| {
+                let mut alpha = alpha.clone();
+                alpha.push(e);
+                alpha
+            }))
+            .collect();
+    }
+
+    let (coeff, exponents) = first_nonzero(&points, &columns, &alphas, &mut vec![])
+        .expect("the perturbed determinant is never identically 0");
+    (coeff.is_positive() != odd, exponents)
+}
+
+fn coords<V: IntoIterator<Item = f64>>(point: V) -> Vec<f64> {
+    point.into_iter().collect()
+}
+
+macro_rules! reference_fn {
+    ($(#[$attr:meta])* $name:ident: $vec:ty, lifted $lifted:expr, $($idx:ident),*) => {
+        /// Reference implementation of the predicate of the same name.
+        $(#[$attr])*
+        pub fn $name<T: ?Sized, Idx: Ord + Copy>(
+            list: &T,
+            index_fn: impl Fn(&T, Idx) -> $vec,
+            $($idx: Idx),*
+        ) -> bool {
+            perturbed_sign(&[$($idx),*], vec![$(coords(index_fn(list, $idx).iter().copied())),*], $lifted)
+        }
+    };
+}
+
+reference_fn!(orient_1d: Vec1, lifted false, i, j);
+reference_fn!(orient_2d: Vec2, lifted false, i, j, k);
+reference_fn!(orient_3d: Vec3, lifted false, i, j, k, l);
+reference_fn!(in_circle: Vec2, lifted true, i, j, k, l);
+reference_fn!(#[allow(clippy::too_many_arguments)] in_sphere: Vec3, lifted true, i, j, k, l, m);
+#[cfg(feature = "high-dim")]
+reference_fn!(#[allow(clippy::too_many_arguments)] orient_4d: nalgebra::Vector4<f64>, lifted false, i, j, k, l, m);
+#[cfg(feature = "high-dim")]
+reference_fn!(#[allow(clippy::too_many_arguments)] orient_5d: nalgebra::Vector5<f64>, lifted false, i, j, k, l, m, n);
+#[cfg(feature = "high-dim")]
+reference_fn!(#[allow(clippy::too_many_arguments)] in_hypersphere_4d: nalgebra::Vector4<f64>, lifted true, i, j, k, l, m, n);
+#[cfg(feature = "high-dim")]
+reference_fn!(#[allow(clippy::too_many_arguments)] in_hypersphere_5d: nalgebra::Vector5<f64>, lifted true, i, j, k, l, m, n, o);
+
+/// Reference implementation of [`in_circle_unoriented`](crate::in_circle_unoriented).
+pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    orient_2d(list, &index_fn, i, j, k) == in_circle(list, &index_fn, i, j, k, l)
+}
+
+/// Reference implementation of [`in_sphere_unoriented`](crate::in_sphere_unoriented).
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    orient_3d(list, &index_fn, i, j, k, l) == in_sphere(list, &index_fn, i, j, k, l, m)
+}
+
+/// A configuration where a predicate disagrees with the expansion of its perturbed determinant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Counterexample {
+    pub predicate: Predicate,
+    /// The coordinates of the points in the list
+    pub points: Vec<Vec<f64>>,
+    /// The indexes the predicate was called with
+    pub indexes: Vec<usize>,
+    /// The sign of the first nonzero term of the expansion
+    pub expected: bool,
+    /// The predicate's result
+    pub actual: bool,
+    /// The ε-exponents of the coordinates of each point, sorted by index,
+    /// in the first nonzero term of the expansion of the (in-hypersphere) determinant
+    pub exponents: Vec<Vec<u32>>,
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} returned {} instead of {} for indexes {:?} into points {:?}; the first nonzero term has ε-exponents {:?}",
+            self.predicate.name(), self.actual, self.expected, self.indexes, self.points, self.exponents,
+        )
+    }
+}
+
+impl Error for Counterexample {}
+
+/// Checks a predicate against the expansion of its perturbed determinant on `num_configs`
+/// small configurations of points generated from `seed`.
+///
+/// The coordinates are in {-1, 0, 1} and points are often repeated, so most configurations
+/// are degenerate, many of them deeply. A wrong case order, sign, or elimination
+/// in a predicate's cascade shows up as a configuration where it returns the wrong result.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{reference, transcript::Predicate};
+/// assert_eq!(reference::verify(Predicate::InCircle, 500, 1), Ok(()));
+/// ```
+pub fn verify(predicate: Predicate, num_configs: usize, seed: u64) -> Result<(), Counterexample> {
+    let n = predicate.num_points();
+    let dim = predicate.dim();
+    let lifted = !matches!(predicate, Predicate::Orient1d | Predicate::Orient2d | Predicate::Orient3d);
+    let mut rng = SplitMix(seed);
+
+    for _ in 0..num_configs {
+        let mut points: Vec<Vec<f64>> = vec![];
+        for _ in 0..n + 2 {
+            let point = if !points.is_empty() && rng.below(4) == 0 {
+                points[rng.below(points.len())].clone()
+            } else {
+                (0..dim).map(|_| rng.below(3) as f64 - 1.0).collect()
+            };
+            points.push(point);
+        }
+        let mut indexes = (0..n + 2).collect::<Vec<_>>();
+        rng.shuffle(&mut indexes);
+        indexes.truncate(n);
+
+        let ix = &indexes;
+        let p1 = |l: &[Vec<f64>], i: usize| Vec1::from_iterator(l[i].iter().copied());
+        let p2 = |l: &[Vec<f64>], i: usize| Vec2::from_iterator(l[i].iter().copied());
+        let p3 = |l: &[Vec<f64>], i: usize| Vec3::from_iterator(l[i].iter().copied());
+        let list = &points[..];
+        let actual = match predicate {
+            Predicate::Orient1d => crate::orient_1d(list, p1, ix[0], ix[1]),
+            Predicate::Orient2d => crate::orient_2d(list, p2, ix[0], ix[1], ix[2]),
+            Predicate::Orient3d => crate::orient_3d(list, p3, ix[0], ix[1], ix[2], ix[3]),
+            Predicate::InCircle => crate::in_circle(list, p2, ix[0], ix[1], ix[2], ix[3]),
+            Predicate::InCircleUnoriented => crate::in_circle_unoriented(list, p2, ix[0], ix[1], ix[2], ix[3]),
+            Predicate::InSphere => crate::in_sphere(list, p3, ix[0], ix[1], ix[2], ix[3], ix[4]),
+            Predicate::InSphereUnoriented => crate::in_sphere_unoriented(list, p3, ix[0], ix[1], ix[2], ix[3], ix[4]),
+        };
+
+        let coords = |indexes: &[usize]| indexes.iter().map(|i| points[*i].clone()).collect::<Vec<_>>();
+        let (mut expected, exponents) = expansion(ix, coords(ix), lifted);
+        if let Predicate::InCircleUnoriented | Predicate::InSphereUnoriented = predicate {
+            expected = expected == expansion(&ix[..n - 1], coords(&ix[..n - 1]), false).0;
+        }
+
+        if actual != expected {
+            return Err(Counterexample { predicate, points, indexes, expected, actual, exponents });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript;
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use test_case::test_case;
+
+    #[cfg(feature = "reference")]
+    #[test_case(transcript::Predicate::Orient1d ; "orient_1d")]
+    #[test_case(transcript::Predicate::Orient2d ; "orient_2d")]
+    #[test_case(transcript::Predicate::Orient3d ; "orient_3d")]
+    #[test_case(transcript::Predicate::InCircle ; "in_circle")]
+    #[test_case(transcript::Predicate::InCircleUnoriented ; "in_circle_unoriented")]
+    #[test_case(transcript::Predicate::InSphere ; "in_sphere")]
+    #[test_case(transcript::Predicate::InSphereUnoriented ; "in_sphere_unoriented")]
+    fn test_verify(predicate: transcript::Predicate) {
+        if let Err(counterexample) = super::verify(predicate, 2000, 0x5EED) {
+            panic!("{}", counterexample);
+        }
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn test_reference_2d() {
+        let points = grid_points_2d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    assert_eq!(
+                        orient_2d(&points, |l, i| l[i], i, j, k),
+                        super::orient_2d(&points, |l, i| l[i], i, j, k),
+                    );
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(
+                            in_circle(&points, |l, i| l[i], i, j, k, l),
+                            super::in_circle(&points, |l, i| l[i], i, j, k, l),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "reference")]
+    #[test]
+    fn test_reference_3d() {
+        let points = grid_points_3d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(
+                            orient_3d(&points, |l, i| l[i], i, j, k, l),
+                            super::orient_3d(&points, |l, i| l[i], i, j, k, l),
+                        );
+                        let m = (i + 2 * j + k + l) % points.len();
+                        if ![i, j, k, l].contains(&m) {
+                            assert_eq!(
+                                in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                                super::in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(all(feature = "high-dim", feature = "reference"))]
+    #[test]
+    fn test_reference_high_dim() {
+        use nalgebra::Vector4;
+        let mut points = (0..16).map(|i| Vector4::new((i % 2) as f64, (i / 2 % 2) as f64, (i / 4 % 2) as f64, (i / 8) as f64)).collect::<Vec<_>>();
+        points.push(Vector4::new(0.0, 0.0, 0.0, 0.0));
+
+        for a in 0..points.len() - 5 {
+            for b in (0..points.len()).filter(|b| ![a, a + 1, a + 2, a + 3].contains(b)) {
+                let t = [a + 3, b, a, a + 2, a + 1, (a + b + 7) % points.len()];
+                assert_eq!(
+                    orient_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4]),
+                    super::orient_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4]),
+                );
+                if !t[..5].contains(&t[5]) {
+                    assert_eq!(
+                        in_hypersphere_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4], t[5]),
+                        super::in_hypersphere_4d(&points, |l, i| l[i], t[0], t[1], t[2], t[3], t[4], t[5]),
+                    );
+                }
+            }
+        }
+    }
+}
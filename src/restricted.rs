@@ -0,0 +1,257 @@
+//! Predicates for Delaunay triangulations restricted to surfaces.
+//!
+//! A facet of a 3D Delaunay tetrahedralization is in the restricted Delaunay triangulation
+//! of a surface if its dual Voronoi edge crosses the surface. Surface reconstruction
+//! from samples approximates the surface near each sample **s** by the ball around it with
+//! the radius of the sampling, the *surface ball*, so the test is whether the dual edge of
+//! facet *abc* meets the ball around **s**.
+//!
+//! The dual edge lies on the line through the circumcenter of *abc* perpendicular to it.
+//! Its ends are the circumcenters of the tetrahedra on either side of the facet, and on
+//! the convex hull, it goes off to infinity away from the only one. Every circumcenter is
+//! kept as an exact fraction, and the points are perturbed as usual, so the result doesn't
+//! depend on rounding, and an edge that only touches the ball, or that ends on its boundary,
+//! is decided consistently. The radius isn't perturbed.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, restricted::{self, DualEdge}};
+//! # use nalgebra::Vector3;
+//! let points = vec![
+//!     Vector3::new(0.0, 0.0, 0.0),
+//!     Vector3::new(2.0, 0.0, 0.0),
+//!     Vector3::new(0.0, 2.0, 0.0),
+//!     Vector3::new(1.0, 1.0, 1.0),
+//!     Vector3::new(1.0, 1.0, -1.0),
+//!     Vector3::new(1.5, 1.0, 3.0),
+//! ];
+//! // The dual line of the facet is x = y = 1, 0.5 away from the sample
+//! let meets = |edge, radius_sq| restricted::dual_edge_meets_ball(&points, |l, i| l[i], [0, 1, 2], edge, 5, radius_sq);
+//! assert!(meets(DualEdge::Line, 0.3));
+//! assert!(!meets(DualEdge::Line, 0.2));
+//! // But the edge between the tetrahedra with apexes 3 and 4 ends at z = -0.5 and z = 0.5
+//! assert!(!meets(DualEdge::Segment(3, 4), 0.3));
+//! // As a hull facet, the edge goes up from z = 0.5, away from apex 4
+//! assert!(meets(DualEdge::Ray(4), 0.3));
+//! assert!(!meets(DualEdge::Ray(3), 0.3));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{Perturbed, Polynomial};
+use crate::Vec3;
+use alloc::vec;
+
+/// The part of the line dual to a facet that's its Voronoi edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DualEdge<Idx> {
+    /// The whole line
+    Line,
+    /// The ray from the circumcenter of the tetrahedron with the facet and this apex,
+    /// away from the apex, for a facet on the convex hull
+    Ray(Idx),
+    /// The segment between the circumcenters of the tetrahedra with the facet and these apexes
+    Segment(Idx, Idx),
+}
+
+type Vector = [Polynomial; 3];
+
+fn dot(x: &Vector, y: &Vector) -> Polynomial {
+    (0..3).fold(Polynomial::default(), |acc, k| acc + x[k].clone() * y[k].clone())
+}
+
+fn cross(x: &Vector, y: &Vector) -> Vector {
+    [0, 1, 2].map(|k| {
+        let (k1, k2) = ((k + 1) % 3, (k + 2) % 3);
+        x[k1].clone() * y[k2].clone() - x[k2].clone() * y[k1].clone()
+    })
+}
+
+fn scaled(x: &Vector, a: &Polynomial) -> Vector {
+    [0, 1, 2].map(|k| x[k].clone() * a.clone())
+}
+
+fn sub(x: &Vector, y: &Vector) -> Vector {
+    [0, 1, 2].map(|k| x[k].clone() - y[k].clone())
+}
+
+/// The line dual to facet *abc*, with the sample **s**. Points on it are **o** + *t* **n**,
+/// where **o** = **a** + **C** / *W* is the circumcenter of the facet.
+struct Dual {
+    /// The normal of the facet, **n** = (**b** - **a**) × (**c** - **a**)
+    n: Vector,
+    /// |**n**|²
+    nn: Polynomial,
+    /// *W* = 2 |**n**|², which is positive
+    w: Polynomial,
+    /// **C**
+    center: Vector,
+    /// **S** = *W* (**s** - **o**)
+    s: Vector,
+}
+
+impl Dual {
+    fn new<Idx: Ord + Copy, F: Fn(Idx) -> Vec3>(perturbed: &Perturbed<Idx, F>, [a, b, c]: [Idx; 3], sample: Idx) -> Self {
+        let diff = |i, j| [0, 1, 2].map(|k| perturbed.diff(i, j, k));
+        let (u, v) = (diff(b, a), diff(c, a));
+        let n = cross(&u, &v);
+        let nn = dot(&n, &n);
+        let w = nn.scale(2.0);
+        let center = sub(&scaled(&cross(&v, &n), &dot(&u, &u)), &scaled(&cross(&u, &n), &dot(&v, &v)));
+        let s = sub(&scaled(&diff(sample, a), &w), &center);
+        Self { n, nn, w, center, s }
+    }
+
+    /// The parameter of the circumcenter of the tetrahedron with the facet and apex **d**,
+    /// as *P* / *Q*, where *Q* has the sign of the side of the facet **d** is on.
+    fn apex(&self, da: &Vector) -> (Polynomial, Polynomial) {
+        // |o + t n - d|² = |o - a|², and o - a is perpendicular to n
+        let p = self.w.clone() * dot(da, da) - dot(&self.center, da).scale(2.0);
+        let q = self.w.clone() * dot(da, &self.n).scale(2.0);
+        (p, q)
+    }
+}
+
+/// Returns whether the Voronoi edge dual to the facet with vertices `facet` meets the open ball
+/// around point `sample` with squared radius `radius_sq`, after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the indexes of the vertices of the facet,
+/// which part of the dual line is the edge, the index of the sample, and the squared radius.
+/// The sample can be a vertex of the facet. Returns `false` if the facet is degenerate even
+/// after perturbing, like when an index is repeated.
+pub fn dual_edge_meets_ball<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3,
+    facet: [Idx; 3],
+    edge: DualEdge<Idx>,
+    sample: Idx,
+    radius_sq: f64,
+) -> bool {
+    // The normal stays zero at every degree, so don't run the cascade to the end
+    if facet[0] == facet[1] || facet[1] == facet[2] || facet[0] == facet[2] {
+        return false;
+    }
+    let mut indexes = vec![facet[0], facet[1], facet[2], sample];
+    match edge {
+        DualEdge::Line => {}
+        DualEdge::Ray(d) => indexes.push(d),
+        DualEdge::Segment(d, e) => indexes.extend([d, e]),
+    }
+    // Whether the circumcenters are in the ball has degree 24 in the coordinates
+    let mut perturbed = Perturbed::new(3, 24, &indexes, |i| index_fn(list, i));
+    let radius_sq = Polynomial::from(Expansion::from(radius_sq));
+
+    // |S|² / W² - (S · n)² / (W² |n|²) < r²
+    let line_inside = |perturbed: &mut Perturbed<Idx, _>| {
+        let sign = perturbed.lazy_signum(|perturbed| {
+            let dual = Dual::new(perturbed, facet, sample);
+            let sn = dot(&dual.s, &dual.n);
+            let w2 = dual.w.clone() * dual.w.clone();
+            dual.nn.clone() * dot(&dual.s, &dual.s) - sn.clone() * sn - radius_sq.clone() * w2 * dual.nn
+        });
+        sign < 0.0
+    };
+    let apex = |perturbed: &Perturbed<Idx, _>, d| {
+        let dual = Dual::new(perturbed, facet, sample);
+        let (p, q) = dual.apex(&[0, 1, 2].map(|k| perturbed.diff(d, facet[0], k)));
+        (dual, p, q)
+    };
+    // |o + (P / Q) n - s|² = |P W n - Q S|² / (W Q)² < r²
+    let apex_inside = |perturbed: &mut Perturbed<Idx, _>, d| {
+        let sign = perturbed.lazy_signum(|perturbed| {
+            let (dual, p, q) = apex(perturbed, d);
+            let offset = sub(&scaled(&dual.n, &(p * dual.w.clone())), &scaled(&dual.s, &q));
+            let wq = dual.w * q;
+            dot(&offset, &offset) - radius_sq.clone() * wq.clone() * wq
+        });
+        sign < 0.0
+    };
+    // The signs of t_s - P / Q, where t_s = (S · n) / (W |n|²) is the parameter of the sample,
+    // and of Q
+    let past_apex = |perturbed: &mut Perturbed<Idx, _>, d| {
+        let q = perturbed.lazy_signum(|perturbed| apex(perturbed, d).2);
+        let diff = perturbed.lazy_signum(|perturbed| {
+            let (dual, p, q) = apex(perturbed, d);
+            dot(&dual.s, &dual.n) * q - p * dual.w * dual.nn
+        });
+        (diff * q, q)
+    };
+
+    // The closest point of the edge to the sample is an end, unless the projection of the sample onto the line is
+    match edge {
+        DualEdge::Line => line_inside(&mut perturbed),
+        DualEdge::Ray(d) => {
+            let (past, side) = past_apex(&mut perturbed, d);
+            apex_inside(&mut perturbed, d) || (past == -side && past != 0.0 && line_inside(&mut perturbed))
+        }
+        DualEdge::Segment(d, e) => {
+            let (past_d, _) = past_apex(&mut perturbed, d);
+            let (past_e, _) = past_apex(&mut perturbed, e);
+            apex_inside(&mut perturbed, d)
+                || apex_inside(&mut perturbed, e)
+                || (past_d * past_e < 0.0 && line_inside(&mut perturbed))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_restricted() {
+        use nalgebra::Matrix3;
+
+        // The circumcenter of a tetrahedron, solving for the point equidistant from the vertices
+        let circumcenter = |[a, b, c, d]: [Vector3<f64>; 4]| {
+            let m = Matrix3::from_rows(&[(b - a).transpose(), (c - a).transpose(), (d - a).transpose()]);
+            let rhs = Vector3::new(b.norm_squared() - a.norm_squared(), c.norm_squared() - a.norm_squared(), d.norm_squared() - a.norm_squared());
+            (m * 2.0).try_inverse().unwrap() * rhs
+        };
+        let dist_sq = |s: Vector3<f64>, from: Vector3<f64>, dir: Vector3<f64>, range: (f64, f64)| {
+            let t = ((s - from).dot(&dir) / dir.norm_squared()).clamp(range.0, range.1);
+            (from + dir * t - s).norm_squared()
+        };
+
+        let mut rng = rng::SplitMix(1496);
+        let points = (0..12).map(|_| Vector3::new(rng.int(-8, 8) / 2.0, rng.int(-8, 8) / 2.0, rng.int(-8, 8) / 2.0)).collect::<Vec<_>>();
+        let mut checked = 0;
+        for _ in 0..300 {
+            let all = [(); 6].map(|_| rng.below(points.len()));
+            if all[..5].iter().enumerate().any(|(n, i)| all[..n].contains(i)) {
+                continue;
+            }
+            let [a, b, c, d, e, s] = all;
+            let [pa, pb, pc, pd, pe, ps] = all.map(|i| points[i]);
+            let normal = (pb - pa).cross(&(pc - pa));
+            let (side_d, side_e) = ((pd - pa).dot(&normal), (pe - pa).dot(&normal));
+            if normal.norm_squared() == 0.0 || side_d == 0.0 || side_e == 0.0 {
+                continue;
+            }
+            let (cd, ce) = (circumcenter([pa, pb, pc, pd]), circumcenter([pa, pb, pc, pe]));
+            let radius_sq = rng.int(1, 40) / 4.0;
+
+            let line = dist_sq(ps, cd, normal, (f64::NEG_INFINITY, f64::INFINITY));
+            let ray = dist_sq(ps, cd, normal * -side_d.signum(), (0.0, f64::INFINITY));
+            let segment = dist_sq(ps, cd, ce - cd, (0.0, 1.0));
+            for (edge, dist_sq) in [(DualEdge::Line, line), (DualEdge::Ray(d), ray), (DualEdge::Segment(d, e), segment)] {
+                if (dist_sq - radius_sq).abs() > 1e-6 {
+                    let meets = super::dual_edge_meets_ball(&points, |l, i| l[i], [a, b, c], edge, s, radius_sq);
+                    assert_eq!(meets, dist_sq < radius_sq, "{:?} {:?}", all, edge);
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked > 100);
+
+        // A sample at a vertex, with the circumcenter of the facet sqrt(2) away
+        let points = vec![Vector3::new(0.0, 0.0, 0.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 2.0, 0.0)];
+        let meets = |radius_sq| super::dual_edge_meets_ball(&points, |l, i| l[i], [0, 1, 2], DualEdge::Line, 0, radius_sq);
+        assert!(!meets(1.9));
+        assert!(meets(2.1));
+        // A repeated index is degenerate
+        assert!(!super::dual_edge_meets_ball(&points, |l, i| l[i], [0, 1, 1], DualEdge::Line, 2, 100.0));
+    }
+}
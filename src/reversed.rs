@@ -0,0 +1,180 @@
+//! Variants of the predicates that perturb by -ε instead of ε.
+//!
+//! Perturbing every coordinate the other way flips the degenerate cases that are decided
+//! by a term of odd degree in the perturbation, which is useful for matching another library's
+//! convention. Non-degenerate input gives the same answers as the usual predicates.
+//!
+//! Perturbing **p** by -δ is the same as perturbing -**p** by δ and negating the result,
+//! so these negate the points and call the usual predicates. Negating the points negates
+//! an orientation determinant once for each dimension, and an in-hypersphere determinant
+//! the same way, since the lifted coordinate doesn't change.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, orient_2d, reversed};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! // Degenerate, so the direction of the perturbation matters
+//! assert_ne!(orient_2d(&points, |l, i| l[i], 0, 1, 2), reversed::orient_2d(&points, |l, i| l[i], 0, 1, 2));
+//! // Not degenerate
+//! assert_eq!(orient_2d(&points, |l, i| l[i], 0, 1, 3), reversed::orient_2d(&points, |l, i| l[i], 0, 1, 3));
+//! ```
+
+use crate::{Vec1, Vec2, Vec3};
+use core::ops::Neg;
+
+fn negated<T: ?Sized, Idx, P: Neg<Output = P>>(index_fn: impl Fn(&T, Idx) -> P + Clone) -> impl Fn(&T, Idx) -> P + Clone {
+    move |list, i| -index_fn(list, i)
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but perturbing by -ε.
+pub fn orient_1d<T: ?Sized, Idx: Ord + Copy>(list: &T, index_fn: impl Fn(&T, Idx) -> Vec1 + Clone, i: Idx, j: Idx) -> bool {
+    !crate::orient_1d(list, negated(index_fn), i, j)
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but perturbing by -ε.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    crate::orient_2d(list, negated(index_fn), i, j, k)
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but perturbing by -ε.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    !crate::orient_3d(list, negated(index_fn), i, j, k, l)
+}
+
+/// Like [`in_circle`](crate::in_circle), but perturbing by -ε.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::in_circle(list, negated(index_fn), i, j, k, l)
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but perturbing by -ε.
+pub fn in_circle_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec2 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    crate::in_circle_unoriented(list, negated(index_fn), i, j, k, l)
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but perturbing by -ε.
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    !crate::in_sphere(list, negated(index_fn), i, j, k, l, m)
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but perturbing by -ε.
+pub fn in_sphere_unoriented<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> Vec3 + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    // Both the orientation and the in-sphere determinant flip, so this doesn't
+    crate::in_sphere_unoriented(list, negated(index_fn), i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, in_sphere_unoriented, orient_1d, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::{Vector1, Vector3};
+
+    #[test]
+    fn test_reversed() {
+        let points = grid_points_2d();
+        let n = points.len();
+        let vec1 = |l: &Vec<Vec2>, i: usize| Vector1::new(l[i].x);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                // Equal points tie the other way
+                let tied = points[i].x == points[j].x;
+                assert_eq!(super::orient_1d(&points, vec1, i, j), orient_1d(&points, vec1, i, j) != tied);
+
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    // Distinct collinear points are decided by a term of degree 1, which flips
+                    let (pi, pj, pk) = (points[i], points[j], points[k]);
+                    let collinear = (pj - pi).perp(&(pk - pi)) == 0.0;
+                    let distinct = pi != pj && pj != pk && pk != pi;
+                    let reversed_orient = super::orient_2d(&points, |l, i| l[i], i, j, k);
+                    if !collinear || distinct {
+                        assert_eq!(reversed_orient, orient_2d(&points, |l, i| l[i], i, j, k) != collinear);
+                    }
+
+                    for l in (0..n).filter(|l| *l != i && *l != j && *l != k) {
+                        let lift = |p: Vec2| Vector3::new(p.x, p.y, p.norm_squared());
+                        let pl = points[l];
+                        let det = (lift(pj) - lift(pi)).cross(&(lift(pk) - lift(pi))).dot(&(lift(pl) - lift(pi)));
+                        let reversed_in = super::in_circle(&points, |l, i| l[i], i, j, k, l);
+                        if !collinear && det != 0.0 {
+                            assert_eq!(reversed_in, in_circle(&points, |l, i| l[i], i, j, k, l));
+                        }
+                        assert_eq!(
+                            super::in_circle_unoriented(&points, |l, i| l[i], i, j, k, l),
+                            reversed_orient == reversed_in
+                        );
+                    }
+                }
+            }
+        }
+
+        // Negating the points and the perturbation negates everything
+        let points = grid_points_3d();
+        let negated = points.iter().map(|p| -p).collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 6) % n);
+            assert_eq!(
+                super::orient_3d(&negated, |l, i| l[i], i, j, k, l),
+                !orient_3d(&points, |l, i| l[i], i, j, k, l)
+            );
+            assert_eq!(
+                super::in_sphere(&negated, |l, i| l[i], i, j, k, l, m),
+                !in_sphere(&points, |l, i| l[i], i, j, k, l, m)
+            );
+            assert_eq!(
+                super::in_sphere_unoriented(&negated, |l, i| l[i], i, j, k, l, m),
+                in_sphere_unoriented(&points, |l, i| l[i], i, j, k, l, m)
+            );
+        }
+    }
+}
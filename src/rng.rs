@@ -0,0 +1,43 @@
+//! A small pseudorandom number generator, so generated configurations are reproducible.
+
+pub(crate) struct SplitMix(pub(crate) u64);
+
+impl SplitMix {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A random integer in `lo..=hi`, as a float.
+    pub(crate) fn int(&mut self, lo: i32, hi: i32) -> f64 {
+        f64::from(lo + self.below((hi - lo + 1) as usize) as i32)
+    }
+
+    /// `N` distinct random integers below `n`.
+    #[cfg(test)]
+    pub(crate) fn distinct<const N: usize>(&mut self, n: usize) -> [usize; N] {
+        let mut indexes = [0; N];
+        for a in 0..N {
+            indexes[a] = loop {
+                let i = self.below(n);
+                if !indexes[..a].contains(&i) {
+                    break i;
+                }
+            };
+        }
+        indexes
+    }
+
+    pub(crate) fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Predicates on points with coordinates of other types than `f64`.
+//!
+//! A [`Scalar`] is any type whose values are exactly sums of `f64`s, like `f32`, integers
+//! up to 128 bits, and fixed-point numbers, so the determinants can be computed exactly with
+//! [`Expansion`]s without converting the coordinates to `f64` and rounding them first.
+//! These predicates then perturb the points the same way as the rest of the crate, so points
+//! with coordinates that are exactly `f64`s get the same results as with the usual predicates.
+//!
+//! They don't have the fast floating-point paths of the usual predicates, so they're slower.
+//! Exact rationals aren't sums of `f64`s, but multiplying all the points by a common denominator,
+//! which doesn't change any result, turns them into integers.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, exact::Expansion, scalar::{self, Scalar}};
+//! # use nalgebra::Vector2;
+//! /// A fixed-point number with 16 fractional bits
+//! #[derive(Clone, Copy)]
+//! struct Fixed(i32);
+//!
+//! impl Scalar for Fixed {
+//!     fn to_expansion(self) -> Expansion {
+//!         Expansion::from(self.0 as f64).scale(1.0 / 65536.0)
+//!     }
+//! }
+//!
+//! let points = vec![[Fixed(0), Fixed(0)], [Fixed(65536), Fixed(1)], [Fixed(131072), Fixed(2)]];
+//! // Collinear, so the perturbation decides, like for the same points as `f64`s
+//! let floats = points.iter().map(|[x, y]| Vector2::new(x.0 as f64, y.0 as f64) / 65536.0).collect::<Vec<_>>();
+//! assert_eq!(
+//!     scalar::orient_2d(&points, |l, i| l[i], 0, 1, 2),
+//!     simplicity::orient_2d(&floats, |l, i| l[i], 0, 1, 2),
+//! );
+//! // 64-bit integers that aren't exactly `f64`s are fine too, like 2^60 + 1
+//! let big = vec![[(1i64 << 60) + 1, 1], [1 << 60, 1], [0, 0]];
+//! assert!(scalar::orient_2d(&big, |l, i| l[i], 0, 1, 2));
+//! ```
+
+use crate::exact::Expansion;
+use crate::perturbed::{self, Perturbed, Polynomial};
+use alloc::vec::Vec;
+use core::ops::Index;
+
+/// A type of coordinate whose values can be computed with exactly.
+pub trait Scalar: Copy {
+    /// The value, exactly.
+    fn to_expansion(self) -> Expansion;
+}
+
+impl Scalar for f64 {
+    fn to_expansion(self) -> Expansion {
+        Expansion::from(self)
+    }
+}
+
+impl Scalar for f32 {
+    fn to_expansion(self) -> Expansion {
+        Expansion::from(self as f64)
+    }
+}
+
+macro_rules! small_int_scalar {
+    ($($ty:ty),*) => {
+        $(impl Scalar for $ty {
+            fn to_expansion(self) -> Expansion {
+                Expansion::from(self as f64)
+            }
+        })*
+    };
+}
+
+small_int_scalar!(i8, i16, i32, u8, u16, u32);
+
+/// Splits an integer into its high half, which is converted recursively, and its low half.
+macro_rules! split_int_scalar {
+    ($($ty:ty: $hi:ty, $lo:ty, $bits:expr);*) => {
+        $(impl Scalar for $ty {
+            fn to_expansion(self) -> Expansion {
+                let hi = ((self >> $bits) as $hi).to_expansion().scale(2f64.powi($bits));
+                hi + (self as $lo).to_expansion()
+            }
+        })*
+    };
+}
+
+split_int_scalar!(i64: i32, u32, 32; u64: u32, u32, 32; i128: i64, u64, 64; u128: u64, u64, 64);
+
+/// The sign of the determinant with a row (**p**, 1) for each point, or (**p**, |**p**|², 1)
+/// if `lifted`, after perturbing the `dim`-dimensional points.
+fn evaluate<Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    dim: usize,
+    lifted: bool,
+    indexes: &[Idx],
+    point: impl Fn(Idx) -> P,
+) -> bool {
+    let point = |i| {
+        let p = point(i);
+        (0..dim).map(|c| p[c].to_expansion()).collect::<Vec<_>>()
+    };
+    let mut perturbed = Perturbed::new(dim, if lifted { 2 } else { 1 }, indexes, point);
+    let sign = perturbed.lazy_signum(|perturbed| {
+        let rows = indexes
+            .iter()
+            .map(|i| {
+                let mut row = (0..dim).map(|c| perturbed.coord(*i, c)).collect::<Vec<_>>();
+                if lifted {
+                    row.push(row.iter().fold(Polynomial::default(), |acc, x| acc + x.clone() * x.clone()));
+                }
+                row.push(Polynomial::from(Expansion::from(1.0)));
+                row
+            })
+            .collect::<Vec<_>>();
+        perturbed::det(&rows)
+    });
+    sign > 0.0
+}
+
+/// Like [`orient_1d`](crate::orient_1d), for any scalar.
+pub fn orient_1d<T: ?Sized, Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+) -> bool {
+    evaluate(1, false, &[i, j], |i| index_fn(list, i))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), for any scalar.
+pub fn orient_2d<T: ?Sized, Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> bool {
+    evaluate(2, false, &[i, j, k], |i| index_fn(list, i))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), for any scalar.
+pub fn orient_3d<T: ?Sized, Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    evaluate(3, false, &[i, j, k, l], |i| index_fn(list, i))
+}
+
+/// Like [`in_circle`](crate::in_circle), for any scalar.
+pub fn in_circle<T: ?Sized, Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    evaluate(2, true, &[i, j, k, l], |i| index_fn(list, i))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), for any scalar.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized, Idx: Ord + Copy, S: Scalar, P: Index<usize, Output = S>>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    evaluate(3, true, &[i, j, k, l, m], |i| index_fn(list, i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_1d, orient_2d, orient_3d, rng};
+    use crate::tests::grid_points_3d;
+    use nalgebra::{Vector1, Vector3};
+
+    #[test]
+    fn test_scalar() {
+        // Grid points as f32s and integers get the same results as f64s, ties and all
+        let points = grid_points_3d();
+        let floats = points.iter().map(|p| p.map(|x| x as f32)).collect::<Vec<_>>();
+        let ints = points.iter().map(|p| p.map(|x| x as i64 * 3 - 1)).collect::<Vec<_>>();
+        let shifted = points.iter().map(|p| p * 3.0 - Vector3::repeat(1.0)).collect::<Vec<_>>();
+        let wide = points.iter().map(|p| p.map(|x| (x as i128) << 80)).collect::<Vec<_>>();
+        let n = points.len();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                assert_eq!(super::orient_1d(&ints, |l, i| l[i], i, j), orient_1d(&shifted, |l, i| Vector1::new(l[i].x), i, j));
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    let expected = orient_2d(&points, |l, i| l[i].xy(), i, j, k);
+                    assert_eq!(super::orient_2d(&floats, |l, i| l[i].xy(), i, j, k), expected);
+                    assert_eq!(super::orient_2d(&wide, |l, i| l[i].xy(), i, j, k), expected);
+                    // The predicates sort the indexes, so the first 3 can go in order
+                    if i > j || j > k {
+                        continue;
+                    }
+                    for l in (0..n).filter(|l| *l != i && *l != j && *l != k) {
+                        let expected = orient_3d(&shifted, |l, i| l[i], i, j, k, l);
+                        assert_eq!(super::orient_3d(&ints, |l, i| l[i], i, j, k, l), expected);
+                        let expected = in_circle(&points, |l, i| l[i].xy(), i, j, k, l);
+                        assert_eq!(super::in_circle(&floats, |l, i| l[i].xy(), i, j, k, l), expected);
+                    }
+                }
+            }
+        }
+        let mut rng = rng::SplitMix(1504);
+        for _ in 0..300 {
+            let indexes = [(); 5].map(|_| rng.below(n));
+            if indexes.iter().enumerate().any(|(n, i)| indexes[..n].contains(i)) {
+                continue;
+            }
+            let [i, j, k, l, m] = indexes;
+            let expected = in_sphere(&shifted, |l, i| l[i], i, j, k, l, m);
+            assert_eq!(super::in_sphere(&ints, |l, i| l[i], i, j, k, l, m), expected);
+        }
+
+        // Integers too big to be f64s exactly
+        use crate::exact::Expansion;
+        let one = || Expansion::from(1.0);
+        assert_eq!((i64::MAX.to_expansion() - Expansion::from(2f64.powi(63)) + one()).signum(), 0.0);
+        assert_eq!((i64::MIN.to_expansion() + Expansion::from(2f64.powi(63))).signum(), 0.0);
+        assert_eq!((u128::MAX.to_expansion() - Expansion::from(2f64.powi(128)) + one()).signum(), 0.0);
+        assert_eq!((i128::MIN.to_expansion() + Expansion::from(2f64.powi(127))).signum(), 0.0);
+        let big = [[u64::MAX], [u64::MAX - 1]];
+        assert!(super::orient_1d(&big, |l, i| l[i], 0, 1));
+        assert!(!super::orient_1d(&big, |l, i| l[i], 1, 0));
+    }
+}
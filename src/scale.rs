@@ -0,0 +1,413 @@
+//! Exact power-of-2 prescaling of points too small for the exact predicates.
+//!
+//! The exact predicates multiply coordinate differences together, up to 5 at a time for the
+//! in-sphere test, and products that underflow lose bits, so a nonzero determinant of tiny points
+//! can come out as 0 and get decided by the perturbation instead. Scaling every point of a
+//! predicate by the same power of 2 is exact, and every determinant and every term of the
+//! perturbation is homogeneous in the coordinates, so it doesn't change any of their signs.
+//! Weights are compared with squared distances, so they get scaled by the square of the power of 2.
+
+use alloc::vec::Vec;
+use core::ops::{Index, MulAssign};
+
+/// Points whose largest coordinate has an exponent at least this don't get scaled.
+/// A 5th power of a difference of the smallest magnitude these can have,
+/// 2^(-64 - 52), still doesn't come near underflowing, even with the error terms.
+const MIN_EXPONENT: i32 = -64;
+
+/// The exponent of the largest power of 2 at most `x`, which is positive and finite.
+fn exponent(x: f64) -> i32 {
+    let bits = x.to_bits();
+    let biased = (bits >> 52) as i32;
+    if biased == 0 {
+        // Subnormal
+        63 - bits.leading_zeros() as i32 - 1074
+    } else {
+        biased - 1023
+    }
+}
+
+/// 2^`e`, for `e` a normal exponent.
+pub(crate) fn pow2(e: i32) -> f64 {
+    f64::from_bits(((e + 1023) as u64) << 52)
+}
+
+/// The exponent of the largest of `values` in absolute value,
+/// or `None` if they're all 0 or one isn't finite.
+fn max_exponent<'a>(values: impl IntoIterator<Item = &'a f64>) -> Option<i32> {
+    let max = values.into_iter().fold(0.0, |max: f64, c| max.max(c.abs()));
+    if max == 0.0 || !max.is_finite() {
+        None
+    } else {
+        Some(exponent(max))
+    }
+}
+
+/// The exponent of the power of 2 to scale `points` by, or 0 if they don't need it.
+/// Scaling brings the largest coordinate into [1, 2).
+pub fn prescale_exponent<P>(points: &[P]) -> i32
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    max_exponent(points.iter().flatten()).filter(|e| *e < MIN_EXPONENT).map_or(0, |e| -e)
+}
+
+/// Like [`prescale_exponent`], for points with weights, which get scaled by the square
+/// of the power of 2. A weight counts as much as a coordinate of about its square root.
+pub(crate) fn weighted_prescale_exponent<'a>(
+    coords: impl IntoIterator<Item = &'a f64>,
+    weights: impl IntoIterator<Item = &'a f64>,
+) -> i32 {
+    let weight = max_exponent(weights).map(|e| e.div_euclid(2));
+    max_exponent(coords).into_iter().chain(weight).max().filter(|e| *e < MIN_EXPONENT).map_or(0, |e| -e)
+}
+
+/// Scales a point by 2^`e`, exactly as long as it doesn't overflow.
+pub fn scaled<P: MulAssign<f64>>(mut point: P, e: i32) -> P {
+    // 2^e can be too big for an f64 when the coordinates are subnormal, or for weights
+    let mut e = e;
+    while e != 0 {
+        let step = e.clamp(-1000, 1000);
+        point *= pow2(step);
+        e -= step;
+    }
+    point
+}
+
+/// Scales all of `points` by the same power of 2 if they're small enough to underflow.
+pub fn prescaled<P: Copy + MulAssign<f64>, const N: usize>(points: [P; N]) -> [P; N]
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let e = prescale_exponent(&points);
+    points.map(|p| scaled(p, e))
+}
+
+/// Fetches the points at `indexes` once and scales them like [`prescaled`],
+/// returning an indexing function that looks them up, for predicate bodies that index the list.
+pub(crate) fn prefetched<'l, T: ?Sized, Idx: PartialEq + Copy, P: Copy + MulAssign<f64>, const N: usize>(
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> P,
+    indexes: [Idx; N],
+) -> impl Fn(&T, Idx) -> P + Clone
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let points = prescaled(indexes.map(|i| index_fn(list, i)));
+    move |_: &T, i| points[indexes.iter().position(|j| *j == i).expect("point not in the predicate")]
+}
+
+/// The points of a predicate, fetched once and scaled together
+/// by [`weighted_prescale_exponent`] if they're small enough to underflow.
+pub(crate) struct Prescaled<Idx> {
+    indexes: Vec<Idx>,
+    points: Vec<Vec<f64>>,
+    exponent: i32,
+}
+
+impl<Idx: PartialEq + Copy> Prescaled<Idx> {
+    /// Fetches the points at `indexes`, each with `dim` coordinates followed by `weights` weights.
+    /// `others` are more weights the predicate compares the points with, such as α,
+    /// which count towards the scale and get scaled with [`weight`](Self::weight).
+    pub(crate) fn new<P: Index<usize, Output = f64>>(
+        dim: usize,
+        weights: usize,
+        indexes: &[Idx],
+        others: &[f64],
+        point: impl Fn(Idx) -> P,
+    ) -> Self {
+        let mut unique = Vec::with_capacity(indexes.len());
+        for i in indexes {
+            if !unique.contains(i) {
+                unique.push(*i);
+            }
+        }
+        let mut points = unique
+            .iter()
+            .map(|i| {
+                let p = point(*i);
+                (0..dim + weights).map(|c| p[c]).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let exponent = weighted_prescale_exponent(
+            points.iter().flat_map(|p| &p[..dim]),
+            points.iter().flat_map(|p| &p[dim..]).chain(others),
+        );
+        if exponent != 0 {
+            for p in &mut points {
+                for (c, x) in p.iter_mut().enumerate() {
+                    *x = scaled(*x, if c < dim { exponent } else { 2 * exponent });
+                }
+            }
+        }
+        Self { indexes: unique, points, exponent }
+    }
+
+    /// The scaled coordinates and weights of point `i`.
+    pub(crate) fn point(&self, i: Idx) -> Coords<'_> {
+        let n = self.indexes.iter().position(|j| *j == i).expect("point not in the predicate");
+        Coords(&self.points[n])
+    }
+
+    /// Scales a weight the same way as the points' weights.
+    pub(crate) fn weight(&self, w: f64) -> f64 {
+        scaled(w, 2 * self.exponent)
+    }
+}
+
+/// The coordinates of a point in [`Prescaled`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Coords<'a>(&'a [f64]);
+
+impl Index<usize> for Coords<'_> {
+    type Output = f64;
+
+    fn index(&self, c: usize) -> &f64 {
+        &self.0[c]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Vec2, calipers, cdt, cgal, distance, in_circle, in_circle_multi, in_circle_strict,
+        in_sphere, in_sphere_strict, orient_2d, orient_2d_strict, orient_3d, orient_3d_strict,
+        pinned, query, rg, soa, weighted,
+    };
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_subnormal() {
+        let points = grid_points_2d();
+        let n = points.len();
+        // The exact predicates alone lose the determinant to underflow
+        let tiny = |p: Vec2| super::scaled(p, -1070);
+        assert_eq!(rg::orient_2d(tiny(points[0]), tiny(points[1]), tiny(points[3])), 0.0);
+
+        // Subnormal, then normal but with underflowing products
+        for e in [-1070, -1000, -300] {
+            let small = points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>();
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        assert_eq!(
+                            orient_2d(&small, |l, i| l[i], i, j, k),
+                            orient_2d(&points, |l, i| l[i], i, j, k)
+                        );
+                        let l = (k + 1) % n;
+                        if l != i && l != j {
+                            assert_eq!(
+                                in_circle(&small, |l, i| l[i], i, j, k, l),
+                                in_circle(&points, |l, i| l[i], i, j, k, l)
+                            );
+                        }
+                    }
+                }
+                let (j, k) = ((i + 1) % n, (i + 4) % n);
+                let queries = (0..n).filter(|l| *l != i && *l != j && *l != k).collect::<Vec<_>>();
+                assert_eq!(
+                    in_circle_multi(&small, |l, i| l[i], i, j, k, &queries),
+                    in_circle_multi(&points, |l, i| l[i], i, j, k, &queries)
+                );
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        for e in [-1070, -1000, -300] {
+            let small = points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>();
+            for i in 0..n {
+                let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+                assert_eq!(
+                    orient_3d(&small, |l, i| l[i], i, j, k, l),
+                    orient_3d(&points, |l, i| l[i], i, j, k, l)
+                );
+                assert_eq!(
+                    in_sphere(&small, |l, i| l[i], i, j, k, l, m),
+                    in_sphere(&points, |l, i| l[i], i, j, k, l, m)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_subnormal_strict_and_diagnostics() {
+        use crate::analysis::{analyze_2d, analyze_3d, Options};
+        use crate::diagnostics::{in_circle_case, in_sphere_case, orient_2d_case, orient_3d_case};
+
+        let points = grid_points_2d();
+        let n = points.len();
+        // Around 1e-170, where products underflow, and subnormal
+        let tiny = [-565, -1070].map(|e| points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>());
+        for small in &tiny {
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        let strict = orient_2d_strict(small, |l, i| l[i], i, j, k);
+                        assert_eq!(strict, orient_2d_strict(&points, |l, i| l[i], i, j, k));
+                        if let Ok(result) = strict {
+                            assert_eq!(result, orient_2d(small, |l, i| l[i], i, j, k));
+                        }
+                        assert_eq!(orient_2d_case(small, |l, i| l[i], i, j, k), orient_2d_case(&points, |l, i| l[i], i, j, k));
+
+                        let l = (k + 1) % n;
+                        if l != i && l != j {
+                            let strict = in_circle_strict(small, |l, i| l[i], i, j, k, l);
+                            assert_eq!(strict, in_circle_strict(&points, |l, i| l[i], i, j, k, l));
+                            if let Ok(result) = strict {
+                                assert_eq!(result, in_circle(small, |l, i| l[i], i, j, k, l));
+                                assert_eq!(result, cgal::in_circle(small, |l, i| l[i], i, j, k, l));
+                            }
+                            assert_eq!(
+                                in_circle_case(small, |l, i| l[i], i, j, k, l),
+                                in_circle_case(&points, |l, i| l[i], i, j, k, l)
+                            );
+                            assert_eq!(
+                                cgal::in_circle(small, |l, i| l[i], i, j, k, l),
+                                cgal::in_circle(&points, |l, i| l[i], i, j, k, l)
+                            );
+                        }
+                    }
+                }
+            }
+            let options = Options::default();
+            assert_eq!(analyze_2d(small, |l, i| l[i], n, &options), analyze_2d(&points, |l, i| l[i], n, &options));
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        let tiny = [-565, -1070].map(|e| points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>());
+        for small in &tiny {
+            for i in 0..n {
+                let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+                let strict = orient_3d_strict(small, |l, i| l[i], i, j, k, l);
+                assert_eq!(strict, orient_3d_strict(&points, |l, i| l[i], i, j, k, l));
+                if let Ok(result) = strict {
+                    assert_eq!(result, orient_3d(small, |l, i| l[i], i, j, k, l));
+                }
+                assert_eq!(orient_3d_case(small, |l, i| l[i], i, j, k, l), orient_3d_case(&points, |l, i| l[i], i, j, k, l));
+
+                let strict = in_sphere_strict(small, |l, i| l[i], i, j, k, l, m);
+                assert_eq!(strict, in_sphere_strict(&points, |l, i| l[i], i, j, k, l, m));
+                if let Ok(result) = strict {
+                    assert_eq!(result, in_sphere(small, |l, i| l[i], i, j, k, l, m));
+                    assert_eq!(result, cgal::in_sphere(small, |l, i| l[i], i, j, k, l, m));
+                }
+                assert_eq!(
+                    in_sphere_case(small, |l, i| l[i], i, j, k, l, m),
+                    in_sphere_case(&points, |l, i| l[i], i, j, k, l, m)
+                );
+                assert_eq!(
+                    cgal::in_sphere(small, |l, i| l[i], i, j, k, l, m),
+                    cgal::in_sphere(&points, |l, i| l[i], i, j, k, l, m)
+                );
+            }
+            let options = Options::default();
+            assert_eq!(analyze_3d(small, |l, i| l[i], n, &options), analyze_3d(&points, |l, i| l[i], n, &options));
+        }
+    }
+
+    #[test]
+    fn test_subnormal_perturbed() {
+        use std::cmp::Ordering;
+
+        // Outside the circle through the first 3, which products of these lose to underflow
+        let fixture = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.1, 1.1)].map(|(x, y)| Vector2::new(x, y) * 1e-200);
+        let xs = fixture.map(|p| p.x);
+        let ys = fixture.map(|p| p.y);
+        let weighted_fixture = fixture.map(|p| (p, 0.0));
+        assert_eq!(pinned::in_circle(&fixture, |l, i| l[i], |_| false, 0, 1, 2, 3), Ok(false));
+        assert_eq!(query::in_circle(&fixture, |l, i| l[i], 0, 1, 2, 3), Ok(false));
+        assert_eq!(distance::distance_cmp_2d(&fixture, |l, i| l[i], 0, 3, 1, 2), Ordering::Greater);
+        assert!(!soa::in_circle(&xs, &ys, 0, 1, 2, 3));
+        assert!(!weighted::power_in_circle(&fixture, |l, i| l[i], |_, _| 0.0, 0, 1, 2, 3));
+        assert_eq!(weighted::in_power_circle(&weighted_fixture, |l, i| l[i], 0, 1, 2, 3), Ok(false));
+
+        let points = grid_points_2d();
+        let n = points.len();
+        let weights = (0..n).map(|i| (i % 3) as f64).collect::<Vec<_>>();
+        for e in [-1070, -565, -300] {
+            let small = points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>();
+            let (xs, ys) = (small.iter().map(|p| p.x).collect::<Vec<_>>(), small.iter().map(|p| p.y).collect::<Vec<_>>());
+            // Weights and α scale with the square, so only the largest scale keeps them
+            let weights = if e == -300 { weights.clone() } else { vec![0.0; n] };
+            let alpha = if e == -300 { 2.0 } else { 0.0 };
+            let small_weighted = small.iter().zip(&weights).map(|(p, w)| (*p, super::scaled(*w, 2 * e))).collect::<Vec<_>>();
+            let weighted = points.iter().zip(&weights).map(|(p, w)| (*p, *w)).collect::<Vec<_>>();
+            for i in 0..n {
+                let (j, k, l) = ((i + 1) % n, (i + 3) % n, (i + 4) % n);
+                let pinned = |x| x == j;
+                assert_eq!(
+                    pinned::orient_2d(&small, |l, i| l[i], pinned, i, j, k),
+                    pinned::orient_2d(&points, |l, i| l[i], pinned, i, j, k)
+                );
+                assert_eq!(
+                    pinned::in_circle(&small, |l, i| l[i], pinned, i, j, k, l),
+                    pinned::in_circle(&points, |l, i| l[i], pinned, i, j, k, l)
+                );
+                assert_eq!(
+                    distance::distance_cmp_2d(&small, |l, i| l[i], i, j, k, l),
+                    distance::distance_cmp_2d(&points, |l, i| l[i], i, j, k, l)
+                );
+                assert_eq!(
+                    distance::in_diametral_circle(&small, |l, i| l[i], i, j, k),
+                    distance::in_diametral_circle(&points, |l, i| l[i], i, j, k)
+                );
+                assert_eq!(soa::orient_2d(&xs, &ys, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+                assert_eq!(soa::in_circle(&xs, &ys, i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+                assert_eq!(
+                    weighted::power_in_circle(&small, |l, i| l[i], |_, i| super::scaled(weights[i], 2 * e), i, j, k, l),
+                    weighted::power_in_circle(&points, |l, i| l[i], |_, i| weights[i], i, j, k, l)
+                );
+                assert_eq!(
+                    weighted::in_power_circle(&small_weighted, |l, i| l[i], i, j, k, l).ok(),
+                    weighted::in_power_circle(&weighted, |l, i| l[i], i, j, k, l).ok()
+                );
+                assert_eq!(
+                    weighted::power_distance_cmp_2d(&small_weighted, |l, i| l[i], l, i, j),
+                    weighted::power_distance_cmp_2d(&weighted, |l, i| l[i], l, i, j)
+                );
+                assert_eq!(
+                    weighted::power_radius_cmp_2d(&small_weighted, |l, i| l[i], &[i, j, k], super::scaled(alpha, 2 * e)),
+                    weighted::power_radius_cmp_2d(&weighted, |l, i| l[i], &[i, j, k], alpha)
+                );
+                assert_eq!(
+                    calipers::cross_sign(&small, |l, i| l[i], [i, j], [k, l]),
+                    calipers::cross_sign(&points, |l, i| l[i], [i, j], [k, l])
+                );
+                assert_eq!(cdt::encroaches(&small, |l, i| l[i], i, j, k), cdt::encroaches(&points, |l, i| l[i], i, j, k));
+                assert_eq!(
+                    cdt::segment_encroaches(&small, |l, i| l[i], i, j, k, l),
+                    cdt::segment_encroaches(&points, |l, i| l[i], i, j, k, l)
+                );
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        for e in [-1070, -565] {
+            let small = points.iter().map(|p| super::scaled(*p, e)).collect::<Vec<_>>();
+            let coords = |c: usize| small.iter().map(|p| p[c]).collect::<Vec<_>>();
+            let (xs, ys, zs) = (coords(0), coords(1), coords(2));
+            for i in 0..n {
+                let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+                assert_eq!(
+                    pinned::in_sphere(&small, |l, i| l[i], |x| x == k, i, j, k, l, m),
+                    pinned::in_sphere(&points, |l, i| l[i], |x| x == k, i, j, k, l, m)
+                );
+                assert_eq!(
+                    distance::distance_cmp_3d(&small, |l, i| l[i], i, j, k, l),
+                    distance::distance_cmp_3d(&points, |l, i| l[i], i, j, k, l)
+                );
+                assert_eq!(soa::orient_3d(&xs, &ys, &zs, i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+                assert_eq!(soa::in_sphere(&xs, &ys, &zs, i, j, k, l, m), in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+                assert_eq!(
+                    weighted::power_in_sphere(&small, |l, i| l[i], |_, _| 0.0, i, j, k, l, m),
+                    weighted::power_in_sphere(&points, |l, i| l[i], |_, _| 0.0, i, j, k, l, m)
+                );
+            }
+        }
+    }
+}
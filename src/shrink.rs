@@ -0,0 +1,205 @@
+//! Minimizing a recorded failure to a small reproducer.
+//!
+//! Given the points and the [`Transcript`] of a run where some invariant of downstream code
+//! fails, such as a triangulation becoming corrupt, the functions here repeatedly
+//!
+//! * drop chunks of calls from the transcript,
+//! * drop points, along with the calls that use them, and
+//! * snap coordinates to 0, integers, or binary fractions with few bits,
+//!
+//! keeping each change if the invariant still fails. Dropping a point relabels the points
+//! after it without changing their order, which doesn't change any predicate's result.
+//! Snapping is only kept if the transcript still replays without mismatches,
+//! so the reproducer stays consistent.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, shrink, transcript::{Predicate, Recorder}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.1, 0.2),
+//!     Vector2::new(3.7, 0.4),
+//!     Vector2::new(1.3, 2.9),
+//!     Vector2::new(2.2, 5.1),
+//! ];
+//! let recorder = Recorder::new();
+//! recorder.orient_2d(&points, |l, i| l[i], 0, 1, 2);
+//! recorder.orient_2d(&points, |l, i| l[i], 0, 1, 3);
+//! recorder.orient_2d(&points, |l, i| l[i], 1, 2, 3);
+//!
+//! // The "invariant" fails when some orientation is negative
+//! let repro = shrink::shrink_2d(&points, &recorder.into_transcript(), |_, transcript| {
+//!     transcript.calls.iter().any(|call| call.predicate == Predicate::Orient2d && !call.result)
+//! });
+//! assert_eq!(repro.transcript.calls.len(), 1);
+//! assert_eq!(repro.points.len(), 3);
+//! ```
+
+use crate::float;
+use crate::transcript::Transcript;
+use crate::{Vec1, Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::ops::IndexMut;
+
+/// A point set and a transcript of calls on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reproducer<P> {
+    pub points: Vec<P>,
+    pub transcript: Transcript,
+}
+
+/// Simpler values to try for a coordinate, simplest first.
+fn snaps(x: f64) -> Vec<f64> {
+    let mut snaps = vec![0.0, float::trunc(x), float::round(x)];
+    snaps.extend((1..=8).map(|k| float::round(x * f64::from(1 << k)) / f64::from(1 << k)));
+    snaps.retain(|s| *s != x);
+    snaps.dedup();
+    snaps
+}
+
+/// Removes a point and the calls that use it, relabeling the points after it.
+fn remove_point<P: Clone>(repro: &Reproducer<P>, point: usize) -> Reproducer<P> {
+    let mut points = repro.points.clone();
+    points.remove(point);
+    let mut transcript = repro.transcript.clone();
+    transcript.calls.retain(|call| !call.indexes.contains(&point));
+    for index in transcript.calls.iter_mut().flat_map(|call| &mut call.indexes) {
+        if *index > point {
+            *index -= 1;
+        }
+    }
+    Reproducer { points, transcript }
+}
+
+fn shrink<P: Clone + IndexMut<usize, Output = f64>>(
+    dim: usize,
+    mut repro: Reproducer<P>,
+    mut fails: impl FnMut(&[P], &Transcript) -> bool,
+    consistent: impl Fn(&[P], &Transcript) -> bool,
+) -> Reproducer<P> {
+    if !fails(&repro.points, &repro.transcript) {
+        return repro;
+    }
+
+    loop {
+        let mut progress = false;
+
+        // Drop chunks of calls, halving the chunk size each round
+        let mut chunk = (repro.transcript.calls.len() / 2).max(1);
+        while chunk > 0 && !repro.transcript.calls.is_empty() {
+            let mut start = 0;
+            while start < repro.transcript.calls.len() {
+                let mut transcript = repro.transcript.clone();
+                transcript.calls.drain(start..(start + chunk).min(transcript.calls.len()));
+                if fails(&repro.points, &transcript) {
+                    repro.transcript = transcript;
+                    progress = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            chunk /= 2;
+        }
+
+        // Drop points, with the calls that use them
+        for point in (0..repro.points.len()).rev() {
+            let candidate = remove_point(&repro, point);
+            if fails(&candidate.points, &candidate.transcript) {
+                repro = candidate;
+                progress = true;
+            }
+        }
+
+        // Snap coordinates
+        for point in 0..repro.points.len() {
+            for coord in 0..dim {
+                for value in snaps(repro.points[point][coord]) {
+                    let mut points = repro.points.clone();
+                    points[point][coord] = value;
+                    if consistent(&points, &repro.transcript) && fails(&points, &repro.transcript) {
+                        repro.points = points;
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if !progress {
+            return repro;
+        }
+    }
+}
+
+/// Minimizes 1-dimensional points and a transcript of calls on them
+/// while `fails` still returns `true` for them.
+/// If it doesn't return `true` for the input, the input is returned unchanged.
+pub fn shrink_1d(
+    points: &[Vec1],
+    transcript: &Transcript,
+    fails: impl FnMut(&[Vec1], &Transcript) -> bool,
+) -> Reproducer<Vec1> {
+    let repro = Reproducer { points: points.to_vec(), transcript: transcript.clone() };
+    shrink(1, repro, fails, |points, transcript| transcript.replay_1d(points, |l, i| l[i]).is_empty())
+}
+
+/// Minimizes 2-dimensional points and a transcript of calls on them
+/// while `fails` still returns `true` for them.
+/// If it doesn't return `true` for the input, the input is returned unchanged.
+pub fn shrink_2d(
+    points: &[Vec2],
+    transcript: &Transcript,
+    fails: impl FnMut(&[Vec2], &Transcript) -> bool,
+) -> Reproducer<Vec2> {
+    let repro = Reproducer { points: points.to_vec(), transcript: transcript.clone() };
+    shrink(2, repro, fails, |points, transcript| transcript.replay_2d(points, |l, i| l[i]).is_empty())
+}
+
+/// Minimizes 3-dimensional points and a transcript of calls on them
+/// while `fails` still returns `true` for them.
+/// If it doesn't return `true` for the input, the input is returned unchanged.
+pub fn shrink_3d(
+    points: &[Vec3],
+    transcript: &Transcript,
+    fails: impl FnMut(&[Vec3], &Transcript) -> bool,
+) -> Reproducer<Vec3> {
+    let repro = Reproducer { points: points.to_vec(), transcript: transcript.clone() };
+    shrink(3, repro, fails, |points, transcript| transcript.replay_3d(points, |l, i| l[i]).is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transcript;
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector3;
+
+    #[test]
+    fn test_shrink() {
+        use crate::transcript::{Predicate, Recorder};
+
+        let points = grid_points_3d().iter().map(|p| p * 1.375 + Vector3::new(0.1, 0.2, 0.3)).collect::<Vec<_>>();
+        let recorder = Recorder::new();
+        for i in 0..points.len() - 4 {
+            recorder.orient_3d(&points, |l, i| l[i], i, i + 1, i + 2, i + 3);
+            recorder.in_sphere(&points, |l, i| l[i], i, i + 1, i + 2, i + 3, i + 4);
+        }
+        let transcript = recorder.into_transcript();
+        let fails = |_: &[Vec3], transcript: &transcript::Transcript| {
+            transcript.calls.iter().any(|call| call.predicate == Predicate::InSphere && call.result)
+        };
+        assert!(fails(&points, &transcript));
+
+        let repro = super::shrink_3d(&points, &transcript, fails);
+        assert_eq!(repro.transcript.calls.len(), 1);
+        assert_eq!(repro.points.len(), 5);
+        assert!(fails(&repro.points, &repro.transcript));
+        assert!(repro.transcript.replay_3d(&repro.points, |l, i| l[i]).is_empty());
+        assert!(repro.points.iter().flat_map(|p| p.iter()).all(|c| c.fract() == 0.0));
+
+        // Unchanged if it doesn't fail
+        let repro = super::shrink_3d(&points, &transcript, |_, _| false);
+        assert_eq!(repro, super::Reproducer { points, transcript });
+    }
+}
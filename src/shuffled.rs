@@ -0,0 +1,167 @@
+//! Variants of the predicates that perturb points in a pseudorandom order.
+//!
+//! The usual predicates perturb lower indexes more, so on sorted or otherwise structured input,
+//! the ties in a degenerate configuration all break the same way, which can be a worst case
+//! for algorithms like incremental construction. These perturb the points in the order of
+//! a permutation of the indexes that's chosen pseudorandomly from a seed, so the ties break
+//! as if the points were shuffled first, and the results are still reproducible.
+//!
+//! The order is the order of the [`key`]s of the indexes, which any predicate in the crate
+//! can use directly. The keys are distinct for distinct indexes, so this is a genuine permutation.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, shuffled};
+//! # use nalgebra::Vector2;
+//! let points = (0..8).map(|i| Vector2::new(i as f64, 0.0)).collect::<Vec<_>>();
+//! // Every triple is collinear, so the seed decides how the ties break
+//! let orient = |seed| shuffled::orient_2d(&points, |l, i| l[i], seed, 2, 5, 7);
+//! assert_eq!(orient(1), orient(1));
+//! assert!((0..16).any(|seed| orient(seed) != orient(0)));
+//! ```
+
+use crate::rng::SplitMix;
+use crate::{Keyed, Vec1, Vec2, Vec3};
+
+/// The key that decides where index `i` goes in the perturbation order for `seed`.
+///
+/// This is the `i`-th output of a SplitMix64 generator seeded with `seed`,
+/// which is a bijection of `i`, so different indexes never share a key.
+pub fn key(seed: u64, i: usize) -> Keyed<u64> {
+    Keyed::new(SplitMix(seed.wrapping_add((i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))).next(), i)
+}
+
+fn keyed<T: ?Sized, P>(index_fn: impl Fn(&T, usize) -> P + Clone) -> impl Fn(&T, Keyed<u64>) -> P + Clone {
+    move |list, k| index_fn(list, k.index)
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but perturbing in the order for `seed`.
+pub fn orient_1d<T: ?Sized>(list: &T, index_fn: impl Fn(&T, usize) -> Vec1 + Clone, seed: u64, i: usize, j: usize) -> bool {
+    crate::orient_1d(list, keyed(index_fn), key(seed, i), key(seed, j))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but perturbing in the order for `seed`.
+pub fn orient_2d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+) -> bool {
+    crate::orient_2d(list, keyed(index_fn), key(seed, i), key(seed, j), key(seed, k))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but perturbing in the order for `seed`.
+pub fn orient_3d<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    crate::orient_3d(list, keyed(index_fn), key(seed, i), key(seed, j), key(seed, k), key(seed, l))
+}
+
+/// Like [`in_circle`](crate::in_circle), but perturbing in the order for `seed`.
+pub fn in_circle<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    crate::in_circle(list, keyed(index_fn), key(seed, i), key(seed, j), key(seed, k), key(seed, l))
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), but perturbing in the order for `seed`.
+pub fn in_circle_unoriented<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+) -> bool {
+    crate::in_circle_unoriented(list, keyed(index_fn), key(seed, i), key(seed, j), key(seed, k), key(seed, l))
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but perturbing in the order for `seed`.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+    m: usize,
+) -> bool {
+    crate::in_sphere(list, keyed(index_fn), key(seed, i), key(seed, j), key(seed, k), key(seed, l), key(seed, m))
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), but perturbing in the order for `seed`.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_unoriented<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+    seed: u64,
+    i: usize,
+    j: usize,
+    k: usize,
+    l: usize,
+    m: usize,
+) -> bool {
+    let (i, j, k, l, m) = (key(seed, i), key(seed, j), key(seed, k), key(seed, l), key(seed, m));
+    crate::in_sphere_unoriented(list, keyed(index_fn), i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, orient_2d};
+    use crate::tests::grid_points_2d;
+
+    #[test]
+    fn test_shuffled() {
+        let points = grid_points_2d();
+        let n = points.len();
+        let mut orders = vec![];
+        for seed in [0, 1, 0xDEAD_BEEF] {
+            // The same as perturbing the points in order after moving each to its rank
+            let mut order = (0..n).collect::<Vec<_>>();
+            order.sort_by_key(|i| super::key(seed, *i));
+            let mut rank = vec![0; n];
+            for (r, i) in order.iter().enumerate() {
+                rank[*i] = r;
+            }
+            let permuted = order.iter().map(|i| points[*i]).collect::<Vec<_>>();
+
+            for i in 0..n {
+                for j in (0..n).filter(|j| *j != i) {
+                    for k in (0..n).filter(|k| *k != i && *k != j) {
+                        assert_eq!(
+                            super::orient_2d(&points, |l, i| l[i], seed, i, j, k),
+                            orient_2d(&permuted, |l, i| l[i], rank[i], rank[j], rank[k])
+                        );
+                        let l = (k + 1) % n;
+                        if l != i && l != j {
+                            assert_eq!(
+                                super::in_circle(&points, |l, i| l[i], seed, i, j, k, l),
+                                in_circle(&permuted, |l, i| l[i], rank[i], rank[j], rank[k], rank[l])
+                            );
+                        }
+                    }
+                }
+            }
+            orders.push(order);
+        }
+        assert!(orders[0] != orders[1] && orders[1] != orders[2]);
+    }
+}
@@ -0,0 +1,206 @@
+//! Variants of the predicates that also say whether the perturbation decided them.
+//!
+//! The predicates return `bool`, which hides whether the unperturbed determinant was 0.
+//! These return an [`Orientation`] instead, which still has the perturbed result,
+//! so callers can count degeneracies, log them, or handle them some other way,
+//! without evaluating the predicate twice.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, orient_2d_sign, Orientation};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(2.0, 0.0),
+//! ];
+//! assert_eq!(orient_2d_sign(&points, |l, i| l[i], 0, 1, 2), Orientation::Positive);
+//! assert_eq!(orient_2d_sign(&points, |l, i| l[i], 1, 0, 2), Orientation::Negative);
+//! // Collinear, so the perturbation decides
+//! let sign = orient_2d_sign(&points, |l, i| l[i], 0, 1, 3);
+//! assert!(sign.is_degenerate());
+//! assert_eq!(sign.is_positive(), simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 3));
+//! ```
+
+use crate::scale;
+use crate::{rg, SosPoint, Vec1, Vec2, Vec3};
+use core::ops::MulAssign;
+
+/// The sign of a predicate's determinant, and the result of the predicate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    /// The determinant is positive
+    Positive,
+    /// The determinant is negative
+    Negative,
+    /// The determinant is 0, and the perturbation decided the result
+    Degenerate {
+        /// The result of the predicate
+        positive: bool,
+    },
+}
+
+impl Orientation {
+    /// Returns the result of the predicate, which is decided by the perturbation if it's degenerate.
+    pub fn is_positive(self) -> bool {
+        match self {
+            Orientation::Positive => true,
+            Orientation::Negative => false,
+            Orientation::Degenerate { positive } => positive,
+        }
+    }
+
+    /// Returns whether the determinant is 0, so the perturbation decided the result.
+    pub fn is_degenerate(self) -> bool {
+        matches!(self, Orientation::Degenerate { .. })
+    }
+}
+
+/// Evaluates the determinant of some points, scaled up if they need it,
+/// and falls back to `perturbed` if it's 0.
+fn evaluate<P: Copy + MulAssign<f64>, const N: usize>(
+    points: [P; N],
+    det: impl FnOnce([P; N]) -> f64,
+    perturbed: impl FnOnce() -> bool,
+) -> Orientation
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let e = scale::prescale_exponent(&points);
+    let val = det(points.map(|p| scale::scaled(p, e)));
+    if val > 0.0 {
+        Orientation::Positive
+    } else if val < 0.0 {
+        Orientation::Negative
+    } else {
+        Orientation::Degenerate { positive: perturbed() }
+    }
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but also says whether the points are equal.
+pub fn orient_1d_sign<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+) -> Orientation {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j)];
+    evaluate(points, |p| p[0].x - p[1].x, || crate::orient_1d(list, &index_fn, i, j))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but also says whether the points are collinear.
+pub fn orient_2d_sign<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> Orientation {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k)];
+    evaluate(points, |p| rg::orient_2d(p[0], p[1], p[2]), || crate::orient_2d(list, &index_fn, i, j, k))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but also says whether the points are coplanar.
+pub fn orient_3d_sign<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Orientation {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+    evaluate(points, |p| rg::orient_3d(p[0], p[1], p[2], p[3]), || {
+        crate::orient_3d(list, &index_fn, i, j, k, l)
+    })
+}
+
+/// Like [`in_circle`](crate::in_circle), but also says whether the points are cocircular.
+pub fn in_circle_sign<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Orientation {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+    evaluate(points, |p| rg::in_circle(p[0], p[1], p[2], p[3]), || {
+        crate::in_circle(list, index_fn, i, j, k, l)
+    })
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but also says whether the points are cospherical.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_sign<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> Orientation {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l), index_fn(list, m)];
+    evaluate(points, |p| rg::in_sphere(p[0], p[1], p[2], p[3], p[4]), || {
+        crate::in_sphere(list, index_fn, i, j, k, l, m)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        SimplicityError, in_circle, in_circle_strict, in_sphere, orient_1d, orient_1d_strict,
+        orient_2d, orient_2d_strict, orient_3d, orient_3d_strict,
+    };
+    use crate::tests::grid_points_3d;
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_sign() {
+        let points = grid_points_3d();
+        let flat = points.iter().map(|p| p.xy()).collect::<Vec<_>>();
+        let degenerate = |result: Result<bool, SimplicityError<usize>>| result.map_err(|e| e.is_degenerate()) == Err(true);
+        let mut count = 0;
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                let sign = orient_1d_sign(&points, |l, i| Vector1::new(l[i].x), i, j);
+                assert_eq!(sign.is_positive(), orient_1d(&points, |l, i| Vector1::new(l[i].x), i, j));
+                assert_eq!(sign.is_degenerate(), degenerate(orient_1d_strict(&points, |l, i| Vector1::new(l[i].x), i, j)));
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    let sign = orient_2d_sign(&flat, |l, i| l[i], i, j, k);
+                    assert_eq!(sign.is_positive(), orient_2d(&flat, |l, i| l[i], i, j, k));
+                    assert_eq!(sign.is_degenerate(), degenerate(orient_2d_strict(&flat, |l, i| l[i], i, j, k)));
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        let sign = orient_3d_sign(&points, |l, i| l[i], i, j, k, l);
+                        assert_eq!(sign.is_positive(), orient_3d(&points, |l, i| l[i], i, j, k, l));
+                        assert_eq!(sign.is_degenerate(), degenerate(orient_3d_strict(&points, |l, i| l[i], i, j, k, l)));
+                        let sign = in_circle_sign(&flat, |l, i| l[i], i, j, k, l);
+                        assert_eq!(sign.is_positive(), in_circle(&flat, |l, i| l[i], i, j, k, l));
+                        assert_eq!(sign.is_degenerate(), degenerate(in_circle_strict(&flat, |l, i| l[i], i, j, k, l)));
+                        count += sign.is_degenerate() as usize;
+                    }
+                }
+            }
+        }
+        assert!(count > 0);
+
+        // Tiny points are scaled up, so they aren't degenerate
+        let tiny = 2f64.powi(-500);
+        let scaled = points.iter().map(|p| p * tiny).collect::<Vec<_>>();
+        for (i, j, k, l, m) in [(0, 1, 2, 4, 3), (0, 1, 2, 4, 7), (0, 1, 2, 3, 8)].iter().copied() {
+            let sign = in_sphere_sign(&points, |l, i| l[i], i, j, k, l, m);
+            assert_eq!(in_sphere_sign(&scaled, |l, i| l[i], i, j, k, l, m), sign);
+            assert_eq!(sign.is_positive(), in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+            assert_eq!(sign == Orientation::Positive, rg::in_sphere(points[i], points[j], points[k], points[l], points[m]) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Variants of the predicates that take a slice of points instead of an indexing function.
+//!
+//! Most of the time, the points are just in a slice or a `Vec`, and the indexes are positions in it.
+//! These take the slice directly, so they need no closure. The points can be anything that
+//! implements [`SosPoint`], like `nalgebra` vectors, arrays, or structs that derive it.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, slice};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//!     Vector2::new(1.0, 1.0),
+//! ];
+//! assert!(slice::orient_2d(&points, 0, 1, 2));
+//! assert_eq!(slice::in_circle(&points, 0, 1, 2, 3), simplicity::in_circle(&points, |l, i| l[i], 0, 1, 2, 3));
+//! ```
+
+use crate::{SosPoint, Vec1, Vec2, Vec3};
+
+fn vector<P: SosPoint>(points: &[P], i: usize) -> P::Vector {
+    points[i].to_vector()
+}
+
+/// Like [`orient_1d`](crate::orient_1d), with the points in a slice.
+pub fn orient_1d<P: SosPoint<Vector = Vec1>>(points: &[P], i: usize, j: usize) -> bool {
+    crate::orient_1d(points, vector, i, j)
+}
+
+/// Like [`orient_2d`](crate::orient_2d), with the points in a slice.
+pub fn orient_2d<P: SosPoint<Vector = Vec2>>(points: &[P], i: usize, j: usize, k: usize) -> bool {
+    crate::orient_2d(points, vector, i, j, k)
+}
+
+/// Like [`orient_3d`](crate::orient_3d), with the points in a slice.
+pub fn orient_3d<P: SosPoint<Vector = Vec3>>(points: &[P], i: usize, j: usize, k: usize, l: usize) -> bool {
+    crate::orient_3d(points, vector, i, j, k, l)
+}
+
+/// Like [`in_circle`](crate::in_circle), with the points in a slice.
+pub fn in_circle<P: SosPoint<Vector = Vec2>>(points: &[P], i: usize, j: usize, k: usize, l: usize) -> bool {
+    crate::in_circle(points, vector, i, j, k, l)
+}
+
+/// Like [`in_circle_unoriented`](crate::in_circle_unoriented), with the points in a slice.
+pub fn in_circle_unoriented<P: SosPoint<Vector = Vec2>>(points: &[P], i: usize, j: usize, k: usize, l: usize) -> bool {
+    crate::in_circle_unoriented(points, vector, i, j, k, l)
+}
+
+/// Like [`in_sphere`](crate::in_sphere), with the points in a slice.
+pub fn in_sphere<P: SosPoint<Vector = Vec3>>(points: &[P], i: usize, j: usize, k: usize, l: usize, m: usize) -> bool {
+    crate::in_sphere(points, vector, i, j, k, l, m)
+}
+
+/// Like [`in_sphere_unoriented`](crate::in_sphere_unoriented), with the points in a slice.
+pub fn in_sphere_unoriented<P: SosPoint<Vector = Vec3>>(points: &[P], i: usize, j: usize, k: usize, l: usize, m: usize) -> bool {
+    crate::in_sphere_unoriented(points, vector, i, j, k, l, m)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        in_circle, in_circle_unoriented, in_sphere, in_sphere_unoriented, orient_2d, orient_3d, rng,
+    };
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_slice() {
+        let points = grid_points_2d();
+        let mut rng = rng::SplitMix(11);
+        for _ in 0..200 {
+            let [i, j, k, l] = rng.distinct(points.len());
+            assert_eq!(super::orient_2d(&points, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+            assert_eq!(super::in_circle(&points, i, j, k, l), in_circle(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(
+                super::in_circle_unoriented(&points, i, j, k, l),
+                in_circle_unoriented(&points, |l, i| l[i], i, j, k, l)
+            );
+        }
+
+        let points = grid_points_3d();
+        for _ in 0..200 {
+            let [i, j, k, l, m] = rng.distinct(points.len());
+            assert_eq!(super::orient_3d(&points, i, j, k, l), orient_3d(&points, |l, i| l[i], i, j, k, l));
+            assert_eq!(super::in_sphere(&points, i, j, k, l, m), in_sphere(&points, |l, i| l[i], i, j, k, l, m));
+            assert_eq!(
+                super::in_sphere_unoriented(&points, i, j, k, l, m),
+                in_sphere_unoriented(&points, |l, i| l[i], i, j, k, l, m)
+            );
+        }
+
+        let points = [1.0, 0.0].map(Vector1::new);
+        assert!(super::orient_1d(&points, 0, 1));
+        assert!(!super::orient_1d(&points[..], 1, 0));
+    }
+}
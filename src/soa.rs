@@ -0,0 +1,96 @@
+//! Predicates on points stored as separate coordinate slices.
+//!
+//! These read each coordinate straight from its slice at the indexes given,
+//! so columnar data needn't be gathered into vectors first.
+//! Each predicate gives the same result as its counterpart in the crate root
+//! on the points `(xs[i], ys[i], ...)`.
+//!
+//! # Example
+//!
+//! ```
+//! let xs = [0.0, 1.0, 1.0, 2.0];
+//! let ys = [0.0, 0.0, 1.0, 2.0];
+//! assert!(simplicity::soa::orient_2d(&xs, &ys, 0, 1, 2));
+//! assert!(!simplicity::soa::orient_2d(&xs, &ys, 0, 3, 2));
+//! ```
+
+/// Returns whether the orientation of 3 points in 2-dimensional space
+/// is positive after perturbing them. See [`crate::orient_2d`].
+pub fn orient_2d(xs: &[f64], ys: &[f64], i: usize, j: usize, k: usize) -> bool {
+    simplicity_derive::generate_sos_predicate!{[xs, ys], i, j, k; x, y}
+}
+
+/// Returns whether the orientation of 4 points in 3-dimensional space
+/// is positive after perturbing them. See [`crate::orient_3d`].
+pub fn orient_3d(xs: &[f64], ys: &[f64], zs: &[f64], i: usize, j: usize, k: usize, l: usize) -> bool {
+    simplicity_derive::generate_sos_predicate!{[xs, ys, zs], i, j, k, l; x, y, z}
+}
+
+/// Returns whether the last point is inside the oriented circle that goes through
+/// the first 3 points after perturbing them. See [`crate::in_circle`].
+pub fn in_circle(xs: &[f64], ys: &[f64], i: usize, j: usize, k: usize, l: usize) -> bool {
+    simplicity_derive::generate_sos_predicate!{[xs, ys], i, j, k, l; x, y, x*x + y*y}
+}
+
+/// Returns whether the last point is inside the oriented sphere that goes through
+/// the first 4 points after perturbing them. See [`crate::in_sphere`].
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere(xs: &[f64], ys: &[f64], zs: &[f64], i: usize, j: usize, k: usize, l: usize, m: usize) -> bool {
+    simplicity_derive::generate_sos_predicate!{[xs, ys, zs], i, j, k, l, m; x, y, z, x*x + y*y + z*z}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_soa_in_circle() {
+        let points = grid_points_2d();
+        let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+        let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    assert_eq!(super::orient_2d(&xs, &ys, i, j, k), orient_2d(&points, |l, i| l[i], i, j, k));
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        assert_eq!(
+                            super::in_circle(&xs, &ys, i, j, k, l),
+                            in_circle(&points, |l, i| l[i], i, j, k, l),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_soa_orient_3d() {
+        let points = grid_points_3d();
+        let xs = points.iter().map(|p| p.x).collect::<Vec<_>>();
+        let ys = points.iter().map(|p| p.y).collect::<Vec<_>>();
+        let zs = points.iter().map(|p| p.z).collect::<Vec<_>>();
+        // The cascades sort the indexes, so only the last one needs to take every place
+        for i in 0..points.len() {
+            for j in i + 1..points.len() {
+                for k in j + 1..points.len() {
+                    for l in (0..points.len()).filter(|l| ![i, j, k].contains(l)) {
+                        assert_eq!(
+                            super::orient_3d(&xs, &ys, &zs, i, j, k, l),
+                            orient_3d(&points, |l, i| l[i], i, j, k, l),
+                        );
+                        // The first point from a pseudorandom start that isn't already taken
+                        let m = (i + j + k + l..)
+                            .map(|m| m % points.len())
+                            .find(|m| ![i, j, k, l].contains(m))
+                            .unwrap();
+                        assert_eq!(
+                            super::in_sphere(&xs, &ys, &zs, i, j, k, l, m),
+                            in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+//! Variants of the predicates that reject degenerate input instead of breaking the tie.
+
+use crate::{rg, scale, ErrorReason, PredicateKind, SimplicityError, SosPoint, Vec1, Vec2, Vec3};
+use alloc::{vec, vec::Vec};
+use core::ops::{Index, MulAssign};
+
+/// Evaluates the sign of a determinant of the `dim`-dimensional points at `indexes`,
+/// scaled up if they need it, failing if an index is repeated, a coordinate isn't finite,
+/// or the determinant is 0.
+fn evaluate<Idx: Clone + PartialEq, P: Copy + MulAssign<f64> + Index<usize, Output = f64>>(
+    predicate: PredicateKind,
+    dim: usize,
+    indexes: Vec<Idx>,
+    point: impl Fn(Idx) -> P,
+    det: impl FnOnce(&[P]) -> f64,
+) -> Result<bool, SimplicityError<Idx>>
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let fail = |indexes, reason| Err(SimplicityError { predicate, indexes, reason });
+
+    let mut repeated = vec![];
+    for (n, i) in indexes.iter().enumerate() {
+        if indexes[..n].contains(i) && !repeated.contains(i) {
+            repeated.push(i.clone());
+        }
+    }
+    if !repeated.is_empty() {
+        return fail(repeated, ErrorReason::DuplicateIndex);
+    }
+
+    let points = indexes.iter().cloned().map(point).collect::<Vec<_>>();
+    let non_finite = indexes
+        .iter()
+        .zip(&points)
+        .filter(|(_, p)| (0..dim).any(|c| !p[c].is_finite()))
+        .map(|(i, _)| i.clone())
+        .collect::<Vec<_>>();
+    if !non_finite.is_empty() {
+        return fail(non_finite, ErrorReason::NonFinite);
+    }
+
+    let e = scale::prescale_exponent(&points);
+    let val = det(&points.into_iter().map(|p| scale::scaled(p, e)).collect::<Vec<_>>());
+    if val == 0.0 {
+        fail(indexes, ErrorReason::Degenerate)
+    } else {
+        Ok(val > 0.0)
+    }
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but returns an error with [`ErrorReason::Degenerate`]
+/// if the points are equal.
+pub fn orient_1d_strict<'a, T: ?Sized, Idx: Clone + PartialEq, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    evaluate(PredicateKind::Orient1d, 1, vec![i, j], |i| index_fn(list, i), |p| p[0].x - p[1].x)
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but returns an error with [`ErrorReason::Degenerate`]
+/// if the points are collinear.
+///
+/// Like all the `_strict` predicates, this also fails if an index is repeated
+/// or a coordinate isn't finite.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d_strict, ErrorReason};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(1.0, 0.0),
+///     Vector2::new(1.0, 1.0),
+///     Vector2::new(2.0, 0.0),
+/// ];
+/// assert_eq!(orient_2d_strict(&points, |l, i| l[i], 0, 1, 2), Ok(true));
+/// let err = orient_2d_strict(&points, |l, i| l[i], 0, 1, 3).unwrap_err();
+/// assert_eq!((err.indexes, err.reason), (vec![0, 1, 3], ErrorReason::Degenerate));
+/// let err = orient_2d_strict(&points, |l, i| l[i], 0, 1, 0).unwrap_err();
+/// assert_eq!((err.indexes, err.reason), (vec![0], ErrorReason::DuplicateIndex));
+/// ```
+pub fn orient_2d_strict<'a, T: ?Sized, Idx: Clone + PartialEq, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    evaluate(PredicateKind::Orient2d, 2, vec![i, j, k], |i| index_fn(list, i), |p| rg::orient_2d(p[0], p[1], p[2]))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but returns an error with [`ErrorReason::Degenerate`]
+/// if the points are coplanar.
+pub fn orient_3d_strict<'a, T: ?Sized, Idx: Clone + PartialEq, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    evaluate(PredicateKind::Orient3d, 3, vec![i, j, k, l], |i| index_fn(list, i), |p| {
+        rg::orient_3d(p[0], p[1], p[2], p[3])
+    })
+}
+
+/// Like [`in_circle`](crate::in_circle), but returns an error with [`ErrorReason::Degenerate`]
+/// if the in-circle determinant is 0, such as when the points are cocircular.
+pub fn in_circle_strict<'a, T: ?Sized, Idx: Clone + PartialEq, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    evaluate(PredicateKind::InCircle, 2, vec![i, j, k, l], |i| index_fn(list, i), |p| {
+        rg::in_circle(p[0], p[1], p[2], p[3])
+    })
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but returns an error with [`ErrorReason::Degenerate`]
+/// if the in-sphere determinant is 0, such as when the points are cospherical.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_strict<'a, T: ?Sized, Idx: Clone + PartialEq, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    evaluate(PredicateKind::InSphere, 3, vec![i, j, k, l, m], |i| index_fn(list, i), |p| {
+        rg::in_sphere(p[0], p[1], p[2], p[3], p[4])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, orient_2d};
+    use crate::diagnostics::{in_circle_case, orient_2d_case};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::Vector1;
+
+    #[test]
+    fn test_strict() {
+        let points = grid_points_2d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    let strict = orient_2d_strict(&points, |l, i| l[i], i, j, k);
+                    assert_eq!(strict.is_err(), orient_2d_case(&points, |l, i| l[i], i, j, k) != [3, 3, 3]);
+                    if let Ok(strict) = strict {
+                        assert_eq!(strict, orient_2d(&points, |l, i| l[i], i, j, k));
+                    }
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        let strict = in_circle_strict(&points, |l, i| l[i], i, j, k, l);
+                        assert_eq!(strict.is_err(), in_circle_case(&points, |l, i| l[i], i, j, k, l) != 0);
+                        if let Ok(strict) = strict {
+                            assert_eq!(strict, in_circle(&points, |l, i| l[i], i, j, k, l));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_strict_errors() {
+        let mut points = grid_points_3d();
+        points[1].y = f64::INFINITY;
+        points[3].z = f64::NAN;
+        let err = orient_3d_strict(&points, |l, i| l[i], 0, 1, 2, 3).unwrap_err();
+        assert_eq!(err, SimplicityError { predicate: PredicateKind::Orient3d, indexes: vec![1, 3], reason: ErrorReason::NonFinite });
+        // Repeated indexes are reported before anything else
+        let err = in_sphere_strict(&points, |l, i| l[i], 3, 4, 3, 5, 4).unwrap_err();
+        assert_eq!(err, SimplicityError { predicate: PredicateKind::InSphere, indexes: vec![3, 4], reason: ErrorReason::DuplicateIndex });
+
+        let err = orient_1d_strict(&[0.0, 0.0], |l, i| Vector1::new(l[i]), 0, 1).unwrap_err();
+        assert_eq!(err.to_string(), "orient_1d on [0, 1]: the points are degenerate");
+    }
+}
@@ -0,0 +1,204 @@
+//! Support for testing code built on the predicates, enabled by the `testing` feature.
+//!
+//! The checks take predicates as functions of indexes only, so they work for wrappers
+//! around this crate's predicates with any point storage. Each check enumerates every
+//! tuple of distinct indexes below `n` in every order, so it's meant for small point sets,
+//! such as the grids generated here, which are full of degenerate configurations.
+//!
+//! # Example
+//!
+//! ```
+//! use simplicity::{orient_2d, in_circle, testing};
+//!
+//! let points = testing::grid_2d(3);
+//! testing::check_in_circle(
+//!     points.len(),
+//!     |i, j, k| orient_2d(&points, |l, i| l[i], i, j, k),
+//!     |i, j, k, l| in_circle(&points, |l, i| l[i], i, j, k, l),
+//! ).unwrap();
+//! ```
+//!
+//! The [`axioms`] module checks this crate's own predicates with random point sets instead.
+
+use crate::analysis::for_each_combination;
+use crate::{Vec2, Vec3};
+use std::error::Error;
+use core::fmt::{self, Display, Formatter};
+
+pub mod axioms;
+
+/// An axiom a predicate doesn't satisfy.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Violation {
+    /// The axiom
+    pub axiom: &'static str,
+    /// The indexes, in an order the axiom fails for
+    pub indexes: Vec<usize>,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "axiom \"{}\" fails for indexes {:?}", self.axiom, self.indexes)
+    }
+}
+
+impl Error for Violation {}
+
+/// The points of a `size` × `size` integer grid, each twice,
+/// so configurations with repeated points are enumerated too.
+pub fn grid_2d(size: usize) -> Vec<Vec2> {
+    let points = (0..size * size).map(|i| Vec2::new((i % size) as f64, (i / size) as f64));
+    points.clone().chain(points).collect()
+}
+
+/// The points of a `size` × `size` × `size` integer grid, each twice,
+/// so configurations with repeated points are enumerated too.
+pub fn grid_3d(size: usize) -> Vec<Vec3> {
+    let points = (0..size * size * size)
+        .map(|i| Vec3::new((i % size) as f64, (i / size % size) as f64, (i / size / size) as f64));
+    points.clone().chain(points).collect()
+}
+
+/// All permutations of `0..k`, with whether each is odd.
+fn permutations(k: usize) -> Vec<(Vec<usize>, bool)> {
+    if k == 0 {
+        return vec![(vec![], false)];
+    }
+    permutations(k - 1).into_iter()
+        .flat_map(|(perm, odd)| (0..k).map(move |pos| {
+            // Inserting k - 1 at `pos` moves it past `k - 1 - pos` elements
+            let mut perm = perm.clone();
+            perm.insert(pos, k - 1);
+            (perm, odd != ((k - 1 - pos) % 2 == 1))
+        }))
+        .collect()
+}
+
+/// Checks that swapping any 2 indexes negates a predicate of `k` indexes below `n`.
+fn check_antisymmetry(
+    name: &'static str,
+    n: usize,
+    k: usize,
+    pred: impl Fn(&[usize]) -> bool,
+) -> Result<(), Violation> {
+    let perms = permutations(k);
+    for_each_combination(n, k, |combo| {
+        let base = pred(combo);
+        for (perm, odd) in &perms {
+            let indexes = perm.iter().map(|p| combo[*p]).collect::<Vec<_>>();
+            if pred(&indexes) != (base != *odd) {
+                return Err(Violation { axiom: name, indexes });
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Checks that the in-hypersphere predicate, made independent of the orientation
+/// of the first points, doesn't depend on their order.
+fn check_unoriented(
+    name: &'static str,
+    n: usize,
+    k: usize,
+    orient: impl Fn(&[usize]) -> bool,
+    in_hypersphere: impl Fn(&[usize]) -> bool,
+) -> Result<(), Violation> {
+    let perms = permutations(k - 1);
+    let unoriented = |indexes: &[usize]| orient(&indexes[..k - 1]) == in_hypersphere(indexes);
+    for_each_combination(n, k, |combo| {
+        for last in 0..k {
+            let rest = combo.iter().enumerate().filter(|(i, _)| *i != last).map(|(_, c)| *c).collect::<Vec<_>>();
+            let mut base = rest.clone();
+            base.push(combo[last]);
+            let base = unoriented(&base);
+            for (perm, _) in &perms {
+                let mut indexes = perm.iter().map(|p| rest[*p]).collect::<Vec<_>>();
+                indexes.push(combo[last]);
+                if unoriented(&indexes) != base {
+                    return Err(Violation { axiom: name, indexes });
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Checks that swapping any 2 points negates `orient`, a 2D orientation predicate
+/// on indexes below `n`.
+pub fn check_orient_2d(n: usize, orient: impl Fn(usize, usize, usize) -> bool) -> Result<(), Violation> {
+    check_antisymmetry("swapping 2 points negates orient_2d", n, 3, |i| orient(i[0], i[1], i[2]))
+}
+
+/// Checks that swapping any 2 points negates `orient`, a 3D orientation predicate
+/// on indexes below `n`.
+pub fn check_orient_3d(n: usize, orient: impl Fn(usize, usize, usize, usize) -> bool) -> Result<(), Violation> {
+    check_antisymmetry("swapping 2 points negates orient_3d", n, 4, |i| orient(i[0], i[1], i[2], i[3]))
+}
+
+/// Checks `orient` with [`check_orient_2d`], that swapping any 2 points negates `in_circle`,
+/// and that whether the last point is in the circle, taking the orientation of the first 3 into account,
+/// doesn't depend on their order.
+pub fn check_in_circle(
+    n: usize,
+    orient: impl Fn(usize, usize, usize) -> bool,
+    in_circle: impl Fn(usize, usize, usize, usize) -> bool,
+) -> Result<(), Violation> {
+    check_orient_2d(n, &orient)?;
+    check_antisymmetry("swapping 2 points negates in_circle", n, 4, |i| in_circle(i[0], i[1], i[2], i[3]))?;
+    check_unoriented(
+        "in_circle is consistent with orient_2d",
+        n,
+        4,
+        |i| orient(i[0], i[1], i[2]),
+        |i| in_circle(i[0], i[1], i[2], i[3]),
+    )
+}
+
+/// Checks `orient` with [`check_orient_3d`], that swapping any 2 points negates `in_sphere`,
+/// and that whether the last point is in the sphere, taking the orientation of the first 4 into account,
+/// doesn't depend on their order.
+pub fn check_in_sphere(
+    n: usize,
+    orient: impl Fn(usize, usize, usize, usize) -> bool,
+    in_sphere: impl Fn(usize, usize, usize, usize, usize) -> bool,
+) -> Result<(), Violation> {
+    check_orient_3d(n, &orient)?;
+    check_antisymmetry("swapping 2 points negates in_sphere", n, 5, |i| in_sphere(i[0], i[1], i[2], i[3], i[4]))?;
+    check_unoriented(
+        "in_sphere is consistent with orient_3d",
+        n,
+        5,
+        |i| orient(i[0], i[1], i[2], i[3]),
+        |i| in_sphere(i[0], i[1], i[2], i[3], i[4]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, in_sphere, orient_2d, orient_3d};
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_grid_axioms() {
+        let points = super::grid_2d(3);
+        super::check_in_circle(
+            points.len(),
+            |i, j, k| orient_2d(&points, |l, i| l[i], i, j, k),
+            |i, j, k, l| in_circle(&points, |l, i| l[i], i, j, k, l),
+        ).unwrap();
+
+        // Only some of the repeated points, to keep it fast
+        let points = super::grid_3d(2);
+        super::check_in_sphere(
+            11,
+            |i, j, k, l| orient_3d(&points, |l, i| l[i], i, j, k, l),
+            |i, j, k, l, m| in_sphere(&points, |l, i| l[i], i, j, k, l, m),
+        ).unwrap();
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_proptest_axioms() {
+        super::axioms::check_all(&proptest::test_runner::Config::with_cases(256));
+    }
+}
@@ -0,0 +1,162 @@
+//! Property-based tests of the axioms of simulation of simplicity, using `proptest`.
+//!
+//! The strategies generate small point sets with integer coordinates in \[-2, 2\],
+//! so most of them are degenerate, along with a shuffled list of their indexes.
+//! The checks panic with the failing, shrunk input if an axiom fails.
+//!
+//! # Example
+//!
+//! ```
+//! use simplicity::testing::axioms;
+//! use proptest::test_runner::Config;
+//!
+//! axioms::check_all(&Config::with_cases(64));
+//! ```
+
+use crate::{Vec1, Vec2, Vec3};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::test_runner::{Config, TestCaseResult, TestRunner};
+
+/// A point set with many degeneracies, and a shuffled list of its indexes.
+pub type Case<P> = (Vec<P>, Vec<usize>);
+
+fn coord() -> impl Strategy<Value = f64> + Clone {
+    (-2i32..=2).prop_map(f64::from)
+}
+
+fn with_indexes<P: Clone + std::fmt::Debug>(point: impl Strategy<Value = P> + Clone) -> impl Strategy<Value = Case<P>> {
+    (6usize..12).prop_flat_map(move |n| (vec(point.clone(), n), Just((0..n).collect::<Vec<_>>()).prop_shuffle()))
+}
+
+/// 1D point sets and shuffled indexes.
+pub fn points_1d() -> impl Strategy<Value = Case<Vec1>> {
+    with_indexes(coord().prop_map(Vec1::new))
+}
+
+/// 2D point sets and shuffled indexes.
+pub fn points_2d() -> impl Strategy<Value = Case<Vec2>> {
+    with_indexes((coord(), coord()).prop_map(|(x, y)| Vec2::new(x, y)))
+}
+
+/// 3D point sets and shuffled indexes.
+pub fn points_3d() -> impl Strategy<Value = Case<Vec3>> {
+    with_indexes((coord(), coord(), coord()).prop_map(|(x, y, z)| Vec3::new(x, y, z)))
+}
+
+fn run<P: Clone + std::fmt::Debug>(
+    config: &Config,
+    strategy: impl Strategy<Value = Case<P>>,
+    test: impl Fn(&[P], &[usize]) -> TestCaseResult,
+) {
+    let mut runner = TestRunner::new(config.clone());
+    if let Err(err) = runner.run(&strategy, |(points, indexes)| test(&points, &indexes)) {
+        panic!("{}", err);
+    }
+}
+
+/// Checks that swapping 2 points negates each predicate and rotating the points of
+/// an orientation predicate preserves it when there's an odd number of them.
+pub fn check_permutation_parity(config: &Config) {
+    run(config, points_2d(), |p, ix| {
+        let [i, j, k, l] = [ix[0], ix[1], ix[2], ix[3]];
+        let orient = crate::orient_2d(p, |l, i| l[i], i, j, k);
+        prop_assert_ne!(orient, crate::orient_2d(p, |l, i| l[i], j, i, k));
+        prop_assert_eq!(orient, crate::orient_2d(p, |l, i| l[i], j, k, i));
+        let inside = crate::in_circle(p, |l, i| l[i], i, j, k, l);
+        prop_assert_ne!(inside, crate::in_circle(p, |l, i| l[i], i, j, l, k));
+        prop_assert_ne!(inside, crate::in_circle(p, |l, i| l[i], k, j, i, l));
+        Ok(())
+    });
+    run(config, points_3d(), |p, ix| {
+        let [i, j, k, l, m] = [ix[0], ix[1], ix[2], ix[3], ix[4]];
+        let orient = crate::orient_3d(p, |l, i| l[i], i, j, k, l);
+        prop_assert_ne!(orient, crate::orient_3d(p, |l, i| l[i], j, i, k, l));
+        prop_assert_ne!(orient, crate::orient_3d(p, |l, i| l[i], i, j, l, k));
+        let inside = crate::in_sphere(p, |l, i| l[i], i, j, k, l, m);
+        prop_assert_ne!(inside, crate::in_sphere(p, |l, i| l[i], i, j, k, m, l));
+        prop_assert_eq!(inside, crate::in_sphere(p, |l, i| l[i], j, k, l, m, i));
+        Ok(())
+    });
+}
+
+/// Checks that `orient_1d` is a strict total order: antisymmetric and transitive.
+pub fn check_orient_1d_order(config: &Config) {
+    run(config, points_1d(), |p, ix| {
+        let [a, b, c] = [ix[0], ix[1], ix[2]];
+        let gt = |i, j| crate::orient_1d(p, |l, i| l[i], i, j);
+        prop_assert_ne!(gt(a, b), gt(b, a));
+        if gt(a, b) && gt(b, c) {
+            prop_assert!(gt(a, c));
+        }
+        Ok(())
+    });
+}
+
+/// Checks that the in-circle and in-sphere predicates agree with the orientation predicates:
+/// whether the last point is inside, ignoring orientation, flips when swapping
+/// the last 2 points exactly when the orientation of the first points does.
+pub fn check_in_hypersphere_orientation(config: &Config) {
+    run(config, points_2d(), |p, ix| {
+        let [i, j, k, l] = [ix[0], ix[1], ix[2], ix[3]];
+        prop_assert_eq!(
+            crate::in_circle_unoriented(p, |l, i| l[i], i, j, k, l)
+                == crate::in_circle_unoriented(p, |l, i| l[i], i, j, l, k),
+            crate::orient_2d(p, |l, i| l[i], i, j, k) != crate::orient_2d(p, |l, i| l[i], i, j, l),
+        );
+        Ok(())
+    });
+    run(config, points_3d(), |p, ix| {
+        let [i, j, k, l, m] = [ix[0], ix[1], ix[2], ix[3], ix[4]];
+        prop_assert_eq!(
+            crate::in_sphere_unoriented(p, |l, i| l[i], i, j, k, l, m)
+                == crate::in_sphere_unoriented(p, |l, i| l[i], i, j, k, m, l),
+            crate::orient_3d(p, |l, i| l[i], i, j, k, l) != crate::orient_3d(p, |l, i| l[i], i, j, k, m),
+        );
+        Ok(())
+    });
+}
+
+/// Checks that relabeling the points without changing the order of their indexes,
+/// here by interleaving other points, doesn't change any predicate.
+pub fn check_relabeling(config: &Config) {
+    fn relabel<P: Clone>(points: &[P], filler: &P) -> (Vec<P>, impl Fn(usize) -> usize) {
+        let relabeled = points.iter().flat_map(|p| vec![filler.clone(), p.clone()]).collect();
+        (relabeled, |i| 2 * i + 1)
+    }
+
+    run(config, points_2d(), |p, ix| {
+        let (q, f) = relabel(p, &Vec2::zeros());
+        let [i, j, k, l] = [ix[0], ix[1], ix[2], ix[3]];
+        prop_assert_eq!(
+            crate::orient_2d(p, |l, i| l[i], i, j, k),
+            crate::orient_2d(&q, |l, i| l[i], f(i), f(j), f(k)),
+        );
+        prop_assert_eq!(
+            crate::in_circle(p, |l, i| l[i], i, j, k, l),
+            crate::in_circle(&q, |l, i| l[i], f(i), f(j), f(k), f(l)),
+        );
+        Ok(())
+    });
+    run(config, points_3d(), |p, ix| {
+        let (q, f) = relabel(p, &Vec3::zeros());
+        let [i, j, k, l, m] = [ix[0], ix[1], ix[2], ix[3], ix[4]];
+        prop_assert_eq!(
+            crate::orient_3d(p, |l, i| l[i], i, j, k, l),
+            crate::orient_3d(&q, |l, i| l[i], f(i), f(j), f(k), f(l)),
+        );
+        prop_assert_eq!(
+            crate::in_sphere(p, |l, i| l[i], i, j, k, l, m),
+            crate::in_sphere(&q, |l, i| l[i], f(i), f(j), f(k), f(l), f(m)),
+        );
+        Ok(())
+    });
+}
+
+/// Runs all the checks.
+pub fn check_all(config: &Config) {
+    check_permutation_parity(config);
+    check_orient_1d_order(config);
+    check_in_hypersphere_orientation(config);
+    check_relabeling(config);
+}
@@ -0,0 +1,98 @@
+//! `tracing` events for predicates whose sign is decided by the ε-cascade
+//! instead of the unperturbed determinant, enabled by the `tracing` feature.
+//!
+//! The events are at the `DEBUG` level. The case is only calculated
+//! if a subscriber is interested, and it's calculated separately from the predicate.
+//! Indexes aren't required to implement `Debug`, so the points are recorded instead,
+//! in the order they were passed, along with the rank of each index among them,
+//! which is what the perturbation depends on.
+
+use crate::{diagnostics, Vec2, Vec3};
+use tracing::Level;
+
+const MESSAGE: &str = "degenerate input; sign decided by the ε-cascade";
+
+/// The rank of each index among `indexes`, which are distinct. The smallest,
+/// which is perturbed the most, has rank 0.
+pub(crate) fn ranks<Idx: Ord, const N: usize>(indexes: [Idx; N]) -> [usize; N] {
+    let mut ranks = [0; N];
+    for (rank, i) in ranks.iter_mut().zip(&indexes) {
+        *rank = indexes.iter().filter(|j| *j < i).count();
+    }
+    ranks
+}
+
+pub(crate) fn orient_2d<'l, T: ?Sized, Idx: Ord + Copy>(
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) {
+    if tracing::enabled!(Level::DEBUG) {
+        let case = diagnostics::orient_2d_case(list, |_: &T, i| index_fn(list, i), i, j, k);
+        if case != [3, 3, 3] {
+            let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k)];
+            let ranks = ranks([i, j, k]);
+            tracing::debug!(predicate = "orient_2d", ?points, ?ranks, ?case, "{}", MESSAGE);
+        }
+    }
+}
+
+pub(crate) fn orient_3d<'l, T: ?Sized, Idx: Ord + Copy>(
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) {
+    if tracing::enabled!(Level::DEBUG) {
+        let case = diagnostics::orient_3d_case(list, |_: &T, i| index_fn(list, i), i, j, k, l);
+        if case != [4, 4, 4, 4] {
+            let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+            let ranks = ranks([i, j, k, l]);
+            tracing::debug!(predicate = "orient_3d", ?points, ?ranks, ?case, "{}", MESSAGE);
+        }
+    }
+}
+
+pub(crate) fn in_circle<'l, T: ?Sized, Idx: Ord + Copy>(
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> Vec2,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) {
+    if tracing::enabled!(Level::DEBUG) {
+        let case = diagnostics::in_circle_case(list, |_: &T, i| index_fn(list, i), i, j, k, l);
+        if case != 0 {
+            let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+            let ranks = ranks([i, j, k, l]);
+            tracing::debug!(predicate = "in_circle", ?points, ?ranks, case, "{}", MESSAGE);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn in_sphere<'l, T: ?Sized, Idx: Ord + Copy>(
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> Vec3,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) {
+    if tracing::enabled!(Level::DEBUG) {
+        let case = diagnostics::in_sphere_case(list, |_: &T, i| index_fn(list, i), i, j, k, l, m);
+        if case != 0 {
+            let points = [
+                index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l), index_fn(list, m),
+            ];
+            let ranks = ranks([i, j, k, l, m]);
+            tracing::debug!(predicate = "in_sphere", ?points, ?ranks, case, "{}", MESSAGE);
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Recording predicate calls into a transcript, and replaying it.
+//!
+//! A [`Recorder`] has the same predicates as the crate root, and records
+//! each call, with its indexes and result, into a [`Transcript`].
+//! Transcripts convert to and from a compact text format with one call per line,
+//! so a failing run can be saved and replayed later, or against another version
+//! of this crate, to find where the results differ.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, transcript::{Recorder, Transcript}};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(0.0, 0.0),
+//!     Vector2::new(1.0, 0.0),
+//!     Vector2::new(2.0, 0.0),
+//!     Vector2::new(0.0, 1.0),
+//! ];
+//! let recorder = Recorder::new();
+//! recorder.orient_2d(&points, |l, i| l[i], 0, 1, 2);
+//! recorder.in_circle(&points, |l, i| l[i], 0, 1, 3, 2);
+//!
+//! let text = recorder.into_transcript().to_string();
+//! assert_eq!(text, "orient_2d 0 1 2 1\nin_circle 0 1 3 2 0\n");
+//!
+//! let transcript = text.parse::<Transcript>().unwrap();
+//! assert!(transcript.replay_2d(&points, |l, i| l[i]).is_empty());
+//! ```
+
+use crate::{Vec1, Vec2, Vec3};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+
+/// A predicate that can be recorded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Predicate {
+    /// [`orient_1d`](crate::orient_1d)
+    Orient1d,
+    /// [`orient_2d`](crate::orient_2d)
+    Orient2d,
+    /// [`orient_3d`](crate::orient_3d)
+    Orient3d,
+    /// [`in_circle`](crate::in_circle)
+    InCircle,
+    /// [`in_circle_unoriented`](crate::in_circle_unoriented)
+    InCircleUnoriented,
+    /// [`in_sphere`](crate::in_sphere)
+    InSphere,
+    /// [`in_sphere_unoriented`](crate::in_sphere_unoriented)
+    InSphereUnoriented,
+}
+
+impl Predicate {
+    const ALL: [Predicate; 7] = [
+        Predicate::Orient1d,
+        Predicate::Orient2d,
+        Predicate::Orient3d,
+        Predicate::InCircle,
+        Predicate::InCircleUnoriented,
+        Predicate::InSphere,
+        Predicate::InSphereUnoriented,
+    ];
+
+    /// The name of the predicate's function.
+    pub fn name(self) -> &'static str {
+        match self {
+            Predicate::Orient1d => "orient_1d",
+            Predicate::Orient2d => "orient_2d",
+            Predicate::Orient3d => "orient_3d",
+            Predicate::InCircle => "in_circle",
+            Predicate::InCircleUnoriented => "in_circle_unoriented",
+            Predicate::InSphere => "in_sphere",
+            Predicate::InSphereUnoriented => "in_sphere_unoriented",
+        }
+    }
+
+    /// The number of points the predicate takes.
+    pub fn num_points(self) -> usize {
+        match self {
+            Predicate::Orient1d => 2,
+            Predicate::Orient2d => 3,
+            Predicate::Orient3d | Predicate::InCircle | Predicate::InCircleUnoriented => 4,
+            Predicate::InSphere | Predicate::InSphereUnoriented => 5,
+        }
+    }
+
+    /// The dimension of the points the predicate takes.
+    pub fn dim(self) -> usize {
+        match self {
+            Predicate::Orient1d => 1,
+            Predicate::Orient2d | Predicate::InCircle | Predicate::InCircleUnoriented => 2,
+            Predicate::Orient3d | Predicate::InSphere | Predicate::InSphereUnoriented => 3,
+        }
+    }
+}
+
+/// A recorded predicate call.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Call {
+    /// The predicate that was called
+    pub predicate: Predicate,
+    /// The indexes passed to it, in order
+    pub indexes: Vec<usize>,
+    /// What it returned
+    pub result: bool,
+}
+
+/// A list of recorded predicate calls, in the order they were made.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Transcript {
+    /// The calls, oldest first
+    pub calls: Vec<Call>,
+}
+
+/// A replayed call whose result differs from the recorded one.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Mismatch {
+    /// The index of the call in the transcript
+    pub index: usize,
+    /// The call, with its recorded result
+    pub call: Call,
+}
+
+impl Transcript {
+    /// Creates an empty transcript.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn replay(&self, dim: usize, mut eval: impl FnMut(Predicate, &[usize]) -> bool) -> Vec<Mismatch> {
+        self.calls.iter().enumerate()
+            .filter(|(_, call)| call.predicate.dim() == dim)
+            .filter(|(_, call)| eval(call.predicate, &call.indexes) != call.result)
+            .map(|(index, call)| Mismatch { index, call: call.clone() })
+            .collect()
+    }
+
+    /// Re-executes the 1-dimensional calls against some points,
+    /// returning the calls whose results differ from the recorded ones.
+    pub fn replay_1d<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec1) -> Vec<Mismatch> {
+        self.replay(1, |_, i| crate::orient_1d(list, &index_fn, i[0], i[1]))
+    }
+
+    /// Re-executes the 2-dimensional calls against some points,
+    /// returning the calls whose results differ from the recorded ones.
+    pub fn replay_2d<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec2 + Clone) -> Vec<Mismatch> {
+        self.replay(2, |predicate, i| match predicate {
+            Predicate::Orient2d => crate::orient_2d(list, &index_fn, i[0], i[1], i[2]),
+            Predicate::InCircle => crate::in_circle(list, &index_fn, i[0], i[1], i[2], i[3]),
+            Predicate::InCircleUnoriented => crate::in_circle_unoriented(list, &index_fn, i[0], i[1], i[2], i[3]),
+            _ => unreachable!(),
+        })
+    }
+
+    /// Re-executes the 3-dimensional calls against some points,
+    /// returning the calls whose results differ from the recorded ones.
+    pub fn replay_3d<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec3 + Clone) -> Vec<Mismatch> {
+        self.replay(3, |predicate, i| match predicate {
+            Predicate::Orient3d => crate::orient_3d(list, &index_fn, i[0], i[1], i[2], i[3]),
+            Predicate::InSphere => crate::in_sphere(list, &index_fn, i[0], i[1], i[2], i[3], i[4]),
+            Predicate::InSphereUnoriented => crate::in_sphere_unoriented(list, &index_fn, i[0], i[1], i[2], i[3], i[4]),
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// One call per line: the predicate's name, the indexes, and the result as `1` or `0`,
+/// separated by spaces.
+impl Display for Transcript {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for call in &self.calls {
+            write!(f, "{}", call.predicate.name())?;
+            for index in &call.indexes {
+                write!(f, " {}", index)?;
+            }
+            writeln!(f, " {}", call.result as u8)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error from parsing a transcript.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseTranscriptError {
+    /// The line the error is on, starting from 1
+    pub line: usize,
+}
+
+impl Display for ParseTranscriptError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "invalid predicate call on line {}", self.line)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseTranscriptError {}
+
+impl FromStr for Transcript {
+    type Err = ParseTranscriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let calls = s.lines().enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let err = ParseTranscriptError { line: i + 1 };
+                let mut words = line.split_whitespace();
+                let name = words.next().ok_or_else(|| err.clone())?;
+                let predicate = *Predicate::ALL.iter().find(|p| p.name() == name).ok_or_else(|| err.clone())?;
+                let nums = words.map(|w| w.parse::<usize>()).collect::<Result<Vec<_>, _>>().map_err(|_| err.clone())?;
+
+                match nums.split_last() {
+                    Some((&result, indexes)) if indexes.len() == predicate.num_points() && result <= 1 => Ok(Call {
+                        predicate,
+                        indexes: indexes.to_vec(),
+                        result: result == 1,
+                    }),
+                    _ => Err(err),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Transcript { calls })
+    }
+}
+
+/// Calls predicates and records the calls into a transcript.
+///
+/// The predicates take `&self`, so a recorder can be shared by everything
+/// that needs to call them.
+#[derive(Debug, Default)]
+pub struct Recorder {
+    transcript: RefCell<Transcript>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, predicate: Predicate, indexes: &[usize], result: bool) -> bool {
+        self.transcript.borrow_mut().calls.push(Call { predicate, indexes: indexes.to_vec(), result });
+        result
+    }
+
+    /// Returns the transcript recorded so far.
+    pub fn transcript(&self) -> Transcript {
+        self.transcript.borrow().clone()
+    }
+
+    /// Returns the transcript, consuming the recorder.
+    pub fn into_transcript(self) -> Transcript {
+        self.transcript.into_inner()
+    }
+
+    /// Calls and records [`orient_1d`](crate::orient_1d).
+    pub fn orient_1d<T: ?Sized>(&self, list: &T, index_fn: impl Fn(&T, usize) -> Vec1, i: usize, j: usize) -> bool {
+        self.record(Predicate::Orient1d, &[i, j], crate::orient_1d(list, index_fn, i, j))
+    }
+
+    /// Calls and records [`orient_2d`](crate::orient_2d).
+    pub fn orient_2d<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec2,
+        i: usize,
+        j: usize,
+        k: usize,
+    ) -> bool {
+        self.record(Predicate::Orient2d, &[i, j, k], crate::orient_2d(list, index_fn, i, j, k))
+    }
+
+    /// Calls and records [`orient_3d`](crate::orient_3d).
+    pub fn orient_3d<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec3,
+        i: usize,
+        j: usize,
+        k: usize,
+        l: usize,
+    ) -> bool {
+        self.record(Predicate::Orient3d, &[i, j, k, l], crate::orient_3d(list, index_fn, i, j, k, l))
+    }
+
+    /// Calls and records [`in_circle`](crate::in_circle).
+    pub fn in_circle<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+        i: usize,
+        j: usize,
+        k: usize,
+        l: usize,
+    ) -> bool {
+        self.record(Predicate::InCircle, &[i, j, k, l], crate::in_circle(list, index_fn, i, j, k, l))
+    }
+
+    /// Calls and records [`in_circle_unoriented`](crate::in_circle_unoriented).
+    pub fn in_circle_unoriented<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec2 + Clone,
+        i: usize,
+        j: usize,
+        k: usize,
+        l: usize,
+    ) -> bool {
+        let result = crate::in_circle_unoriented(list, index_fn, i, j, k, l);
+        self.record(Predicate::InCircleUnoriented, &[i, j, k, l], result)
+    }
+
+    /// Calls and records [`in_sphere`](crate::in_sphere).
+    #[allow(clippy::too_many_arguments)]
+    pub fn in_sphere<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+        i: usize,
+        j: usize,
+        k: usize,
+        l: usize,
+        m: usize,
+    ) -> bool {
+        self.record(Predicate::InSphere, &[i, j, k, l, m], crate::in_sphere(list, index_fn, i, j, k, l, m))
+    }
+
+    /// Calls and records [`in_sphere_unoriented`](crate::in_sphere_unoriented).
+    #[allow(clippy::too_many_arguments)]
+    pub fn in_sphere_unoriented<T: ?Sized>(
+        &self,
+        list: &T,
+        index_fn: impl Fn(&T, usize) -> Vec3 + Clone,
+        i: usize,
+        j: usize,
+        k: usize,
+        l: usize,
+        m: usize,
+    ) -> bool {
+        let result = crate::in_sphere_unoriented(list, index_fn, i, j, k, l, m);
+        self.record(Predicate::InSphereUnoriented, &[i, j, k, l, m], result)
+    }
+}
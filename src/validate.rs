@@ -0,0 +1,84 @@
+//! Checks of the arguments of every predicate call, enabled by the `validate` feature
+//! in debug builds.
+//!
+//! The predicates assume their indexes are distinct and their points finite, and give
+//! meaningless results otherwise, which tend to surface far from the call that caused them.
+//! These panic at the call instead. Indexes aren't required to implement `Debug`,
+//! so the messages name the parameters, like the `tracing` events record the points.
+
+use crate::transcript::Predicate;
+use core::any::Any;
+use core::fmt::Debug;
+use core::ops::Index;
+use std::panic::{self, AssertUnwindSafe};
+
+const NAMES: [&str; 5] = ["i", "j", "k", "l", "m"];
+
+/// The message of a panic's payload, if it has one.
+fn message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s
+    } else {
+        "no message"
+    }
+}
+
+/// Panics if an index is repeated, the indexing function panics, or a point isn't finite.
+pub(crate) fn check<'l, T: ?Sized, Idx: Ord + Copy, P: Debug + Index<usize, Output = f64>>(
+    predicate: Predicate,
+    dim: usize,
+    list: &'l T,
+    index_fn: impl Fn(&'l T, Idx) -> P,
+    indexes: &[Idx],
+) {
+    // Only formatted when a check fails, so that passing calls don't allocate
+    let call = || format!("{}({})", predicate.name(), NAMES[..indexes.len()].join(", "));
+
+    for (b, ib) in indexes.iter().enumerate() {
+        if let Some(a) = indexes[..b].iter().position(|ia| ia == ib) {
+            panic!("{}: {} and {} are the same index", call(), NAMES[a], NAMES[b]);
+        }
+    }
+
+    for (n, i) in indexes.iter().enumerate() {
+        let point = panic::catch_unwind(AssertUnwindSafe(|| index_fn(list, *i))).unwrap_or_else(|payload| {
+            panic!("{}: indexing with {} panicked, so it may be out of range: {}", call(), NAMES[n], message(&*payload))
+        });
+        if (0..dim).any(|c| !point[c].is_finite()) {
+            panic!("{}: the point at {} is {:?}, which isn't finite", call(), NAMES[n], point);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{in_circle, orient_2d};
+    use crate::tests::grid_points_2d;
+
+    #[cfg(all(feature = "validate", debug_assertions))]
+    #[test]
+    fn test_validate() {
+        let panic_message = |f: &dyn Fn()| {
+            let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+            payload.downcast::<String>().map(|s| *s).unwrap()
+        };
+        let mut points = grid_points_2d();
+        points[5].y = f64::NAN;
+
+        assert_eq!(
+            panic_message(&|| { orient_2d(&points, |l, i| l[i], 0, 1, 0); }),
+            "orient_2d(i, j, k): i and k are the same index"
+        );
+        assert_eq!(
+            panic_message(&|| { in_circle(&points, |l, i| l[i], 0, 1, 2, 20); }),
+            "in_circle(i, j, k, l): indexing with l panicked, so it may be out of range: \
+             index out of bounds: the len is 11 but the index is 20"
+        );
+        assert!(panic_message(&|| { orient_2d(&points, |l, i| l[i], 0, 5, 2); })
+            .starts_with("orient_2d(i, j, k): the point at j is "));
+        // Valid calls go through
+        orient_2d(&points, |l, i| l[i], 0, 1, 3);
+    }
+}
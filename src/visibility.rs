@@ -0,0 +1,138 @@
+//! Visibility graphs of polygonal obstacles.
+//!
+//! Two points see each other if the segment between them doesn't cross an edge of an obstacle
+//! and doesn't go through the inside of one. Crossings are decided by
+//! [`cdt::segments_cross`](crate::cdt::segments_cross), and a segment from a vertex of an
+//! obstacle goes inside it if it leaves the vertex between its 2 edges, which is decided by
+//! [`orient_2d`](crate::orient_2d). Because of the perturbation, a segment through a chain of
+//! collinear vertices passes each of them on one side or the other, the same way every time,
+//! so shortest paths through the graph never squeeze through gaps of width 0.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, visibility};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     Vector2::new(1.0, 1.0),
+//!     Vector2::new(2.0, 1.0),
+//!     Vector2::new(2.0, 2.0),
+//!     Vector2::new(1.0, 2.0),
+//!     Vector2::new(0.0, 1.5),
+//!     Vector2::new(3.0, 1.5),
+//! ];
+//! let graph = visibility::visibility_graph(&points, |l, i| l[i], &[vec![0, 1, 2, 3]], &[4, 5]);
+//! // The square is in the way, and its diagonals go through it
+//! assert!(!graph.contains(&[4, 5]));
+//! assert!(!graph.contains(&[0, 2]));
+//! assert!(graph.contains(&[0, 1]));
+//! assert!(graph.contains(&[0, 4]));
+//! ```
+
+use crate::Vec2;
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// Computes the visibility graph of some obstacles and some free points after perturbing the points.
+///
+/// Takes a list of all the points, an indexing function, the obstacles as polygons of indexes
+/// into the list in either orientation, and the indexes of other points, such as the start and
+/// goal of a path. An obstacle with 2 vertices is a wall. No index can appear twice.
+/// Returns the pairs of vertices that see each other, with the smaller index first, sorted.
+/// The edges of the obstacles are included. This tests every pair against every edge,
+/// so it takes O(n³) tests.
+pub fn visibility_graph<T: ?Sized>(
+    list: &T,
+    index_fn: impl Fn(&T, usize) -> Vec2,
+    obstacles: &[Vec<usize>],
+    free: &[usize],
+) -> Vec<[usize; 2]> {
+    let orient = |i, j, k| crate::orient_2d(list, &index_fn, i, j, k);
+    let obstacles = obstacles
+        .iter()
+        .map(|obstacle| {
+            let mut obstacle = obstacle.clone();
+            if obstacle.len() >= 3 && !crate::polygon::is_counterclockwise(list, &index_fn, &obstacle) {
+                obstacle.reverse();
+            }
+            obstacle
+        })
+        .collect::<Vec<_>>();
+
+    // The neighbors of each vertex of an obstacle, previous first
+    let mut neighbors = BTreeMap::new();
+    let mut edges = vec![];
+    for obstacle in &obstacles {
+        let n = obstacle.len();
+        for i in 0..n {
+            if n >= 3 {
+                neighbors.insert(obstacle[i], [obstacle[(i + n - 1) % n], obstacle[(i + 1) % n]]);
+            }
+            if n >= 3 || i == 0 {
+                edges.push([obstacle[i], obstacle[(i + 1) % n]]);
+            }
+        }
+    }
+
+    // Whether the segment from u to v leaves u inside the obstacle u is on
+    let inside = |u: usize, v: usize| {
+        neighbors.get(&u).is_some_and(|&[p, n]| {
+            if v == p || v == n {
+                false
+            } else if orient(p, u, n) {
+                orient(u, n, v) && orient(p, u, v)
+            } else {
+                orient(u, n, v) || orient(p, u, v)
+            }
+        })
+    };
+
+    let mut vertices = obstacles.iter().flatten().chain(free).copied().collect::<Vec<_>>();
+    vertices.sort_unstable();
+    let mut graph = vec![];
+    for (k, u) in vertices.iter().enumerate() {
+        for v in &vertices[k + 1..] {
+            let blocked = inside(*u, *v)
+                || inside(*v, *u)
+                || edges.iter().any(|[a, b]| crate::cdt::segments_cross(list, &index_fn, *u, *v, *a, *b));
+            if !blocked {
+                graph.push([*u, *v]);
+            }
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Vector2;
+
+    #[test]
+    fn test_visibility_graph() {
+        // 2 squares with sides on the same lines as each other and some free points, and a wall
+        let points = [(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0), (3.0, 1.0), (4.0, 1.0), (4.0, 2.0), (3.0, 2.0)]
+            .iter()
+            .chain(&[(0.0, 0.0), (5.0, 3.0), (0.0, 1.0), (5.0, 1.0), (2.0, 3.0), (3.0, 3.0), (2.5, 4.0), (2.5, 2.5)])
+            .map(|(x, y)| Vector2::new(*x, *y))
+            .collect::<Vec<_>>();
+        let obstacles = vec![vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![12, 13]];
+        let free = [8, 9, 10, 11, 14, 15];
+        let graph = super::visibility_graph(&points, |l, i| l[i], &obstacles, &free);
+
+        // Obstacle edges, but not diagonals
+        for edge in [[0, 1], [1, 2], [2, 3], [0, 3], [12, 13]] {
+            assert!(graph.contains(&edge));
+        }
+        assert!(!graph.contains(&[0, 2]) && !graph.contains(&[1, 3]));
+        // Through a square, and through the wall
+        assert!(!graph.contains(&[8, 9]));
+        assert!(!graph.contains(&[14, 15]));
+        // Along a side from outside the square, which stays outside whichever way it's perturbed
+        assert!(graph.contains(&[0, 10]));
+        assert!(graph.contains(&[1, 4]));
+
+        // The same either way around
+        let reversed = obstacles.iter().map(|o| o.iter().rev().copied().collect()).collect::<Vec<_>>();
+        assert_eq!(super::visibility_graph(&points, |l, i| l[i], &reversed, &free), graph);
+    }
+}
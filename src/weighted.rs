@@ -0,0 +1,729 @@
+//! Power tests on weighted points, for regular triangulations.
+//!
+//! A weighted point (**p**, *w*) lifts to |**p**|² - *w*, and the power test of a weighted point
+//! against some others measures whether it's below the hyperplane through their lifts, the same
+//! way the in-circle test does with weights of 0. Regular triangulations use it in place of
+//! the in-circle test.
+//!
+//! When the points are fixed but the weights collide, perturbing the coordinates would move
+//! the points. These perturb only the weights instead, with the weight of the point with the
+//! *r*-th smallest index increased by ε^(2^*r*). The power test is linear in each lift,
+//! so the first point in index order whose lift has a nonzero coefficient decides it, and that
+//! coefficient is the orientation of the other points, with that point replaced by the last one.
+//! The coordinates are never perturbed, so if all the points are collinear, or coplanar in 3D,
+//! no coefficient is nonzero, and these fail with [`ErrorReason::Degenerate`].
+//!
+//! Weighted alpha complexes also compare the squared radius of a simplex's power circle
+//! or sphere, the one orthogonal to all of its weighted points, with α. Those comparisons
+//! perturb the coordinates the usual way, and the weights as one more coordinate after the others,
+//! so no simplex is ever born exactly at α, even when its points are degenerate.
+//! [`power_in_circle`] and [`power_in_sphere`] are power tests perturbed the same way,
+//! which never fail. Locating a point in a power diagram compares its power distances |**q** - **p**|² - *w*
+//! to 2 weighted points, which perturbs them the same way, so no point is ever exactly
+//! on a radical axis or plane.
+//!
+//! # Example
+//!
+//! ```
+//! # use simplicity::{nalgebra, weighted};
+//! # use nalgebra::Vector2;
+//! let points = vec![
+//!     (Vector2::new(0.0, 0.0), 1.0),
+//!     (Vector2::new(2.0, 0.0), 1.0),
+//!     (Vector2::new(2.0, 2.0), 1.0),
+//!     (Vector2::new(0.0, 2.0), 1.0),
+//! ];
+//! // The weight of the first point goes up, pulling its lift down below the others' plane
+//! assert_eq!(weighted::in_power_circle(&points, |l, i| l[i], 1, 2, 3, 0), Ok(true));
+//! assert_eq!(weighted::in_power_circle(&points, |l, i| l[i], 0, 1, 2, 3), Ok(false));
+//! ```
+
+use crate::exact::{self, Expansion};
+use crate::perturbed::{self, Perturbed, Polynomial};
+use crate::scale::{self, Prescaled};
+use crate::{rg, ErrorReason, PredicateKind, SimplicityError, SosPoint, Vec2, Vec3};
+use nalgebra::Vector4;
+use alloc::{vec, vec::Vec};
+use core::cmp::Ordering;
+use core::ops::{Add, Index, Mul, MulAssign, Sub};
+
+/// Evaluates the sign of a determinant with a row for each point but the last, relative to the last,
+/// with the lift as the extra column.
+fn evaluate<Idx: Ord + Copy, P: Copy + Index<usize, Output = f64> + MulAssign<f64>>(
+    predicate: PredicateKind,
+    dim: usize,
+    indexes: Vec<Idx>,
+    points: Vec<(P, f64)>,
+    orient: impl Fn(&[P]) -> f64,
+) -> Result<bool, SimplicityError<Idx>>
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let e = scale::weighted_prescale_exponent(points.iter().flat_map(|(p, _)| p), points.iter().map(|(_, w)| w));
+    let points = points.into_iter().map(|(p, w)| (scale::scaled(p, e), scale::scaled(w, 2 * e))).collect::<Vec<_>>();
+    let lift = |(p, w): &(P, f64)| (0..dim).fold(Expansion::from(-w), |acc, c| acc + Expansion::product(p[c], &[p[c]]));
+    let (last, rest) = points.split_last().unwrap();
+    let matrix = rest
+        .iter()
+        .map(|q| {
+            let mut row = (0..dim).map(|c| Expansion::from(q.0[c]) - Expansion::from(last.0[c])).collect::<Vec<_>>();
+            row.push(lift(q) - lift(last));
+            row
+        })
+        .collect();
+    let sign = exact::det(matrix).signum();
+    if sign != 0.0 {
+        return Ok(sign > 0.0);
+    }
+
+    // A weight's coefficient is minus its lift's
+    let coords = points.iter().map(|(p, _)| *p).collect::<Vec<_>>();
+    let last = coords.len() - 1;
+    let mut order = (0..indexes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|n| indexes[*n]);
+    let decided = order.into_iter().find_map(|n| {
+        let coeff = if n == last {
+            orient(&coords[..last])
+        } else {
+            let mut replaced = coords[..last].to_vec();
+            replaced[n] = coords[last];
+            -orient(&replaced)
+        };
+        if coeff == 0.0 {
+            None
+        } else {
+            Some(coeff > 0.0)
+        }
+    });
+    decided.ok_or(SimplicityError { predicate, indexes, reason: ErrorReason::Degenerate })
+}
+
+/// Returns whether the last weighted point is inside the power circle of the first 3,
+/// after perturbing the weights.
+/// The first 3 points should be oriented positive or the result will be flipped.
+///
+/// Takes a list of all the points, an indexing function that returns a point and its weight,
+/// and 4 indexes. With weights of 0, this agrees with [`in_circle`](crate::in_circle)
+/// on non-degenerate input. Errors name the predicate [`PredicateKind::InPowerCircle`].
+pub fn in_power_circle<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec2, f64),
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let indexes = vec![i, j, k, l];
+    let points = indexes.iter().map(|i| index_fn(list, *i)).collect();
+    evaluate(PredicateKind::InPowerCircle, 2, indexes, points, |p| rg::orient_2d(p[0], p[1], p[2]))
+}
+
+/// Returns whether the last weighted point is inside the power sphere of the first 4,
+/// after perturbing the weights.
+/// The first 4 points should be oriented positive or the result will be flipped.
+///
+/// Takes a list of all the points, an indexing function that returns a point and its weight,
+/// and 5 indexes. With weights of 0, this agrees with [`in_sphere`](crate::in_sphere)
+/// on non-degenerate input. Errors name the predicate [`PredicateKind::InPowerSphere`].
+pub fn in_power_sphere<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec3, f64),
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> Result<bool, SimplicityError<Idx>> {
+    let indexes = vec![i, j, k, l, m];
+    let points = indexes.iter().map(|i| index_fn(list, *i)).collect();
+    evaluate(PredicateKind::InPowerSphere, 3, indexes, points, |p| rg::orient_3d(p[0], p[1], p[2], p[3]))
+}
+
+/// Evaluates the sign of the determinant with a row (**p**, |**p**|² - *w*, 1) for each point,
+/// after perturbing the points and weights. The weight of each point is its coordinate `dim`.
+fn power_test<Idx: Ord + Copy, P: Index<usize, Output = f64>>(dim: usize, indexes: &[Idx], point: impl Fn(Idx) -> P) -> bool {
+    let points = Prescaled::new(dim, 1, indexes, &[], point);
+    let mut perturbed = Perturbed::new(dim + 1, 2, indexes, |i| points.point(i));
+    let sign = perturbed.lazy_signum(|perturbed| {
+        let rows = indexes
+            .iter()
+            .map(|i| {
+                let mut row = (0..dim).map(|c| perturbed.coord(*i, c)).collect::<Vec<_>>();
+                let lift = row.iter().fold(Polynomial::default(), |acc, x| acc + x.clone() * x.clone());
+                row.push(lift - perturbed.coord(*i, dim));
+                row.push(Polynomial::from(Expansion::from(1.0)));
+                row
+            })
+            .collect::<Vec<_>>();
+        perturbed::det(&rows)
+    });
+    sign > 0.0
+}
+
+/// Returns whether the last weighted point is inside the power circle of the first 3,
+/// after perturbing the points and weights. The first 3 points should be oriented positive
+/// or the result will be flipped.
+///
+/// Takes a list of all the points, an indexing function, a function that returns the weight
+/// of a point, and 4 indexes. Unlike [`in_power_circle`], this perturbs the coordinates too,
+/// so it never fails, even if the points are collinear. With weights of 0, this agrees with
+/// [`in_circle`](crate::in_circle) on non-degenerate input.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, weighted};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(2.0, 0.0),
+///     Vector2::new(2.0, 2.0),
+///     Vector2::new(0.0, 2.0),
+///     Vector2::new(1.0, 0.0),
+/// ];
+/// let weights = [1.0, 1.0, 1.0, 1.0, -1.0];
+/// let inside = |i, j, k, l| weighted::power_in_circle(&points, |l, i| l[i], |_, i| weights[i], i, j, k, l);
+/// // The lifts of the corners are coplanar, so the perturbation decides
+/// assert_ne!(inside(0, 1, 2, 3), inside(1, 2, 3, 0));
+/// // Point 4 is on the edge from 0 to 1, but with less weight, so its lift is above theirs
+/// assert!(!inside(0, 1, 2, 4));
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn power_in_circle<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    weight_fn: impl Fn(&T, Idx) -> f64,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    power_test(2, &[i, j, k, l], |i| {
+        let p = index_fn(list, i);
+        Vec3::new(p.x, p.y, weight_fn(list, i))
+    })
+}
+
+/// Returns whether the last weighted point is inside the power sphere of the first 4,
+/// after perturbing the points and weights. This is like [`power_in_circle`].
+#[allow(clippy::too_many_arguments)]
+pub fn power_in_sphere<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    weight_fn: impl Fn(&T, Idx) -> f64,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> bool {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    power_test(3, &[i, j, k, l, m], |i| {
+        let p = index_fn(list, i);
+        Vector4::new(p.x, p.y, p.z, weight_fn(list, i))
+    })
+}
+
+/// The matrix [[G, b], [bᵀ, c]] whose determinant decides a power radius comparison,
+/// given the entries of G and the differences of the weights from the first point's.
+fn power_matrix<E: Clone + Add<Output = E> + Sub<Output = E> + Mul<Output = E>>(
+    k: usize,
+    gram: impl Fn(usize, usize) -> E,
+    weight_diff: impl Fn(usize) -> E,
+    c: E,
+) -> Vec<Vec<E>> {
+    let b = (0..k).map(|j| gram(j, j) - weight_diff(j)).collect::<Vec<_>>();
+    let mut matrix = (0..k)
+        .map(|i| {
+            let mut row = (0..k).map(|j| gram(i, j)).collect::<Vec<_>>();
+            row.push(b[i].clone());
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut last = b;
+    last.push(c);
+    matrix.push(last);
+    matrix
+}
+
+/// Compares the squared radius of the power sphere of a simplex, in `dim` dimensions,
+/// with `alpha`, after perturbing the points and weights.
+/// The weight of each point is its coordinate `dim`.
+fn power_radius_cmp<Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    dim: usize,
+    simplex: &[Idx],
+    alpha: f64,
+    point: impl Fn(Idx) -> P,
+) -> Ordering {
+    assert!(
+        !simplex.is_empty() && simplex.len() <= dim + 1,
+        "a simplex in {} dimensions has 1 to {} points, not {}",
+        dim,
+        dim + 1,
+        simplex.len()
+    );
+    // With v_j = p_j - p_0, the center is p_0 + u with 2 v_j·u = |v_j|² - w_j + w_0 =: b_j,
+    // and the squared radius is |u|² - w_0 = bᵀG⁻¹b / 4 - w_0, where G is the Gram matrix of the v_j.
+    // Multiplying by det G, which is positive since the perturbed points are independent,
+    // the squared radius is more than α exactly when det [[G, b], [bᵀ, 4(w_0 + α)]] is negative.
+    let k = simplex.len() - 1;
+    let prescaled = Prescaled::new(dim, 1, simplex, &[alpha], point);
+    let alpha = prescaled.weight(alpha);
+    let points = simplex.iter().map(|i| prescaled.point(*i)).collect::<Vec<_>>();
+    let diff = |i: usize, c: usize| Expansion::from(points[i + 1][c]) - Expansion::from(points[0][c]);
+    let gram = |i, j| (0..dim).fold(Expansion::zero(), |acc, c| acc + diff(i, c) * diff(j, c));
+    let c = (Expansion::from(points[0][dim]) + Expansion::from(alpha)).scale(4.0);
+    let sign = exact::det(power_matrix(k, gram, |j| diff(j, dim), c)).signum();
+    if sign != 0.0 {
+        return 0.0.partial_cmp(&sign).unwrap();
+    }
+
+    let perturbed = Perturbed::new(dim + 1, 2 * k as u64 + 2, simplex, |i| prescaled.point(i));
+    let (p0, rest) = (simplex[0], &simplex[1..]);
+    let gram = |i: usize, j: usize| {
+        (0..dim).fold(Polynomial::default(), |acc, c| acc + perturbed.diff(rest[i], p0, c) * perturbed.diff(rest[j], p0, c))
+    };
+    let c = (perturbed.coord(p0, dim) + Polynomial::from(Expansion::from(alpha))).scale(4.0);
+    let matrix = power_matrix(k, gram, |j| perturbed.diff(rest[j], p0, dim), c);
+    0.0.partial_cmp(&perturbed::det(&matrix).signum()).unwrap()
+}
+
+/// Compares the squared radius of the power circle of a weighted simplex with `alpha`,
+/// after perturbing the points and weights. The power circle of a triangle is orthogonal to
+/// its 3 weighted points, and the power circle of an edge or a vertex is the smallest circle
+/// orthogonal to its weighted points. This is when the simplex is born in a weighted alpha complex,
+/// if it isn't attached to a simplex born earlier.
+/// Returns `Ordering::Equal` only if the perturbation can't decide it, which doesn't happen
+/// for distinct indexes.
+///
+/// Takes a list of all the points, an indexing function that returns a point and its weight,
+/// the indexes of the 1 to 3 points of the simplex, and α. With weights of 0, the squared radius
+/// is the squared circumradius, or the squared half length of an edge. Panics if the simplex
+/// has the wrong number of points.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, weighted};
+/// # use nalgebra::Vector2;
+/// # use std::cmp::Ordering;
+/// let points = vec![
+///     (Vector2::new(0.0, 0.0), 1.0),
+///     (Vector2::new(4.0, 0.0), 1.0),
+///     (Vector2::new(0.0, 4.0), 0.0),
+/// ];
+/// // The edge's power circle has a squared radius of 4 - 1
+/// assert_eq!(weighted::power_radius_cmp_2d(&points, |l, i| l[i], &[0, 1], 2.0), Ordering::Greater);
+/// assert_eq!(weighted::power_radius_cmp_2d(&points, |l, i| l[i], &[0, 1], 4.0), Ordering::Less);
+/// // Exactly 3, so the perturbation decides
+/// let tied = weighted::power_radius_cmp_2d(&points, |l, i| l[i], &[0, 1], 3.0);
+/// assert_ne!(tied, Ordering::Equal);
+/// ```
+pub fn power_radius_cmp_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec2, f64),
+    simplex: &[Idx],
+    alpha: f64,
+) -> Ordering {
+    power_radius_cmp(2, simplex, alpha, |i| {
+        let (p, w) = index_fn(list, i);
+        Vec3::new(p.x, p.y, w)
+    })
+}
+
+/// Compares the squared radius of the power sphere of a weighted simplex with `alpha`,
+/// after perturbing the points and weights. This is like [`power_radius_cmp_2d`],
+/// with 1 to 4 points, which is what alpha complexes of atoms, with their radii squared
+/// as weights, are built on.
+pub fn power_radius_cmp_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec3, f64),
+    simplex: &[Idx],
+    alpha: f64,
+) -> Ordering {
+    power_radius_cmp(3, simplex, alpha, |i| {
+        let (p, w) = index_fn(list, i);
+        Vector4::new(p.x, p.y, p.z, w)
+    })
+}
+
+/// Compares the power distances of point `query` to the weighted points `i` and `j`,
+/// in `dim` dimensions, after perturbing the points and weights.
+/// The weight of each point is its coordinate `dim`, and the query's is ignored.
+fn power_distance_cmp<Idx: Ord + Copy, P: Index<usize, Output = f64>>(
+    dim: usize,
+    query: Idx,
+    i: Idx,
+    j: Idx,
+    point: impl Fn(Idx) -> P,
+) -> Ordering {
+    let points = Prescaled::new(dim, 1, &[query, i, j], &[], point);
+    let mut perturbed = Perturbed::new(dim + 1, 2, &[query, i, j], |i| points.point(i));
+    let sign = perturbed.lazy_signum(|perturbed| {
+        let power = |p| {
+            let dist_sq = (0..dim).fold(Polynomial::default(), |acc, c| {
+                let diff = perturbed.diff(query, p, c);
+                acc + diff.clone() * diff
+            });
+            dist_sq - perturbed.coord(p, dim)
+        };
+        power(i) - power(j)
+    });
+    sign.partial_cmp(&0.0).unwrap()
+}
+
+/// Compares the power distance of point `query` to the weighted point `i` with its power distance
+/// to the weighted point `j`, after perturbing the points and weights. The power distance to
+/// (**p**, *w*) is |**q** - **p**|² - *w*, so this is which side of the radical axis of `i` and `j`
+/// the query is on, and `Ordering::Less` means it's in the power cell of `i` rather than `j`.
+/// Returns `Ordering::Equal` only if `i` and `j` are the same.
+///
+/// Takes a list of all the points, an indexing function that returns a point and its weight,
+/// the index of the query, whose weight is ignored, and the indexes of the 2 weighted points.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, weighted};
+/// # use nalgebra::Vector2;
+/// # use std::cmp::Ordering;
+/// let points = vec![
+///     (Vector2::new(0.0, 0.0), 0.0),
+///     (Vector2::new(4.0, 0.0), 8.0),
+///     (Vector2::new(2.0, 1.0), 0.0),
+///     (Vector2::new(1.0, 5.0), 0.0),
+/// ];
+/// // The radical axis is x = 1
+/// assert_eq!(weighted::power_distance_cmp_2d(&points, |l, i| l[i], 2, 0, 1), Ordering::Greater);
+/// // Exactly on it, so the perturbation decides
+/// let tied = weighted::power_distance_cmp_2d(&points, |l, i| l[i], 3, 0, 1);
+/// assert_eq!(tied, weighted::power_distance_cmp_2d(&points, |l, i| l[i], 3, 1, 0).reverse());
+/// assert_ne!(tied, Ordering::Equal);
+/// ```
+pub fn power_distance_cmp_2d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec2, f64),
+    query: Idx,
+    i: Idx,
+    j: Idx,
+) -> Ordering {
+    power_distance_cmp(2, query, i, j, |i| {
+        let (p, w) = index_fn(list, i);
+        Vec3::new(p.x, p.y, w)
+    })
+}
+
+/// Compares the power distance of point `query` to the weighted point `i` with its power distance
+/// to the weighted point `j`, after perturbing the points and weights. This is like
+/// [`power_distance_cmp_2d`], with the radical plane of `i` and `j` in place of the radical axis.
+pub fn power_distance_cmp_3d<T: ?Sized, Idx: Ord + Copy>(
+    list: &T,
+    index_fn: impl Fn(&T, Idx) -> (Vec3, f64),
+    query: Idx,
+    i: Idx,
+    j: Idx,
+) -> Ordering {
+    power_distance_cmp(3, query, i, j, |i| {
+        let (p, w) = index_fn(list, i);
+        Vector4::new(p.x, p.y, p.z, w)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, rng};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+    use nalgebra::{Vector1, Vector2, Vector3};
+
+    #[test]
+    fn test_weighted() {
+        use crate::exact::Expansion;
+        use crate::perturbed::{self, Perturbed, Polynomial};
+
+        let points = grid_points_2d();
+        let n = points.len();
+        let weights = [0.0, 1.0, 0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 0.0, 2.0, 0.0];
+        let weighted = points.iter().copied().zip(weights.iter().copied()).collect::<Vec<_>>();
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                for k in (0..n).filter(|k| *k != i && *k != j) {
+                    for l in (k + 1..n).filter(|l| *l != i && *l != j) {
+                        let indexes = [i, j, k, l];
+                        let result = super::in_power_circle(&weighted, |l, i| l[i], i, j, k, l);
+
+                        // The weights as 1-dimensional points, so they're perturbed in order of index
+                        let perturbed = Perturbed::new(1, 1, &indexes, |i| Vector1::new(weights[i]));
+                        let lift = |i: usize| {
+                            let p = points[i];
+                            Polynomial::from(Expansion::from(p.norm_squared())) - perturbed.coord(i, 0)
+                        };
+                        let row = |i: usize| {
+                            let d = points[i] - points[l];
+                            let coord = |x: f64| Polynomial::from(Expansion::from(x));
+                            vec![coord(d.x), coord(d.y), lift(i) - lift(l)]
+                        };
+                        let sign = perturbed::det(&[row(i), row(j), row(k)]).signum();
+                        match &result {
+                            Ok(result) => assert_eq!(*result, sign > 0.0),
+                            Err(err) => {
+                                assert!(err.is_degenerate() && sign == 0.0);
+                                assert_eq!(err.predicate, PredicateKind::InPowerCircle);
+                            }
+                        }
+
+                        let triples = [[i, j, k], [i, j, l], [i, k, l], [j, k, l]];
+                        let collinear =
+                            triples.iter().all(|[a, b, c]| rg::orient_2d(points[*a], points[*b], points[*c]) == 0.0);
+                        assert_eq!(result.is_err(), collinear);
+
+                        let unweighted = |l: &Vec<Vec2>, i: usize| (l[i], 0.0);
+                        if rg::in_circle(points[i], points[j], points[k], points[l]) != 0.0 {
+                            assert_eq!(
+                                super::in_power_circle(&points, unweighted, i, j, k, l),
+                                Ok(in_circle(&points, |l, i| l[i], i, j, k, l))
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let points = grid_points_3d();
+        let n = points.len();
+        let unweighted = |l: &Vec<Vec3>, i: usize| (l[i], 0.0);
+        for i in 0..n {
+            let (j, k, l, m) = ((i + 1) % n, (i + 3) % n, (i + 4) % n, (i + 9) % n);
+            if rg::in_sphere(points[i], points[j], points[k], points[l], points[m]) != 0.0 {
+                assert_eq!(
+                    super::in_power_sphere(&points, unweighted, i, j, k, l, m),
+                    Ok(in_sphere(&points, |l, i| l[i], i, j, k, l, m))
+                );
+            } else if rg::orient_3d(points[i], points[j], points[k], points[l]) != 0.0 {
+                // Cospherical, so the first point in index order decides
+                assert!(super::in_power_sphere(&points, unweighted, i, j, k, l, m).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_power_radius_cmp() {
+        use nalgebra::{DMatrix, DVector};
+        use std::cmp::Ordering;
+
+        // The squared radius in floating point, by solving for the center
+        fn squared_radius(points: &[(&[f64], f64)]) -> f64 {
+            let (p0, w0) = points[0];
+            let v = points[1..].iter().map(|(p, _)| DVector::from_fn(p.len(), |c, _| p[c] - p0[c])).collect::<Vec<_>>();
+            if v.is_empty() {
+                return -w0;
+            }
+            let gram = DMatrix::from_fn(v.len(), v.len(), |i, j| v[i].dot(&v[j]));
+            let b = DVector::from_fn(v.len(), |j, _| (v[j].norm_squared() - points[j + 1].1 + w0) / 2.0);
+            let lambda = gram.clone().lu().solve(&b).unwrap();
+            lambda.dot(&(gram * &lambda)) - w0
+        }
+
+        let mut rng = rng::SplitMix(1477);
+        let mut random = || rng.next() as f64 / u64::MAX as f64 * 4.0 - 2.0;
+        let points_2d = (0..6).map(|_| (Vector2::new(random(), random()), random())).collect::<Vec<_>>();
+        let points_3d = (0..6).map(|_| (Vector3::new(random(), random(), random()), random())).collect::<Vec<_>>();
+        for len in 1..=4 {
+            for start in 0..3 {
+                let simplex = (start..start + len).collect::<Vec<_>>();
+                let radius = squared_radius(&simplex.iter().map(|i| (points_3d[*i].0.as_slice(), points_3d[*i].1)).collect::<Vec<_>>());
+                let cmp = |alpha| super::power_radius_cmp_3d(&points_3d, |l, i| l[i], &simplex, alpha);
+                assert_eq!(cmp(radius - 0.01), Ordering::Greater);
+                assert_eq!(cmp(radius + 0.01), Ordering::Less);
+                if len < 4 {
+                    let radius = squared_radius(&simplex.iter().map(|i| (points_2d[*i].0.as_slice(), points_2d[*i].1)).collect::<Vec<_>>());
+                    let cmp = |alpha| super::power_radius_cmp_2d(&points_2d, |l, i| l[i], &simplex, alpha);
+                    assert_eq!(cmp(radius - 0.01), Ordering::Greater);
+                    assert_eq!(cmp(radius + 0.01), Ordering::Less);
+                }
+            }
+        }
+
+        // A vertex is born at minus its weight, and a larger weight is born earlier
+        let vertex = vec![(Vector2::new(1.0, 1.0), 2.0)];
+        assert_eq!(super::power_radius_cmp_2d(&vertex, |l, i| l[i], &[0], -2.0), Ordering::Less);
+
+        // Unweighted, ties agree with either side of the exact radius, and collinear points
+        // have a finite radius after perturbing them
+        let points = grid_points_2d().into_iter().map(|p| (p, 0.0)).collect::<Vec<_>>();
+        for (simplex, radius) in [(vec![0, 1], 0.25), (vec![0, 1, 3], 0.5), (vec![0, 1, 2], 0.0)].iter() {
+            let cmp = |alpha| super::power_radius_cmp_2d(&points, |l, i| l[i], simplex, alpha);
+            if *radius > 0.0 {
+                assert_eq!(cmp(radius - 0.01), Ordering::Greater);
+                assert_eq!(cmp(radius + 0.01), Ordering::Less);
+                assert_ne!(cmp(*radius), Ordering::Equal);
+            } else {
+                assert_eq!(cmp(1e300), Ordering::Greater);
+            }
+        }
+        let points = grid_points_3d().into_iter().map(|p| (p, 0.0)).collect::<Vec<_>>();
+        assert_eq!(super::power_radius_cmp_3d(&points, |l, i| l[i], &[0, 1, 2, 3], 1e300), Ordering::Greater);
+        assert_ne!(super::power_radius_cmp_3d(&points, |l, i| l[i], &[0, 1, 2, 4], 0.75), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_power_in_circle() {
+        let flat = grid_points_2d();
+        let mut rng = rng::SplitMix(1505);
+        let weights = (0..flat.len()).map(|_| rng.int(-2, 2)).collect::<Vec<_>>();
+        for _ in 0..400 {
+            let indexes = [(); 4].map(|_| rng.below(flat.len()));
+            if indexes.iter().enumerate().any(|(n, i)| indexes[..n].contains(i)) {
+                continue;
+            }
+            let [i, j, k, l] = indexes;
+
+            // With weights of 0, this agrees with in_circle away from ties
+            let det = rg::in_circle(flat[i], flat[j], flat[k], flat[l]);
+            if det != 0.0 {
+                assert_eq!(super::power_in_circle(&flat, |l, i| l[i], |_, _| 0.0, i, j, k, l), det > 0.0);
+            }
+
+            // Swapping 2 points flips the result, even for ties
+            let inside = super::power_in_circle(&flat, |l, i| l[i], |_, i| weights[i], i, j, k, l);
+            assert_ne!(inside, super::power_in_circle(&flat, |l, i| l[i], |_, i| weights[i], j, i, k, l));
+            assert_ne!(inside, super::power_in_circle(&flat, |l, i| l[i], |_, i| weights[i], i, j, l, k));
+
+            // And agrees with the power test that only perturbs the weights when that one decides
+            let weighted_flat = flat.iter().zip(&weights).map(|(p, w)| (*p, *w)).collect::<Vec<_>>();
+            let orientation = rg::orient_2d(flat[i], flat[j], flat[k]);
+            let lifted = weighted_flat.iter().map(|(p, w)| Vector3::new(p.x, p.y, p.norm_squared() - w)).collect::<Vec<_>>();
+            let det = rg::orient_3d(lifted[i], lifted[j], lifted[k], lifted[l]);
+            if orientation > 0.0 && det != 0.0 {
+                let expected = super::in_power_circle(&weighted_flat, |l, i| l[i], i, j, k, l);
+                assert_eq!(Ok(super::power_in_circle(&flat, |l, i| l[i], |_, i| weights[i], i, j, k, l)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_power_in_sphere() {
+        let points = grid_points_3d();
+        let mut rng = rng::SplitMix(1506);
+        let weights = (0..points.len()).map(|_| rng.int(-2, 2)).collect::<Vec<_>>();
+        for _ in 0..400 {
+            let indexes = [(); 5].map(|_| rng.below(points.len()));
+            if indexes.iter().enumerate().any(|(n, i)| indexes[..n].contains(i)) {
+                continue;
+            }
+            let [i, j, k, l, m] = indexes;
+
+            // With weights of 0, this agrees with in_sphere away from ties
+            let det = rg::in_sphere(points[i], points[j], points[k], points[l], points[m]);
+            if det != 0.0 {
+                assert_eq!(super::power_in_sphere(&points, |l, i| l[i], |_, _| 0.0, i, j, k, l, m), det > 0.0);
+            }
+
+            // Swapping 2 points flips the result, even for ties
+            let inside = super::power_in_sphere(&points, |l, i| l[i], |_, i| weights[i], i, j, k, l, m);
+            assert_ne!(inside, super::power_in_sphere(&points, |l, i| l[i], |_, i| weights[i], i, k, j, l, m));
+            assert_ne!(inside, super::power_in_sphere(&points, |l, i| l[i], |_, i| weights[i], i, j, k, m, l));
+
+            // And agrees with the power test that only perturbs the weights when that one decides
+            let weighted_points = points.iter().zip(&weights).map(|(p, w)| (*p, *w)).collect::<Vec<_>>();
+            let orientation = rg::orient_3d(points[i], points[j], points[k], points[l]);
+            let lift = |n: usize| points[n].norm_squared() - weights[n];
+            let rows = [i, j, k, l].map(|n| (points[n] - points[m]).push(lift(n) - lift(m)));
+            let det = nalgebra::Matrix4::from_columns(&rows).determinant();
+            if orientation > 0.0 && det.abs() > 1e-9 {
+                let expected = super::in_power_sphere(&weighted_points, |l, i| l[i], i, j, k, l, m);
+                assert_eq!(Ok(super::power_in_sphere(&points, |l, i| l[i], |_, i| weights[i], i, j, k, l, m)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_power_tiny() {
+        // Products of these underflow unless the predicates scale them up first
+        let fixture = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.1, 1.1)].map(|(x, y)| Vector2::new(x, y) * 1e-200);
+        assert!(!super::power_in_circle(&fixture, |l, i| l[i], |_, _| 0.0, 0, 1, 2, 3));
+        let unit = [(0.0, 0.0, 0.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0), (0.0, 0.0, 1.0), (1.1, 1.1, 1.1)]
+            .map(|(x, y, z)| Vector3::new(x, y, z));
+        let fixture = unit.map(|p| p * 1e-200);
+        assert_eq!(
+            super::power_in_sphere(&fixture, |l, i| l[i], |_, _| 0.0, 0, 1, 2, 3, 4),
+            in_sphere(&unit, |l, i| l[i], 0, 1, 2, 3, 4)
+        );
+
+        // Scaling by a power of 2 changes nothing, even for ties
+        let flat = grid_points_2d();
+        let points = grid_points_3d();
+        let small_flat = flat.iter().map(|p| scale::scaled(*p, -664)).collect::<Vec<_>>();
+        let small_points = points.iter().map(|p| scale::scaled(*p, -664)).collect::<Vec<_>>();
+        let mut rng = rng::SplitMix(1505);
+        for _ in 0..50 {
+            let [i, j, k, l] = rng.distinct(flat.len());
+            assert_eq!(
+                super::power_in_circle(&small_flat, |l, i| l[i], |_, _| 0.0, i, j, k, l),
+                super::power_in_circle(&flat, |l, i| l[i], |_, _| 0.0, i, j, k, l)
+            );
+            let [i, j, k, l, m] = rng.distinct(points.len());
+            assert_eq!(
+                super::power_in_sphere(&small_points, |l, i| l[i], |_, _| 0.0, i, j, k, l, m),
+                super::power_in_sphere(&points, |l, i| l[i], |_, _| 0.0, i, j, k, l, m)
+            );
+        }
+    }
+
+    #[test]
+    fn test_power_distance_cmp() {
+        use std::cmp::Ordering;
+
+        let mut rng = rng::SplitMix(1497);
+        let points_2d = (0..8).map(|_| (Vector2::new(rng.int(-4, 4), rng.int(-4, 4)), rng.int(-4, 4))).collect::<Vec<_>>();
+        let points_3d = (0..8).map(|_| (Vector3::new(rng.int(-4, 4), rng.int(-4, 4), rng.int(-4, 4)), rng.int(-4, 4))).collect::<Vec<_>>();
+        let mut ties = 0;
+        for query in 0..8 {
+            for i in 0..8 {
+                for j in 0..8 {
+                    let cmp_2d = super::power_distance_cmp_2d(&points_2d, |l, i| l[i], query, i, j);
+                    let cmp_3d = super::power_distance_cmp_3d(&points_3d, |l, i| l[i], query, i, j);
+                    assert_eq!(cmp_2d == Ordering::Equal, i == j);
+                    assert_eq!(cmp_3d == Ordering::Equal, i == j);
+                    assert_eq!(cmp_2d, super::power_distance_cmp_2d(&points_2d, |l, i| l[i], query, j, i).reverse());
+                    assert_eq!(cmp_3d, super::power_distance_cmp_3d(&points_3d, |l, i| l[i], query, j, i).reverse());
+
+                    // The coordinates are small integers, so the powers are exact
+                    let q = points_2d[query].0;
+                    let power = |(p, w): (Vector2<f64>, f64)| (q - p).norm_squared() - w;
+                    let (a, b) = (power(points_2d[i]), power(points_2d[j]));
+                    if a != b {
+                        assert_eq!(cmp_2d, a.partial_cmp(&b).unwrap());
+                    } else if i != j {
+                        ties += 1;
+                    }
+                    let q = points_3d[query].0;
+                    let power = |(p, w): (Vector3<f64>, f64)| (q - p).norm_squared() - w;
+                    let (a, b) = (power(points_3d[i]), power(points_3d[j]));
+                    if a != b {
+                        assert_eq!(cmp_3d, a.partial_cmp(&b).unwrap());
+                    }
+                }
+            }
+        }
+        assert!(ties > 0);
+
+        // Power cells are consistent at a tie: of 3 sites with the query equidistant from all of them,
+        // the comparisons are transitive
+        let points = vec![
+            (Vector2::new(0.0, 0.0), 0.0),
+            (Vector2::new(1.0, 0.0), 0.0),
+            (Vector2::new(0.0, 1.0), 0.0),
+            (Vector2::new(-1.0, 0.0), 0.0),
+        ];
+        let cmp = |i, j| super::power_distance_cmp_2d(&points, |l, i| l[i], 0, i, j);
+        let mut sites = [1, 2, 3];
+        sites.sort_by(|i, j| cmp(*i, *j));
+        for (n, i) in sites.iter().enumerate() {
+            for j in &sites[n + 1..] {
+                assert_eq!(cmp(*i, *j), Ordering::Less);
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Variants of the predicates that also return the determinant that decided them.
+//!
+//! Heuristics like ranking triangles by quality or refining the worst ones first want to know
+//! how far from degenerate the points are, and the determinant the predicate computes first
+//! already says that. These return it along with the result when it's nonzero, computed
+//! adaptively, so it's approximate but always has the right sign. When it's 0,
+//! the perturbation decides and the value is 0.0.
+//!
+//! Points too small for the exact predicates are scaled up first, like in the predicates,
+//! and the value is scaled back down, so it can underflow to 0.0 even though the decision
+//! came from it.
+
+use crate::scale;
+use crate::{rg, SosPoint, Vec1, Vec2, Vec3};
+use core::ops::MulAssign;
+
+/// Evaluates the determinant of `degree` of some points, scaled up if they need it,
+/// and falls back to `perturbed` if it's 0.
+fn evaluate<P: Copy + MulAssign<f64>, const N: usize>(
+    points: [P; N],
+    degree: usize,
+    det: impl FnOnce([P; N]) -> f64,
+    perturbed: impl FnOnce() -> bool,
+) -> (bool, f64)
+where
+    for<'a> &'a P: IntoIterator<Item = &'a f64>,
+{
+    let e = scale::prescale_exponent(&points);
+    let val = det(points.map(|p| scale::scaled(p, e)));
+    if val == 0.0 {
+        (perturbed(), 0.0)
+    } else {
+        (val > 0.0, (0..degree).fold(val, |val, _| scale::scaled(val, -e)))
+    }
+}
+
+/// Like [`orient_1d`](crate::orient_1d), but also returns the difference of the points
+/// if they aren't equal, or 0.0 if they are.
+pub fn orient_1d_with_det<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec1>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+) -> (bool, f64) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j)];
+    evaluate(points, 1, |p| p[0].x - p[1].x, || crate::orient_1d(list, &index_fn, i, j))
+}
+
+/// Like [`orient_2d`](crate::orient_2d), but also returns twice the signed area of the triangle
+/// if the points aren't collinear, or 0.0 if they are.
+///
+/// # Example
+///
+/// ```
+/// # use simplicity::{nalgebra, orient_2d_with_det};
+/// # use nalgebra::Vector2;
+/// let points = vec![
+///     Vector2::new(0.0, 0.0),
+///     Vector2::new(2.0, 0.0),
+///     Vector2::new(0.0, 3.0),
+///     Vector2::new(4.0, 0.0),
+/// ];
+/// assert_eq!(orient_2d_with_det(&points, |l, i| l[i], 0, 1, 2), (true, 6.0));
+/// assert_eq!(orient_2d_with_det(&points, |l, i| l[i], 1, 0, 2), (false, -6.0));
+/// // Collinear, so the perturbation decides
+/// let (positive, det) = orient_2d_with_det(&points, |l, i| l[i], 0, 1, 3);
+/// assert_eq!((positive, det), (simplicity::orient_2d(&points, |l, i| l[i], 0, 1, 3), 0.0));
+/// ```
+pub fn orient_2d_with_det<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+) -> (bool, f64) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k)];
+    evaluate(points, 2, |p| rg::orient_2d(p[0], p[1], p[2]), || crate::orient_2d(list, &index_fn, i, j, k))
+}
+
+/// Like [`orient_3d`](crate::orient_3d), but also returns 6 times the signed volume
+/// of the tetrahedron if the points aren't coplanar, or 0.0 if they are.
+pub fn orient_3d_with_det<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> (bool, f64) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+    evaluate(points, 3, |p| rg::orient_3d(p[0], p[1], p[2], p[3]), || {
+        crate::orient_3d(list, &index_fn, i, j, k, l)
+    })
+}
+
+/// Like [`in_circle`](crate::in_circle), but also returns the in-circle determinant
+/// if it isn't 0, or 0.0 if it is.
+pub fn in_circle_with_det<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec2>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+) -> (bool, f64) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l)];
+    evaluate(points, 4, |p| rg::in_circle(p[0], p[1], p[2], p[3]), || {
+        crate::in_circle(list, index_fn, i, j, k, l)
+    })
+}
+
+/// Like [`in_sphere`](crate::in_sphere), but also returns the in-sphere determinant
+/// if it isn't 0, or 0.0 if it is.
+#[allow(clippy::too_many_arguments)]
+pub fn in_sphere_with_det<'a, T: ?Sized, Idx: Ord + Copy, P: SosPoint<Vector = Vec3>>(
+    list: &'a T,
+    index_fn: impl Fn(&'a T, Idx) -> P + Clone,
+    i: Idx,
+    j: Idx,
+    k: Idx,
+    l: Idx,
+    m: Idx,
+) -> (bool, f64) {
+    let index_fn = move |list: &'a T, i| index_fn(list, i).to_vector();
+    let points = [index_fn(list, i), index_fn(list, j), index_fn(list, k), index_fn(list, l), index_fn(list, m)];
+    evaluate(points, 5, |p| rg::in_sphere(p[0], p[1], p[2], p[3], p[4]), || {
+        crate::in_sphere(list, index_fn, i, j, k, l, m)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{in_circle, in_sphere, orient_2d};
+    use crate::tests::{grid_points_2d, grid_points_3d};
+
+    #[test]
+    fn test_with_det() {
+        let points = grid_points_2d();
+        for i in 0..points.len() {
+            for j in (0..points.len()).filter(|j| *j != i) {
+                for k in (0..points.len()).filter(|k| *k != i && *k != j) {
+                    let (positive, det) = orient_2d_with_det(&points, |l, i| l[i], i, j, k);
+                    assert_eq!(positive, orient_2d(&points, |l, i| l[i], i, j, k));
+                    assert_eq!(det, rg::orient_2d(points[i], points[j], points[k]));
+                    for l in (0..points.len()).filter(|l| *l != i && *l != j && *l != k) {
+                        let (inside, det) = in_circle_with_det(&points, |l, i| l[i], i, j, k, l);
+                        assert_eq!(inside, in_circle(&points, |l, i| l[i], i, j, k, l));
+                        assert_eq!(det, rg::in_circle(points[i], points[j], points[k], points[l]));
+                    }
+                }
+            }
+        }
+
+        // Tiny points are decided like the others, and the determinant comes back down
+        let tiny = 2f64.powi(-500);
+        let points = grid_points_3d();
+        let scaled = points.iter().map(|p| p * tiny).collect::<Vec<_>>();
+        // 2^-1500 underflows
+        let (positive, det) = orient_3d_with_det(&points, |l, i| l[i], 0, 1, 2, 4);
+        assert_ne!(det, 0.0);
+        assert_eq!(orient_3d_with_det(&scaled, |l, i| l[i], 0, 1, 2, 4), (positive, 0.0));
+        let (positive, det) = orient_2d_with_det(&points, |l, i| l[i].xy(), 0, 1, 2);
+        assert_eq!(orient_2d_with_det(&scaled, |l, i| l[i].xy(), 0, 1, 2), (positive, det * tiny * tiny));
+        for (i, j, k, l, m) in [(0, 1, 2, 4, 3), (0, 1, 2, 4, 7)].iter().copied() {
+            let inside = in_sphere(&points, |l, i| l[i], i, j, k, l, m);
+            assert_eq!(in_sphere_with_det(&scaled, |l, i| l[i], i, j, k, l, m).0, inside);
+        }
+    }
+}